@@ -0,0 +1,30 @@
+use renoir::bench::{self, Workload};
+use renoir::prelude::*;
+
+/// Run the standard workloads, printing a JSON report for each of them.
+///
+/// `cargo run --release --features bench --example bench -- -l 8 1000000 [workload...]`
+fn main() {
+    let (config, args) = RuntimeConfig::from_args();
+    // the first argument is the name of the executable
+    let items = args
+        .get(1)
+        .map(|n| n.parse().expect("invalid number of items"))
+        .unwrap_or(1_000_000);
+    let workloads: Vec<_> = match args.get(2..).unwrap_or_default() {
+        [] => Workload::ALL.to_vec(),
+        names => names
+            .iter()
+            .map(|n| Workload::from_name(n).unwrap_or_else(|| panic!("unknown workload {n}")))
+            .collect(),
+    };
+
+    config.spawn_remote_workers();
+    for workload in workloads {
+        let report = bench::run(workload, config.clone(), items);
+        if report.output_items.is_some() {
+            eprintln!("{report}");
+            println!("{}", serde_json::to_string(&report).unwrap());
+        }
+    }
+}
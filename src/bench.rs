@@ -0,0 +1,244 @@
+//! Standard workloads for measuring the performance of Renoir across versions.
+//!
+//! Each [`Workload`] builds a job over a synthetic input generated in parallel by the replicas, so
+//! no dataset is needed and the same workload can be compared between two versions of the crate,
+//! or between two deployments. [`run`] executes a workload and returns a [`BenchReport`], which
+//! can be serialized (e.g. to JSON) and stored for comparing later runs.
+//!
+//! This module is available only with the `bench` feature, which is also needed by the `bench`
+//! example.
+//!
+//! ## Example
+//!
+//! ```
+//! # use renoir::RuntimeConfig;
+//! # use renoir::bench::{self, Workload};
+//! let report = bench::run(Workload::WordCount, RuntimeConfig::local(4).unwrap(), 1000);
+//! println!("{report}");
+//! assert_eq!(report.input_items, 1000);
+//! ```
+
+use std::fmt::Display;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::block::structure::ExecutionPlan;
+use crate::operator::sink::StreamOutput;
+use crate::operator::source::{NexmarkBid, NexmarkEvent, NexmarkGenerator};
+use crate::operator::Operator;
+use crate::{CoordUInt, RuntimeConfig, Stream, StreamContext};
+
+/// The number of different words of the lines generated by [`Workload::WordCount`].
+const VOCABULARY_SIZE: u64 = 1000;
+/// The number of words of each line generated by [`Workload::WordCount`].
+const WORDS_PER_LINE: usize = 10;
+
+/// A standard workload, see [`run`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Workload {
+    /// Count the occurrences of each word in lines of text, with an associative fold.
+    WordCount,
+    /// Nexmark query 1: convert the price of each bid to another currency.
+    NexmarkQ1,
+    /// Nexmark query 2: select the bids of some auctions.
+    NexmarkQ2,
+    /// Nexmark query 5 over the whole stream instead of a sliding window: find the auction with
+    /// the most bids.
+    NexmarkHotItems,
+}
+
+impl Workload {
+    /// All the available workloads.
+    pub const ALL: [Workload; 4] = [
+        Workload::WordCount,
+        Workload::NexmarkQ1,
+        Workload::NexmarkQ2,
+        Workload::NexmarkHotItems,
+    ];
+
+    /// The name of the workload, as shown in the reports.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Workload::WordCount => "wordcount",
+            Workload::NexmarkQ1 => "nexmark-q1",
+            Workload::NexmarkQ2 => "nexmark-q2",
+            Workload::NexmarkHotItems => "nexmark-hot-items",
+        }
+    }
+
+    /// Find the workload with the given name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|w| w.name() == name)
+    }
+
+    /// Build the job of the workload over `items` input items, returning the number of output
+    /// items.
+    fn build(&self, env: &StreamContext, items: u64) -> StreamOutput<usize> {
+        match self {
+            Workload::WordCount => lines(env, items)
+                .flat_map(|line| {
+                    line.split_whitespace()
+                        .map(str::to_string)
+                        .collect::<Vec<_>>()
+                })
+                .group_by_fold(
+                    |w| w.clone(),
+                    0usize,
+                    |count, _| *count += 1,
+                    |count, c| *count += c,
+                )
+                .unkey()
+                .collect_count(),
+            Workload::NexmarkQ1 => bids(env, items)
+                .map(|mut b| {
+                    b.price = (b.price as f32 * 0.908) as u64;
+                    b
+                })
+                .collect_count(),
+            Workload::NexmarkQ2 => bids(env, items)
+                .filter(|b| b.auction % 123 == 0)
+                .map(|b| (b.auction, b.price))
+                .collect_count(),
+            Workload::NexmarkHotItems => bids(env, items)
+                .group_by_count(|b| b.auction)
+                .unkey()
+                .reduce_assoc(|a, b| if b.1 > a.1 { b } else { a })
+                .collect_count(),
+        }
+    }
+}
+
+impl Display for Workload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The result of the execution of a [`Workload`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BenchReport {
+    /// The executed workload.
+    pub workload: Workload,
+    /// The version of Renoir that executed the workload.
+    pub version: String,
+    /// The number of generated input items: lines of text or Nexmark events.
+    pub input_items: u64,
+    /// The number of output items, `None` on the hosts that did not collect the output.
+    pub output_items: Option<usize>,
+    /// The time spent executing the job, excluding its construction.
+    pub elapsed: Duration,
+    /// How the job was executed.
+    pub plan: ExecutionPlan,
+}
+
+impl BenchReport {
+    /// The number of input items processed per second.
+    pub fn throughput(&self) -> f64 {
+        self.input_items as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+impl Display for BenchReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} (renoir {}): {} items in {:?} ({:.0} items/s)",
+            self.workload,
+            self.version,
+            self.input_items,
+            self.elapsed,
+            self.throughput()
+        )
+    }
+}
+
+/// Execute `workload` with the given configuration over `items` generated input items.
+///
+/// With a remote configuration, this must be called by all the hosts, like
+/// [`StreamContext::execute_blocking`], and the remote workers must have been spawned.
+pub fn run(workload: Workload, config: RuntimeConfig, items: u64) -> BenchReport {
+    let env = StreamContext::new(config);
+    let output = workload.build(&env, items);
+    let plan = env.plan();
+    let start = Instant::now();
+    env.execute_blocking();
+    let elapsed = start.elapsed();
+    BenchReport {
+        workload,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        input_items: items,
+        output_items: output.get(),
+        elapsed,
+        plan,
+    }
+}
+
+/// Execute all the workloads one after the other, see [`run`].
+pub fn run_all(config: RuntimeConfig, items: u64) -> Vec<BenchReport> {
+    Workload::ALL
+        .into_iter()
+        .map(|workload| run(workload, config.clone(), items))
+        .collect()
+}
+
+/// A cheap deterministic hash of the index of an item, for generating the inputs.
+fn mix(mut x: u64) -> u64 {
+    x = x.wrapping_mul(0x9e3779b97f4a7c15);
+    x ^= x >> 31;
+    x.wrapping_mul(0xbf58476d1ce4e5b9)
+}
+
+/// The indices of the items generated by each replica.
+fn indices(env: &StreamContext, items: u64) -> Stream<impl Operator<Out = u64>> {
+    env.stream_par_iter(move |id, peers: CoordUInt| (id..items).step_by(peers as usize))
+}
+
+fn lines(env: &StreamContext, items: u64) -> Stream<impl Operator<Out = String>> {
+    indices(env, items).map(|i| {
+        let mut line = String::new();
+        let mut x = i;
+        for _ in 0..WORDS_PER_LINE {
+            x = mix(x + 1);
+            line.push_str(&format!("w{} ", x % VOCABULARY_SIZE));
+        }
+        line
+    })
+}
+
+/// The bids of the events generated by a [`NexmarkGenerator`].
+fn bids(env: &StreamContext, items: u64) -> Stream<impl Operator<Out = NexmarkBid>> {
+    env.stream_nexmark(NexmarkGenerator::new(items))
+        .filter_map(|e| match e {
+            NexmarkEvent::Bid(b) => Some(b),
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run, run_all, Workload};
+    use crate::RuntimeConfig;
+
+    #[test]
+    fn bench_workloads() {
+        let reports = run_all(RuntimeConfig::local(4).unwrap(), 10_000);
+        assert_eq!(reports.len(), Workload::ALL.len());
+        let outputs: Vec<_> = reports.iter().map(|r| r.output_items.unwrap()).collect();
+        // all the words of the vocabulary appear in 10000 lines
+        assert_eq!(outputs[0], 1000);
+        // 46 out of 50 events are bids
+        assert_eq!(outputs[1], 9200);
+        assert!(outputs[2] > 0 && outputs[2] < 9200);
+        assert_eq!(outputs[3], 1);
+    }
+
+    #[test]
+    fn bench_report_json() {
+        let report = run(Workload::NexmarkQ2, RuntimeConfig::local(2).unwrap(), 100);
+        let json = serde_json::to_string(&report).unwrap();
+        assert!(json.contains("\"workload\":\"NexmarkQ2\""));
+        assert_eq!(Workload::from_name("nexmark-q2"), Some(Workload::NexmarkQ2));
+        assert!(!report.plan.blocks.is_empty());
+    }
+}
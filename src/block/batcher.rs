@@ -8,8 +8,8 @@ use crate::operator::{ExchangeData, StreamElement};
 
 /// Which policy to use for batching the messages before sending them.
 ///
-/// Avoid constructing directly this enumeration, please use [`BatchMode::fixed()`] and
-/// [`BatchMode::adaptive()`] constructors.
+/// Avoid constructing directly this enumeration, please use [`BatchMode::fixed()`],
+/// [`BatchMode::adaptive()`] and [`BatchMode::auto()`] constructors.
 ///
 /// The default batch mode is `Adaptive(1024, 50ms)`, meaning that a batch is flushed either when
 /// it has at least 1024 messages, or no message has been received in the last 50ms.
@@ -20,6 +20,10 @@ pub enum BatchMode {
     /// A batch is flushed only when the specified number of messages is present or a timeout
     /// expires.
     Adaptive(NonZeroUsize, Duration),
+    /// The size of the batches is tuned at runtime from the arrival rate of the messages, up to
+    /// the specified maximum, so that a message waits in a batch at most for the specified
+    /// latency target.
+    Auto(NonZeroUsize, Duration),
 
     /// Send each message infdividually
     Single,
@@ -30,18 +34,93 @@ impl BatchMode {
         match self {
             BatchMode::Fixed(s) => s.get(),
             BatchMode::Adaptive(s, _) => s.get(),
+            BatchMode::Auto(s, _) => s.get(),
             BatchMode::Single => 1,
         }
     }
 
     pub fn interval(&self) -> Option<Duration> {
         match self {
-            BatchMode::Adaptive(_, ts) => Some(*ts),
+            BatchMode::Adaptive(_, ts) | BatchMode::Auto(_, ts) => Some(*ts),
             _ => None,
         }
     }
 }
 
+/// Weight of the last batch in the estimate of the arrival rate.
+const RATE_SMOOTHING: f64 = 0.25;
+
+/// The state of a batcher in [`BatchMode::Auto`], choosing the size of the next batches.
+///
+/// The size is chosen so that, at the current arrival rate of the messages, a batch is filled in
+/// half of the latency target, leaving the other half to the delays of the network and of the
+/// receiver. The first message of a batch never waits more than the latency target, so at a low
+/// rate the batches are flushed before being full and the size shrinks accordingly.
+#[derive(Debug, Clone)]
+pub(crate) struct BatchTuner {
+    max_size: usize,
+    target: Duration,
+    /// The size of the next batches.
+    size: usize,
+    /// The arrival rate of the messages, in messages per second, smoothed over the batches.
+    rate: Option<f64>,
+    /// When the first message of the current batch has been enqueued.
+    first: Option<Instant>,
+}
+
+impl BatchTuner {
+    pub(crate) fn new(mode: BatchMode) -> Option<Self> {
+        match mode {
+            BatchMode::Auto(max_size, target) => Some(Self {
+                max_size: max_size.get(),
+                target,
+                size: 1,
+                rate: None,
+                first: None,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Record a new message, returning whether the batch, now with `len` messages, should be
+    /// flushed.
+    pub(crate) fn enqueue(&mut self, len: usize) -> bool {
+        self.enqueue_at(len, Instant::now())
+    }
+
+    fn enqueue_at(&mut self, len: usize, now: Instant) -> bool {
+        let first = *self.first.get_or_insert(now);
+        len >= self.size || now.duration_since(first) >= self.target.into()
+    }
+
+    /// Record the flush of a batch with `len` messages, updating the size of the next ones.
+    pub(crate) fn flushed(&mut self, len: usize) {
+        self.flushed_at(len, Instant::now())
+    }
+
+    fn flushed_at(&mut self, len: usize, now: Instant) {
+        let Some(first) = self.first.take() else {
+            return;
+        };
+        let elapsed = now.duration_since(first).as_f64();
+        if elapsed == 0.0 {
+            // the messages arrive faster than the resolution of the clock
+            if len >= self.size {
+                self.size = (2 * self.size).min(self.max_size);
+            }
+            return;
+        }
+        let rate = len as f64 / elapsed;
+        let rate = match self.rate {
+            Some(prev) => prev + RATE_SMOOTHING * (rate - prev),
+            None => rate,
+        };
+        self.rate = Some(rate);
+        let size = rate * self.target.as_secs_f64() / 2.0;
+        self.size = (size as usize).clamp(1, self.max_size);
+    }
+}
+
 /// A `Batcher` wraps a sender and sends the messages in batches to reduce the network overhead.
 ///
 /// Internally it spawns a new task to handle the timeouts and join it at the end.
@@ -54,6 +133,8 @@ pub(crate) struct Batcher<Out: Send + 'static> {
     buffer: Vec<StreamElement<Out>>,
     /// Time of the last flush of the buffer.    
     last_send: Instant,
+    /// State of the batcher in [`BatchMode::Auto`].
+    tuner: Option<BatchTuner>,
     /// The coordinate of this block, used for marking the sender of the batch.
     coord: Coord,
 }
@@ -65,6 +146,7 @@ impl<Out: ExchangeData> Batcher<Out> {
             mode,
             buffer: Default::default(),
             last_send: Instant::now(),
+            tuner: BatchTuner::new(mode),
             coord,
         }
     }
@@ -73,18 +155,25 @@ impl<Out: ExchangeData> Batcher<Out> {
     pub(crate) fn enqueue(&mut self, message: StreamElement<Out>) {
         match self.mode {
             BatchMode::Adaptive(n, max_delay) => {
-                self.buffer.push(message);
+                self.push(message);
                 let timeout_elapsed = self.last_send.elapsed() > max_delay.into();
                 if self.buffer.len() >= n.get() || timeout_elapsed {
                     self.flush()
                 }
             }
             BatchMode::Fixed(n) => {
-                self.buffer.push(message);
+                self.push(message);
                 if self.buffer.len() >= n.get() {
                     self.flush()
                 }
             }
+            BatchMode::Auto(..) => {
+                self.push(message);
+                let tuner = self.tuner.as_mut().unwrap();
+                if tuner.enqueue(self.buffer.len()) {
+                    self.flush()
+                }
+            }
             BatchMode::Single => {
                 let message = NetworkMessage::new_single(message, self.coord);
                 self.remote_sender.send(message).unwrap();
@@ -92,9 +181,25 @@ impl<Out: ExchangeData> Batcher<Out> {
         }
     }
 
+    /// Push a message in the buffer, compacting the consecutive watermarks.
+    ///
+    /// The watermarks of a replica are increasing, so a watermark makes the previous one redundant
+    /// if no other message has been enqueued in between.
+    fn push(&mut self, message: StreamElement<Out>) {
+        match (self.buffer.last_mut(), &message) {
+            (Some(last @ StreamElement::Watermark(_)), StreamElement::Watermark(_)) => {
+                *last = message
+            }
+            _ => self.buffer.push(message),
+        }
+    }
+
     /// Flush the internal buffer if it's not empty.
     pub(crate) fn flush(&mut self) {
         if !self.buffer.is_empty() {
+            if let Some(tuner) = self.tuner.as_mut() {
+                tuner.flushed(self.buffer.len());
+            }
             let cap = self.buffer.capacity();
             let new_cap = if self.buffer.len() < cap / 4 {
                 cap / 2
@@ -133,6 +238,18 @@ impl BatchMode {
         )
     }
 
+    /// Construct a new `BatchMode::Auto` with the given positive maximum batch size and latency
+    /// target.
+    ///
+    /// The batch size is adjusted at runtime by each replica, independently for each of its
+    /// outgoing connections.
+    pub fn auto(max_size: usize, target_latency: Duration) -> BatchMode {
+        BatchMode::Auto(
+            NonZeroUsize::new(max_size).expect("The batch size must be positive"),
+            target_latency,
+        )
+    }
+
     /// Construct a new `BatchMode::Single`.
     pub fn single() -> BatchMode {
         BatchMode::Single
@@ -140,7 +257,7 @@ impl BatchMode {
 
     pub fn max_delay(&self) -> Option<Duration> {
         match &self {
-            BatchMode::Adaptive(_, max_delay) => Some(*max_delay),
+            BatchMode::Adaptive(_, max_delay) | BatchMode::Auto(_, max_delay) => Some(*max_delay),
             BatchMode::Fixed(_) | BatchMode::Single => None,
         }
     }
@@ -151,3 +268,92 @@ impl Default for BatchMode {
         BatchMode::adaptive(1024, Duration::from_millis(50))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use coarsetime::Instant;
+
+    use crate::block::{BatchMode, BatchTuner, Batcher};
+    use crate::operator::StreamElement;
+    use crate::test::FakeNetworkTopology;
+
+    fn ms(millis: u64) -> coarsetime::Duration {
+        Duration::from_millis(millis).into()
+    }
+
+    #[test]
+    fn batch_tuner_follows_rate() {
+        let mut tuner = BatchTuner::new(BatchMode::auto(1000, Duration::from_millis(100))).unwrap();
+        let start = Instant::now();
+        assert_eq!(tuner.size, 1);
+
+        // a burst of messages doubles the size
+        for expected in [2, 4, 8] {
+            assert!(tuner.enqueue_at(tuner.size, start));
+            tuner.flushed_at(tuner.size, start);
+            assert_eq!(tuner.size, expected);
+        }
+
+        // 8 messages in 2ms: 4000 msg/s, filling 200 messages in 50ms
+        assert!(tuner.enqueue_at(8, start));
+        tuner.flushed_at(8, start + ms(2));
+        assert!((190..=210).contains(&tuner.size), "{}", tuner.size);
+
+        // each message waits at most the latency target
+        assert!(!tuner.enqueue_at(1, start));
+        assert!(!tuner.enqueue_at(10, start + ms(50)));
+        assert!(tuner.enqueue_at(11, start + ms(101)));
+        tuner.flushed_at(11, start + ms(101));
+        // the rate decreased to 4000 + (110 - 4000) / 4
+        assert!((140..=155).contains(&tuner.size), "{}", tuner.size);
+
+        // the size is bounded
+        for _ in 0..10 {
+            tuner.enqueue_at(1, start);
+            tuner.flushed_at(1000, start + ms(1));
+        }
+        assert_eq!(tuner.size, 1000);
+    }
+
+    #[test]
+    fn batch_tuner_only_auto() {
+        assert!(BatchTuner::new(BatchMode::adaptive(10, Duration::from_millis(1))).is_none());
+        assert!(BatchTuner::new(BatchMode::fixed(10)).is_none());
+        assert_eq!(
+            BatchMode::auto(10, Duration::from_millis(5)).max_delay(),
+            Some(Duration::from_millis(5))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "timestamp")]
+    fn batcher_compacts_watermarks() {
+        let mut t = FakeNetworkTopology::<u32>::new(1, 1);
+        let (from, sender) = t.senders_mut()[0].pop().unwrap();
+        let mut batcher = Batcher::new(sender, BatchMode::fixed(100), from);
+
+        let messages = [
+            StreamElement::Watermark(1),
+            StreamElement::Watermark(2),
+            StreamElement::Timestamped(42, 3),
+            StreamElement::Watermark(4),
+            StreamElement::Watermark(5),
+            StreamElement::Watermark(6),
+            StreamElement::FlushAndRestart,
+        ];
+        for message in messages {
+            batcher.enqueue(message);
+        }
+        assert_eq!(
+            batcher.buffer,
+            vec![
+                StreamElement::Watermark(2),
+                StreamElement::Timestamped(42, 3),
+                StreamElement::Watermark(6),
+                StreamElement::FlushAndRestart,
+            ]
+        );
+    }
+}
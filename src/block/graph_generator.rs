@@ -73,7 +73,11 @@ impl JobGraphGenerator {
 
         for (index, operator) in block.operators.iter().enumerate() {
             let id = Self::operator_id(block_id, index);
-            let label = format!("{}\\l{}", operator.title, operator.subtitle); // TODO: escape
+            // TODO: escape
+            let mut label = format!("{}\\l{}", operator.display_name(), operator.subtitle);
+            if let Some(uid) = &operator.uid {
+                label += &format!("\\luid: {uid}");
+            }
             let shape = match operator.kind {
                 OperatorKind::Operator => "box",
                 OperatorKind::Sink => "house",
@@ -7,6 +7,7 @@ pub(crate) use batcher::*;
 pub(crate) use graph_generator::*;
 pub(crate) use next_strategy::*;
 pub(crate) use structure::*;
+pub(crate) use validation::*;
 
 use crate::operator::iteration::IterationStateLock;
 use crate::operator::Operator;
@@ -17,6 +18,7 @@ mod batcher;
 mod graph_generator;
 mod next_strategy;
 pub mod structure;
+mod validation;
 
 /// A chain of operators that will be run inside the same host. The block takes as input elements of
 /// type `In` and produces elements of type `Out`.
@@ -43,6 +45,8 @@ where
     pub(crate) is_only_one_strategy: bool,
     /// The set of requirements that the block imposes on the scheduler.
     pub(crate) scheduling: Scheduling,
+    /// The priority of the stream produced by this block.
+    pub(crate) priority: Priority,
 }
 
 impl<OperatorChain> Clone for Block<OperatorChain>
@@ -57,6 +61,7 @@ where
             iteration_ctx: self.iteration_ctx.clone(),
             is_only_one_strategy: self.is_only_one_strategy,
             scheduling: self.scheduling.clone(),
+            priority: self.priority,
         }
     }
 }
@@ -78,6 +83,7 @@ where
             iteration_ctx: self.iteration_ctx,
             is_only_one_strategy: false,
             scheduling: self.scheduling,
+            priority: self.priority,
         }
     }
 }
@@ -92,6 +98,17 @@ pub(crate) struct Scheduling {
     pub(crate) replication: Replication,
 }
 
+/// The priority of a stream, see [`Stream::priority`](crate::Stream::priority).
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Default)]
+pub enum Priority {
+    /// The stream is delivered as it comes.
+    #[default]
+    Normal,
+    /// When the stream is merged or joined with a stream with a lower priority, its elements are
+    /// received first.
+    High,
+}
+
 /// Replication factor for a block
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
 pub enum Replication {
@@ -165,6 +182,7 @@ where
             iteration_ctx,
             is_only_one_strategy: false,
             scheduling,
+            priority: Priority::Normal,
         }
     }
 
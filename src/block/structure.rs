@@ -7,6 +7,7 @@ use serde::{Deserialize, Serialize};
 use crate::block::NextStrategy;
 use crate::operator::{ExchangeData, KeyerFn};
 use crate::scheduler::BlockId;
+use crate::CoordUInt;
 
 /// Wrapper type that contains a string representing the type.
 ///
@@ -47,6 +48,22 @@ pub struct OperatorStructure {
     pub connections: Vec<Connection>,
     /// The type of the data that comes out of this operator.
     pub out_type: DataType,
+    /// The name given to the operator with [`Stream::name`](crate::Stream::name), if any.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// The stable identifier given to the operator with [`Stream::uid`](crate::Stream::uid), if
+    /// any.
+    #[serde(default)]
+    pub uid: Option<String>,
+    /// How the operator handles the timestamps of the elements.
+    #[serde(default)]
+    pub timestamps: TimestampUsage,
+    /// Whether the stream produced by the operator terminates.
+    #[serde(default)]
+    pub boundedness: Boundedness,
+    /// The state kept in memory by the operator, if any.
+    #[serde(default)]
+    pub state: Option<OperatorState>,
 }
 
 /// The kind of operator: either `Operator`, `Source` or `Sink`.
@@ -62,6 +79,55 @@ pub enum OperatorKind {
     Source,
 }
 
+/// How an operator handles the timestamps of the elements, used for validating the job graph.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimestampUsage {
+    /// The timestamps of the incoming elements, if any, are kept.
+    #[default]
+    Forward,
+    /// The operator assigns a timestamp to the elements.
+    Assign,
+    /// The elements produced by the operator have no timestamp.
+    Drop,
+    /// The operator needs the incoming elements to be timestamped.
+    Require,
+}
+
+/// Whether the stream produced by an operator terminates, used for validating the job graph.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Boundedness {
+    /// The stream terminates if all the inputs of the operator terminate.
+    #[default]
+    Forward,
+    /// The operator produces a stream that never terminates.
+    Unbounded,
+    /// The operator needs the incoming stream to terminate, for example because it produces its
+    /// result only at the end of the stream.
+    RequireBounded,
+}
+
+/// The state kept in memory by a stateful operator, used for estimating its memory usage.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OperatorState {
+    /// The type of an entry of the state.
+    pub entry_type: DataType,
+    /// The size in bytes of an entry, not counting the memory it owns on the heap.
+    pub entry_size: usize,
+    /// What the number of entries depends on.
+    pub entries: StateEntries,
+}
+
+/// What the number of entries of the state of an operator depends on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StateEntries {
+    /// The operator keeps a single entry.
+    Single,
+    /// The operator keeps an entry for each key it receives.
+    PerKey,
+    /// The operator keeps an entry for each element it buffers.
+    PerElement,
+}
+
 /// A receiver registered by an operator.
 ///
 /// This receiver tells that an operator will receive some data from the network from the specified
@@ -103,6 +169,105 @@ pub enum ConnectionStrategy {
     All,
 }
 
+/// How a job would be executed, computed without executing it.
+///
+/// See [`StreamContext::plan`](crate::StreamContext::plan).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExecutionPlan {
+    /// The blocks of the job graph, sorted by id.
+    pub blocks: Vec<BlockPlan>,
+    /// The connections between the blocks, sorted by the id of the blocks.
+    pub connections: Vec<ConnectionPlan>,
+}
+
+/// How a block of the job graph would be executed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BlockPlan {
+    /// The identifier of the block.
+    pub block_id: BlockId,
+    /// The string representation of the block.
+    pub repr: String,
+    /// The structure of the block, before its operators are set up.
+    pub structure: BlockStructure,
+    /// The number of replicas of the block on each host, indexed by the id of the host.
+    pub replicas: Vec<CoordUInt>,
+}
+
+/// The channels that would connect the replicas of two blocks.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConnectionPlan {
+    /// The identifier of the block that sends the data.
+    pub from: BlockId,
+    /// The identifier of the block that receives the data.
+    pub to: BlockId,
+    /// The number of channels between replicas on the same host.
+    pub local_channels: usize,
+    /// The number of channels between replicas on different hosts.
+    pub remote_channels: usize,
+}
+
+impl BlockPlan {
+    /// A rough estimate of the memory used by the state of the operators of a replica, in bytes.
+    ///
+    /// The state is assumed to have an entry for each of the `keys` keys and each of the
+    /// `elements` buffered elements, and the memory owned on the heap by each entry is not counted.
+    pub fn state_size(&self, keys: usize, elements: usize) -> usize {
+        self.structure
+            .operators
+            .iter()
+            .filter_map(|op| op.state.as_ref())
+            .map(|state| {
+                state.entry_size
+                    * match state.entries {
+                        StateEntries::Single => 1,
+                        StateEntries::PerKey => keys,
+                        StateEntries::PerElement => elements,
+                    }
+            })
+            .sum()
+    }
+}
+
+impl Display for ExecutionPlan {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for block in &self.blocks {
+            writeln!(f, "block {}: {}", block.block_id, block.repr)?;
+            let replicas = block
+                .replicas
+                .iter()
+                .enumerate()
+                .map(|(host_id, n)| format!("h{host_id:02}: {n}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(f, "  replicas: {replicas}")?;
+            for op in &block.structure.operators {
+                if let Some(state) = &op.state {
+                    let entries = match state.entries {
+                        StateEntries::Single => "",
+                        StateEntries::PerKey => " per key",
+                        StateEntries::PerElement => " per buffered element",
+                    };
+                    writeln!(
+                        f,
+                        "  state of {}: {} bytes{entries} ({})",
+                        op.display_name(),
+                        state.entry_size,
+                        state.entry_type
+                    )?;
+                }
+            }
+            for conn in self.connections.iter().filter(|c| c.from == block.block_id) {
+                writeln!(
+                    f,
+                    "  -> block {}: {} local channels, {} remote channels",
+                    conn.to, conn.local_channels, conn.remote_channels
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
 impl DataType {
     /// Construct the `DataType` for the specified type.
     pub fn of<T: ?Sized>() -> Self {
@@ -164,8 +329,28 @@ impl OperatorStructure {
             receivers: Default::default(),
             connections: Default::default(),
             out_type: DataType::of::<Out>(),
+            name: None,
+            uid: None,
+            timestamps: TimestampUsage::Forward,
+            boundedness: Boundedness::Forward,
+            state: None,
         }
     }
+
+    /// Set the state kept by the operator, made of `entries` entries of type `Entry`.
+    pub fn with_state<Entry>(mut self, entries: StateEntries) -> Self {
+        self.state = Some(OperatorState {
+            entry_type: DataType::of::<Entry>(),
+            entry_size: std::mem::size_of::<Entry>(),
+            entries,
+        });
+        self
+    }
+
+    /// The name given to the operator, falling back to its title.
+    pub fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.title)
+    }
 }
 
 impl OperatorReceiver {
@@ -0,0 +1,201 @@
+use std::collections::HashSet;
+
+use indexmap::IndexMap;
+
+use crate::block::{BlockStructure, Boundedness, OperatorStructure, TimestampUsage};
+use crate::scheduler::BlockId;
+
+/// This struct tracks the structure of all the blocks of the job graph and checks it for common
+/// mistakes before the workers are started.
+///
+/// All the problems found are collected, so that they can be reported together.
+#[derive(Clone, Debug)]
+pub struct JobGraphValidator {
+    /// The list of known blocks, indexed by block id.
+    blocks: IndexMap<BlockId, BlockStructure, crate::block::CoordHasherBuilder>,
+    /// The blocks each block receives from, indexed by block id.
+    prev_blocks: IndexMap<BlockId, Vec<BlockId>, crate::block::CoordHasherBuilder>,
+    /// The problems found so far.
+    issues: Vec<String>,
+}
+
+impl JobGraphValidator {
+    pub fn new() -> Self {
+        Self {
+            blocks: Default::default(),
+            prev_blocks: Default::default(),
+            issues: Default::default(),
+        }
+    }
+
+    /// Register a new block inside the validator.
+    ///
+    /// If a block with the same id has already been registered, the structure will be overwritten.
+    pub fn add_block(&mut self, block_id: BlockId, structure: BlockStructure) {
+        self.blocks.insert(block_id, structure);
+    }
+
+    /// Register a connection between two blocks of the job graph.
+    pub fn add_connection(&mut self, from: BlockId, to: BlockId) {
+        let prev = self.prev_blocks.entry(to).or_default();
+        if !prev.contains(&from) {
+            prev.push(from);
+        }
+    }
+
+    /// Report a problem that has been found outside of the validator.
+    pub fn add_issue(&mut self, issue: String) {
+        self.issues.push(issue);
+    }
+
+    /// Run all the checks, returning the list of problems found in the job graph.
+    pub fn finalize(mut self) -> Vec<String> {
+        self.blocks.sort_keys();
+        self.check_timestamps();
+        self.check_boundedness();
+        self.check_connections();
+        self.issues
+    }
+
+    /// The name of an operator, with the block it belongs to.
+    fn operator_name(&self, block_id: BlockId, index: usize) -> String {
+        let operator = &self.blocks[&block_id].operators[index];
+        format!("{} (block {block_id})", operator.display_name())
+    }
+
+    /// Check that the operators that need timestamps receive timestamped elements from all their
+    /// inputs.
+    fn check_timestamps(&mut self) {
+        let mut issues = vec![];
+        for (&block_id, structure) in &self.blocks {
+            for (index, operator) in structure.operators.iter().enumerate() {
+                if operator.timestamps != TimestampUsage::Require {
+                    continue;
+                }
+                let found = self.find_before(block_id, index, &mut HashSet::new(), &|op| match op
+                    .timestamps
+                {
+                    TimestampUsage::Assign => Some(false),
+                    TimestampUsage::Drop => Some(true),
+                    TimestampUsage::Forward | TimestampUsage::Require => None,
+                });
+                if let Some((b, i)) = found {
+                    issues.push(format!(
+                        "{} needs timestamped elements, but the elements from {} have no timestamp \
+                        (use `add_timestamps` to assign them)",
+                        self.operator_name(block_id, index),
+                        self.operator_name(b, i),
+                    ));
+                }
+            }
+        }
+        self.issues.extend(issues);
+    }
+
+    /// Check that the operators that need the stream to terminate do not receive elements from an
+    /// operator that never terminates.
+    fn check_boundedness(&mut self) {
+        let mut issues = vec![];
+        for (&block_id, structure) in &self.blocks {
+            for (index, operator) in structure.operators.iter().enumerate() {
+                if operator.boundedness != Boundedness::RequireBounded {
+                    continue;
+                }
+                let found = self.find_before(block_id, index, &mut HashSet::new(), &|op| {
+                    (op.boundedness == Boundedness::Unbounded).then_some(true)
+                });
+                if let Some((b, i)) = found {
+                    issues.push(format!(
+                        "{} needs a stream that terminates, but {} never terminates",
+                        self.operator_name(block_id, index),
+                        self.operator_name(b, i),
+                    ));
+                }
+            }
+        }
+        self.issues.extend(issues);
+    }
+
+    /// Look for the closest operator before the one at `index` in the block for which `check`
+    /// returns `Some(true)`, following all the inputs of the blocks.
+    ///
+    /// The search along a path stops at the first operator for which `check` returns
+    /// `Some(false)`, or when the origin of the elements is not known.
+    fn find_before(
+        &self,
+        block_id: BlockId,
+        index: usize,
+        visited: &mut HashSet<BlockId>,
+        check: &dyn Fn(&OperatorStructure) -> Option<bool>,
+    ) -> Option<(BlockId, usize)> {
+        let structure = self.blocks.get(&block_id)?;
+        for i in (0..index).rev() {
+            match check(&structure.operators[i]) {
+                Some(true) => return Some((block_id, i)),
+                Some(false) => return None,
+                None => {}
+            }
+        }
+        for &prev in self.prev_blocks.get(&block_id).into_iter().flatten() {
+            if !visited.insert(prev) {
+                continue;
+            }
+            let len = self.blocks.get(&prev).map_or(0, |s| s.operators.len());
+            if let Some(found) = self.find_before(prev, len, visited, check) {
+                return Some(found);
+            }
+        }
+        None
+    }
+
+    /// Check that each connection between two blocks has a receiver on the other end which expects
+    /// the same type of data.
+    fn check_connections(&mut self) {
+        let mut issues = vec![];
+        for (&from, structure) in &self.blocks {
+            for (index, operator) in structure.operators.iter().enumerate() {
+                for connection in &operator.connections {
+                    let to = connection.to_block_id;
+                    let Some(to_structure) = self.blocks.get(&to) else {
+                        // the block is not running on this host
+                        continue;
+                    };
+                    let receivers: Vec<_> = to_structure
+                        .operators
+                        .iter()
+                        .enumerate()
+                        .flat_map(|(i, op)| op.receivers.iter().map(move |r| (i, r)))
+                        .filter(|(_, r)| r.previous_block_id == from)
+                        .collect();
+                    if receivers.is_empty() {
+                        // custom operators may not report the structure of the previous ones
+                        if to_structure
+                            .operators
+                            .iter()
+                            .all(|op| op.receivers.is_empty())
+                        {
+                            continue;
+                        }
+                        issues.push(format!(
+                            "{} sends to block {to}, but no operator of block {to} receives from it",
+                            self.operator_name(from, index),
+                        ));
+                    } else if !receivers
+                        .iter()
+                        .any(|(_, r)| r.data_type == connection.data_type)
+                    {
+                        let (i, receiver) = receivers[0];
+                        issues.push(format!(
+                            "{} sends elements of type {}, but {} expects elements of type {}",
+                            self.operator_name(from, index),
+                            connection.data_type,
+                            self.operator_name(to, i),
+                            receiver.data_type,
+                        ));
+                    }
+                }
+            }
+        }
+        self.issues.extend(issues);
+    }
+}
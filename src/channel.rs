@@ -129,7 +129,6 @@ impl<T: ChannelItem> Receiver<T> {
     }
 
     #[inline]
-
     pub async fn recv_async(&self) -> Result<T, RecvError> {
         self.0.recv_async().await.map_err(RecvError::from)
     }
@@ -161,6 +160,38 @@ impl<T: ChannelItem> Receiver<T> {
     ) -> Result<SelectResult<T, T2>, RecvTimeoutError> {
         select_timeout_impl!(self, other, timeout)
     }
+
+    /// Receive a message from any of the provided receivers, returning also the index of the
+    /// receiver it comes from.
+    ///
+    /// Like `select`, but with any number of receivers of the same type. `receivers` must not be
+    /// empty.
+    #[inline]
+    pub fn select_any(receivers: &[&Receiver<T>]) -> (usize, Result<T, RecvError>) {
+        Self::selector(receivers).wait()
+    }
+
+    /// Same as `select_any`, with a timeout.
+    #[inline]
+    pub fn select_any_timeout(
+        receivers: &[&Receiver<T>],
+        timeout: Duration,
+    ) -> Result<(usize, Result<T, RecvError>), RecvTimeoutError> {
+        Self::selector(receivers)
+            .wait_timeout(timeout)
+            .map_err(|_| RecvTimeoutError::Timeout)
+    }
+
+    fn selector<'a>(
+        receivers: &[&'a Receiver<T>],
+    ) -> flume::Selector<'a, (usize, Result<T, RecvError>)> {
+        receivers
+            .iter()
+            .enumerate()
+            .fold(flume::Selector::new(), |selector, (i, receiver)| {
+                selector.recv(&receiver.0, move |el| (i, el.map_err(RecvError::from)))
+            })
+    }
 }
 
 /// A wrapper on an unbounded channel sender.
@@ -220,7 +251,7 @@ mod tests {
 
     use itertools::Itertools;
 
-    use crate::channel::{bounded, SelectResult};
+    use crate::channel::{bounded, Receiver, SelectResult};
 
     const TEST_CAPACITY: usize = 10;
 
@@ -298,6 +329,30 @@ mod tests {
         assert_eq!(elem2, SelectResult::B(Ok("test".to_string())));
     }
 
+    #[test]
+    fn test_select_any_local() {
+        let (sender1, receiver1) = bounded(TEST_CAPACITY);
+        let (sender2, receiver2) = bounded(TEST_CAPACITY);
+        let (sender3, receiver3) = bounded(TEST_CAPACITY);
+        let receivers = [&receiver1, &receiver2, &receiver3];
+
+        sender3.send(3).unwrap();
+        assert_eq!(Receiver::select_any(&receivers), (2, Ok(3)));
+
+        sender1.send(1).unwrap();
+        assert_eq!(
+            Receiver::select_any_timeout(&receivers, Duration::from_millis(1)).unwrap(),
+            (0, Ok(1))
+        );
+
+        let timeout = Receiver::select_any_timeout(&receivers, Duration::from_millis(50));
+        assert!(timeout.is_err());
+
+        drop(sender2);
+        assert_eq!(Receiver::select_any(&receivers).0, 1);
+        drop((sender1, sender3));
+    }
+
     /// This test checks if the `select` function selects randomly between the two channels if they
     /// are both ready. The actual distribution of probability does not really matters in practice,
     /// as long as eventually both channels are selected.
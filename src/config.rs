@@ -22,6 +22,12 @@ pub const HOST_ID_ENV_VAR: &str = "NOIR_HOST_ID";
 /// Environment variable set by the runner with the content of the config file so that it's not
 /// required to have it on all the hosts.
 pub const CONFIG_ENV_VAR: &str = "NOIR_CONFIG";
+/// Environment variable with the name of the profile of the config file to use, see
+/// [`ConfigBuilder::profile`].
+pub const PROFILE_ENV_VAR: &str = "NOIR_PROFILE";
+/// Environment variable with a `;` separated list of `key=value` overrides of the config file, see
+/// [`ConfigBuilder::set`].
+pub const OVERRIDES_ENV_VAR: &str = "NOIR_SET";
 
 /// The runtime configuration of the environment,
 ///
@@ -65,6 +71,42 @@ pub const CONFIG_ENV_VAR: &str = "NOIR_CONFIG";
 /// let env = StreamContext::new(config);
 /// ```
 ///
+/// ## Profiles and overrides
+///
+/// The configuration file can contain profiles, which are applied on top of the rest of the file
+/// when selected. Then single values can be overridden with `key=value` pairs, where the key is
+/// the path of the value in the file:
+///
+/// ```
+/// # use renoir::config::ConfigBuilder;
+/// let config = r#"
+/// [[host]]
+/// address = "localhost"
+/// base_port = 9500
+/// num_cores = 4
+///
+/// [profile.production]
+/// checksums = true
+/// [[profile.production.host]]
+/// address = "host1"
+/// base_port = 9500
+/// num_cores = 16
+/// "#;
+///
+/// let config = ConfigBuilder::new_remote()
+///     .parse_toml_str(config)
+///     .unwrap()
+///     .profile("production")
+///     .set("host.0.num_cores=24")
+///     .unwrap()
+///     .build()
+///     .unwrap();
+/// ```
+///
+/// When the configuration is read with [`RuntimeConfig::remote`], the profile and the overrides
+/// are taken from the environment variables [`PROFILE_ENV_VAR`] and [`OVERRIDES_ENV_VAR`], or from
+/// the `--profile` and `--set` command line arguments with [`RuntimeConfig::from_args`].
+///
 /// ## From command line arguments
 /// This reads from `std::env::args()` and reads the most common options (`--local`, `--remote`,
 /// `--verbose`). All the unparsed options will be returned into `args`. You can use `--help` to see
@@ -124,6 +166,107 @@ pub struct RemoteConfig {
     /// Remove remote binaries after execution
     #[serde(default)]
     pub cleanup_executable: bool,
+    /// Add a checksum to each batch sent between the hosts, verified on receipt.
+    ///
+    /// The batches are always numbered, so a lost or reordered batch is detected even without the
+    /// checksums.
+    #[serde(default)]
+    pub checksums: bool,
+    /// If specified the output of all the hosts is also appended to this file, each line prefixed
+    /// by the id of the host it comes from.
+    #[serde(default)]
+    pub log_file: Option<PathBuf>,
+    /// If specified the job shares the hosts with other jobs, and uses its own range of ports and
+    /// its own temporary directories.
+    #[serde(default)]
+    pub job: Option<JobConfig>,
+}
+
+/// The identity of a job running on the same hosts as other jobs.
+///
+/// Each job binds the ports in its own range: the range of the job with slot `n` starts at
+/// `base_port + n * ports` on each host. The id of the job names the directory of the remote
+/// executables and the tracing files, so the jobs do not overwrite each other's files.
+///
+/// ```toml
+/// [job]
+/// id = "nightly-etl"
+/// slot = 2
+/// ports = 50
+///
+/// [job.quota]
+/// max_output_rows = 1000000
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct JobConfig {
+    /// The identifier of the job, unique among the jobs running on the same hosts.
+    ///
+    /// It can only contain letters, digits, `-` and `_`.
+    pub id: String,
+    /// The index of the range of ports of this job, unique among the jobs running on the same
+    /// hosts.
+    pub slot: u16,
+    /// The number of ports in the range of each job.
+    #[serde(default = "job_default_ports")]
+    pub ports: u16,
+    /// The limits on the resources the job can use on each host.
+    #[serde(default)]
+    pub quota: QuotaConfig,
+}
+
+/// The limits on the resources used by a job on each host, see [`JobConfig::quota`].
+///
+/// A job exceeding one of its limits fails, instead of slowing down the other jobs running on the
+/// same hosts.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Eq, PartialEq)]
+pub struct QuotaConfig {
+    /// The maximum number of items that can reach the sinks of the job.
+    #[serde(default)]
+    pub max_output_rows: Option<u64>,
+    /// The maximum number of bytes that can be written to disk by the
+    /// [`disk_shuffle`](crate::Stream::disk_shuffle) operators of the job.
+    #[serde(default)]
+    pub max_spill_bytes: Option<u64>,
+    /// The memory that can be used by the state of the stateful operators of the job, in bytes.
+    ///
+    /// The budget is split evenly among the replicas of the stateful operators on each host. An
+    /// operator over its share emits its partial results early when it can (like the local step
+    /// of [`group_by_fold`](crate::Stream::group_by_fold)), otherwise a warning is logged.
+    ///
+    /// **Note**: the size of the state is estimated from the inline size of the keys and of the
+    /// accumulators (`size_of::<(K, O)>()` for each key). The memory they own on the heap (e.g. the
+    /// content of a `String` or a `Vec`) and the overhead of the hash tables are not counted, so
+    /// the budget should be lowered accordingly for such types.
+    #[serde(default)]
+    pub max_state_bytes: Option<u64>,
+}
+
+impl JobConfig {
+    /// A job with the given id and slot, with the default number of ports.
+    pub fn new(id: impl Into<String>, slot: u16) -> Self {
+        Self {
+            id: id.into(),
+            slot,
+            ports: job_default_ports(),
+            quota: Default::default(),
+        }
+    }
+
+    /// The offset of the first port of this job from the base port of each host.
+    pub(crate) fn port_offset(&self) -> u16 {
+        self.slot * self.ports
+    }
+}
+
+impl RemoteConfig {
+    /// The directory of the hosts where the executables are copied.
+    pub(crate) fn remote_dir(&self) -> PathBuf {
+        let dir = Path::new("/tmp/renoir");
+        match &self.job {
+            Some(job) => dir.join(&job.id),
+            None => dir.to_path_buf(),
+        }
+    }
 }
 
 /// The configuration of a single remote host.
@@ -136,7 +279,9 @@ pub struct HostConfig {
     /// The first port to use for inter-host communication.
     ///
     /// This port and the following ones will be bound by the host, one for each connection between
-    /// blocks of the job graph..
+    /// blocks of the job graph. The first host binds one more port, for the barrier that makes the
+    /// hosts start together. If a [`JobConfig`] is specified, the ports of the job start from its
+    /// range instead.
     pub base_port: u16,
     /// The number of cores of the remote host.
     ///
@@ -212,6 +357,16 @@ pub struct CommandLineOptions {
     #[clap(short, long)]
     local: Option<CoordUInt>,
 
+    /// The profile of the configuration file to use for the remote execution.
+    #[clap(long)]
+    profile: Option<String>,
+
+    /// Override a value of the configuration file for the remote execution, as `key=value`.
+    ///
+    /// The key is the path of the value in the file, like `host.0.num_cores`.
+    #[clap(long = "set")]
+    overrides: Vec<String>,
+
     /// The rest of the arguments.
     args: Vec<String>,
 }
@@ -229,7 +384,16 @@ impl RuntimeConfig {
         if let Some(parallelism) = opt.local {
             (Self::local(parallelism).expect("Configuration error"), args)
         } else if let Some(remote) = opt.remote {
-            (Self::remote(remote).expect("Configuration error"), args)
+            let config = Self::remote_with(remote, |builder| {
+                if let Some(profile) = &opt.profile {
+                    builder.profile(profile);
+                }
+                for assignment in &opt.overrides {
+                    builder.set(assignment)?;
+                }
+                Ok(())
+            });
+            (config.expect("Configuration error"), args)
         } else {
             unreachable!("Invalid configuration")
         }
@@ -247,14 +411,30 @@ impl RuntimeConfig {
     /// If it's the runner, the configuration file is read. If it's a worker, the configuration is
     /// read directly from the environment variable and not from the file (remote hosts may not have
     /// the configuration file).
+    ///
+    /// The profile and the overrides of the configuration are read from the environment variables
+    /// [`PROFILE_ENV_VAR`] and [`OVERRIDES_ENV_VAR`], if set.
     pub fn remote<P: AsRef<Path>>(toml_path: P) -> Result<RuntimeConfig, ConfigError> {
+        Self::remote_with(toml_path, |_| Ok(()))
+    }
+
+    /// Like [`RuntimeConfig::remote`], with some more profile and overrides set by `customize`
+    /// after the ones of the environment.
+    fn remote_with<P, F>(toml_path: P, customize: F) -> Result<RuntimeConfig, ConfigError>
+    where
+        P: AsRef<Path>,
+        F: FnOnce(&mut ConfigBuilder) -> Result<(), ConfigError>,
+    {
         let mut builder = ConfigBuilder::new_remote();
 
         if env::var(CONFIG_ENV_VAR).is_ok() {
+            // the runner already resolved the profile and the overrides
             builder.parse_env()?;
             builder.host_id_from_env()?;
         } else {
             builder.parse_file(toml_path)?;
+            builder.overrides_from_env()?;
+            customize(&mut builder)?;
         }
 
         builder.build()
@@ -276,6 +456,14 @@ impl RuntimeConfig {
         }
     }
 
+    /// The limits on the resources used by the job, see [`QuotaConfig`].
+    pub(crate) fn quota(&self) -> QuotaConfig {
+        match self {
+            RuntimeConfig::Remote(RemoteConfig { job: Some(job), .. }) => job.quota.clone(),
+            _ => Default::default(),
+        }
+    }
+
     pub fn host_id(&self) -> Option<HostId> {
         match self {
             RuntimeConfig::Local(_) => Some(0),
@@ -305,6 +493,15 @@ pub struct ConfigBuilder {
     hosts: Vec<HostConfig>,
     tracing_dir: Option<PathBuf>,
     cleanup_executable: bool,
+    checksums: bool,
+    log_file: Option<PathBuf>,
+    job: Option<JobConfig>,
+    /// The profiles found in the parsed files.
+    profiles: toml::Table,
+    /// The profile to apply, if any.
+    profile: Option<String>,
+    /// The values to override, in order.
+    overrides: Vec<(String, toml::Value)>,
 }
 
 impl ConfigBuilder {
@@ -324,18 +521,43 @@ impl ConfigBuilder {
             hosts: Vec::new(),
             tracing_dir: None,
             cleanup_executable: false,
+            checksums: false,
+            log_file: None,
+            job: None,
+            profiles: Default::default(),
+            profile: None,
+            overrides: Vec::new(),
         }
     }
     /// Parse toml and integrate it in the builder.
     /// Hosts are appended to the list, the rest of the parameters set only if they were not present.
     /// host_id is ignored. Configure it directly
+    /// The profiles are kept aside, the one selected with [`ConfigBuilder::profile`] is applied
+    /// when the configuration is built.
     pub fn parse_toml_str(&mut self, config_str: &str) -> Result<&mut Self, ConfigError> {
+        let mut config: toml::Table = toml::from_str(config_str)?;
+        if let Some(profiles) = config.remove("profile") {
+            let toml::Value::Table(profiles) = profiles else {
+                return Err(ConfigError::Invalid("`profile` must be a table".into()));
+            };
+            for (name, profile) in profiles {
+                match self.profiles.get_mut(&name) {
+                    Some(prev) => merge_toml(prev, profile),
+                    None => {
+                        self.profiles.insert(name, profile);
+                    }
+                }
+            }
+        }
         let RemoteConfig {
             host_id: _, // Ignore serialized host_id
             hosts,
             tracing_dir,
             cleanup_executable,
-        } = toml::from_str(config_str)?;
+            checksums,
+            log_file,
+            job,
+        } = toml::Value::Table(config).try_into()?;
 
         // validate the configuration
         for host in hosts.into_iter() {
@@ -349,6 +571,9 @@ impl ConfigBuilder {
         }
         self.tracing_dir = self.tracing_dir.take().or(tracing_dir);
         self.cleanup_executable |= cleanup_executable;
+        self.checksums |= checksums;
+        self.log_file = self.log_file.take().or(log_file);
+        self.job = self.job.take().or(job);
 
         Ok(self)
     }
@@ -373,6 +598,68 @@ impl ConfigBuilder {
         self.parse_toml_str(&config_str)
     }
 
+    /// Add a checksum to each batch sent between the hosts, verified on receipt.
+    pub fn checksums(&mut self, checksums: bool) -> &mut Self {
+        self.checksums = checksums;
+        self
+    }
+
+    /// Append the output of all the hosts to a single file, see [`RemoteConfig::log_file`].
+    pub fn log_file(&mut self, path: impl Into<PathBuf>) -> &mut Self {
+        self.log_file = Some(path.into());
+        self
+    }
+
+    /// Apply the profile with the given name, defined in the parsed files as the table
+    /// `[profile.<name>]`.
+    ///
+    /// The values of the profile replace the ones in the rest of the file, the tables are merged
+    /// key by key while the arrays (like the list of hosts) are replaced entirely.
+    pub fn profile(&mut self, name: impl Into<String>) -> &mut Self {
+        self.profile = Some(name.into());
+        self
+    }
+
+    /// Override a value of the configuration, after applying the profile.
+    ///
+    /// The assignment has the form `key=value`, where the key is the path of the value, with the
+    /// components separated by `.` and the elements of the arrays selected by their index (like
+    /// `host.0.num_cores`). The value is parsed as TOML, falling back to a string.
+    pub fn set(&mut self, assignment: &str) -> Result<&mut Self, ConfigError> {
+        let (key, value) = assignment.split_once('=').ok_or_else(|| {
+            ConfigError::Invalid(format!(
+                "invalid override {assignment:?}, expected key=value"
+            ))
+        })?;
+        let value = value.trim();
+        let value = toml::from_str::<toml::Table>(&format!("value = {value}"))
+            .ok()
+            .and_then(|mut table| table.remove("value"))
+            .unwrap_or_else(|| toml::Value::String(value.to_string()));
+        self.overrides.push((key.trim().to_string(), value));
+        Ok(self)
+    }
+
+    /// Read the profile from the environment variable [PROFILE_ENV_VAR] and the overrides from
+    /// [OVERRIDES_ENV_VAR], if they are set.
+    pub fn overrides_from_env(&mut self) -> Result<&mut Self, ConfigError> {
+        if let Ok(profile) = env::var(PROFILE_ENV_VAR) {
+            self.profile(profile);
+        }
+        if let Ok(overrides) = env::var(OVERRIDES_ENV_VAR) {
+            for assignment in overrides.split(';').filter(|a| !a.trim().is_empty()) {
+                self.set(assignment)?;
+            }
+        }
+        Ok(self)
+    }
+
+    /// Share the hosts with other jobs, see [`JobConfig`].
+    pub fn job(&mut self, job: JobConfig) -> &mut Self {
+        self.job = Some(job);
+        self
+    }
+
     pub fn host_id(&mut self, host_id: HostId) -> &mut Self {
         self.host_id = Some(host_id);
         self
@@ -389,22 +676,98 @@ impl ConfigBuilder {
     }
 
     pub fn build(&mut self) -> Result<RuntimeConfig, ConfigError> {
+        let mut config = RemoteConfig {
+            host_id: self.host_id,
+            hosts: self.hosts.clone(),
+            tracing_dir: self.tracing_dir.clone(),
+            cleanup_executable: self.cleanup_executable,
+            checksums: self.checksums,
+            log_file: self.log_file.clone(),
+            job: self.job.clone(),
+        };
+        if self.profile.is_some() || !self.overrides.is_empty() {
+            config = self.resolve(config)?;
+        }
+
         if let Some(host_id) = self.host_id {
-            let num_hosts = self.hosts.len() as u64;
+            let num_hosts = config.hosts.len() as u64;
             if host_id >= num_hosts {
                 return Err(ConfigError::Invalid(format!(
                     "invalid host_id, must be between 0 and the number of hosts - 1: (0..{num_hosts})",
                 )));
             }
         };
+        if let Some(job) = &config.job {
+            if job.id.is_empty()
+                || !job
+                    .id
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                return Err(ConfigError::Invalid(format!(
+                    "invalid job id {:?}, it can only contain letters, digits, `-` and `_`",
+                    job.id
+                )));
+            }
+            if job.ports == 0 {
+                return Err(ConfigError::Invalid(format!(
+                    "job {} must have at least one port",
+                    job.id
+                )));
+            }
+            for host in &config.hosts {
+                let last_port = (job.slot as u32 + 1) * job.ports as u32 + host.base_port as u32;
+                if last_port > u16::MAX as u32 + 1 {
+                    return Err(ConfigError::Invalid(format!(
+                        "the ports of job {} on host {host} exceed the maximum port",
+                        job.id
+                    )));
+                }
+            }
+        }
 
-        let conf = RuntimeConfig::Remote(RemoteConfig {
-            host_id: self.host_id,
-            hosts: self.hosts.clone(),
-            tracing_dir: self.tracing_dir.clone(),
-            cleanup_executable: self.cleanup_executable,
-        });
-        Ok(conf)
+        Ok(RuntimeConfig::Remote(config))
+    }
+
+    /// Apply the profile and the overrides to the configuration.
+    fn resolve(&self, config: RemoteConfig) -> Result<RemoteConfig, ConfigError> {
+        let mut value = toml::Value::try_from(&config)
+            .map_err(|e| ConfigError::Invalid(format!("cannot serialize the config: {e}")))?;
+        if let Some(name) = &self.profile {
+            let profile = self
+                .profiles
+                .get(name)
+                .ok_or_else(|| ConfigError::Invalid(format!("unknown profile {name:?}")))?;
+            merge_toml(&mut value, profile.clone());
+        }
+        for (key, new_value) in &self.overrides {
+            let mut target = &mut value;
+            for component in key.split('.') {
+                target = match target {
+                    toml::Value::Table(table) => table
+                        .entry(component)
+                        .or_insert_with(|| toml::Value::Table(Default::default())),
+                    toml::Value::Array(array) => component
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|index| array.get_mut(index))
+                        .ok_or_else(|| {
+                            ConfigError::Invalid(format!(
+                                "cannot override {key}: no element {component} in the array"
+                            ))
+                        })?,
+                    _ => {
+                        return Err(ConfigError::Invalid(format!(
+                            "cannot override {key}: {component} is not inside a table or an array"
+                        )))
+                    }
+                };
+            }
+            *target = new_value.clone();
+        }
+        let mut config: RemoteConfig = value.try_into()?;
+        config.host_id = self.host_id;
+        Ok(config)
     }
 }
 
@@ -413,6 +776,28 @@ fn ssh_default_port() -> u16 {
     22
 }
 
+/// Merge `overlay` into `base`: the tables are merged recursively, the other values are replaced.
+fn merge_toml(base: &mut toml::Value, overlay: toml::Value) {
+    match (base, overlay) {
+        (toml::Value::Table(base), toml::Value::Table(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(prev) => merge_toml(prev, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Default number of ports of each job, used by the serde default value.
+fn job_default_ports() -> u16 {
+    100
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ConfigError {
     #[error("Serialization error: {0}")]
@@ -427,3 +812,95 @@ pub enum ConfigError {
     #[error("Missing environment variable {0}: {1}")]
     Environment(String, env::VarError),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ConfigBuilder, RuntimeConfig};
+
+    const CONFIG: &str = r#"
+checksums = false
+
+[[host]]
+address = "localhost"
+base_port = 9500
+num_cores = 4
+
+[profile.staging]
+tracing_dir = "/tmp/staging"
+
+[profile.production]
+checksums = true
+[profile.production.job]
+id = "etl"
+slot = 1
+[[profile.production.host]]
+address = "host1"
+base_port = 9500
+num_cores = 16
+[[profile.production.host]]
+address = "host2"
+base_port = 9500
+num_cores = 16
+"#;
+
+    fn remote(builder: &mut ConfigBuilder) -> super::RemoteConfig {
+        match builder.build().unwrap() {
+            RuntimeConfig::Remote(config) => config,
+            RuntimeConfig::Local(_) => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn config_profiles() {
+        let mut builder = ConfigBuilder::new_remote();
+        builder.parse_toml_str(CONFIG).unwrap();
+        let base = remote(&mut builder);
+        assert_eq!(base.hosts.len(), 1);
+        assert!(!base.checksums);
+        assert!(base.job.is_none());
+
+        let staging = remote(builder.profile("staging"));
+        assert_eq!(staging.hosts, base.hosts);
+        assert_eq!(staging.tracing_dir, Some("/tmp/staging".into()));
+
+        let production = remote(builder.profile("production"));
+        assert!(production.checksums);
+        assert_eq!(production.job.unwrap().id, "etl");
+        let addresses: Vec<_> = production.hosts.iter().map(|h| &h.address).collect();
+        assert_eq!(addresses, vec!["host1", "host2"]);
+
+        assert!(builder.profile("testing").build().is_err());
+    }
+
+    #[test]
+    fn config_overrides() {
+        let mut builder = ConfigBuilder::new_remote();
+        builder
+            .parse_toml_str(CONFIG)
+            .unwrap()
+            .profile("production")
+            .set("host.1.num_cores=32")
+            .unwrap()
+            .set("checksums = false")
+            .unwrap()
+            .set("log_file=/var/log/renoir.log")
+            .unwrap()
+            .set("job.quota.max_output_rows=1000")
+            .unwrap()
+            .host_id(1);
+        let config = remote(&mut builder);
+        assert_eq!(config.host_id, Some(1));
+        assert_eq!(config.hosts[0].num_cores, 16);
+        assert_eq!(config.hosts[1].num_cores, 32);
+        assert!(!config.checksums);
+        assert_eq!(config.log_file, Some("/var/log/renoir.log".into()));
+        let quota = config.job.unwrap().quota;
+        assert_eq!(quota.max_output_rows, Some(1000));
+        assert_eq!(quota.max_spill_bytes, None);
+        assert_eq!(quota.max_state_bytes, None);
+
+        assert!(builder.set("checksums").is_err());
+        let err = builder.set("host.5.num_cores=1").unwrap().build();
+        assert!(err.is_err());
+    }
+}
@@ -1,9 +1,14 @@
 use parking_lot::Mutex;
 use std::any::TypeId;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+use std::thread::JoinHandle;
 
-use crate::block::{Block, Scheduling};
+use crate::block::{Block, ExecutionPlan, Scheduling};
 use crate::config::RuntimeConfig;
+use crate::listener::ExecutionListener;
 use crate::operator::iteration::IterationStateLock;
 use crate::operator::source::Source;
 use crate::operator::{Data, Operator};
@@ -14,6 +19,72 @@ use crate::{BatchMode, CoordUInt};
 
 // static LAST_REMOTE_CONFIG: Lazy<Mutex<Option<RemoteConfig>>> = Lazy::new(|| Mutex::new(None));
 
+#[derive(Default)]
+struct HandleState {
+    finished: bool,
+    waker: Option<Waker>,
+}
+
+/// Mark the execution as finished when dropped by its thread.
+struct Completion(Arc<Mutex<HandleState>>);
+
+impl Drop for Completion {
+    fn drop(&mut self) {
+        let mut state = self.0.lock();
+        state.finished = true;
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Handle of a computation started with [`StreamContext::execute_background`].
+///
+/// The handle is a future that completes when the computation ends. If the computation panics,
+/// the panic is propagated when the handle is awaited or joined.
+pub struct ExecutionHandle {
+    state: Arc<Mutex<HandleState>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ExecutionHandle {
+    /// Whether the computation has ended.
+    pub fn is_finished(&self) -> bool {
+        self.state.lock().finished
+    }
+
+    /// Block the current thread until the computation ends.
+    pub fn join(mut self) {
+        self.join_thread();
+    }
+
+    fn join_thread(&mut self) {
+        let thread = self
+            .thread
+            .take()
+            .expect("ExecutionHandle polled after completion");
+        if let Err(panic) = thread.join() {
+            std::panic::resume_unwind(panic);
+        }
+    }
+}
+
+impl Future for ExecutionHandle {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        {
+            let mut state = self.state.lock();
+            if !state.finished {
+                state.waker = Some(cx.waker().clone());
+                return Poll::Pending;
+            }
+        }
+        self.join_thread();
+        Poll::Ready(())
+    }
+}
+
 /// Actual content of the StreamContext. This is stored inside a `Rc` and it's shared among all
 /// the blocks.
 pub(crate) struct StreamContextInner {
@@ -97,6 +168,103 @@ impl StreamContext {
         info!("finished execution");
     }
 
+    /// Start the computation in a background thread, returning immediately.
+    ///
+    /// The returned [`ExecutionHandle`] can be awaited from any async runtime, or joined, to wait
+    /// for the end of the computation: after that the [`StreamOutput`](crate::operator::sink::StreamOutput)s
+    /// of the job hold their results. This allows embedding a job in an async service without
+    /// blocking one of its threads, even without the `tokio` feature.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+    /// let res = env.stream_iter(0..10).collect_vec();
+    ///
+    /// let handle = env.execute_background();
+    /// // `handle.await` in an async context
+    /// handle.join();
+    ///
+    /// assert_eq!(res.get().unwrap(), (0..10).collect::<Vec<_>>());
+    /// ```
+    pub fn execute_background(self) -> ExecutionHandle {
+        let state = Arc::new(Mutex::new(HandleState::default()));
+        let completion = Completion(state.clone());
+        let thread = std::thread::Builder::new()
+            .name("renoir-execute".into())
+            .spawn(move || {
+                // notify the handle even if the execution panics
+                let _completion = completion;
+                self.execute_blocking();
+            })
+            .expect("cannot spawn the thread of the execution");
+        ExecutionHandle {
+            state,
+            thread: Some(thread),
+        }
+    }
+
+    /// Describe how the job would be executed, without executing it.
+    ///
+    /// The plan contains the blocks of the job graph, how many replicas of each block every host
+    /// runs, the channels between the replicas and the state kept by the stateful operators. It
+    /// can be used for checking a deployment before launching it, since the sources are not
+    /// opened and no socket is bound.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+    /// let res = env
+    ///     .stream_iter(0..100u64)
+    ///     .group_by_fold(|n| n % 10, 0, |acc, n| *acc += n, |acc, n| *acc += n)
+    ///     .collect_vec();
+    ///
+    /// let plan = env.plan();
+    /// println!("{plan}");
+    /// // the local fold, the global fold and the collection of the results
+    /// assert_eq!(plan.blocks.len(), 3);
+    /// // a replica of the global fold needs 16 bytes for each of the 10 keys
+    /// assert_eq!(plan.blocks[1].state_size(10, 0), 160);
+    /// ```
+    pub fn plan(&self) -> ExecutionPlan {
+        self.inner
+            .lock()
+            .scheduler
+            .as_ref()
+            .expect("The job has already been executed")
+            .plan()
+    }
+
+    /// Register a listener notified when the job starts, when each replica of a block is scheduled
+    /// and when the job completes, see [`ExecutionListener`].
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{ExecutionListener, StreamContext, RuntimeConfig, TracingData};
+    /// # let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+    /// struct Alert;
+    ///
+    /// impl ExecutionListener for Alert {
+    ///     fn on_job_completed(&self, tracing: &TracingData) {
+    ///         println!("job completed with {} replicas", tracing.structures.len());
+    ///     }
+    /// }
+    ///
+    /// env.add_listener(Alert);
+    /// env.stream_iter(0..10).for_each(|_| {});
+    /// env.execute_blocking();
+    /// ```
+    pub fn add_listener(&self, listener: impl ExecutionListener + 'static) {
+        self.inner
+            .lock()
+            .scheduler_mut()
+            .add_listener(Arc::new(listener));
+    }
+
     /// Get the total number of processing cores in the cluster.
     pub fn parallelism(&self) -> CoordUInt {
         match &self.inner.lock().config {
@@ -119,18 +119,24 @@ extern crate tracing;
 
 pub use block::structure;
 pub use block::BatchMode;
+pub use block::Priority;
 pub use block::Replication;
 pub use block::{group_by_hash, GroupHasherBuilder};
 pub use config::RuntimeConfig;
-pub use environment::StreamContext;
+pub use environment::{ExecutionHandle, StreamContext};
+pub use listener::ExecutionListener;
 pub use operator::iteration::IterationStateHandle;
+pub use profiler::TracingData;
 pub use scheduler::ExecutionMetadata;
 pub use stream::{KeyedStream, Stream, WindowedStream};
 
+#[cfg(feature = "bench")]
+pub mod bench;
 pub(crate) mod block;
 pub(crate) mod channel;
 pub mod config;
 pub(crate) mod environment;
+pub(crate) mod listener;
 pub(crate) mod network;
 pub mod operator;
 mod profiler;
@@ -140,6 +146,8 @@ pub(crate) mod scheduler;
 pub(crate) mod stream;
 #[cfg(test)]
 pub(crate) mod test;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub(crate) mod worker;
 
 pub type CoordUInt = u64;
@@ -0,0 +1,33 @@
+use crate::block::structure::{BlockStructure, ExecutionPlan};
+use crate::profiler::TracingData;
+use crate::scheduler::{BlockId, ReplicaId};
+
+/// Receive the events of the execution of a job on this host.
+///
+/// A listener is registered with [`StreamContext::add_listener`](crate::StreamContext::add_listener)
+/// and can be used to integrate the job with an external orchestrator or alerting system. All the
+/// callbacks are called from the thread that executes the job, and do nothing by default.
+///
+/// In a distributed environment each host calls the listeners registered in its own process, with
+/// the events of the replicas it runs.
+pub trait ExecutionListener: Send + Sync {
+    /// The execution of the job is starting, before any block is set up.
+    fn on_job_start(&self, _plan: &ExecutionPlan) {}
+
+    /// A replica of a block has been set up and is about to be started.
+    ///
+    /// `global_id` is the identifier of the replica among all the replicas of the block, like
+    /// [`ExecutionMetadata::global_id`](crate::ExecutionMetadata::global_id).
+    fn on_block_scheduled(
+        &self,
+        _block_id: BlockId,
+        _global_id: ReplicaId,
+        _structure: &BlockStructure,
+    ) {
+    }
+
+    /// All the replicas of the job on this host have terminated.
+    ///
+    /// The profiler results in `tracing` are empty unless the `profiler` feature is enabled.
+    fn on_job_completed(&self, _tracing: &TracingData) {}
+}
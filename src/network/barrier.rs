@@ -0,0 +1,216 @@
+//! Startup barrier between the hosts of a remote execution.
+//!
+//! A multiplexer that connects to a remote host before its demultiplexer has bound the socket is
+//! refused, and after too many attempts it gives up. For this reason the hosts wait for each other
+//! before starting: each host binds all its sockets and then tells the first host it is ready; when
+//! all the hosts are ready the first one lets them start.
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+use crate::scheduler::HostId;
+
+/// Maximum time to wait for the first host to accept the connection, and for the first host to
+/// wait for all the others.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(300);
+/// How often the first host checks for new connections while waiting for the other hosts.
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+/// To avoid spamming the connections, wait this timeout before trying again. If the connection
+/// fails again this timeout will be doubled up to `RETRY_MAX_TIMEOUT`.
+const RETRY_INITIAL_TIMEOUT: Duration = Duration::from_millis(8);
+/// Maximum timeout between connection attempts.
+const RETRY_MAX_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Wait until all the `num_hosts` hosts have reached the barrier.
+///
+/// The first host listens at `address` for the other hosts, which connect to it and send their
+/// id. Once all of them are connected, the first host replies to each one and the barrier is
+/// passed.
+pub(crate) fn startup_barrier(host_id: HostId, num_hosts: usize, address: (String, u16)) {
+    if num_hosts <= 1 {
+        return;
+    }
+    if host_id == 0 {
+        wait_hosts(num_hosts, address, CONNECT_TIMEOUT);
+    } else {
+        notify_ready(host_id, address);
+    }
+    log::debug!("host {host_id} passed the startup barrier");
+}
+
+/// Wait for the other hosts to be ready and then release them.
+///
+/// Panics if not all the hosts are ready within `timeout`, or if a host sends an id that is not
+/// valid for the configuration or that was already received.
+fn wait_hosts(num_hosts: usize, address: (String, u16), timeout: Duration) {
+    let listener = TcpListener::bind((address.0.as_str(), address.1)).unwrap_or_else(|e| {
+        panic!("Failed to bind socket for the startup barrier at {address:?}: {e:?}")
+    });
+    listener
+        .set_nonblocking(true)
+        .expect("Failed to set the startup barrier socket as non-blocking");
+    log::debug!("startup barrier at {address:?}, waiting for {num_hosts} hosts");
+
+    let deadline = Instant::now() + timeout;
+    let mut hosts: Vec<Option<TcpStream>> = (0..num_hosts).map(|_| None).collect();
+    let mut ready = 1;
+    while ready < num_hosts {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            let missing: Vec<_> = (1..num_hosts).filter(|&h| hosts[h].is_none()).collect();
+            panic!(
+                "Startup barrier at {address:?}: hosts {missing:?} did not connect within {}s",
+                timeout.as_secs_f64()
+            );
+        }
+        let mut stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                std::thread::sleep(ACCEPT_POLL_INTERVAL.min(remaining));
+                continue;
+            }
+            Err(e) => {
+                log::warn!("startup barrier failed to accept incoming connection: {e:?}");
+                continue;
+            }
+        };
+        stream
+            .set_nonblocking(false)
+            .and_then(|_| stream.set_read_timeout(Some(remaining)))
+            .expect("Failed to configure a connection of the startup barrier");
+        let mut host_id = [0; 8];
+        stream
+            .read_exact(&mut host_id)
+            .expect("Failed to receive the id of a host at the startup barrier");
+        let host_id = HostId::from_le_bytes(host_id);
+        match usize::try_from(host_id) {
+            Ok(id @ 1..) if id < num_hosts => {
+                if hosts[id].is_some() {
+                    panic!(
+                        "Startup barrier at {address:?}: host {host_id} connected twice, check \
+                        that each host is started with a different id"
+                    );
+                }
+                hosts[id] = Some(stream);
+            }
+            _ => panic!(
+                "Startup barrier at {address:?}: received invalid host id {host_id}, the \
+                configuration has hosts from 1 to {} connecting to host 0",
+                num_hosts - 1
+            ),
+        }
+        ready += 1;
+        log::debug!("host {host_id} ready ({ready} / {num_hosts})");
+    }
+
+    for mut stream in hosts.into_iter().flatten() {
+        stream
+            .write_all(&[1])
+            .expect("Failed to release a host from the startup barrier");
+    }
+}
+
+/// Tell the first host that this host is ready and wait until it releases all the hosts.
+fn notify_ready(host_id: HostId, address: (String, u16)) {
+    let socket_addrs: Vec<_> = address
+        .to_socket_addrs()
+        .unwrap_or_else(|e| panic!("Failed to get the address of the startup barrier: {e:?}"))
+        .collect();
+
+    let start = Instant::now();
+    let mut retry_delay = RETRY_INITIAL_TIMEOUT;
+    let mut stream = loop {
+        match TcpStream::connect(&*socket_addrs) {
+            Ok(stream) => break stream,
+            Err(e) if start.elapsed() < CONNECT_TIMEOUT => {
+                if e.kind() != ErrorKind::ConnectionRefused {
+                    log::warn!("host {host_id} failed to connect to the startup barrier: {e:?}");
+                }
+                std::thread::sleep(retry_delay);
+                retry_delay = (2 * retry_delay).min(RETRY_MAX_TIMEOUT);
+            }
+            Err(e) => panic!(
+                "Host {host_id} failed to reach the startup barrier at {address:?} in {}s: {e:?}",
+                CONNECT_TIMEOUT.as_secs()
+            ),
+        }
+    };
+
+    stream
+        .write_all(&host_id.to_le_bytes())
+        .expect("Failed to notify the startup barrier");
+    let mut release = [0];
+    stream
+        .read_exact(&mut release)
+        .expect("The first host failed before releasing the startup barrier");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+    use std::time::{Duration, Instant};
+
+    use super::{notify_ready, startup_barrier, wait_hosts};
+
+    fn free_address() -> (String, u16) {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        ("127.0.0.1".to_string(), port)
+    }
+
+    #[test]
+    fn startup_barrier_waits_all_hosts() {
+        let address = free_address();
+        let delay = Duration::from_millis(200);
+        let start = Instant::now();
+        let hosts: Vec<_> = (0..4)
+            .map(|host_id| {
+                let address = address.clone();
+                std::thread::spawn(move || {
+                    // the last host is late: nobody can pass the barrier before it arrives
+                    if host_id == 3 {
+                        std::thread::sleep(delay);
+                    }
+                    startup_barrier(host_id, 4, address);
+                    start.elapsed()
+                })
+            })
+            .collect();
+        for host in hosts {
+            assert!(host.join().unwrap() >= delay);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "hosts [2] did not connect within")]
+    fn startup_barrier_timeout() {
+        let address = free_address();
+        let host = address.clone();
+        std::thread::spawn(move || notify_ready(1, host));
+        wait_hosts(3, address, Duration::from_millis(300));
+    }
+
+    #[test]
+    #[should_panic(expected = "received invalid host id 3")]
+    fn startup_barrier_host_out_of_range() {
+        let address = free_address();
+        let host = address.clone();
+        std::thread::spawn(move || notify_ready(3, host));
+        wait_hosts(3, address, Duration::from_secs(10));
+    }
+
+    #[test]
+    #[should_panic(expected = "host 1 connected twice")]
+    fn startup_barrier_duplicate_host() {
+        let address = free_address();
+        for _ in 0..2 {
+            let host = address.clone();
+            std::thread::spawn(move || notify_ready(1, host));
+        }
+        wait_hosts(3, address, Duration::from_secs(10));
+    }
+}
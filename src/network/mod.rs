@@ -16,11 +16,55 @@ use tokio::*;
 #[cfg(not(feature = "tokio"))]
 mod sync;
 #[cfg(not(feature = "tokio"))]
+pub(crate) use sync::remote::{remote_recv, remote_send};
+#[cfg(not(feature = "tokio"))]
 use sync::*;
 
+mod barrier;
 mod network_channel;
 mod topology;
 
+/// An error receiving a message from a remote host. The connection cannot be used anymore.
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum RemoteRecvError {
+    #[error("failed to receive message {sequence} to {coord} from {address}: {source}")]
+    Io {
+        coord: DemuxCoord,
+        address: String,
+        sequence: u64,
+        source: std::io::Error,
+    },
+    #[error("malformed header of message {sequence} to {coord} from {address}: {source}")]
+    MalformedHeader {
+        coord: DemuxCoord,
+        address: String,
+        sequence: u64,
+        source: bincode::Error,
+    },
+    #[error(
+        "message {received} to {coord} from {address} received out of order, expected {expected}"
+    )]
+    OutOfOrder {
+        coord: DemuxCoord,
+        address: String,
+        received: u64,
+        expected: u64,
+    },
+    #[error("corrupted message {sequence} to {coord} from {address}: checksum mismatch")]
+    Checksum {
+        coord: DemuxCoord,
+        address: String,
+        sequence: u64,
+    },
+    #[error("malformed message {sequence} to {coord} from {address}: {source}")]
+    MalformedMessage {
+        coord: DemuxCoord,
+        address: String,
+        sequence: u64,
+        source: bincode::Error,
+    },
+}
+
 #[derive(Debug, Clone)]
 pub enum NetworkDataIterator<T> {
     Batch(std::vec::IntoIter<T>),
@@ -64,13 +64,12 @@ impl<In: Send + 'static> NetworkReceiver<In> {
         &self,
         message: Result<NetworkMessage<In>, E>,
     ) -> Result<NetworkMessage<In>, E> {
-        message.map(|message| {
+        message.inspect(|message| {
             get_profiler().items_in(
                 message.sender,
                 self.receiver_endpoint.coord,
                 message.num_items(),
             );
-            message
         })
     }
 
@@ -109,6 +108,28 @@ impl<In: Send + 'static> NetworkReceiver<In> {
     ) -> Result<SelectResult<NetworkMessage<In>, NetworkMessage<In2>>, RecvTimeoutError> {
         self.receiver.select_timeout(&other.receiver, timeout)
     }
+
+    /// Receive a message from any of the provided receivers, returning also the index of the
+    /// receiver it comes from.
+    ///
+    /// Like `select`, but with any number of receivers of the same type.
+    pub fn select_any(
+        receivers: &[&NetworkReceiver<In>],
+    ) -> (usize, Result<NetworkMessage<In>, RecvError>) {
+        let inner: Vec<_> = receivers.iter().map(|r| &r.receiver).collect();
+        let (i, message) = Receiver::select_any(&inner);
+        (i, receivers[i].profile_message(message))
+    }
+
+    /// Same as `select_any`, with a timeout.
+    pub fn select_any_timeout(
+        receivers: &[&NetworkReceiver<In>],
+        timeout: Duration,
+    ) -> Result<(usize, Result<NetworkMessage<In>, RecvError>), RecvTimeoutError> {
+        let inner: Vec<_> = receivers.iter().map(|r| &r.receiver).collect();
+        let (i, message) = Receiver::select_any_timeout(&inner, timeout)?;
+        Ok((i, receivers[i].profile_message(message)))
+    }
 }
 
 /// The sender part of a connection between two replicas.
@@ -28,11 +28,13 @@ impl<In: ExchangeData> DemuxHandle<In> {
     /// All the local replicas of this block should be registered to this demultiplexer.
     /// `num_client` is the number of multiplexers that will connect to this demultiplexer. Since
     /// the remote senders are all multiplexed this corresponds to the number of remote replicas in
-    /// the previous block (relative to the block this demultiplexer refers to).
+    /// the previous block (relative to the block this demultiplexer refers to). A message is sent
+    /// to `ready` once the socket is bound.
     pub fn new(
         coord: DemuxCoord,
         address: (String, u16),
         num_clients: usize,
+        ready: flume::Sender<()>,
     ) -> (Self, JoinHandle<()>) {
         let (tx_senders, rx_senders) = channel::unbounded();
         let join_handle = std::thread::Builder::new()
@@ -40,7 +42,7 @@ impl<In: ExchangeData> DemuxHandle<In> {
                 "reg-{}:{}-{}",
                 coord.coord.host_id, coord.prev_block_id, coord.coord.block_id
             ))
-            .spawn(move || bind_remotes(coord, address, num_clients, rx_senders))
+            .spawn(move || bind_remotes(coord, address, num_clients, rx_senders, ready))
             .unwrap();
         (Self { coord, tx_senders }, join_handle)
     }
@@ -64,6 +66,7 @@ fn bind_remotes<In: ExchangeData>(
     address: (String, u16),
     num_clients: usize,
     rx_senders: UnboundedReceiver<(ReceiverEndpoint, Sender<NetworkMessage<In>>)>,
+    ready: flume::Sender<()>,
 ) {
     let address = (address.0.as_ref(), address.1);
     let address: Vec<_> = address
@@ -85,6 +88,9 @@ fn bind_remotes<In: ExchangeData>(
         .local_addr()
         .map(|a| a.to_string())
         .unwrap_or_else(|_| "unknown".to_string());
+    let _ = ready.send(());
+    // only the demultiplexers still binding keep the channel alive, so that a failure disconnects it
+    drop(ready);
     debug!(
         "{} ready at {}, waiting for {} clients",
         coord, address, num_clients
@@ -175,12 +181,64 @@ fn demux_thread<In: ExchangeData>(
     // let mut r = std::io::BufReader::new(&mut stream);
     let mut r = &mut stream;
 
-    while let Some((dest, message)) = remote_recv(coord, &mut r, &address) {
-        if let Err(e) = senders[&dest].send(message) {
-            warn!("demux failed to send message to {}: {:?}", dest, e);
+    let mut sequence = 0;
+    loop {
+        match remote_recv(coord, &mut r, &address, &mut sequence) {
+            Ok(Some((dest, message))) => {
+                if let Err(e) = senders[&dest].send(message) {
+                    warn!("demux failed to send message to {}: {:?}", dest, e);
+                }
+            }
+            Ok(None) => break,
+            Err(e) => {
+                log::error!("{coord} closing the connection: {e}");
+                break;
+            }
         }
     }
 
     let _ = stream.shutdown(Shutdown::Both);
     log::debug!("{} finished", coord);
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+
+    use super::demux_thread;
+    use crate::channel;
+    use crate::network::remote::remote_send;
+    use crate::network::{Coord, NetworkMessage, ReceiverEndpoint};
+    use crate::operator::StreamElement;
+
+    #[test]
+    fn demux_closes_corrupted_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+
+        let endpoint = ReceiverEndpoint::new(Coord::new(1, 0, 0), 0);
+        let (tx, rx) = channel::bounded(16);
+        let senders = HashMap::from([(endpoint, tx)]);
+        let demux =
+            std::thread::spawn(move || demux_thread::<u64>(endpoint.into(), senders, server));
+
+        // a valid message followed by a corrupted one
+        let mut wire = Vec::new();
+        for sequence in 0..2 {
+            let msg = NetworkMessage::new_single(StreamElement::Item(sequence), Coord::default());
+            remote_send(msg, endpoint, &mut wire, "test", sequence, true);
+        }
+        *wire.last_mut().unwrap() ^= 1;
+        client.write_all(&wire).unwrap();
+
+        // the demultiplexer stops without panicking, dropping the senders
+        demux.join().unwrap();
+        assert!(rx.recv().is_ok());
+        assert!(rx.recv().is_err());
+        // and closes the connection
+        assert_eq!(client.read(&mut [0]).unwrap(), 0);
+    }
+}
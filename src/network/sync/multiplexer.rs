@@ -34,7 +34,12 @@ pub struct MultiplexingSender<Out: ExchangeData> {
 }
 
 impl<Out: ExchangeData> MultiplexingSender<Out> {
-    pub fn new(coord: DemuxCoord, address: (String, u16)) -> (Self, JoinHandle<()>) {
+    pub fn new(
+        coord: DemuxCoord,
+        address: (String, u16),
+        checksums: bool,
+        start: flume::Receiver<()>,
+    ) -> (Self, JoinHandle<()>) {
         let (tx, rx) = channel::bounded(MUX_CHANNEL_CAPACITY);
 
         let join_handle = std::thread::Builder::new()
@@ -43,13 +48,15 @@ impl<Out: ExchangeData> MultiplexingSender<Out> {
                 coord.coord.host_id, coord.prev_block_id, coord.coord.block_id
             ))
             .spawn(move || {
+                // wait for the startup barrier, the channel is closed when it is passed
+                let _ = start.recv();
                 log::debug!(
                     "mux {coord} connecting to {}",
                     address.to_socket_addrs().unwrap().next().unwrap()
                 );
                 let stream = connect_remote(coord, address);
 
-                mux_thread::<Out>(coord, rx, stream);
+                mux_thread::<Out>(coord, rx, stream, checksums);
             })
             .unwrap();
         (Self { tx: Some(tx) }, join_handle)
@@ -122,6 +129,7 @@ fn mux_thread<Out: ExchangeData>(
     coord: DemuxCoord,
     rx: Receiver<(ReceiverEndpoint, NetworkMessage<Out>)>,
     mut stream: TcpStream,
+    checksums: bool,
 ) {
     use std::io::Write;
 
@@ -134,8 +142,10 @@ fn mux_thread<Out: ExchangeData>(
     // let mut w = std::io::BufWriter::new(&mut stream);
     let mut w = &mut stream;
 
+    let mut sequence = 0;
     while let Ok((dest, message)) = rx.recv() {
-        remote_send(message, dest, &mut w, &address);
+        remote_send(message, dest, &mut w, &address, sequence, checksums);
+        sequence += 1;
     }
 
     w.flush().unwrap();
@@ -8,7 +8,7 @@ use bincode::config::{FixintEncoding, RejectTrailing, WithOtherIntEncoding, With
 use bincode::{DefaultOptions, Options};
 use serde::{Deserialize, Serialize};
 
-use crate::network::{Coord, DemuxCoord, NetworkMessage, ReceiverEndpoint};
+use crate::network::{Coord, DemuxCoord, NetworkMessage, ReceiverEndpoint, RemoteRecvError};
 use crate::operator::ExchangeData;
 use crate::profiler::{get_profiler, Profiler};
 use crate::scheduler::BlockId;
@@ -25,7 +25,10 @@ static BINCODE_HEADER_CONFIG: Lazy<
 
 static BINCODE_MSG_CONFIG: Lazy<DefaultOptions> = Lazy::new(bincode::DefaultOptions::new);
 
-pub(crate) const HEADER_SIZE: usize = 20; // std::mem::size_of::<MessageHeader>();
+pub(crate) const HEADER_SIZE: usize = 37; // std::mem::size_of::<MessageHeader>();
+
+/// The seed of the checksums of the messages.
+const CHECKSUM_SEED: u64 = 0x6e6f6972;
 
 /// Header of a message sent before the actual message.
 #[derive(Serialize, Deserialize, Default)]
@@ -36,6 +39,17 @@ struct MessageHeader {
     replica_id: ReplicaId,
     /// The id of the block that is sending the message.
     sender_block_id: BlockId,
+    /// The position of the message in the connection, starting from zero.
+    sequence: u64,
+    /// Whether `checksum` has been computed.
+    has_checksum: bool,
+    /// The checksum of the actual message.
+    checksum: u64,
+}
+
+/// The checksum of a serialized message.
+fn checksum(buf: &[u8]) -> u64 {
+    wyhash::wyhash(buf, CHECKSUM_SEED)
 }
 
 /// Serialize and send a message to a remote socket.
@@ -43,12 +57,17 @@ struct MessageHeader {
 /// The network protocol works as follow:
 /// - send a `MessageHeader` serialized with bincode with `FixintEncoding`
 /// - send the message
+///
+/// `sequence` is the number of messages already sent on the connection. If `with_checksum` the
+/// header contains the checksum of the message.
 #[cfg(not(feature = "tokio"))]
 pub(crate) fn remote_send<T: ExchangeData, W: Write>(
     msg: NetworkMessage<T>,
     dest: ReceiverEndpoint,
     writer: &mut W,
     address: &str,
+    sequence: u64,
+    with_checksum: bool,
 ) {
     let serialized_len = BINCODE_MSG_CONFIG
         .serialized_size(&msg)
@@ -56,32 +75,40 @@ pub(crate) fn remote_send<T: ExchangeData, W: Write>(
             panic!("Failed to compute serialized length of outgoing message to {dest}: {e:?}",)
         });
 
+    // the header is written once the checksum of the message is known
+    let mut buf = vec![0; HEADER_SIZE];
+    buf.reserve(serialized_len as usize);
+
+    BINCODE_MSG_CONFIG
+        .serialize_into(&mut buf, &msg)
+        .unwrap_or_else(|e| {
+            panic!(
+                "Failed to serialize message, {serialized_len} bytes to {dest} at {address}: {e:?}",
+            )
+        });
+
+    assert_eq!(buf.len(), HEADER_SIZE + serialized_len as usize);
+
     let header = MessageHeader {
         size: serialized_len.try_into().unwrap(),
         replica_id: dest.coord.replica_id,
         sender_block_id: dest.prev_block_id,
+        sequence,
+        has_checksum: with_checksum,
+        checksum: if with_checksum {
+            checksum(&buf[HEADER_SIZE..])
+        } else {
+            0
+        },
     };
-
-    let mut buf = Vec::with_capacity(HEADER_SIZE + serialized_len as usize);
-
     BINCODE_HEADER_CONFIG
-        .serialize_into(&mut buf, &header)
+        .serialize_into(&mut buf[..HEADER_SIZE], &header)
         .unwrap_or_else(|e| {
             panic!(
                 "Failed to serialize header of message (was {serialized_len} bytes) to {dest} at {address}: {e:?}",
             )
         });
 
-    BINCODE_MSG_CONFIG
-        .serialize_into(&mut buf, &msg)
-        .unwrap_or_else(|e| {
-            panic!(
-                "Failed to serialize message, {serialized_len} bytes to {dest} at {address}: {e:?}",
-            )
-        });
-
-    assert_eq!(buf.len(), HEADER_SIZE + serialized_len as usize);
-
     writer.write_all(buf.as_ref()).unwrap_or_else(|e| {
         panic!("Failed to send message {serialized_len} bytes to {dest} at {address}: {e:?}",)
     });
@@ -93,8 +120,11 @@ pub(crate) fn remote_send<T: ExchangeData, W: Write>(
     );
 }
 
-/// Receive a message from the remote channel. Returns `None` if there was a failure receiving the
-/// last message.
+/// Receive a message from the remote channel. Returns `Ok(None)` if the connection has been
+/// closed before the next message.
+///
+/// After an error the connection must be closed: the stream may be in the middle of a message and
+/// the following ones cannot be trusted.
 ///
 /// The message won't be deserialized, use `deserialize()`.
 #[cfg(not(feature = "tokio"))]
@@ -102,7 +132,8 @@ pub(crate) fn remote_recv<T: ExchangeData, R: Read>(
     coord: DemuxCoord,
     reader: &mut R,
     address: &str,
-) -> Option<(ReceiverEndpoint, NetworkMessage<T>)> {
+    sequence: &mut u64,
+) -> Result<Option<(ReceiverEndpoint, NetworkMessage<T>)>, RemoteRecvError> {
     let mut header = [0u8; HEADER_SIZE];
     match reader.read_exact(&mut header) {
         Ok(_) => {}
@@ -114,29 +145,58 @@ pub(crate) fn remote_recv<T: ExchangeData, R: Read>(
                 address,
                 e
             );
-            return None;
+            return Ok(None);
         }
     }
     let header: MessageHeader = BINCODE_HEADER_CONFIG
         .deserialize(&header)
-        .expect("Malformed header");
+        .map_err(|source| RemoteRecvError::MalformedHeader {
+            coord,
+            address: address.to_string(),
+            sequence: *sequence,
+            source,
+        })?;
     let mut buf = vec![0u8; header.size as usize];
-    reader.read_exact(&mut buf).unwrap_or_else(|e| {
-        panic!(
-            "Failed to receive {} bytes to {} from {}: {:?}",
-            header.size, coord, address, e
-        )
-    });
-    let msg: NetworkMessage<T> = BINCODE_MSG_CONFIG
-        .deserialize(buf.as_ref())
-        .expect("Malformed message");
+    reader
+        .read_exact(&mut buf)
+        .map_err(|source| RemoteRecvError::Io {
+            coord,
+            address: address.to_string(),
+            sequence: header.sequence,
+            source,
+        })?;
+    if header.sequence != *sequence {
+        return Err(RemoteRecvError::OutOfOrder {
+            coord,
+            address: address.to_string(),
+            received: header.sequence,
+            expected: *sequence,
+        });
+    }
+    *sequence += 1;
+    if header.has_checksum && header.checksum != checksum(&buf) {
+        return Err(RemoteRecvError::Checksum {
+            coord,
+            address: address.to_string(),
+            sequence: header.sequence,
+        });
+    }
+    let msg: NetworkMessage<T> =
+        BINCODE_MSG_CONFIG
+            .deserialize(buf.as_ref())
+            .map_err(|source| RemoteRecvError::MalformedMessage {
+                coord,
+                address: address.to_string(),
+                sequence: header.sequence,
+                source,
+            })?;
 
     let dest = ReceiverEndpoint::new(
         Coord::new(coord.coord.block_id, coord.coord.host_id, header.replica_id),
         header.sender_block_id,
     );
     get_profiler().net_bytes_in(msg.sender, dest.coord, HEADER_SIZE + header.size as usize);
-    Some((dest, msg))
+    Ok(Some((dest, msg)))
 }
 
 #[cfg(test)]
@@ -145,7 +205,44 @@ mod tests {
 
     use crate::network::remote::HEADER_SIZE;
 
-    use super::{MessageHeader, BINCODE_HEADER_CONFIG};
+    use super::{remote_recv, remote_send, MessageHeader, BINCODE_HEADER_CONFIG};
+    use crate::network::{Coord, DemuxCoord, NetworkMessage, ReceiverEndpoint, RemoteRecvError};
+    use crate::operator::StreamElement;
+
+    fn endpoint() -> ReceiverEndpoint {
+        ReceiverEndpoint::new(Coord::new(1, 0, 2), 0)
+    }
+
+    /// Send the messages with the given sequence numbers, returning the bytes on the wire.
+    fn send(sequences: &[u64], with_checksum: bool) -> Vec<u8> {
+        let mut wire = Vec::new();
+        for &sequence in sequences {
+            let msg = NetworkMessage::new_batch(
+                vec![StreamElement::Item(sequence), StreamElement::FlushBatch],
+                Coord::new(0, 1, 0),
+            );
+            remote_send(msg, endpoint(), &mut wire, "test", sequence, with_checksum);
+        }
+        wire
+    }
+
+    fn recv_all(wire: &[u8]) -> Result<Vec<u64>, RemoteRecvError> {
+        let coord = DemuxCoord::from(endpoint());
+        let mut reader = wire;
+        let mut sequence = 0;
+        let mut res = Vec::new();
+        while let Some((dest, msg)) =
+            remote_recv::<u64, _>(coord, &mut reader, "test", &mut sequence)?
+        {
+            assert_eq!(dest, endpoint());
+            for el in msg {
+                if let StreamElement::Item(x) = el {
+                    res.push(x);
+                }
+            }
+        }
+        Ok(res)
+    }
 
     #[test]
     fn header_size() {
@@ -155,4 +252,53 @@ mod tests {
 
         assert_eq!(HEADER_SIZE as u64, computed_size);
     }
+
+    #[test]
+    fn remote_checksums() {
+        for with_checksum in [false, true] {
+            assert_eq!(
+                recv_all(&send(&[0, 1, 2], with_checksum)).unwrap(),
+                vec![0, 1, 2]
+            );
+        }
+    }
+
+    #[test]
+    fn remote_corrupted_frame() {
+        // flip a bit of the payload of the last message
+        let mut wire = send(&[0, 1], true);
+        *wire.last_mut().unwrap() ^= 1;
+        let err = recv_all(&wire).unwrap_err();
+        assert!(
+            matches!(err, RemoteRecvError::Checksum { sequence: 1, .. }),
+            "{err}"
+        );
+        assert!(err.to_string().contains("checksum mismatch"), "{err}");
+    }
+
+    #[test]
+    fn remote_sequence_numbers() {
+        let err = recv_all(&send(&[0, 2, 1], false)).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                RemoteRecvError::OutOfOrder {
+                    received: 2,
+                    expected: 1,
+                    ..
+                }
+            ),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn remote_truncated_frame() {
+        let wire = send(&[0, 1], true);
+        let err = recv_all(&wire[..wire.len() - 1]).unwrap_err();
+        assert!(
+            matches!(err, RemoteRecvError::Io { sequence: 1, .. }),
+            "{err}"
+        );
+    }
 }
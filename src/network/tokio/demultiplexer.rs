@@ -34,15 +34,18 @@ impl<In: ExchangeData> DemuxHandle<In> {
     /// All the local replicas of this block should be registered to this demultiplexer.
     /// `num_client` is the number of multiplexers that will connect to this demultiplexer. Since
     /// the remote senders are all multiplexed this corresponds to the number of remote replicas in
-    /// the previous block (relative to the block this demultiplexer refers to).
+    /// the previous block (relative to the block this demultiplexer refers to). A message is sent
+    /// to `ready` once the socket is bound.
     pub fn new(
         coord: DemuxCoord,
         address: (String, u16),
         num_clients: usize,
+        ready: flume::Sender<()>,
     ) -> (Self, JoinHandle<()>) {
         let (tx_senders, rx_senders) = channel::unbounded();
 
-        let join_handle = tokio::spawn(bind_remotes(coord, address, num_clients, rx_senders));
+        let join_handle =
+            tokio::spawn(bind_remotes(coord, address, num_clients, rx_senders, ready));
         (Self { coord, tx_senders }, join_handle)
     }
 
@@ -70,6 +73,7 @@ async fn bind_remotes<In: ExchangeData>(
     address: (String, u16),
     num_clients: usize,
     rx_senders: UnboundedReceiver<(ReceiverEndpoint, Sender<NetworkMessage<In>>)>,
+    ready: flume::Sender<()>,
 ) {
     let address = (address.0.as_ref(), address.1);
     let address: Vec<_> = address
@@ -94,6 +98,9 @@ async fn bind_remotes<In: ExchangeData>(
         .local_addr()
         .map(|a| a.to_string())
         .unwrap_or_else(|_| "unknown".to_string());
+    let _ = ready.send(());
+    // only the demultiplexers still binding keep the channel alive, so that a failure disconnects it
+    drop(ready);
     info!(
         "Remote receiver at {} is ready to accept {} connections to {}",
         coord, num_clients, address
@@ -173,9 +180,19 @@ async fn demux_thread<In: ExchangeData>(
         .unwrap_or_else(|_| "unknown".to_string());
     log::debug!("{} started", coord);
 
-    while let Some((dest, message)) = remote_recv(coord, &mut stream, &address).await {
-        if let Err(e) = senders[&dest].send(message) {
-            warn!("demux failed to send message to {}: {:?}", dest, e);
+    let mut sequence = 0;
+    loop {
+        match remote_recv(coord, &mut stream, &address, &mut sequence).await {
+            Ok(Some((dest, message))) => {
+                if let Err(e) = senders[&dest].send(message) {
+                    warn!("demux failed to send message to {}: {:?}", dest, e);
+                }
+            }
+            Ok(None) => break,
+            Err(e) => {
+                log::error!("{coord} closing the connection: {e}");
+                break;
+            }
         }
     }
 
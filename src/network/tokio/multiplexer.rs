@@ -45,15 +45,22 @@ impl<Out: ExchangeData> MultiplexingSender<Out> {
     /// Construct a new `MultiplexingSender` for a block.
     ///
     /// All the replicas of this block should point to this multiplexer (or one of its clones).
-    pub fn new(coord: DemuxCoord, address: (String, u16)) -> (Self, JoinHandle<()>) {
+    pub fn new(
+        coord: DemuxCoord,
+        address: (String, u16),
+        checksums: bool,
+        start: flume::Receiver<()>,
+    ) -> (Self, JoinHandle<()>) {
         let (tx, rx) = channel::bounded(MUX_CHANNEL_CAPACITY);
         let join_handle = tokio::spawn(async move {
+            // wait for the startup barrier, the channel is closed when it is passed
+            let _ = start.recv_async().await;
             log::debug!(
                 "mux connecting to {}",
                 address.to_socket_addrs().unwrap().next().unwrap()
             );
             let stream = connect_remote(coord, address).await;
-            mux_thread::<Out>(coord, rx, stream).await;
+            mux_thread::<Out>(coord, rx, stream, checksums).await;
         });
         (Self { tx: Some(tx) }, join_handle)
     }
@@ -142,6 +149,7 @@ async fn mux_thread<Out: ExchangeData>(
     coord: DemuxCoord,
     rx: Receiver<(ReceiverEndpoint, NetworkMessage<Out>)>,
     mut stream: TcpStream,
+    checksums: bool,
 ) {
     use tokio::io::AsyncWriteExt;
 
@@ -151,8 +159,10 @@ async fn mux_thread<Out: ExchangeData>(
         .unwrap_or_else(|_| "unknown".to_string());
     log::debug!("{} connected to {:?}", coord, address);
 
+    let mut sequence = 0;
     while let Ok((dest, message)) = rx.recv_async().await {
-        remote_send(message, dest, &mut stream, &address).await;
+        remote_send(message, dest, &mut stream, &address, sequence, checksums).await;
+        sequence += 1;
     }
 
     stream.shutdown().await.unwrap();
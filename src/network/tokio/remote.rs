@@ -6,7 +6,7 @@ use bincode::config::{FixintEncoding, RejectTrailing, WithOtherIntEncoding, With
 use bincode::{DefaultOptions, Options};
 use serde::{Deserialize, Serialize};
 
-use crate::network::{Coord, DemuxCoord, NetworkMessage, ReceiverEndpoint};
+use crate::network::{Coord, DemuxCoord, NetworkMessage, ReceiverEndpoint, RemoteRecvError};
 use crate::operator::ExchangeData;
 use crate::profiler::{get_profiler, Profiler};
 use crate::scheduler::BlockId;
@@ -23,7 +23,10 @@ static BINCODE_HEADER_CONFIG: Lazy<
 
 static BINCODE_MSG_CONFIG: Lazy<DefaultOptions> = Lazy::new(bincode::DefaultOptions::new);
 
-pub(crate) const HEADER_SIZE: usize = 20; // std::mem::size_of::<MessageHeader>();
+pub(crate) const HEADER_SIZE: usize = 37; // std::mem::size_of::<MessageHeader>();
+
+/// The seed of the checksums of the messages.
+const CHECKSUM_SEED: u64 = 0x6e6f6972;
 
 /// Header of a message sent before the actual message.
 #[derive(Serialize, Deserialize, Default)]
@@ -34,6 +37,17 @@ struct MessageHeader {
     replica_id: ReplicaId,
     /// The id of the block that is sending the message.
     sender_block_id: BlockId,
+    /// The position of the message in the connection, starting from zero.
+    sequence: u64,
+    /// Whether `checksum` has been computed.
+    has_checksum: bool,
+    /// The checksum of the actual message.
+    checksum: u64,
+}
+
+/// The checksum of a serialized message.
+fn checksum(buf: &[u8]) -> u64 {
+    wyhash::wyhash(buf, CHECKSUM_SEED)
 }
 
 /// Serialize and send a message to a remote socket.
@@ -41,12 +55,17 @@ struct MessageHeader {
 /// The network protocol works as follow:
 /// - send a `MessageHeader` serialized with bincode with `FixintEncoding`
 /// - send the message
+///
+/// `sequence` is the number of messages already sent on the connection. If `with_checksum` the
+/// header contains the checksum of the message.
 #[cfg(feature = "tokio")]
 pub(crate) async fn remote_send<T: ExchangeData, W: AsyncWrite + Unpin>(
     msg: NetworkMessage<T>,
     dest: ReceiverEndpoint,
     writer: &mut W,
     address: &str,
+    sequence: u64,
+    with_checksum: bool,
 ) {
     let serialized_len = BINCODE_MSG_CONFIG
         .serialized_size(&msg)
@@ -57,32 +76,40 @@ pub(crate) async fn remote_send<T: ExchangeData, W: AsyncWrite + Unpin>(
             )
         });
 
-    let header = MessageHeader {
-        size: serialized_len.try_into().unwrap(),
-        replica_id: dest.coord.replica_id,
-        sender_block_id: dest.prev_block_id,
-    };
-
-    let mut buf = Vec::with_capacity(HEADER_SIZE + serialized_len as usize);
+    // the header is written once the checksum of the message is known
+    let mut buf = vec![0; HEADER_SIZE];
+    buf.reserve(serialized_len as usize);
 
-    BINCODE_HEADER_CONFIG
-        .serialize_into(&mut buf, &header)
+    BINCODE_MSG_CONFIG
+        .serialize_into(&mut buf, &msg)
         .unwrap_or_else(|e| {
             panic!(
-                "Failed to serialize header of message (was {} bytes) to {} at {}: {:?}",
+                "Failed to serialize message, {} bytes to {} at {}: {:?}",
                 serialized_len, dest, address, e
             )
         });
+    assert_eq!(buf.len(), HEADER_SIZE + serialized_len as usize);
 
-    BINCODE_MSG_CONFIG
-        .serialize_into(&mut buf, &msg)
+    let header = MessageHeader {
+        size: serialized_len.try_into().unwrap(),
+        replica_id: dest.coord.replica_id,
+        sender_block_id: dest.prev_block_id,
+        sequence,
+        has_checksum: with_checksum,
+        checksum: if with_checksum {
+            checksum(&buf[HEADER_SIZE..])
+        } else {
+            0
+        },
+    };
+    BINCODE_HEADER_CONFIG
+        .serialize_into(&mut buf[..HEADER_SIZE], &header)
         .unwrap_or_else(|e| {
             panic!(
-                "Failed to serialize message, {} bytes to {} at {}: {:?}",
+                "Failed to serialize header of message (was {} bytes) to {} at {}: {:?}",
                 serialized_len, dest, address, e
             )
         });
-    assert_eq!(buf.len(), HEADER_SIZE + serialized_len as usize);
 
     writer.write_all(buf.as_ref()).await.unwrap_or_else(|e| {
         panic!(
@@ -98,12 +125,20 @@ pub(crate) async fn remote_send<T: ExchangeData, W: AsyncWrite + Unpin>(
     );
 }
 
+/// Receive a message from the remote channel. Returns `Ok(None)` if the connection has been
+/// closed before the next message.
+///
+/// After an error the connection must be closed: the stream may be in the middle of a message and
+/// the following ones cannot be trusted.
+///
+/// The message won't be deserialized, use `deserialize()`.
 #[cfg(feature = "tokio")]
 pub(crate) async fn remote_recv<T: ExchangeData, R: AsyncRead + Unpin>(
     coord: DemuxCoord,
     reader: &mut R,
     address: &str,
-) -> Option<(ReceiverEndpoint, NetworkMessage<T>)> {
+    sequence: &mut u64,
+) -> Result<Option<(ReceiverEndpoint, NetworkMessage<T>)>, RemoteRecvError> {
     let mut header = [0u8; HEADER_SIZE];
     match reader.read_exact(&mut header).await {
         Ok(_) => {}
@@ -115,29 +150,59 @@ pub(crate) async fn remote_recv<T: ExchangeData, R: AsyncRead + Unpin>(
                 address,
                 e
             );
-            return None;
+            return Ok(None);
         }
     }
     let header: MessageHeader = BINCODE_HEADER_CONFIG
         .deserialize(&header)
-        .expect("Malformed header");
+        .map_err(|source| RemoteRecvError::MalformedHeader {
+            coord,
+            address: address.to_string(),
+            sequence: *sequence,
+            source,
+        })?;
     let mut buf = vec![0u8; header.size as usize];
-    reader.read_exact(&mut buf).await.unwrap_or_else(|e| {
-        panic!(
-            "Failed to receive {} bytes to {} from {}: {:?}",
-            header.size, coord, address, e
-        )
-    });
-    let msg: NetworkMessage<T> = BINCODE_MSG_CONFIG
-        .deserialize(buf.as_ref())
-        .expect("Malformed message");
+    reader
+        .read_exact(&mut buf)
+        .await
+        .map_err(|source| RemoteRecvError::Io {
+            coord,
+            address: address.to_string(),
+            sequence: header.sequence,
+            source,
+        })?;
+    if header.sequence != *sequence {
+        return Err(RemoteRecvError::OutOfOrder {
+            coord,
+            address: address.to_string(),
+            received: header.sequence,
+            expected: *sequence,
+        });
+    }
+    *sequence += 1;
+    if header.has_checksum && header.checksum != checksum(&buf) {
+        return Err(RemoteRecvError::Checksum {
+            coord,
+            address: address.to_string(),
+            sequence: header.sequence,
+        });
+    }
+    let msg: NetworkMessage<T> =
+        BINCODE_MSG_CONFIG
+            .deserialize(buf.as_ref())
+            .map_err(|source| RemoteRecvError::MalformedMessage {
+                coord,
+                address: address.to_string(),
+                sequence: header.sequence,
+                source,
+            })?;
 
     let dest = ReceiverEndpoint::new(
         Coord::new(coord.coord.block_id, coord.coord.host_id, header.replica_id),
         header.sender_block_id,
     );
     get_profiler().net_bytes_in(msg.sender, dest.coord, HEADER_SIZE + header.size as usize);
-    Some((dest, msg))
+    Ok(Some((dest, msg)))
 }
 
 #[cfg(test)]
@@ -5,6 +5,7 @@ use std::fmt::Write;
 use std::marker::PhantomData;
 #[cfg(not(feature = "tokio"))]
 use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
 #[cfg(feature = "tokio")]
 use futures::StreamExt;
@@ -13,6 +14,7 @@ use typemap_rev::{TypeMap, TypeMapKey};
 
 use crate::channel::Sender;
 use crate::config::RuntimeConfig;
+use crate::network::barrier::startup_barrier;
 use crate::network::demultiplexer::DemuxHandle;
 use crate::network::multiplexer::MultiplexingSender;
 use crate::network::{
@@ -23,6 +25,49 @@ use crate::scheduler::{BlockId, HostId};
 
 use super::NetworkMessage;
 
+/// Maximum time to wait for the demultiplexers of this host to bind their sockets.
+const DEMUX_BIND_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The wait for the network of all the hosts to be ready before starting the workers, obtained
+/// with [`NetworkTopology::startup_wait`].
+pub(crate) struct StartupWait {
+    /// Each demultiplexer sends a message here once its socket is bound.
+    demux_ready: flume::Receiver<()>,
+    /// The number of demultiplexers started.
+    num_demuxes: usize,
+    /// The id of this host, the number of hosts and the address of the startup barrier, if remote.
+    barrier: Option<(HostId, usize, (String, u16))>,
+    /// The multiplexers connect only when this sender is dropped.
+    mux_start: Option<flume::Sender<()>>,
+}
+
+impl StartupWait {
+    /// Wait until all the sockets of this host are bound and all the other hosts are ready, then
+    /// let the multiplexers connect.
+    ///
+    /// This blocks the current thread. With the `tokio` feature the demultiplexers are tasks of
+    /// the runtime, so this must not be called from one of its threads.
+    pub(crate) fn wait(self) {
+        let deadline = Instant::now() + DEMUX_BIND_TIMEOUT;
+        for _ in 0..self.num_demuxes {
+            match self.demux_ready.recv_deadline(deadline) {
+                Ok(()) => {}
+                Err(flume::RecvTimeoutError::Disconnected) => {
+                    panic!("A demultiplexer failed to bind its socket")
+                }
+                Err(flume::RecvTimeoutError::Timeout) => panic!(
+                    "The demultiplexers did not bind their sockets in {}s",
+                    DEMUX_BIND_TIMEOUT.as_secs()
+                ),
+            }
+        }
+        if let Some((host_id, num_hosts, address)) = self.barrier {
+            startup_barrier(host_id, num_hosts, address);
+        }
+        drop(self.mux_start);
+    }
+}
+
 /// This struct is used to index inside the `typemap` with the `NetworkReceiver`s.
 struct ReceiverKey<In: ExchangeData>(PhantomData<In>);
 
@@ -117,6 +162,18 @@ pub(crate) struct NetworkTopology {
     /// The mapping between the coordinate of a demultiplexer of a block to the actual address/port
     /// of that demultiplexer in the network.
     demultiplexer_addresses: HashMap<DemuxCoord, (String, u16), crate::block::CoordHasherBuilder>,
+    /// The address of the startup barrier, on the first host after its demultiplexers.
+    barrier_address: Option<(String, u16)>,
+
+    /// Each demultiplexer sends a message here once its socket is bound. The sender is dropped
+    /// when the startup wait is created, so that a failed demultiplexer disconnects the channel.
+    demux_ready: Option<flume::Sender<()>>,
+    demux_ready_rx: flume::Receiver<()>,
+    /// The number of demultiplexers started.
+    num_demuxes: usize,
+    /// The multiplexers connect only when this sender is dropped, after the startup barrier.
+    mux_start: Option<flume::Sender<()>>,
+    mux_start_rx: flume::Receiver<()>,
 
     /// The set of join handles of the various threads spawned by the topology.
     #[cfg(not(feature = "tokio"))]
@@ -140,6 +197,8 @@ impl std::fmt::Debug for NetworkTopology {
 
 impl NetworkTopology {
     pub(crate) fn new(config: RuntimeConfig) -> Self {
+        let (mux_start, mux_start_rx) = flume::bounded(0);
+        let (demux_ready, demux_ready_rx) = flume::unbounded();
         NetworkTopology {
             config,
             receivers: Some(TypeMap::new()),
@@ -153,6 +212,12 @@ impl NetworkTopology {
             used_receivers: Default::default(),
             registered_receivers: Default::default(),
             demultiplexer_addresses: Default::default(),
+            barrier_address: None,
+            demux_ready: Some(demux_ready),
+            demux_ready_rx,
+            num_demuxes: 0,
+            mux_start: Some(mux_start),
+            mux_start_rx,
             #[cfg(not(feature = "tokio"))]
             join_handles: Default::default(),
             #[cfg(feature = "tokio")]
@@ -313,7 +378,13 @@ impl NetworkTopology {
             }
             if !prev.is_empty() {
                 let address = self.demultiplexer_addresses[&demux_coord].clone();
-                let (demux, join_handle) = DemuxHandle::new(demux_coord, address, prev.len());
+                let ready = self
+                    .demux_ready
+                    .clone()
+                    .expect("The demultiplexers must be started before the startup wait");
+                let (demux, join_handle) =
+                    DemuxHandle::new(demux_coord, address, prev.len(), ready);
+                self.num_demuxes += 1;
                 #[cfg(not(feature = "tokio"))]
                 self.join_handles.push(join_handle);
                 #[cfg(feature = "tokio")]
@@ -342,7 +413,10 @@ impl NetworkTopology {
 
         if let Entry::Vacant(e) = muxers.entry(demux_coord) {
             let address = self.demultiplexer_addresses[&demux_coord].clone();
-            let (mux, join_handle) = MultiplexingSender::new(demux_coord, address);
+            let checksums = matches!(&self.config, RuntimeConfig::Remote(c) if c.checksums);
+            let start = self.mux_start_rx.clone();
+            let (mux, join_handle) =
+                MultiplexingSender::new(demux_coord, address, checksums, start);
             #[cfg(not(feature = "tokio"))]
             self.join_handles.push(join_handle);
             #[cfg(feature = "tokio")]
@@ -518,17 +592,59 @@ impl NetworkTopology {
         }
         coords.sort();
         let mut used_ports: HashMap<HostId, u16> = HashMap::new();
+        // the jobs sharing the hosts use separate ranges of ports
+        let port = |host_id: HostId, port_offset: u16| {
+            let host = &config.hosts[host_id as usize];
+            match &config.job {
+                Some(job) => {
+                    assert!(
+                        port_offset < job.ports,
+                        "Job {} needs more than the {} ports of its range on host {}",
+                        job.id,
+                        job.ports,
+                        host
+                    );
+                    (
+                        host.address.clone(),
+                        host.base_port + job.port_offset() + port_offset,
+                    )
+                }
+                None => (host.address.clone(), host.base_port + port_offset),
+            }
+        };
         // sort the coords in order to have a deterministic assignment between all the hosts
         for coord in coords.into_iter() {
             let host_id = coord.coord.host_id;
             let port_offset = used_ports.entry(host_id).or_default();
-            let host = &config.hosts[host_id as usize];
-            let port = host.base_port + *port_offset;
+            let address = port(host_id, *port_offset);
             *port_offset += 1;
-            let address = (host.address.clone(), port);
             log::debug!("demux {} socket: {:?}", coord, address);
             self.demultiplexer_addresses.insert(coord, address);
         }
+        self.barrier_address = Some(port(0, used_ports.get(&0).copied().unwrap_or_default()));
+    }
+
+    /// Prepare the wait until all the sockets of this host are bound and all the other hosts are
+    /// ready, see [`StartupWait::wait`].
+    ///
+    /// This has to be called after the setup of all the blocks and before starting them.
+    pub(crate) fn startup_wait(&mut self) -> StartupWait {
+        // drop our sender, so that only the demultiplexers keep the channel alive
+        self.demux_ready.take();
+        let barrier = match &self.config {
+            RuntimeConfig::Remote(config) => Some((
+                self.config.host_id().unwrap(),
+                config.hosts.len(),
+                self.barrier_address.clone().unwrap(),
+            )),
+            RuntimeConfig::Local(_) => None,
+        };
+        StartupWait {
+            demux_ready: self.demux_ready_rx.clone(),
+            num_demuxes: self.num_demuxes,
+            barrier,
+            mux_start: self.mux_start.take(),
+        }
     }
 
     /// Finalize the topology and start mutliplexers and demultiplexers
@@ -576,6 +692,20 @@ mod tests {
         NetworkMessage::new_single(StreamElement::Item(t), Coord::default())
     }
 
+    #[test]
+    #[should_panic(expected = "A demultiplexer failed to bind its socket")]
+    fn startup_wait_failed_demux() {
+        let mut topology = NetworkTopology::new(RuntimeConfig::local(1).unwrap());
+        // a demultiplexer that panics before binding its socket
+        let ready = topology.demux_ready.clone().unwrap();
+        topology.num_demuxes += 1;
+        std::thread::spawn(move || {
+            let _ready = ready;
+            panic!("startup_wait_failed_demux: cannot bind");
+        });
+        topology.startup_wait().wait();
+    }
+
     #[test]
     fn test_local_topology() {
         let config = RuntimeConfig::local(4).unwrap();
@@ -750,6 +880,7 @@ num_cores = 1
                 );
             }
 
+            topology.startup_wait().wait();
             topology.finalize();
 
             for handle in join_handles {
@@ -785,6 +916,71 @@ num_cores = 1
         join1.join().unwrap();
     }
 
+    #[test]
+    fn test_job_port_range() {
+        use crate::config::{ConfigBuilder, HostConfig, JobConfig};
+
+        let hosts: Vec<_> = [9500, 9800]
+            .into_iter()
+            .map(|base_port| HostConfig {
+                address: "127.0.0.1".into(),
+                base_port,
+                num_cores: 1,
+                ssh: Default::default(),
+                perf_path: None,
+            })
+            .collect();
+        let build = |ports| {
+            let config = ConfigBuilder::new_remote()
+                .add_hosts(&hosts)
+                .job(JobConfig {
+                    id: "job-1".into(),
+                    slot: 2,
+                    ports,
+                    quota: Default::default(),
+                })
+                .host_id(0)
+                .build()
+                .unwrap();
+            let mut topology = NetworkTopology::new(config);
+            // b0 on both hosts -> b1 and b2 on host 1
+            for host_id in 0..2 {
+                for block_id in 1..3 {
+                    topology.connect(
+                        Coord::new(0, host_id, 0),
+                        Coord::new(block_id, 1, 0),
+                        TypeId::of::<i32>(),
+                        false,
+                    );
+                }
+            }
+            topology.build();
+            topology
+        };
+
+        let topology = build(10);
+        let ports = topology
+            .demultiplexer_addresses
+            .values()
+            .map(|(_, port)| *port)
+            .sorted()
+            .collect_vec();
+        assert_eq!(ports, vec![9820, 9821]);
+        assert_eq!(
+            topology.barrier_address,
+            Some(("127.0.0.1".to_string(), 9520))
+        );
+
+        let err = std::panic::catch_unwind(|| build(1)).unwrap_err();
+        let err = err.downcast_ref::<String>().unwrap();
+        assert!(err.contains("needs more than the 1 ports"), "{err}");
+        assert!(ConfigBuilder::new_remote()
+            .add_hosts(&hosts)
+            .job(JobConfig::new("../job", 0))
+            .build()
+            .is_err());
+    }
+
     #[cfg(not(feature = "tokio"))]
     fn receiver<T: ExchangeData + Ord + std::fmt::Debug>(
         receiver: NetworkReceiver<T>,
@@ -1,6 +1,6 @@
 use std::fmt::Display;
 
-use crate::block::{BlockStructure, OperatorStructure};
+use crate::block::{BlockStructure, OperatorStructure, TimestampUsage};
 use crate::operator::{Operator, StreamElement, Timestamp};
 use crate::scheduler::ExecutionMetadata;
 
@@ -92,9 +92,9 @@ where
     }
 
     fn structure(&self) -> BlockStructure {
-        self.prev
-            .structure()
-            .add_operator(OperatorStructure::new::<Self::Out, _>("AddTimestamp"))
+        let mut operator = OperatorStructure::new::<Self::Out, _>("AddTimestamp");
+        operator.timestamps = TimestampUsage::Assign;
+        self.prev.structure().add_operator(operator)
     }
 }
 
@@ -146,9 +146,9 @@ where
     }
 
     fn structure(&self) -> BlockStructure {
-        self.prev
-            .structure()
-            .add_operator(OperatorStructure::new::<Self::Out, _>("DropTimestamp"))
+        let mut operator = OperatorStructure::new::<Self::Out, _>("DropTimestamp");
+        operator.timestamps = TimestampUsage::Drop;
+        self.prev.structure().add_operator(operator)
     }
 }
 
@@ -0,0 +1,216 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Display;
+
+use crate::block::{BlockStructure, OperatorStructure, TimestampUsage};
+use crate::operator::merge::MergeElement;
+
+use crate::operator::{ExchangeData, ExchangeDataKey, Operator, StreamElement, Timestamp};
+use crate::scheduler::ExecutionMetadata;
+
+type OutputElement<Key, Out, Out2> = (Key, (Out, Option<Out2>));
+
+/// Operator that performs an ASOF join.
+///
+/// Each element of the left side with timestamp `ts` is matched with the element of the right side
+/// with the same key and the greatest timestamp not after `ts`, if its timestamp is at least
+/// `ts - tolerance`. The left elements without a match are emitted with `None`.
+///
+/// This operator assumes elements are received in increasing order of timestamp.
+#[derive(Clone, Debug)]
+pub struct AsofJoin<Key, Out, Out2, OperatorChain>
+where
+    Key: ExchangeDataKey,
+    Out: ExchangeData,
+    Out2: ExchangeData,
+    OperatorChain: Operator<Out = (Key, MergeElement<Out, Out2>)>,
+{
+    prev: OperatorChain,
+    /// Elements of the left side to be processed.
+    left: VecDeque<(Timestamp, (Key, Out))>,
+    /// For each key, the elements of the right side that might still be matched.
+    right: HashMap<Key, VecDeque<(Timestamp, Out2)>, crate::block::GroupHasherBuilder>,
+    /// Elements ready to be sent downstream.
+    buffer: VecDeque<(Timestamp, OutputElement<Key, Out, Out2>)>,
+    /// Timestamp of the last element (item or watermark).
+    last_seen: Timestamp,
+    /// The last watermark received, no element with an earlier or equal timestamp will arrive.
+    watermark: Timestamp,
+    /// The watermark to forward after the elements in the buffer.
+    pending_watermark: Option<Timestamp>,
+    /// Maximum distance between the timestamps of two matched elements.
+    tolerance: Timestamp,
+    /// Whether the operator has received a `FlushAndRestart` message.
+    received_restart: bool,
+}
+
+impl<Key, Out, Out2, OperatorChain> Display for AsofJoin<Key, Out, Out2, OperatorChain>
+where
+    Key: ExchangeDataKey,
+    Out: ExchangeData,
+    Out2: ExchangeData,
+    OperatorChain: Operator<Out = (Key, MergeElement<Out, Out2>)>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} -> AsofJoin<{}, {:?}>",
+            self.prev,
+            std::any::type_name::<OutputElement<Key, Out, Out2>>(),
+            self.tolerance,
+        )
+    }
+}
+
+/// Remove the elements of the right side that cannot be matched by a left element with timestamp
+/// at least `ts`: all but the latest one not after `ts`, and the ones older than the tolerance.
+fn evict<Out2>(right: &mut VecDeque<(Timestamp, Out2)>, ts: Timestamp, tolerance: Timestamp) {
+    while right.len() >= 2 && right[1].0 <= ts {
+        right.pop_front();
+    }
+    let oldest = ts.checked_sub(tolerance).unwrap_or(Timestamp::MIN);
+    if right
+        .front()
+        .is_some_and(|(right_ts, _)| *right_ts < oldest)
+    {
+        right.pop_front();
+    }
+}
+
+impl<Key, Out, Out2, OperatorChain> AsofJoin<Key, Out, Out2, OperatorChain>
+where
+    Key: ExchangeDataKey,
+    Out: ExchangeData,
+    Out2: ExchangeData,
+    OperatorChain: Operator<Out = (Key, MergeElement<Out, Out2>)>,
+{
+    pub(super) fn new(prev: OperatorChain, tolerance: Timestamp) -> Self {
+        assert!(
+            tolerance >= 0,
+            "the tolerance of asof_join must not be negative"
+        );
+        Self {
+            prev,
+            left: Default::default(),
+            right: Default::default(),
+            buffer: Default::default(),
+            last_seen: Timestamp::MIN,
+            watermark: Timestamp::MIN,
+            pending_watermark: None,
+            tolerance,
+            received_restart: false,
+        }
+    }
+
+    /// Advance the operator, matching the left elements whose match is known.
+    fn advance(&mut self) {
+        while let Some((left_ts, (key, _))) = self.left.front() {
+            if *left_ts >= self.last_seen && *left_ts > self.watermark && !self.received_restart {
+                // a right element with the same timestamp could still arrive
+                break;
+            }
+
+            let matched = self.right.get_mut(key).and_then(|right| {
+                evict(right, *left_ts, self.tolerance);
+                right
+                    .front()
+                    .filter(|(right_ts, _)| right_ts <= left_ts)
+                    .map(|(_, value)| value.clone())
+            });
+
+            let (ts, (key, value)) = self.left.pop_front().unwrap();
+            self.buffer.push_back((ts, (key, (value, matched))));
+        }
+
+        if self.left.is_empty() && self.received_restart {
+            // the operator has received a `FlushAndRestart` message and there are no elements
+            // remaining in the left side, so we can clear also the right side
+            self.right.clear();
+        }
+    }
+
+    /// Drop the right elements that no future left element can match, including the ones of the
+    /// keys that are not seen on the left side.
+    fn cleanup(&mut self) {
+        let ts = self
+            .left
+            .front()
+            .map(|(ts, _)| *ts)
+            .unwrap_or(self.last_seen)
+            .min(self.last_seen);
+        self.right.retain(|_, right| {
+            evict(right, ts, self.tolerance);
+            !right.is_empty()
+        });
+    }
+}
+
+impl<Key, Out, Out2, OperatorChain> Operator for AsofJoin<Key, Out, Out2, OperatorChain>
+where
+    Key: ExchangeDataKey,
+    Out: ExchangeData,
+    Out2: ExchangeData,
+    OperatorChain: Operator<Out = (Key, MergeElement<Out, Out2>)>,
+{
+    type Out = OutputElement<Key, Out, Out2>;
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        self.prev.setup(metadata);
+    }
+
+    fn next(&mut self) -> StreamElement<Self::Out> {
+        while self.buffer.is_empty() {
+            if let Some(ts) = self.pending_watermark.take() {
+                return StreamElement::Watermark(ts);
+            }
+            if self.received_restart {
+                assert!(self.left.is_empty());
+                assert!(self.right.is_empty());
+
+                self.received_restart = false;
+                self.last_seen = Timestamp::MIN;
+                self.watermark = Timestamp::MIN;
+
+                return StreamElement::FlushAndRestart;
+            }
+
+            match self.prev.next() {
+                StreamElement::Timestamped((key, item), ts) => {
+                    assert!(ts >= self.last_seen);
+                    self.last_seen = ts;
+                    match item {
+                        MergeElement::Left(item) => self.left.push_back((ts, (key, item))),
+                        MergeElement::Right(item) => {
+                            self.right.entry(key).or_default().push_back((ts, item))
+                        }
+                    }
+                }
+                StreamElement::Watermark(ts) => {
+                    assert!(ts >= self.last_seen);
+                    self.last_seen = ts;
+                    self.watermark = ts;
+                    self.pending_watermark = Some(ts);
+                    self.advance();
+                    self.cleanup();
+                    continue;
+                }
+                StreamElement::FlushAndRestart => {
+                    self.received_restart = true;
+                }
+                StreamElement::Item(_) => panic!("ASOF Join only supports timestamped streams"),
+                StreamElement::FlushBatch => return StreamElement::FlushBatch,
+                StreamElement::Terminate => return StreamElement::Terminate,
+            }
+
+            self.advance();
+        }
+
+        let (ts, item) = self.buffer.pop_front().unwrap();
+        StreamElement::Timestamped(item, ts)
+    }
+
+    fn structure(&self) -> BlockStructure {
+        let mut operator = OperatorStructure::new::<Self::Out, _>("AsofJoin");
+        operator.timestamps = TimestampUsage::Require;
+        self.prev.structure().add_operator(operator)
+    }
+}
@@ -25,6 +25,22 @@ mod tests {
         assert_eq!(stream.block.batch_mode, batch_mode);
     }
 
+    #[test]
+    fn batch_mode_auto() {
+        let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        let batch_mode = BatchMode::auto(42, Duration::from_millis(10));
+        let res = env
+            .stream_iter(0..1000)
+            .batch_mode(batch_mode)
+            .shuffle()
+            .collect_vec();
+        env.execute_blocking();
+
+        let mut res = res.get().unwrap();
+        res.sort_unstable();
+        assert_eq!(res, (0..1000).collect::<Vec<_>>());
+    }
+
     #[test]
     fn batch_inherit_from_previous() {
         let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
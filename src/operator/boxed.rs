@@ -5,10 +5,11 @@ use dyn_clone::DynClone;
 use crate::{
     block::BlockStructure,
     operator::{Data, Operator, StreamElement},
-    ExecutionMetadata, Stream,
+    stream::KeyedItem,
+    ExecutionMetadata, KeyedStream, Stream,
 };
 
-pub(crate) trait DynOperator: DynClone {
+pub(crate) trait DynOperator: DynClone + Display {
     type Out: Data;
     /// Setup the operator chain. This is called before any call to `next` and it's used to
     /// initialize the operator. When it's called the operator has already been cloned and it will
@@ -47,6 +48,12 @@ where
     }
 }
 
+/// An operator chain whose type has been erased with [`Stream::into_boxed`].
+///
+/// Every operator wraps the type of the previous one, so long pipelines produce deeply nested
+/// types that are slow to compile and bloat the binary. Boxing the chain resets the nesting, at the
+/// cost of a dynamic call for each element crossing the boundary. A `Stream<BoxedOperator<T>>`
+/// can also be named, so a long pipeline can be split into functions that each box their result.
 pub struct BoxedOperator<O> {
     pub(crate) op: Box<dyn DynOperator<Out = O> + 'static + Send>,
 }
@@ -61,7 +68,7 @@ impl<T> Clone for BoxedOperator<T> {
 
 impl<T> Display for BoxedOperator<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "BoxedOperator")
+        write!(f, "{}", self.op)
     }
 }
 
@@ -94,8 +101,41 @@ where
 {
     /// Erase operator type using dynamic dispatching.
     ///
-    /// Use only when strictly necessary as it is decrimental for performance.
+    /// Use only when strictly necessary as it is decrimental for performance: boxing the stream
+    /// every few operators keeps the compile times and the size of the binary under control for
+    /// very long pipelines, see [`BoxedOperator`].
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig, Stream};
+    /// # use renoir::operator::BoxedOperator;
+    /// # let env = StreamContext::new_local();
+    /// fn stage(s: Stream<BoxedOperator<u64>>) -> Stream<BoxedOperator<u64>> {
+    ///     s.map(|n| n * 2).filter(|n| n % 3 != 0).into_boxed()
+    /// }
+    ///
+    /// let s = env.stream_iter(0..6u64).into_boxed();
+    /// let res = stage(stage(s)).collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// assert_eq!(res.get().unwrap(), vec![4, 8, 16, 20]);
+    /// ```
     pub fn into_boxed(self) -> Stream<BoxedOperator<Op::Out>> {
         self.add_operator(|prev| BoxedOperator::new(prev))
     }
 }
+
+impl<Op> KeyedStream<Op>
+where
+    Op: Operator + 'static,
+    Op::Out: KeyedItem + Clone + Send + 'static,
+{
+    /// Erase operator type using dynamic dispatching, keeping the stream partitioned by key.
+    ///
+    /// See [`Stream::into_boxed`].
+    pub fn into_boxed(self) -> KeyedStream<BoxedOperator<Op::Out>> {
+        self.add_operator(|prev| BoxedOperator::new(prev))
+    }
+}
@@ -0,0 +1,77 @@
+//! The source of the current time for the operators working in processing time.
+//!
+//! By default the operators use the [`SystemClock`], a [`ManualClock`] can be used instead for
+//! controlling the passing of time, for example for writing deterministic tests.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+/// A source of the current processing time.
+///
+/// The clock is cloned inside each replica of the operators that use it.
+pub trait Clock: Clone + Send + 'static {
+    /// The current instant according to this clock.
+    ///
+    /// The instants returned by a clock must never decrease.
+    fn now(&self) -> Instant;
+}
+
+/// The wall clock of the host, the time is given by [`Instant::now`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    #[inline]
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock whose time moves forward only when it is explicitly advanced.
+///
+/// All the clones of a `ManualClock` share the same time, so a clone can be kept for moving the
+/// time of the operators that use the clock.
+///
+/// ## Example
+///
+/// ```
+/// # use std::time::Duration;
+/// # use renoir::operator::clock::{Clock, ManualClock};
+/// let clock = ManualClock::new();
+/// let start = clock.now();
+/// clock.advance(Duration::from_secs(5));
+/// assert_eq!(clock.now() - start, Duration::from_secs(5));
+/// ```
+#[derive(Clone, Debug)]
+pub struct ManualClock {
+    now: Arc<Mutex<Instant>>,
+}
+
+impl ManualClock {
+    /// Create a new clock, stopped at the current instant.
+    pub fn new() -> Self {
+        Self {
+            now: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// Move the time of the clock forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock() += duration;
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    #[inline]
+    fn now(&self) -> Instant {
+        *self.now.lock()
+    }
+}
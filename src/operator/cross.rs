@@ -0,0 +1,416 @@
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::sync::Arc;
+
+use crate::block::{
+    BlockStructure, NextStrategy, OperatorReceiver, OperatorStructure, Replication,
+};
+use crate::operator::iteration::IterationStateLock;
+use crate::operator::source::Source;
+use crate::operator::start::{BinaryElement, BinaryStartOperator, Start};
+use crate::operator::{Data, ExchangeData, Operator, StreamElement};
+use crate::scheduler::{BlockId, ExecutionMetadata};
+use crate::Stream;
+
+/// Operator that pairs each element of one side with all the elements of the other side.
+///
+/// The elements of the broadcast side are collected until that side has ended, the elements of the
+/// other side are stashed until then and after that they are paired as they arrive.
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""), Debug)]
+pub struct Cross<L, R, O, F>
+where
+    L: ExchangeData,
+    R: ExchangeData,
+    O: Data,
+    F: Fn(&L, &R) -> Option<O> + Clone + Send + 'static,
+{
+    #[derivative(Debug = "ignore")]
+    prev: BinaryStartOperator<L, R>,
+    #[derivative(Debug = "ignore")]
+    f: F,
+    broadcast_left: bool,
+    /// The elements of the left side, if it's the broadcast one.
+    #[derivative(Debug = "ignore")]
+    left: Vec<L>,
+    /// The elements of the right side, if it's the broadcast one.
+    #[derivative(Debug = "ignore")]
+    right: Vec<R>,
+    /// Whether the broadcast side has ended.
+    broadcast_ended: bool,
+    /// The elements of the other side received before the broadcast side has ended.
+    #[derivative(Debug = "ignore")]
+    stash: VecDeque<StreamElement<BinaryElement<L, R>>>,
+    #[derivative(Debug = "ignore")]
+    buffer: VecDeque<StreamElement<O>>,
+    prev_block_id1: BlockId,
+    prev_block_id2: BlockId,
+}
+
+impl<L, R, O, F> Display for Cross<L, R, O, F>
+where
+    L: ExchangeData,
+    R: ExchangeData,
+    O: Data,
+    F: Fn(&L, &R) -> Option<O> + Clone + Send + 'static,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Cross[{}, {}] -> {}",
+            std::any::type_name::<L>(),
+            std::any::type_name::<R>(),
+            std::any::type_name::<O>()
+        )
+    }
+}
+
+impl<L, R, O, F> Cross<L, R, O, F>
+where
+    L: ExchangeData,
+    R: ExchangeData,
+    O: Data,
+    F: Fn(&L, &R) -> Option<O> + Clone + Send + 'static,
+{
+    fn new(
+        prev_block_id1: BlockId,
+        prev_block_id2: BlockId,
+        left_cache: bool,
+        right_cache: bool,
+        state_lock: Option<Arc<IterationStateLock>>,
+        broadcast_left: bool,
+        f: F,
+    ) -> Self {
+        Self {
+            prev: Start::multiple(
+                prev_block_id1,
+                prev_block_id2,
+                left_cache,
+                right_cache,
+                state_lock,
+            ),
+            f,
+            broadcast_left,
+            left: Default::default(),
+            right: Default::default(),
+            broadcast_ended: false,
+            stash: Default::default(),
+            buffer: Default::default(),
+            prev_block_id1,
+            prev_block_id2,
+        }
+    }
+
+    /// Pair an element of the non broadcast side with all the elements of the broadcast side.
+    fn pair(&mut self, el: StreamElement<BinaryElement<L, R>>) {
+        let ts = el.timestamp().copied();
+        let wrap = |out| match ts {
+            Some(ts) => StreamElement::Timestamped(out, ts),
+            None => StreamElement::Item(out),
+        };
+        match el {
+            StreamElement::Item(BinaryElement::Left(l))
+            | StreamElement::Timestamped(BinaryElement::Left(l), _) => {
+                let pairs = self.right.iter().filter_map(|r| (self.f)(&l, r));
+                self.buffer.extend(pairs.map(wrap));
+            }
+            StreamElement::Item(BinaryElement::Right(r))
+            | StreamElement::Timestamped(BinaryElement::Right(r), _) => {
+                let pairs = self.left.iter().filter_map(|l| (self.f)(l, &r));
+                self.buffer.extend(pairs.map(wrap));
+            }
+            StreamElement::Watermark(ts) => self.buffer.push_back(StreamElement::Watermark(ts)),
+            _ => unreachable!("only items and watermarks are stashed"),
+        }
+    }
+}
+
+impl<L, R, O, F> Operator for Cross<L, R, O, F>
+where
+    L: ExchangeData,
+    R: ExchangeData,
+    O: Data,
+    F: Fn(&L, &R) -> Option<O> + Clone + Send + 'static,
+{
+    type Out = O;
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        self.prev.setup(metadata);
+    }
+
+    fn next(&mut self) -> StreamElement<O> {
+        while self.buffer.is_empty() {
+            let el = self.prev.next();
+            match &el {
+                StreamElement::Item(BinaryElement::Left(l))
+                | StreamElement::Timestamped(BinaryElement::Left(l), _)
+                    if self.broadcast_left =>
+                {
+                    self.left.push(l.clone());
+                }
+                StreamElement::Item(BinaryElement::Right(r))
+                | StreamElement::Timestamped(BinaryElement::Right(r), _)
+                    if !self.broadcast_left =>
+                {
+                    self.right.push(r.clone());
+                }
+                StreamElement::Item(BinaryElement::LeftEnd)
+                | StreamElement::Timestamped(BinaryElement::LeftEnd, _)
+                    if self.broadcast_left =>
+                {
+                    self.broadcast_ended = true;
+                }
+                StreamElement::Item(BinaryElement::RightEnd)
+                | StreamElement::Timestamped(BinaryElement::RightEnd, _)
+                    if !self.broadcast_left =>
+                {
+                    self.broadcast_ended = true;
+                }
+                // ignore the end of the other side
+                StreamElement::Item(BinaryElement::LeftEnd | BinaryElement::RightEnd)
+                | StreamElement::Timestamped(BinaryElement::LeftEnd | BinaryElement::RightEnd, _) =>
+                    {}
+                StreamElement::Item(_)
+                | StreamElement::Timestamped(_, _)
+                | StreamElement::Watermark(_) => {
+                    if self.broadcast_ended {
+                        self.pair(el);
+                    } else {
+                        self.stash.push_back(el);
+                    }
+                }
+                StreamElement::FlushAndRestart => {
+                    // both sides have ended, so the stash has already been emptied
+                    self.left.clear();
+                    self.right.clear();
+                    self.broadcast_ended = false;
+                    return StreamElement::FlushAndRestart;
+                }
+                StreamElement::FlushBatch => return StreamElement::FlushBatch,
+                StreamElement::Terminate => return StreamElement::Terminate,
+            }
+
+            if self.broadcast_ended {
+                while let Some(el) = self.stash.pop_front() {
+                    self.pair(el);
+                }
+            }
+        }
+        self.buffer.pop_front().unwrap()
+    }
+
+    fn structure(&self) -> BlockStructure {
+        let mut operator = OperatorStructure::new::<O, _>("Cross");
+        operator
+            .receivers
+            .push(OperatorReceiver::new::<L>(self.prev_block_id1));
+        operator
+            .receivers
+            .push(OperatorReceiver::new::<R>(self.prev_block_id2));
+        BlockStructure::default().add_operator(operator)
+    }
+}
+
+impl<L, R, O, F> Source for Cross<L, R, O, F>
+where
+    L: ExchangeData,
+    R: ExchangeData,
+    O: Data,
+    F: Fn(&L, &R) -> Option<O> + Clone + Send + 'static,
+{
+    fn replication(&self) -> Replication {
+        Replication::Unlimited
+    }
+}
+
+impl<Op> Stream<Op>
+where
+    Op: Operator + 'static,
+    Op::Out: ExchangeData,
+{
+    /// Pair each element of this stream with each element of `other`, producing the cartesian
+    /// product of the two streams.
+    ///
+    /// One of the two sides is broadcast to all the replicas of the other, which keeps its
+    /// partitioning: `other` is broadcast, unless this stream runs on a single replica (e.g. it
+    /// comes from [`StreamContext::stream_iter`](crate::StreamContext::stream_iter)) and `other`
+    /// does not. The broadcast side is kept in memory and must end before any pair is produced, so
+    /// it should be the smaller one.
+    ///
+    /// **Note**: this operator will split the current block.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # let mut env = StreamContext::new_local();
+    /// let s1 = env.stream_iter(0..3);
+    /// let s2 = env.stream_iter(vec!['a', 'b'].into_iter());
+    /// let res = s1.cross(s2).collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let mut res = res.get().unwrap();
+    /// res.sort_unstable();
+    /// assert_eq!(res, vec![(0, 'a'), (0, 'b'), (1, 'a'), (1, 'b'), (2, 'a'), (2, 'b')]);
+    /// ```
+    pub fn cross<Op2>(self, other: Stream<Op2>) -> Stream<impl Operator<Out = (Op::Out, Op2::Out)>>
+    where
+        Op2: Operator + 'static,
+        Op2::Out: ExchangeData,
+    {
+        self.cross_filter(other, |_, _| true)
+    }
+
+    /// Pair each element of this stream with each element of `other` for which `predicate` is
+    /// true.
+    ///
+    /// The predicate is evaluated inside the nested loop, so the discarded pairs are never built.
+    /// See [`Stream::cross`] for how the two sides are distributed.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # let mut env = StreamContext::new_local();
+    /// let s1 = env.stream_iter(0..4);
+    /// let s2 = env.stream_iter(0..4);
+    /// let res = s1.cross_filter(s2, |a, b| a < b).collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let mut res = res.get().unwrap();
+    /// res.sort_unstable();
+    /// assert_eq!(res, vec![(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)]);
+    /// ```
+    pub fn cross_filter<Op2, P>(
+        self,
+        other: Stream<Op2>,
+        predicate: P,
+    ) -> Stream<impl Operator<Out = (Op::Out, Op2::Out)>>
+    where
+        Op2: Operator + 'static,
+        Op2::Out: ExchangeData,
+        P: Fn(&Op::Out, &Op2::Out) -> bool + Clone + Send + 'static,
+    {
+        self.cross_filter_map(other, move |l, r| {
+            predicate(l, r).then(|| (l.clone(), r.clone()))
+        })
+    }
+
+    /// Combine each element of this stream with each element of `other` using `f`, producing its
+    /// results instead of the pairs.
+    ///
+    /// `f` receives the two elements by reference, so the pairs of the cartesian product are never
+    /// built: only what `f` returns is cloned or allocated. See [`Stream::cross`] for how the two
+    /// sides are distributed.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # let mut env = StreamContext::new_local();
+    /// let s1 = env.stream_iter(1..4);
+    /// let s2 = env.stream_iter(vec![10, 100].into_iter());
+    /// let res = s1.cross_map(s2, |a, b| a * b).collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let mut res = res.get().unwrap();
+    /// res.sort_unstable();
+    /// assert_eq!(res, vec![10, 20, 30, 100, 200, 300]);
+    /// ```
+    pub fn cross_map<Op2, O, F>(self, other: Stream<Op2>, f: F) -> Stream<impl Operator<Out = O>>
+    where
+        Op2: Operator + 'static,
+        Op2::Out: ExchangeData,
+        O: Data,
+        F: Fn(&Op::Out, &Op2::Out) -> O + Clone + Send + 'static,
+    {
+        self.cross_filter_map(other, move |l, r| Some(f(l, r)))
+    }
+
+    fn cross_filter_map<Op2, O, F>(self, other: Stream<Op2>, f: F) -> Stream<impl Operator<Out = O>>
+    where
+        Op2: Operator + 'static,
+        Op2::Out: ExchangeData,
+        O: Data,
+        F: Fn(&Op::Out, &Op2::Out) -> Option<O> + Clone + Send + 'static,
+    {
+        let broadcast_left = self.block.scheduling.replication == Replication::One
+            && other.block.scheduling.replication != Replication::One;
+        let (strategy1, strategy2) = if broadcast_left {
+            (NextStrategy::all(), NextStrategy::only_one())
+        } else {
+            (NextStrategy::only_one(), NextStrategy::all())
+        };
+        self.binary_connection(
+            other,
+            move |id1, id2, cache1, cache2, state_lock| {
+                Cross::new(id1, id2, cache1, cache2, state_lock, broadcast_left, f)
+            },
+            strategy1,
+            strategy2,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::network::{Coord, NetworkMessage, NetworkSender};
+    use crate::operator::cross::Cross;
+    use crate::operator::{Operator, StreamElement};
+    use crate::test::FakeNetworkTopology;
+
+    #[test]
+    fn cross_waits_broadcast_side() {
+        let mut t = FakeNetworkTopology::new(2, 1);
+
+        let (coord_l, sender_l) = t.senders_mut()[0].pop().unwrap();
+        let (coord_r, sender_r) = t.senders_mut()[1].pop().unwrap();
+
+        let mut cross = Cross::new(
+            coord_l.block_id,
+            coord_r.block_id,
+            false,
+            false,
+            None,
+            false,
+            |l: &i32, r: &i32| (l != r).then_some((*l, *r)),
+        );
+        cross.setup(&mut t.metadata());
+
+        let send = |sender: &NetworkSender<i32>, from: Coord, data: Vec<StreamElement<i32>>| {
+            sender.send(NetworkMessage::new_batch(data, from)).unwrap();
+        };
+
+        send(
+            &sender_l,
+            coord_l,
+            vec![
+                StreamElement::Item(1),
+                StreamElement::Item(2),
+                StreamElement::FlushAndRestart,
+            ],
+        );
+        send(
+            &sender_r,
+            coord_r,
+            vec![
+                StreamElement::Item(1),
+                StreamElement::Item(3),
+                StreamElement::FlushAndRestart,
+            ],
+        );
+
+        let mut res = Vec::new();
+        loop {
+            match cross.next() {
+                StreamElement::Item(pair) => res.push(pair),
+                StreamElement::FlushAndRestart => break,
+                other => panic!("unexpected {}", other.variant_str()),
+            }
+        }
+        assert_eq!(res, vec![(1, 3), (2, 1), (2, 3)]);
+    }
+}
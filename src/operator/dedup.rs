@@ -0,0 +1,377 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Display;
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "timestamp")]
+use crate::block::TimestampUsage;
+use crate::block::{BlockStructure, GroupHasherBuilder, OperatorStructure, StateEntries};
+use crate::operator::clock::{Clock, SystemClock};
+#[cfg(feature = "timestamp")]
+use crate::operator::Timestamp;
+use crate::operator::{Data, DataKey, Operator, StreamElement};
+use crate::scheduler::ExecutionMetadata;
+use crate::KeyedStream;
+
+#[derive(Derivative)]
+#[derivative(Clone, Debug)]
+pub struct DedupWithin<K, I, C, Op>
+where
+    K: DataKey,
+    I: Data,
+    C: Clock,
+    Op: Operator<Out = (K, I)>,
+{
+    prev: Op,
+    ttl: Duration,
+    #[derivative(Debug = "ignore")]
+    clock: C,
+    /// The instant each key seen within the ttl expires.
+    #[derivative(Debug = "ignore")]
+    seen: HashMap<K, Instant, GroupHasherBuilder>,
+    /// The keys in `seen` in the order they expire.
+    #[derivative(Debug = "ignore")]
+    expiries: VecDeque<(Instant, K)>,
+    _item: PhantomData<I>,
+}
+
+impl<K, I, C, Op> Display for DedupWithin<K, I, C, Op>
+where
+    K: DataKey,
+    I: Data,
+    C: Clock,
+    Op: Operator<Out = (K, I)>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} -> DedupWithin<{}, {:?}>",
+            self.prev,
+            std::any::type_name::<K>(),
+            self.ttl
+        )
+    }
+}
+
+impl<K, I, C, Op> DedupWithin<K, I, C, Op>
+where
+    K: DataKey,
+    I: Data,
+    C: Clock,
+    Op: Operator<Out = (K, I)>,
+{
+    fn new(prev: Op, ttl: Duration, clock: C) -> Self {
+        Self {
+            prev,
+            ttl,
+            clock,
+            seen: Default::default(),
+            expiries: Default::default(),
+            _item: PhantomData,
+        }
+    }
+
+    /// Whether `key` was not seen within the ttl, remembering it if so.
+    fn is_new(&mut self, key: &K) -> bool {
+        let now = self.clock.now();
+        while let Some((expiry, _)) = self.expiries.front() {
+            if *expiry > now {
+                break;
+            }
+            let (expiry, key) = self.expiries.pop_front().unwrap();
+            if self.seen.get(&key) == Some(&expiry) {
+                self.seen.remove(&key);
+            }
+        }
+        if self.seen.contains_key(key) {
+            return false;
+        }
+        self.seen.insert(key.clone(), now + self.ttl);
+        self.expiries.push_back((now + self.ttl, key.clone()));
+        true
+    }
+}
+
+impl<K, I, C, Op> Operator for DedupWithin<K, I, C, Op>
+where
+    K: DataKey,
+    I: Data,
+    C: Clock,
+    Op: Operator<Out = (K, I)>,
+{
+    type Out = (K, I);
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        self.prev.setup(metadata);
+    }
+
+    fn next(&mut self) -> StreamElement<Self::Out> {
+        loop {
+            match self.prev.next() {
+                StreamElement::Item(item) => {
+                    if self.is_new(&item.0) {
+                        return StreamElement::Item(item);
+                    }
+                }
+                StreamElement::Timestamped(item, ts) => {
+                    if self.is_new(&item.0) {
+                        return StreamElement::Timestamped(item, ts);
+                    }
+                }
+                el => return el,
+            }
+        }
+    }
+
+    fn structure(&self) -> BlockStructure {
+        let operator = OperatorStructure::new::<Self::Out, _>("DedupWithin")
+            .with_state::<(K, Instant)>(StateEntries::PerKey);
+        self.prev.structure().add_operator(operator)
+    }
+}
+
+#[cfg(feature = "timestamp")]
+#[derive(Derivative)]
+#[derivative(Clone, Debug)]
+pub struct DedupWithinEventTime<K, I, Op>
+where
+    K: DataKey,
+    I: Data,
+    Op: Operator<Out = (K, I)>,
+{
+    prev: Op,
+    ttl: Timestamp,
+    /// The timestamp each key seen within the ttl expires.
+    #[derivative(Debug = "ignore")]
+    seen: HashMap<K, Timestamp, GroupHasherBuilder>,
+    _item: PhantomData<I>,
+}
+
+#[cfg(feature = "timestamp")]
+impl<K, I, Op> Display for DedupWithinEventTime<K, I, Op>
+where
+    K: DataKey,
+    I: Data,
+    Op: Operator<Out = (K, I)>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} -> DedupWithinEventTime<{}, {}>",
+            self.prev,
+            std::any::type_name::<K>(),
+            self.ttl
+        )
+    }
+}
+
+#[cfg(feature = "timestamp")]
+impl<K, I, Op> DedupWithinEventTime<K, I, Op>
+where
+    K: DataKey,
+    I: Data,
+    Op: Operator<Out = (K, I)>,
+{
+    fn new(prev: Op, ttl: Timestamp) -> Self {
+        assert!(
+            ttl >= 0,
+            "the ttl of dedup_within_event_time must not be negative"
+        );
+        Self {
+            prev,
+            ttl,
+            seen: Default::default(),
+            _item: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "timestamp")]
+impl<K, I, Op> Operator for DedupWithinEventTime<K, I, Op>
+where
+    K: DataKey,
+    I: Data,
+    Op: Operator<Out = (K, I)>,
+{
+    type Out = (K, I);
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        self.prev.setup(metadata);
+    }
+
+    fn next(&mut self) -> StreamElement<Self::Out> {
+        loop {
+            match self.prev.next() {
+                StreamElement::Timestamped(item, ts) => match self.seen.get(&item.0) {
+                    Some(&expiry) if expiry > ts => {}
+                    _ => {
+                        self.seen
+                            .insert(item.0.clone(), ts.saturating_add(self.ttl));
+                        return StreamElement::Timestamped(item, ts);
+                    }
+                },
+                StreamElement::Watermark(w) => {
+                    self.seen.retain(|_, expiry| *expiry > w);
+                    return StreamElement::Watermark(w);
+                }
+                StreamElement::FlushAndRestart => {
+                    self.seen.clear();
+                    return StreamElement::FlushAndRestart;
+                }
+                StreamElement::Item(_) => {
+                    panic!("DedupWithinEventTime only supports timestamped streams")
+                }
+                el => return el,
+            }
+        }
+    }
+
+    fn structure(&self) -> BlockStructure {
+        let mut operator = OperatorStructure::new::<Self::Out, _>("DedupWithinEventTime")
+            .with_state::<(K, Timestamp)>(StateEntries::PerKey);
+        operator.timestamps = TimestampUsage::Require;
+        self.prev.structure().add_operator(operator)
+    }
+}
+
+impl<K, I, Op> KeyedStream<Op>
+where
+    K: DataKey,
+    I: Data,
+    Op: Operator<Out = (K, I)> + 'static,
+{
+    /// Drop the elements whose key was already seen within `ttl`, in processing time.
+    ///
+    /// The first element of a key is emitted and starts a period of `ttl` in which the other
+    /// elements with the same key are dropped; the first element after the period is emitted and
+    /// starts a new one. This removes the duplicates delivered by the sources with at-least-once
+    /// semantics, keeping only the keys of the last `ttl` in memory.
+    ///
+    /// The time is read from the [`SystemClock`], use [`KeyedStream::dedup_within_with_clock`]
+    /// for changing the clock. See [`KeyedStream::dedup_within_event_time`] for deduplicating in
+    /// event time.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # let mut env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+    /// // the ids of the messages, delivered twice
+    /// let s = env.stream_iter([1, 2, 1, 3, 2].into_iter());
+    /// let res = s
+    ///     .group_by(|&id| id)
+    ///     .dedup_within(Duration::from_secs(60))
+    ///     .drop_key()
+    ///     .collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let mut res = res.get().unwrap();
+    /// res.sort_unstable();
+    /// assert_eq!(res, vec![1, 2, 3]);
+    /// ```
+    pub fn dedup_within(self, ttl: Duration) -> KeyedStream<impl Operator<Out = (K, I)>> {
+        self.dedup_within_with_clock(ttl, SystemClock)
+    }
+
+    /// Drop the elements whose key was already seen within `ttl`, in the processing time given by
+    /// `clock`.
+    ///
+    /// See [`KeyedStream::dedup_within`].
+    pub fn dedup_within_with_clock<C: Clock>(
+        self,
+        ttl: Duration,
+        clock: C,
+    ) -> KeyedStream<impl Operator<Out = (K, I)>> {
+        self.add_operator(|prev| DedupWithin::new(prev, ttl, clock))
+    }
+
+    /// Drop the elements whose key was already seen within `ttl`, in event time.
+    ///
+    /// An element with timestamp `ts` is dropped if an element with the same key was emitted with
+    /// a timestamp in `(ts - ttl, ts]`, or with a later timestamp if the elements are out of
+    /// order. The stream must have timestamps and watermarks: the keys are forgotten when the
+    /// watermark passes the end of their period, so an element later than the watermark can be
+    /// emitted even if it's a duplicate.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # let mut env = StreamContext::new(RuntimeConfig::local(1).unwrap());
+    /// let s = env
+    ///     .stream_iter(vec![('a', 0), ('a', 5), ('b', 6), ('a', 12)].into_iter())
+    ///     .add_timestamps(|&(_, ts)| ts, |_, _| None)
+    ///     .group_by(|&(id, _)| id);
+    /// let res = s.dedup_within_event_time(10).drop_key().collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let mut res = res.get().unwrap();
+    /// res.sort_unstable();
+    /// assert_eq!(res, vec![('a', 0), ('a', 12), ('b', 6)]);
+    /// ```
+    #[cfg(feature = "timestamp")]
+    pub fn dedup_within_event_time(
+        self,
+        ttl: Timestamp,
+    ) -> KeyedStream<impl Operator<Out = (K, I)>> {
+        self.add_operator(|prev| DedupWithinEventTime::new(prev, ttl))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::DedupWithin;
+    use crate::operator::clock::ManualClock;
+    use crate::operator::{Operator, StreamElement};
+    use crate::test::{FakeNetworkTopology, FakeOperator};
+
+    #[test]
+    fn dedup_within_expires() {
+        let clock = ManualClock::new();
+        let fake = FakeOperator::new([('a', 1), ('a', 2), ('b', 3)].into_iter());
+        let mut dedup = DedupWithin::new(fake, Duration::from_secs(10), clock.clone());
+        dedup.setup(&mut FakeNetworkTopology::<u8>::new(0, 0).metadata());
+
+        assert_eq!(dedup.next(), StreamElement::Item(('a', 1)));
+        assert_eq!(dedup.next(), StreamElement::Item(('b', 3)));
+        assert_eq!(dedup.seen.len(), 2);
+
+        clock.advance(Duration::from_secs(10));
+        assert!(dedup.is_new(&'a'));
+        assert!(!dedup.is_new(&'a'));
+        // `b` expired and was evicted
+        assert_eq!(dedup.seen.len(), 1);
+        assert!(dedup.is_new(&'b'));
+    }
+
+    #[cfg(feature = "timestamp")]
+    #[test]
+    fn dedup_within_event_time() {
+        use super::DedupWithinEventTime;
+
+        let mut fake = FakeOperator::empty();
+        fake.push(StreamElement::Timestamped(('a', 0), 10));
+        fake.push(StreamElement::Timestamped(('a', 1), 15));
+        // out of order, but within the ttl of the first one
+        fake.push(StreamElement::Timestamped(('a', 2), 8));
+        fake.push(StreamElement::Watermark(19));
+        fake.push(StreamElement::Timestamped(('a', 3), 19));
+        fake.push(StreamElement::Watermark(20));
+        fake.push(StreamElement::Timestamped(('a', 4), 20));
+        fake.push(StreamElement::Timestamped(('a', 5), 21));
+        let mut dedup = DedupWithinEventTime::new(fake, 10);
+        dedup.setup(&mut FakeNetworkTopology::<u8>::new(0, 0).metadata());
+
+        assert_eq!(dedup.next(), StreamElement::Timestamped(('a', 0), 10));
+        assert_eq!(dedup.next(), StreamElement::Watermark(19));
+        assert_eq!(dedup.next(), StreamElement::Watermark(20));
+        assert!(dedup.seen.is_empty());
+        assert_eq!(dedup.next(), StreamElement::Timestamped(('a', 4), 20));
+        assert_eq!(dedup.next(), StreamElement::Terminate);
+    }
+}
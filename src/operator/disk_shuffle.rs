@@ -0,0 +1,670 @@
+//! Shuffle that moves the elements between the blocks through files.
+//!
+//! See [`Stream::disk_shuffle`] for more details.
+
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind, Write};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use nanorand::{tls_rng, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::block::{group_by_hash, BlockStructure, NextStrategy, OperatorStructure};
+use crate::operator::end::End;
+use crate::operator::key_by::KeyBy;
+use crate::operator::{DataKey, ExchangeData, Operator, StreamElement, Timestamp};
+use crate::scheduler::{ExecutionMetadata, QuotaUsage};
+use crate::{KeyedStream, Stream};
+
+/// Maximum number of bytes written to a segment before starting a new one.
+const SEGMENT_SIZE: usize = 64 << 20;
+
+/// Computes the hash of the key of an element, used for choosing its partition.
+type KeyHasher<T> = Arc<dyn Fn(&T) -> u64 + Send + Sync>;
+
+/// A file with the elements written by a replica, sent to the replica that will read it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShuffleSegment {
+    /// The path of the file.
+    pub path: PathBuf,
+    /// The number of elements inside the file.
+    pub len: usize,
+    /// The partition of the elements inside the file, the segments of a partition are all sent
+    /// to the same replica.
+    pub partition: usize,
+}
+
+impl ShuffleSegment {
+    /// Read the elements stored in the segment, with their timestamps.
+    pub fn read<T: ExchangeData>(&self) -> impl Iterator<Item = (T, Option<Timestamp>)> {
+        let file = File::open(&self.path).unwrap_or_else(|err| {
+            panic!(
+                "DiskShuffle: error while opening file {:?}: {err:?}",
+                self.path
+            )
+        });
+        let mut reader = BufReader::new(file);
+        let path = self.path.clone();
+        (0..self.len).map(move |_| {
+            bincode::deserialize_from(&mut reader).unwrap_or_else(|err| {
+                panic!("DiskShuffle: error while reading segment {path:?}: {err:?}")
+            })
+        })
+    }
+}
+
+/// The segment being written by a replica.
+struct OpenSegment {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    len: usize,
+    bytes: usize,
+}
+
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct DiskShuffleWrite<Op: Operator>
+where
+    Op::Out: ExchangeData,
+{
+    prev: Op,
+    dir: PathBuf,
+    /// Hash of the key of the elements, if they are partitioned by key.
+    #[derivative(Debug = "ignore")]
+    key_hasher: Option<KeyHasher<Op::Out>>,
+    /// Prefix of the names of the segments of this replica, set in `setup`.
+    prefix: Option<String>,
+    next_segment: usize,
+    /// The segment being written for each partition, there is a single partition if the elements
+    /// are not partitioned by key.
+    #[derivative(Debug = "ignore")]
+    segments: Vec<Option<OpenSegment>>,
+    #[derivative(Debug = "ignore")]
+    buffer: Vec<u8>,
+    /// Elements to emit before pulling the next one, e.g. the closed segments followed by the
+    /// watermark that closed them.
+    #[derivative(Debug = "ignore")]
+    pending: VecDeque<StreamElement<ShuffleSegment>>,
+    /// The usage of the job, where the written bytes are counted, set in `setup`.
+    #[derivative(Debug = "ignore")]
+    quota: Option<Arc<QuotaUsage>>,
+}
+
+impl<Op: Operator> Clone for DiskShuffleWrite<Op>
+where
+    Op::Out: ExchangeData,
+{
+    fn clone(&self) -> Self {
+        assert!(
+            self.prefix.is_none(),
+            "DiskShuffleWrite must be cloned before calling setup"
+        );
+        Self::new(self.prev.clone(), self.dir.clone(), self.key_hasher.clone())
+    }
+}
+
+impl<Op: Operator> Display for DiskShuffleWrite<Op>
+where
+    Op::Out: ExchangeData,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} -> DiskShuffleWrite<{}>",
+            self.prev,
+            std::any::type_name::<Op::Out>()
+        )
+    }
+}
+
+impl<Op: Operator> DiskShuffleWrite<Op>
+where
+    Op::Out: ExchangeData,
+{
+    fn new(prev: Op, dir: PathBuf, key_hasher: Option<KeyHasher<Op::Out>>) -> Self {
+        Self {
+            prev,
+            dir,
+            key_hasher,
+            prefix: None,
+            next_segment: 0,
+            segments: vec![None],
+            buffer: Default::default(),
+            pending: Default::default(),
+            quota: None,
+        }
+    }
+
+    /// Append an element to the segment of its partition, returning the segment if it is full.
+    fn write(&mut self, item: Op::Out, ts: Option<Timestamp>) -> Option<ShuffleSegment> {
+        let partition = match &self.key_hasher {
+            Some(hasher) => (hasher(&item) % self.segments.len() as u64) as usize,
+            None => 0,
+        };
+        if self.segments[partition].is_none() {
+            let prefix = self.prefix.as_ref().expect("setup was not called");
+            let path = self
+                .dir
+                .join(format!("{prefix}-{:06}.shuffle", self.next_segment));
+            self.next_segment += 1;
+            let file = File::create(&path).unwrap_or_else(|err| {
+                panic!("DiskShuffle: error while creating file {path:?}: {err:?}")
+            });
+            self.segments[partition] = Some(OpenSegment {
+                path,
+                writer: BufWriter::new(file),
+                len: 0,
+                bytes: 0,
+            });
+        }
+
+        self.buffer.clear();
+        bincode::serialize_into(&mut self.buffer, &(item, ts))
+            .expect("DiskShuffle: cannot serialize the element");
+        let segment = self.segments[partition].as_mut().unwrap();
+        segment
+            .writer
+            .write_all(&self.buffer)
+            .unwrap_or_else(|err| {
+                panic!(
+                    "DiskShuffle: error while writing segment {:?}: {err:?}",
+                    segment.path
+                )
+            });
+        segment.len += 1;
+        segment.bytes += self.buffer.len();
+        if let Some(quota) = &self.quota {
+            quota.add_spill_bytes(self.buffer.len() as u64);
+        }
+
+        if segment.bytes >= SEGMENT_SIZE {
+            self.close(partition)
+        } else {
+            None
+        }
+    }
+
+    /// Close the segment of a partition, if any, making it readable by the next block.
+    fn close(&mut self, partition: usize) -> Option<ShuffleSegment> {
+        let mut segment = self.segments[partition].take()?;
+        segment.writer.flush().unwrap_or_else(|err| {
+            panic!(
+                "DiskShuffle: error while writing segment {:?}: {err:?}",
+                segment.path
+            )
+        });
+        Some(ShuffleSegment {
+            path: segment.path,
+            len: segment.len,
+            partition,
+        })
+    }
+}
+
+impl<Op: Operator> Operator for DiskShuffleWrite<Op>
+where
+    Op::Out: ExchangeData,
+{
+    type Out = ShuffleSegment;
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        self.prev.setup(metadata);
+        std::fs::create_dir_all(&self.dir).unwrap_or_else(|err| {
+            panic!(
+                "DiskShuffle: error while creating directory {:?}: {err:?}",
+                self.dir
+            )
+        });
+        // the nonce avoids clashing with the segments of other jobs using the same directory
+        let coord = metadata.coord;
+        self.prefix = Some(format!(
+            "{:016x}-b{}-h{}-r{}",
+            tls_rng().generate::<u64>(),
+            coord.block_id,
+            coord.host_id,
+            coord.replica_id
+        ));
+        self.quota = Some(metadata.quota.clone());
+        if self.key_hasher.is_some() {
+            // a partition for each replica, assuming the next block has the same replication
+            self.segments = (0..metadata.replicas.len()).map(|_| None).collect();
+        }
+    }
+
+    fn next(&mut self) -> StreamElement<ShuffleSegment> {
+        if let Some(el) = self.pending.pop_front() {
+            return el;
+        }
+        loop {
+            let el = match self.prev.next() {
+                StreamElement::Item(item) => self.write(item, None),
+                StreamElement::Timestamped(item, ts) => self.write(item, Some(ts)),
+                // the elements written so far must reach the next block before the watermark
+                // and the end of the stream
+                StreamElement::Watermark(ts) => self.finish(StreamElement::Watermark(ts)),
+                StreamElement::FlushAndRestart => self.finish(StreamElement::FlushAndRestart),
+                StreamElement::Terminate => self.finish(StreamElement::Terminate),
+                StreamElement::FlushBatch => None,
+            };
+            if let Some(segment) = el {
+                return StreamElement::Item(segment);
+            }
+            if let Some(el) = self.pending.pop_front() {
+                return el;
+            }
+        }
+    }
+
+    fn structure(&self) -> BlockStructure {
+        self.prev
+            .structure()
+            .add_operator(OperatorStructure::new::<ShuffleSegment, _>(
+                "DiskShuffleWrite",
+            ))
+    }
+}
+
+impl<Op: Operator> DiskShuffleWrite<Op>
+where
+    Op::Out: ExchangeData,
+{
+    /// Close all the open segments and emit `el` after them.
+    fn finish(&mut self, el: StreamElement<ShuffleSegment>) -> Option<ShuffleSegment> {
+        for partition in 0..self.segments.len() {
+            if let Some(segment) = self.close(partition) {
+                self.pending.push_back(StreamElement::Item(segment));
+            }
+        }
+        self.pending.push_back(el);
+        None
+    }
+}
+
+#[derive(Derivative)]
+#[derivative(Debug(bound = "Op: std::fmt::Debug"))]
+pub struct DiskShuffleRead<T, Op>
+where
+    T: ExchangeData,
+    Op: Operator<Out = ShuffleSegment>,
+{
+    prev: Op,
+    #[derivative(Debug = "ignore")]
+    current: Option<(BufReader<File>, ShuffleSegment)>,
+    /// Number of elements still to be read from the current segment.
+    remaining: usize,
+    _t: PhantomData<T>,
+}
+
+impl<T, Op> Clone for DiskShuffleRead<T, Op>
+where
+    T: ExchangeData,
+    Op: Operator<Out = ShuffleSegment>,
+{
+    fn clone(&self) -> Self {
+        assert!(
+            self.current.is_none(),
+            "DiskShuffleRead must be cloned before calling setup"
+        );
+        Self {
+            prev: self.prev.clone(),
+            current: None,
+            remaining: 0,
+            _t: PhantomData,
+        }
+    }
+}
+
+impl<T, Op> Display for DiskShuffleRead<T, Op>
+where
+    T: ExchangeData,
+    Op: Operator<Out = ShuffleSegment>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} -> DiskShuffleRead<{}>",
+            self.prev,
+            std::any::type_name::<T>()
+        )
+    }
+}
+
+impl<T, Op> Operator for DiskShuffleRead<T, Op>
+where
+    T: ExchangeData,
+    Op: Operator<Out = ShuffleSegment>,
+{
+    type Out = T;
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        self.prev.setup(metadata);
+    }
+
+    fn next(&mut self) -> StreamElement<T> {
+        loop {
+            if let Some((reader, segment)) = self.current.as_mut() {
+                if self.remaining > 0 {
+                    self.remaining -= 1;
+                    let (item, ts) = bincode::deserialize_from(reader).unwrap_or_else(|err| {
+                        panic!(
+                            "DiskShuffle: error while reading segment {:?}: {err:?}",
+                            segment.path
+                        )
+                    });
+                    return match ts {
+                        Some(ts) => StreamElement::Timestamped(item, ts),
+                        None => StreamElement::Item(item),
+                    };
+                }
+                // the segment has been consumed
+                if let Err(err) = std::fs::remove_file(&segment.path) {
+                    log::warn!(
+                        "DiskShuffle: cannot remove segment {:?}: {err:?}",
+                        segment.path
+                    );
+                }
+                self.current = None;
+            }
+
+            match self.prev.next() {
+                StreamElement::Item(segment) | StreamElement::Timestamped(segment, _) => {
+                    let file = File::open(&segment.path).unwrap_or_else(|err| {
+                        if err.kind() == ErrorKind::NotFound {
+                            panic!(
+                                "DiskShuffle: segment {:?} not found, the directory of a disk \
+                                 shuffle must be on a filesystem shared by all the hosts",
+                                segment.path
+                            );
+                        }
+                        panic!(
+                            "DiskShuffle: error while opening file {:?}: {err:?}",
+                            segment.path
+                        )
+                    });
+                    self.remaining = segment.len;
+                    self.current = Some((BufReader::new(file), segment));
+                }
+                StreamElement::Watermark(ts) => return StreamElement::Watermark(ts),
+                StreamElement::FlushBatch => return StreamElement::FlushBatch,
+                StreamElement::FlushAndRestart => return StreamElement::FlushAndRestart,
+                StreamElement::Terminate => return StreamElement::Terminate,
+            }
+        }
+    }
+
+    fn structure(&self) -> BlockStructure {
+        self.prev
+            .structure()
+            .add_operator(OperatorStructure::new::<T, _>("DiskShuffleRead"))
+    }
+}
+
+impl<Op: Operator + 'static> Stream<Op>
+where
+    Op::Out: ExchangeData,
+{
+    /// Perform a shuffle that moves the elements to the next block through files written inside
+    /// `dir`, instead of sending them over the network.
+    ///
+    /// Each replica writes its elements into segments of up to 64 MiB, and only the path of a
+    /// segment is sent, to a random replica of the next block, once the segment is complete. The
+    /// next block reads a segment only when it is ready to process it, so a slow downstream block
+    /// never slows down the writers, and the memory used by the shuffle doesn't depend on the
+    /// amount of data. This is useful for very large batch jobs.
+    ///
+    /// Each segment is removed as soon as all its elements have been read. Use
+    /// [`Stream::disk_group_by`] for partitioning the elements by key.
+    ///
+    /// **Note**: with a remote configuration the directory must be on a filesystem shared by all
+    /// the hosts (e.g. NFS), mounted with the same path; reading a segment written by another host
+    /// panics otherwise.
+    ///
+    /// **Note**: this operator will split the current block.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # let mut env = StreamContext::new_local();
+    /// # let dir = tempfile::tempdir().unwrap();
+    /// let res = env
+    ///     .stream_iter(0..100)
+    ///     .disk_shuffle(dir.path())
+    ///     .collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let mut res = res.get().unwrap();
+    /// res.sort_unstable();
+    /// assert_eq!(res, (0..100).collect::<Vec<_>>());
+    /// ```
+    pub fn disk_shuffle(
+        self,
+        dir: &Path,
+    ) -> Stream<DiskShuffleRead<Op::Out, impl Operator<Out = ShuffleSegment>>> {
+        let dir = dir.to_path_buf();
+        self.add_operator(|prev| DiskShuffleWrite::new(prev, dir, None))
+            .shuffle()
+            .add_operator(DiskShuffleRead::new)
+    }
+
+    /// Partition the stream by key like [`Stream::group_by`], moving the elements to the next
+    /// block through files written inside `dir` like [`Stream::disk_shuffle`].
+    ///
+    /// Each replica writes a separate sequence of segments for each replica of the next block, and
+    /// all the segments with the elements of a key are read by the same replica.
+    ///
+    /// **Note**: with a remote configuration the directory must be on a filesystem shared by all
+    /// the hosts (e.g. NFS), mounted with the same path; reading a segment written by another host
+    /// panics otherwise.
+    ///
+    /// **Note**: this operator will split the current block.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # let mut env = StreamContext::new_local();
+    /// # let dir = tempfile::tempdir().unwrap();
+    /// let res = env
+    ///     .stream_iter(0..10)
+    ///     .disk_group_by(|n| n % 2, dir.path())
+    ///     .reduce(|acc, n| *acc += n)
+    ///     .collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let mut res = res.get().unwrap();
+    /// res.sort_unstable();
+    /// assert_eq!(res, vec![(0, 0 + 2 + 4 + 6 + 8), (1, 1 + 3 + 5 + 7 + 9)]);
+    /// ```
+    pub fn disk_group_by<K, Fk>(
+        self,
+        keyer: Fk,
+        dir: &Path,
+    ) -> KeyedStream<impl Operator<Out = (K, Op::Out)>>
+    where
+        Fk: Fn(&Op::Out) -> K + Send + Sync + Clone + 'static,
+        K: DataKey,
+    {
+        let dir = dir.to_path_buf();
+        let key_hasher = {
+            let keyer = keyer.clone();
+            Arc::new(move |item: &Op::Out| group_by_hash(&keyer(item)))
+        };
+        let next_strategy = NextStrategy::GroupBy(
+            |segment: &ShuffleSegment| segment.partition as u64,
+            Default::default(),
+        );
+        let stream = self
+            .add_operator(|prev| DiskShuffleWrite::new(prev, dir, Some(key_hasher)))
+            .split_block(End::new, next_strategy)
+            .add_operator(DiskShuffleRead::new)
+            .add_operator(|prev| KeyBy::new(prev, keyer));
+        KeyedStream(stream)
+    }
+}
+
+impl<T, Op> DiskShuffleRead<T, Op>
+where
+    T: ExchangeData,
+    Op: Operator<Out = ShuffleSegment>,
+{
+    fn new(prev: Op) -> Self {
+        Self {
+            prev,
+            current: None,
+            remaining: 0,
+            _t: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use itertools::Itertools;
+
+    use crate::config::{QuotaConfig, RuntimeConfig};
+    use crate::environment::StreamContext;
+    use crate::network::Coord;
+    use crate::operator::disk_shuffle::{DiskShuffleWrite, ShuffleSegment};
+    use crate::operator::{Operator, StreamElement};
+    use crate::scheduler::QuotaUsage;
+    use crate::test::{FakeNetworkTopology, FakeOperator};
+
+    #[test]
+    fn disk_shuffle_segments() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut fake = FakeOperator::empty();
+        fake.push(StreamElement::Timestamped(1u32, 10));
+        fake.push(StreamElement::Item(2));
+        fake.push(StreamElement::Watermark(20));
+        fake.push(StreamElement::Item(3));
+        fake.push(StreamElement::FlushAndRestart);
+
+        let mut write = DiskShuffleWrite::new(fake, dir.path().into(), None);
+        write.setup(&mut FakeNetworkTopology::<u32>::new(0, 0).metadata());
+
+        let StreamElement::Item(first) = write.next() else {
+            panic!("expected a segment");
+        };
+        assert_eq!(first.len, 2);
+        assert_eq!(write.next(), StreamElement::Watermark(20));
+        let StreamElement::Item(second) = write.next() else {
+            panic!("expected a segment");
+        };
+        assert_eq!(second.len, 1);
+        assert_eq!(write.next(), StreamElement::FlushAndRestart);
+        assert_eq!(write.next(), StreamElement::Terminate);
+
+        let first = first.read::<u32>().collect_vec();
+        assert_eq!(first, vec![(1, Some(10)), (2, None)]);
+        let second = ShuffleSegment::read::<u32>(&second).collect_vec();
+        assert_eq!(second, vec![(3, None)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Job quota exceeded")]
+    fn disk_shuffle_quota() {
+        let dir = tempfile::tempdir().unwrap();
+        let fake = FakeOperator::new(0..100u64);
+        let mut write = DiskShuffleWrite::new(fake, dir.path().into(), None);
+        let mut t = FakeNetworkTopology::<u64>::new(0, 0);
+        let mut metadata = t.metadata();
+        metadata.quota = Arc::new(QuotaUsage::new(QuotaConfig {
+            max_spill_bytes: Some(100),
+            ..Default::default()
+        }));
+        write.setup(&mut metadata);
+
+        while write.next() != StreamElement::Terminate {}
+    }
+
+    #[test]
+    fn disk_shuffle_group_by() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        let res = env
+            .stream_iter(0..1000u64)
+            .disk_shuffle(dir.path())
+            .group_by_sum(|n| n % 10, |n| n)
+            .collect_vec();
+        env.execute_blocking();
+
+        let res = res.get().unwrap().into_iter().sorted().collect_vec();
+        let expected = (0..10)
+            .map(|k| (k, (0..1000).filter(|n| n % 10 == k).sum()))
+            .collect_vec();
+        assert_eq!(res, expected);
+        // the segments are removed once consumed
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn disk_shuffle_partitions() {
+        let dir = tempfile::tempdir().unwrap();
+        let fake = FakeOperator::new(0..10u64);
+        let hasher = Arc::new(|n: &u64| *n);
+        let mut write = DiskShuffleWrite::new(fake, dir.path().into(), Some(hasher));
+        let mut t = FakeNetworkTopology::<u64>::new(0, 0);
+        let mut metadata = t.metadata();
+        metadata.replicas = (0..3).map(|r| Coord::new(0, 0, r)).collect();
+        write.setup(&mut metadata);
+
+        let mut segments = Vec::new();
+        while let StreamElement::Item(segment) = write.next() {
+            segments.push(segment);
+        }
+        segments.sort_by_key(|s| s.partition);
+        let partitions = segments
+            .iter()
+            .map(|s| (s.partition, s.read::<u64>().map(|(n, _)| n).collect_vec()))
+            .collect_vec();
+        assert_eq!(
+            partitions,
+            vec![
+                (0, vec![0, 3, 6, 9]),
+                (1, vec![1, 4, 7]),
+                (2, vec![2, 5, 8])
+            ]
+        );
+    }
+
+    #[test]
+    fn disk_group_by() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        let res = env
+            .stream_iter(0..1000u64)
+            .disk_group_by(|n| n % 10, dir.path())
+            .rich_map({
+                // each key must be seen by a single replica
+                let mut count = 0;
+                move |(_, n)| {
+                    count += 1;
+                    (n, count)
+                }
+            })
+            .collect_vec();
+        env.execute_blocking();
+
+        let res = res.get().unwrap();
+        assert_eq!(res.len(), 1000);
+        for k in 0..10 {
+            let counts = res
+                .iter()
+                .filter(|(key, _)| *key == k)
+                .map(|(_, (_, count))| *count)
+                .sorted()
+                .collect_vec();
+            assert_eq!(counts, (1..=100).collect_vec());
+        }
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,128 @@
+use std::fmt::Debug;
+
+use crate::operator::{Data, Operator};
+use crate::Stream;
+
+impl<T, Op> Stream<Op>
+where
+    T: Data,
+    Op: Operator<Out = Option<T>> + 'static,
+{
+    /// Remove the `None` elements from the stream and unwrap the `Some(_)` ones.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let s = env.stream_iter(vec![Some(1), None, Some(3)].into_iter());
+    /// let res = s.flatten_option().collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// assert_eq!(res.get().unwrap(), vec![1, 3]);
+    /// ```
+    pub fn flatten_option(self) -> Stream<impl Operator<Out = T>> {
+        self.filter_map(|x| x)
+    }
+
+    /// Replace the `None` elements of the stream with `default` and unwrap the `Some(_)` ones.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let s = env.stream_iter(vec![Some(1), None, Some(3)].into_iter());
+    /// let res = s.unwrap_or(0).collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// assert_eq!(res.get().unwrap(), vec![1, 0, 3]);
+    /// ```
+    pub fn unwrap_or(self, default: T) -> Stream<impl Operator<Out = T>> {
+        self.map(move |x| x.unwrap_or_else(|| default.clone()))
+    }
+}
+
+impl<T, E, Op> Stream<Op>
+where
+    T: Data,
+    E: Data,
+    Op: Operator<Out = Result<T, E>> + 'static,
+{
+    /// Remove the `Err(_)` elements from the stream and unwrap the `Ok(_)` ones.
+    ///
+    /// The errors are discarded silently, see [`Stream::ok_or_log`] for logging them.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let s = env.stream_iter(vec!["1", "x", "3"].into_iter());
+    /// let res = s.map(|s| s.parse::<i32>()).filter_ok().collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// assert_eq!(res.get().unwrap(), vec![1, 3]);
+    /// ```
+    pub fn filter_ok(self) -> Stream<impl Operator<Out = T>> {
+        self.filter_map(|x| x.ok())
+    }
+
+    /// Remove the `Ok(_)` elements from the stream and unwrap the `Err(_)` ones.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let s = env.stream_iter(vec!["1", "x", "3"].into_iter());
+    /// let res = s.map(|s| s.parse::<i32>().map_err(|_| s.to_string())).filter_err().collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// assert_eq!(res.get().unwrap(), vec!["x"]);
+    /// ```
+    pub fn filter_err(self) -> Stream<impl Operator<Out = E>> {
+        self.filter_map(|x| x.err())
+    }
+}
+
+impl<T, E, Op> Stream<Op>
+where
+    T: Data,
+    E: Data + Debug,
+    Op: Operator<Out = Result<T, E>> + 'static,
+{
+    /// Remove the `Err(_)` elements from the stream, logging them as warnings, and unwrap the
+    /// `Ok(_)` ones.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let s = env.stream_iter(vec!["1", "x", "3"].into_iter());
+    /// let res = s.map(|s| s.parse::<i32>()).ok_or_log().collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// assert_eq!(res.get().unwrap(), vec![1, 3]);
+    /// ```
+    pub fn ok_or_log(self) -> Stream<impl Operator<Out = T>> {
+        self.filter_map(|x| match x {
+            Ok(x) => Some(x),
+            Err(e) => {
+                warn!("discarding error: {e:?}");
+                None
+            }
+        })
+    }
+}
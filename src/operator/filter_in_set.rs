@@ -0,0 +1,314 @@
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::hash::Hash;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::block::{group_by_hash, BlockStructure, NextStrategy, OperatorReceiver};
+use crate::block::{OperatorStructure, Replication};
+use crate::operator::iteration::IterationStateLock;
+use crate::operator::source::Source;
+use crate::operator::start::{BinaryElement, BinaryStartOperator, Start};
+use crate::operator::{ExchangeData, Operator, StreamElement};
+use crate::scheduler::{BlockId, ExecutionMetadata};
+use crate::Stream;
+
+/// A Bloom filter: a compact set that answers whether it contains an element with no false
+/// negatives and a bounded rate of false positives.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    num_bits: u64,
+    num_hashes: u32,
+}
+
+impl BloomFilter {
+    /// Create an empty filter sized for holding `expected_items` elements with the given
+    /// `false_positive_rate`, in the range `(0, 1)`.
+    ///
+    /// The rate of false positives grows if more than `expected_items` elements are inserted.
+    pub fn new(expected_items: usize, false_positive_rate: f64) -> Self {
+        assert!(
+            false_positive_rate > 0.0 && false_positive_rate < 1.0,
+            "the false positive rate of BloomFilter must be in (0, 1), got {false_positive_rate}"
+        );
+        let n = expected_items.max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let num_bits = (-n * false_positive_rate.ln() / (ln2 * ln2))
+            .ceil()
+            .max(64.0) as u64;
+        let num_hashes = ((num_bits as f64 / n) * ln2).round().clamp(1.0, 32.0) as u32;
+        Self {
+            bits: vec![0; num_bits.div_ceil(64) as usize],
+            num_bits,
+            num_hashes,
+        }
+    }
+
+    /// The positions of the bits of an element, using double hashing on the two halves of its
+    /// hash.
+    fn positions<T: Hash + ?Sized>(&self, item: &T) -> impl Iterator<Item = u64> + '_ {
+        let hash = group_by_hash(&item);
+        let (h1, h2) = (hash & 0xffff_ffff, hash >> 32);
+        (0..self.num_hashes as u64)
+            .map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % self.num_bits)
+    }
+
+    pub fn insert<T: Hash + ?Sized>(&mut self, item: &T) {
+        let positions: Vec<_> = self.positions(item).collect();
+        for p in positions {
+            self.bits[(p / 64) as usize] |= 1 << (p % 64);
+        }
+    }
+
+    /// Whether the filter may contain `item`: `false` means that `item` has never been inserted.
+    pub fn contains<T: Hash + ?Sized>(&self, item: &T) -> bool {
+        self.positions(item)
+            .all(|p| self.bits[(p / 64) as usize] & (1 << (p % 64)) != 0)
+    }
+
+    /// Add all the elements of another filter, created with the same parameters.
+    pub fn union(&mut self, other: &BloomFilter) {
+        assert_eq!(
+            (self.num_bits, self.num_hashes),
+            (other.num_bits, other.num_hashes),
+            "cannot merge Bloom filters with different parameters"
+        );
+        for (a, b) in self.bits.iter_mut().zip(&other.bits) {
+            *a |= b;
+        }
+    }
+}
+
+/// Operator that keeps the elements of the left side whose key may be in the Bloom filter
+/// received from the right side.
+///
+/// The left elements are stashed until the right side has ended, the filter is empty if the right
+/// side ends without sending it.
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""), Debug)]
+pub struct FilterInSet<Out: ExchangeData, K, Keyer>
+where
+    Keyer: Fn(&Out) -> K + Clone + Send + 'static,
+{
+    #[derivative(Debug = "ignore")]
+    prev: BinaryStartOperator<Out, BloomFilter>,
+    #[derivative(Debug = "ignore")]
+    keyer: Keyer,
+    filter: Option<BloomFilter>,
+    right_ended: bool,
+    #[derivative(Debug = "ignore")]
+    stash: VecDeque<StreamElement<Out>>,
+    prev_block_id1: BlockId,
+    prev_block_id2: BlockId,
+}
+
+impl<Out: ExchangeData, K, Keyer> Display for FilterInSet<Out, K, Keyer>
+where
+    Keyer: Fn(&Out) -> K + Clone + Send + 'static,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "FilterInSet<{}>", std::any::type_name::<Out>())
+    }
+}
+
+impl<Out: ExchangeData, K: Hash, Keyer> FilterInSet<Out, K, Keyer>
+where
+    Keyer: Fn(&Out) -> K + Clone + Send + 'static,
+{
+    fn new(
+        prev_block_id1: BlockId,
+        prev_block_id2: BlockId,
+        left_cache: bool,
+        right_cache: bool,
+        state_lock: Option<Arc<IterationStateLock>>,
+        keyer: Keyer,
+    ) -> Self {
+        Self {
+            prev: Start::multiple(
+                prev_block_id1,
+                prev_block_id2,
+                left_cache,
+                right_cache,
+                state_lock,
+            ),
+            keyer,
+            filter: None,
+            right_ended: false,
+            stash: Default::default(),
+            prev_block_id1,
+            prev_block_id2,
+        }
+    }
+
+    fn keep(&self, item: &Out) -> bool {
+        self.filter
+            .as_ref()
+            .is_some_and(|filter| filter.contains(&(self.keyer)(item)))
+    }
+}
+
+impl<Out: ExchangeData, K: Hash, Keyer> Operator for FilterInSet<Out, K, Keyer>
+where
+    Keyer: Fn(&Out) -> K + Clone + Send + 'static,
+{
+    type Out = Out;
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        self.prev.setup(metadata);
+    }
+
+    fn next(&mut self) -> StreamElement<Out> {
+        loop {
+            if self.right_ended {
+                if let Some(el) = self.stash.pop_front() {
+                    match &el {
+                        StreamElement::Item(item) | StreamElement::Timestamped(item, _)
+                            if !self.keep(item) => {}
+                        _ => return el,
+                    }
+                    continue;
+                }
+            }
+
+            let el = match self.prev.next() {
+                StreamElement::Item(BinaryElement::Left(item)) => StreamElement::Item(item),
+                StreamElement::Timestamped(BinaryElement::Left(item), ts) => {
+                    StreamElement::Timestamped(item, ts)
+                }
+                StreamElement::Item(BinaryElement::Right(filter))
+                | StreamElement::Timestamped(BinaryElement::Right(filter), _) => {
+                    match self.filter.as_mut() {
+                        Some(f) => f.union(&filter),
+                        None => self.filter = Some(filter),
+                    }
+                    continue;
+                }
+                StreamElement::Item(BinaryElement::RightEnd)
+                | StreamElement::Timestamped(BinaryElement::RightEnd, _) => {
+                    self.right_ended = true;
+                    continue;
+                }
+                // ignore LeftEnd
+                StreamElement::Item(_) | StreamElement::Timestamped(_, _) => continue,
+                StreamElement::Watermark(ts) => StreamElement::Watermark(ts),
+                StreamElement::FlushAndRestart => {
+                    // both sides have ended, so the stash has already been emptied
+                    self.filter = None;
+                    self.right_ended = false;
+                    return StreamElement::FlushAndRestart;
+                }
+                StreamElement::FlushBatch => return StreamElement::FlushBatch,
+                StreamElement::Terminate => return StreamElement::Terminate,
+            };
+            self.stash.push_back(el);
+        }
+    }
+
+    fn structure(&self) -> BlockStructure {
+        let mut operator = OperatorStructure::new::<Out, _>("FilterInSet");
+        operator
+            .receivers
+            .push(OperatorReceiver::new::<Out>(self.prev_block_id1));
+        operator
+            .receivers
+            .push(OperatorReceiver::new::<BloomFilter>(self.prev_block_id2));
+        BlockStructure::default().add_operator(operator)
+    }
+}
+
+impl<Out: ExchangeData, K: Hash, Keyer> Source for FilterInSet<Out, K, Keyer>
+where
+    Keyer: Fn(&Out) -> K + Clone + Send + 'static,
+{
+    fn replication(&self) -> Replication {
+        Replication::Unlimited
+    }
+}
+
+impl<Op> Stream<Op>
+where
+    Op: Operator + 'static,
+    Op::Out: ExchangeData,
+{
+    /// Keep only the elements whose key, obtained with `keyer`, is one of the elements of `keys`.
+    ///
+    /// Instead of shuffling both streams like a join, a [`BloomFilter`] of the keys is built and
+    /// broadcast to all the replicas of this stream, so the elements are filtered where they are.
+    /// The filter is sized for `expected_keys` keys with the given `false_positive_rate`: some
+    /// elements whose key is not in `keys` may be kept, none whose key is in `keys` is dropped.
+    /// This makes it a cheap pre-filter in front of an exact join against a large set of keys.
+    ///
+    /// The elements are held back until all the keys have been received.
+    ///
+    /// **Note**: this operator will split the current block.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # let mut env = StreamContext::new_local();
+    /// let users = env.stream_iter(vec![(1, 'a'), (2, 'b'), (3, 'c')].into_iter());
+    /// let banned = env.stream_iter(vec![2, 3, 42].into_iter());
+    /// let res = users
+    ///     .filter_in_set(banned, |(id, _)| *id, 1000, 0.001)
+    ///     .map(|(id, _)| id)
+    ///     .collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let mut res = res.get().unwrap();
+    /// res.sort_unstable();
+    /// assert_eq!(res, vec![2, 3]);
+    /// ```
+    pub fn filter_in_set<K, Op2, Keyer>(
+        self,
+        keys: Stream<Op2>,
+        keyer: Keyer,
+        expected_keys: usize,
+        false_positive_rate: f64,
+    ) -> Stream<impl Operator<Out = Op::Out>>
+    where
+        K: Hash + Send + 'static,
+        Op2: Operator<Out = K> + 'static,
+        Keyer: Fn(&Op::Out) -> K + Clone + Send + 'static,
+    {
+        let filter = BloomFilter::new(expected_keys, false_positive_rate);
+        let filter = keys.fold_assoc(
+            filter,
+            |filter, key| filter.insert(&key),
+            |filter, other| filter.union(&other),
+        );
+        self.binary_connection(
+            filter,
+            move |id1, id2, cache1, cache2, state_lock| {
+                FilterInSet::new(id1, id2, cache1, cache2, state_lock, keyer)
+            },
+            NextStrategy::only_one(),
+            NextStrategy::all(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BloomFilter;
+
+    #[test]
+    fn bloom_filter() {
+        let mut filter = BloomFilter::new(1000, 0.01);
+        for i in 0..1000 {
+            filter.insert(&i);
+        }
+        assert!((0..1000).all(|i| filter.contains(&i)));
+        let false_positives = (1000..11000).filter(|i| filter.contains(i)).count();
+        assert!(false_positives < 200, "{false_positives} false positives");
+
+        let mut other = BloomFilter::new(1000, 0.01);
+        other.insert(&"a");
+        assert!(!filter.contains(&"a"));
+        filter.union(&other);
+        assert!(filter.contains(&"a"));
+    }
+}
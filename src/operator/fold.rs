@@ -1,6 +1,6 @@
 use std::fmt::Display;
 
-use crate::block::{BlockStructure, OperatorStructure};
+use crate::block::{BlockStructure, OperatorStructure, StateEntries};
 use crate::operator::{Operator, StreamElement, Timestamp};
 use crate::scheduler::ExecutionMetadata;
 
@@ -122,9 +122,9 @@ where
     }
 
     fn structure(&self) -> BlockStructure {
-        self.prev
-            .structure()
-            .add_operator(OperatorStructure::new::<O, _>("Fold"))
+        self.prev.structure().add_operator(
+            OperatorStructure::new::<O, _>("Fold").with_state::<O>(StateEntries::Single),
+        )
     }
 }
 
@@ -0,0 +1,246 @@
+//! Graph algorithms on top of streams of edges.
+//!
+//! A [`Graph`] wraps a stream of edges `(source, destination)`, its algorithms are built with
+//! keyed streams and iterations.
+
+use crate::operator::{ExchangeDataKey, Operator};
+use crate::Stream;
+
+/// The damping factor of [`Graph::page_rank`].
+const DAMPING: f64 = 0.85;
+
+/// A graph defined by a stream of edges `(source, destination)`.
+///
+/// The vertices are the ones appearing in at least one edge. The algorithms consume the graph, use
+/// [`Stream::split`] on the edges for running more than one of them.
+pub struct Graph<Op>
+where
+    Op: Operator,
+{
+    edges: Stream<Op>,
+}
+
+impl<V, Op> Graph<Op>
+where
+    V: ExchangeDataKey + Ord,
+    Op: Operator<Out = (V, V)> + 'static,
+{
+    /// Create a graph from a stream of edges `(source, destination)`.
+    pub fn from_edges(edges: Stream<Op>) -> Self {
+        Self { edges }
+    }
+
+    /// The stream of the edges of the graph.
+    pub fn into_edges(self) -> Stream<Op> {
+        self.edges
+    }
+
+    /// Compute the PageRank of the vertices with `iterations` iterations, using the direction of
+    /// the edges.
+    ///
+    /// The ranks are not normalized: each vertex starts with rank 1 and the ranks sum to the
+    /// number of vertices, less the rank lost by the vertices without outgoing edges.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::graph::Graph;
+    /// # let mut env = StreamContext::new_local();
+    /// let edges = env.stream_iter(vec![(0, 1), (1, 2), (2, 0)].into_iter());
+    /// let res = Graph::from_edges(edges).page_rank(10).collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// // the ranks of a cycle are all equal
+    /// for (_, rank) in res.get().unwrap() {
+    ///     assert!((rank - 1.0).abs() < 1e-9);
+    /// }
+    /// ```
+    pub fn page_rank(self, iterations: usize) -> Stream<impl Operator<Out = (V, f64)>> {
+        let mut splits = self.edges.split(2);
+        let adjacency = splits
+            .pop()
+            .unwrap()
+            .group_by_fold(
+                |(x, _)| x.clone(),
+                Vec::new(),
+                |adj, (_, y)| adj.push(y),
+                |adj, mut other| adj.append(&mut other),
+            )
+            .unkey();
+        let vertices = vertices(splits.pop().unwrap()).map(|v| (v, 1.0));
+
+        let (state, ranks) = vertices.iterate(
+            iterations,
+            (),
+            move |s, _| {
+                let mut splits = s.split(2);
+                let old_ranks = splits.pop().unwrap();
+                let received = splits
+                    .pop()
+                    .unwrap()
+                    .join(adjacency, |(x, _)| x.clone(), |(x, _)| x.clone())
+                    // distribute the rank of the vertex between its neighbours
+                    .flat_map(|(_, ((_, rank), (_, adj)))| {
+                        let share = rank / adj.len() as f64;
+                        adj.into_iter().map(move |y| (y, share))
+                    })
+                    .drop_key()
+                    .group_by_sum(|(y, _)| y.clone(), |(_, share)| share)
+                    .unkey();
+                // the vertices without incoming edges receive nothing, but must not be lost
+                old_ranks
+                    .left_join(received, |(x, _)| x.clone(), |(y, _)| y.clone())
+                    .map(|(_, (_, received))| {
+                        let received = received.map_or(0.0, |(_, r)| r);
+                        (1.0 - DAMPING) + DAMPING * received
+                    })
+                    .unkey()
+            },
+            |_: &mut (), _| {},
+            |_, _| {},
+            |_| true,
+        );
+        state.for_each(|_| {});
+        ranks
+    }
+
+    /// Find the connected components of the graph, ignoring the direction of the edges.
+    ///
+    /// Each vertex is labeled with the smallest vertex of its component. The labels are propagated
+    /// along the edges until no label changes, so the number of iterations is bounded by the
+    /// diameter of the graph.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::graph::Graph;
+    /// # let mut env = StreamContext::new_local();
+    /// let edges = env.stream_iter(vec![(3, 1), (1, 2), (4, 5)].into_iter());
+    /// let res = Graph::from_edges(edges).connected_components().collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let mut res = res.get().unwrap();
+    /// res.sort_unstable();
+    /// assert_eq!(res, vec![(1, 1), (2, 1), (3, 1), (4, 4), (5, 4)]);
+    /// ```
+    pub fn connected_components(self) -> Stream<impl Operator<Out = (V, V)>> {
+        let mut splits = self.edges.split(2);
+        let vertices = vertices(splits.pop().unwrap());
+        // the edges are undirected
+        let edges = splits
+            .pop()
+            .unwrap()
+            .flat_map(|(x, y)| [(x.clone(), y.clone()), (y, x)]);
+
+        // each vertex starts in its own component, the flag tells whether the label has changed
+        // in the last iteration
+        let (state, labels) = vertices.map(|v| (v.clone(), v, true)).iterate(
+            usize::MAX,
+            false,
+            move |s, _| {
+                let mut splits = s.split(2);
+                let old_labels = splits.pop().unwrap();
+                // only the changed labels have to be propagated
+                let received = splits
+                    .pop()
+                    .unwrap()
+                    .filter(|(_, _, changed)| *changed)
+                    .join(edges, |(x, _, _)| x.clone(), |(x, _)| x.clone())
+                    .map(|(_, ((_, label, _), (_, y)))| (y, label))
+                    .drop_key()
+                    .group_by_min_element(|(y, _)| y.clone(), |(_, label)| label.clone())
+                    .drop_key();
+                old_labels
+                    .left_join(received, |(x, _, _)| x.clone(), |(y, _)| y.clone())
+                    .map(|(_, ((_, label, _), received))| match received {
+                        Some((_, received)) if received < label => (received, true),
+                        _ => (label, false),
+                    })
+                    .unkey()
+                    .map(|(x, (label, changed))| (x, label, changed))
+            },
+            |changed: &mut bool, (_, _, c)| *changed |= c,
+            |changed, c| *changed |= c,
+            std::mem::take,
+        );
+        state.for_each(|_| {});
+        labels.map(|(x, label, _)| (x, label))
+    }
+
+    /// Count the triangles of the graph, ignoring the direction of the edges, the self loops and
+    /// the repeated edges.
+    ///
+    /// The stream contains a single element, or none if the graph has no triangles.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::graph::Graph;
+    /// # let mut env = StreamContext::new_local();
+    /// let edges = vec![(0, 1), (1, 2), (2, 0), (2, 3), (3, 0), (1, 0)];
+    /// let edges = env.stream_iter(edges.into_iter());
+    /// let res = Graph::from_edges(edges).triangle_count().collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// assert_eq!(res.get().unwrap(), vec![2]);
+    /// ```
+    pub fn triangle_count(self) -> Stream<impl Operator<Out = u64>> {
+        let mut edges = self
+            .edges
+            .filter(|(x, y)| x != y)
+            // make sure the first vertex is the smallest
+            .map(|(x, y)| if x < y { (x, y) } else { (y, x) })
+            .group_by_fold(|edge| edge.clone(), (), |_, _| {}, |_, _| {})
+            .unkey()
+            .map(|(edge, ())| edge)
+            .split(2);
+
+        edges
+            .pop()
+            .unwrap()
+            .group_by_fold(
+                |(x, _)| x.clone(),
+                Vec::new(),
+                |adj, (_, y)| adj.push(y),
+                |adj, mut other| adj.append(&mut other),
+            )
+            // each pair of neighbours of x closes a triangle if they are connected
+            .flat_map(|(_, adj)| {
+                let mut pairs = Vec::new();
+                for i in 0..adj.len() {
+                    for j in 0..i {
+                        let (y, z) = (&adj[i], &adj[j]);
+                        pairs.push((y.min(z).clone(), y.max(z).clone()));
+                    }
+                }
+                pairs
+            })
+            .drop_key()
+            .join(
+                edges.pop().unwrap(),
+                |pair| pair.clone(),
+                |edge| edge.clone(),
+            )
+            .drop_key()
+            .fold_assoc(0, |count, _| *count += 1, |count, other| *count += other)
+    }
+}
+
+/// The distinct vertices appearing in the edges.
+fn vertices<V, Op>(edges: Stream<Op>) -> Stream<impl Operator<Out = V>>
+where
+    V: ExchangeDataKey,
+    Op: Operator<Out = (V, V)> + 'static,
+{
+    edges
+        .flat_map(|(x, y)| [x, y])
+        .group_by_fold(|v| v.clone(), (), |_, _| {}, |_, _| {})
+        .unkey()
+        .map(|(v, ())| v)
+}
@@ -0,0 +1,241 @@
+use std::collections::VecDeque;
+use std::fmt::Display;
+
+use crate::block::{BlockStructure, OperatorStructure};
+use crate::operator::{Data, Operator, StreamElement};
+use crate::scheduler::ExecutionMetadata;
+use crate::Stream;
+
+#[derive(Clone, Derivative)]
+#[derivative(Debug)]
+pub struct OnStart<F, Op>
+where
+    F: FnMut(&ExecutionMetadata) + Send + Clone,
+    Op: Operator,
+{
+    prev: Op,
+    #[derivative(Debug = "ignore")]
+    f: F,
+}
+
+impl<F, Op> OnStart<F, Op>
+where
+    F: FnMut(&ExecutionMetadata) + Send + Clone,
+    Op: Operator,
+{
+    pub fn new(prev: Op, f: F) -> Self {
+        Self { prev, f }
+    }
+}
+
+impl<F, Op> Display for OnStart<F, Op>
+where
+    F: FnMut(&ExecutionMetadata) + Send + Clone,
+    Op: Operator,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} -> OnStart", self.prev)
+    }
+}
+
+impl<F, Op> Operator for OnStart<F, Op>
+where
+    F: FnMut(&ExecutionMetadata) + Send + Clone,
+    Op: Operator,
+{
+    type Out = Op::Out;
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        self.prev.setup(metadata);
+        (self.f)(metadata);
+    }
+
+    #[inline]
+    fn next(&mut self) -> StreamElement<Self::Out> {
+        self.prev.next()
+    }
+
+    fn structure(&self) -> BlockStructure {
+        let operator = OperatorStructure::new::<Op::Out, _>("OnStart");
+        self.prev.structure().add_operator(operator)
+    }
+}
+
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct OnTerminate<F, I, Op>
+where
+    F: FnMut() -> I + Send + Clone,
+    I: IntoIterator<Item = Op::Out>,
+    Op: Operator,
+{
+    prev: Op,
+    #[derivative(Debug = "ignore")]
+    f: F,
+    /// The items returned by the hook still to be emitted.
+    #[derivative(Debug = "ignore")]
+    buffer: VecDeque<Op::Out>,
+    terminated: bool,
+}
+
+impl<F, I, Op> Clone for OnTerminate<F, I, Op>
+where
+    F: FnMut() -> I + Send + Clone,
+    I: IntoIterator<Item = Op::Out>,
+    Op: Operator,
+{
+    fn clone(&self) -> Self {
+        Self::new(self.prev.clone(), self.f.clone())
+    }
+}
+
+impl<F, I, Op> OnTerminate<F, I, Op>
+where
+    F: FnMut() -> I + Send + Clone,
+    I: IntoIterator<Item = Op::Out>,
+    Op: Operator,
+{
+    pub fn new(prev: Op, f: F) -> Self {
+        Self {
+            prev,
+            f,
+            buffer: Default::default(),
+            terminated: false,
+        }
+    }
+}
+
+impl<F, I, Op> Display for OnTerminate<F, I, Op>
+where
+    F: FnMut() -> I + Send + Clone,
+    I: IntoIterator<Item = Op::Out>,
+    Op: Operator,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} -> OnTerminate", self.prev)
+    }
+}
+
+impl<F, I, Op> Operator for OnTerminate<F, I, Op>
+where
+    F: FnMut() -> I + Send + Clone,
+    I: IntoIterator<Item = Op::Out>,
+    Op: Operator,
+{
+    type Out = Op::Out;
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        self.prev.setup(metadata);
+    }
+
+    fn next(&mut self) -> StreamElement<Self::Out> {
+        if self.terminated {
+            return match self.buffer.pop_front() {
+                Some(item) => StreamElement::Item(item),
+                None => StreamElement::Terminate,
+            };
+        }
+        match self.prev.next() {
+            StreamElement::Terminate => {
+                self.terminated = true;
+                self.buffer.extend((self.f)());
+                self.next()
+            }
+            el => el,
+        }
+    }
+
+    fn structure(&self) -> BlockStructure {
+        let operator = OperatorStructure::new::<Op::Out, _>("OnTerminate");
+        self.prev.structure().add_operator(operator)
+    }
+}
+
+impl<Op> Stream<Op>
+where
+    Op: Operator + 'static,
+    Op::Out: Data,
+{
+    /// Call a function once for each replica of the current block, when the replica is set up and
+    /// before the stream starts.
+    ///
+    /// The function receives the metadata of the replica, and can be used for preparing the
+    /// resources used by the following operators (e.g. loading a model or opening a connection).
+    ///
+    /// **Note**: the replicas are set up one after the other, before the execution starts.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let s = env.stream_iter(0..5);
+    /// s.on_start(|metadata| println!("replica {} is starting", metadata.global_id))
+    ///     .for_each(|_| {});
+    ///
+    /// env.execute_blocking();
+    /// ```
+    pub fn on_start<F>(self, f: F) -> Stream<impl Operator<Out = Op::Out>>
+    where
+        F: FnMut(&ExecutionMetadata) + Send + Clone + 'static,
+    {
+        self.add_operator(|prev| OnStart::new(prev, f))
+    }
+
+    /// Call a function once for each replica of the current block, when its stream terminates.
+    ///
+    /// The items returned by the function are emitted before the end of the stream, for example
+    /// for flushing the elements buffered by the previous operators.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let s = env.stream_iter(0..5);
+    /// let res = s.on_terminate(|| vec![100]).collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// assert_eq!(res.get().unwrap(), vec![0, 1, 2, 3, 4, 100]);
+    /// ```
+    pub fn on_terminate<F, I>(self, f: F) -> Stream<impl Operator<Out = Op::Out>>
+    where
+        F: FnMut() -> I + Send + Clone + 'static,
+        I: IntoIterator<Item = Op::Out> + 'static,
+    {
+        self.add_operator(|prev| OnTerminate::new(prev, f))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use crate::operator::hooks::{OnStart, OnTerminate};
+    use crate::operator::{Operator, StreamElement};
+    use crate::test::{FakeNetworkTopology, FakeOperator};
+
+    #[test]
+    fn on_start_on_terminate() {
+        let started = Arc::new(AtomicUsize::new(0));
+        let fake = FakeOperator::new(0..2u8);
+        let counter = started.clone();
+        let on_start = OnStart::new(fake, move |_: &_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        let mut op = OnTerminate::new(on_start, || vec![10, 11]);
+        op.setup(&mut FakeNetworkTopology::<u8>::new(0, 0).metadata());
+        assert_eq!(started.load(Ordering::SeqCst), 1);
+
+        assert_eq!(op.next(), StreamElement::Item(0));
+        assert_eq!(op.next(), StreamElement::Item(1));
+        assert_eq!(op.next(), StreamElement::Item(10));
+        assert_eq!(op.next(), StreamElement::Item(11));
+        assert_eq!(op.next(), StreamElement::Terminate);
+        assert_eq!(started.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,330 @@
+use std::fmt::Display;
+use std::hash::Hash;
+
+use crate::block::{BlockStructure, OperatorStructure, StateEntries};
+use crate::operator::{ExchangeData, Operator, ReportState, StateProbe, StreamElement, Timestamp};
+use crate::scheduler::ExecutionMetadata;
+
+/// Fixed-width integer keys, supported by [`Stream::group_by_fold_int`](crate::Stream::group_by_fold_int).
+pub trait IntKey: Copy + Eq + Hash + ExchangeData {
+    /// The bits of the key, used for placing it inside the table.
+    fn bits(self) -> u64;
+}
+
+macro_rules! impl_int_key {
+    ($($t:ty),*) => {
+        $(
+            impl IntKey for $t {
+                #[inline]
+                fn bits(self) -> u64 {
+                    self as u64
+                }
+            }
+        )*
+    };
+}
+
+impl_int_key!(i32, i64, u32, u64);
+
+/// An entry of the table: the key, its accumulator and the maximum timestamp of its elements.
+type Slot<K, O> = Option<(K, O, Option<Timestamp>)>;
+
+/// Hash table with open addressing and linear probing, storing the accumulators inline.
+///
+/// The table is used only for aggregating, so the entries can only be inserted and then drained
+/// all together.
+#[derive(Clone, Debug)]
+struct IntKeyTable<K, O> {
+    slots: Vec<Slot<K, O>>,
+    len: usize,
+}
+
+impl<K: IntKey, O> IntKeyTable<K, O> {
+    const INITIAL_CAPACITY: usize = 16;
+
+    fn new() -> Self {
+        Self {
+            slots: Self::empty_slots(Self::INITIAL_CAPACITY),
+            len: 0,
+        }
+    }
+
+    fn empty_slots(capacity: usize) -> Vec<Slot<K, O>> {
+        std::iter::repeat_with(|| None).take(capacity).collect()
+    }
+
+    /// The index of the first slot to probe for a key, using Fibonacci hashing.
+    #[inline]
+    fn home(&self, key: K) -> usize {
+        let shift = 64 - self.slots.len().trailing_zeros();
+        (key.bits().wrapping_mul(0x9e3779b97f4a7c15) >> shift) as usize
+    }
+
+    /// The index of the slot holding the key, or of the empty slot where it should be inserted.
+    #[inline]
+    fn find(&self, key: K) -> usize {
+        let mask = self.slots.len() - 1;
+        let mut index = self.home(key);
+        loop {
+            match &self.slots[index] {
+                Some((k, _, _)) if *k != key => index = (index + 1) & mask,
+                _ => return index,
+            }
+        }
+    }
+
+    /// Get the accumulator of the key, inserting the one built by `init` if the key is missing.
+    #[inline]
+    fn entry(&mut self, key: K, init: impl FnOnce() -> O) -> &mut (K, O, Option<Timestamp>) {
+        // keep the load factor below 1/2 so that the probe sequences stay short
+        if 2 * (self.len + 1) > self.slots.len() {
+            self.grow();
+        }
+        let index = self.find(key);
+        let slot = &mut self.slots[index];
+        if slot.is_none() {
+            self.len += 1;
+        }
+        slot.get_or_insert_with(|| (key, init(), None))
+    }
+
+    fn grow(&mut self) {
+        let capacity = 2 * self.slots.len();
+        let old = std::mem::replace(&mut self.slots, Self::empty_slots(capacity));
+        for entry in old.into_iter().flatten() {
+            let index = self.find(entry.0);
+            self.slots[index] = Some(entry);
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &(K, O, Option<Timestamp>)> {
+        self.slots.iter().flatten()
+    }
+
+    /// Remove all the entries from the table.
+    fn drain(&mut self) -> impl Iterator<Item = (K, O, Option<Timestamp>)> + '_ {
+        self.len = 0;
+        self.slots.iter_mut().filter_map(|slot| slot.take())
+    }
+}
+
+/// Like [`KeyedFold`](super::keyed_fold::KeyedFold), but specialized for fixed-width integer
+/// keys.
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = "Op: std::fmt::Debug"))]
+pub struct IntKeyedFold<K, V, O, F, Op>
+where
+    K: IntKey,
+    O: Send + Clone,
+    F: Fn(&mut O, V) + Send + Clone,
+    Op: Operator<Out = (K, V)>,
+{
+    prev: Op,
+    #[derivative(Debug = "ignore")]
+    fold: F,
+    #[derivative(Debug = "ignore")]
+    init: O,
+    #[derivative(Debug = "ignore")]
+    accumulators: IntKeyTable<K, O>,
+    #[derivative(Debug = "ignore")]
+    ready: Vec<StreamElement<(K, O)>>,
+    max_watermark: Option<Timestamp>,
+    received_end: bool,
+    received_end_iter: bool,
+    /// Where the size of the accumulators is published, if the state is being reported.
+    #[derivative(Debug = "ignore")]
+    probe: Option<StateProbe<K, O>>,
+}
+
+impl<K, V, O, F, Op> Display for IntKeyedFold<K, V, O, F, Op>
+where
+    K: IntKey,
+    O: Send + Clone,
+    F: Fn(&mut O, V) + Send + Clone,
+    Op: Operator<Out = (K, V)>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} -> IntKeyedFold<{} -> {}>",
+            self.prev,
+            std::any::type_name::<(K, V)>(),
+            std::any::type_name::<(K, O)>()
+        )
+    }
+}
+
+impl<K, V, O, F, Op> IntKeyedFold<K, V, O, F, Op>
+where
+    K: IntKey,
+    O: Send + Clone,
+    F: Fn(&mut O, V) + Send + Clone,
+    Op: Operator<Out = (K, V)>,
+{
+    pub(super) fn new(prev: Op, init: O, fold: F) -> Self {
+        Self {
+            prev,
+            fold,
+            init,
+            accumulators: IntKeyTable::new(),
+            ready: Default::default(),
+            max_watermark: None,
+            received_end: false,
+            received_end_iter: false,
+            probe: None,
+        }
+    }
+
+    /// Publish the accumulators held for each key, if the state is being reported.
+    fn publish_state(&self) {
+        if let Some(probe) = &self.probe {
+            probe.publish(self.accumulators.iter().map(|(key, acc, _)| (key, acc, 1)));
+        }
+    }
+
+    /// Process a new item, folding it with the accumulator inside the table.
+    #[inline]
+    fn process_item(&mut self, key: K, value: V, ts: Option<Timestamp>) {
+        let init = &self.init;
+        let (_, acc, max_ts) = self.accumulators.entry(key, || init.clone());
+        (self.fold)(acc, value);
+        if let Some(ts) = ts {
+            *max_ts = Some(max_ts.map_or(ts, |m| m.max(ts)));
+        }
+    }
+}
+
+impl<K, V, O, F, Op> Operator for IntKeyedFold<K, V, O, F, Op>
+where
+    K: IntKey,
+    O: Send + Clone,
+    F: Fn(&mut O, V) + Send + Clone,
+    Op: Operator<Out = (K, V)>,
+{
+    type Out = (K, O);
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        self.prev.setup(metadata);
+        if let Some(probe) = &mut self.probe {
+            probe.setup(metadata.coord);
+        }
+    }
+
+    #[inline]
+    fn next(&mut self) -> StreamElement<Self::Out> {
+        while !self.received_end {
+            match self.prev.next() {
+                StreamElement::Terminate => self.received_end = true,
+                StreamElement::FlushAndRestart => {
+                    self.publish_state();
+                    self.received_end = true;
+                    self.received_end_iter = true;
+                }
+                StreamElement::Watermark(ts) => {
+                    self.max_watermark = Some(self.max_watermark.unwrap_or(ts).max(ts))
+                }
+                StreamElement::Item((k, v)) => self.process_item(k, v, None),
+                StreamElement::Timestamped((k, v), ts) => self.process_item(k, v, Some(ts)),
+                // this block won't sent anything until the stream ends
+                StreamElement::FlushBatch => self.publish_state(),
+            }
+        }
+
+        if !self.accumulators.is_empty() {
+            self.ready
+                .extend(self.accumulators.drain().map(|(key, value, ts)| match ts {
+                    Some(ts) => StreamElement::Timestamped((key, value), ts),
+                    None => StreamElement::Item((key, value)),
+                }));
+        }
+
+        if let Some(elem) = self.ready.pop() {
+            return elem;
+        }
+
+        if let Some(ts) = self.max_watermark.take() {
+            return StreamElement::Watermark(ts);
+        }
+
+        // the end was not really the end... just the end of one iteration!
+        if self.received_end_iter {
+            self.received_end_iter = false;
+            self.received_end = false;
+            return StreamElement::FlushAndRestart;
+        }
+
+        StreamElement::Terminate
+    }
+
+    fn structure(&self) -> BlockStructure {
+        self.prev.structure().add_operator(
+            OperatorStructure::new::<Self::Out, _>("IntKeyedFold")
+                .with_state::<Self::Out>(StateEntries::PerKey),
+        )
+    }
+}
+
+impl<K, V, O, F, Op> ReportState for IntKeyedFold<K, V, O, F, Op>
+where
+    K: IntKey,
+    O: Send + Clone,
+    F: Fn(&mut O, V) + Send + Clone,
+    Op: Operator<Out = (K, V)>,
+{
+    type Key = K;
+    type State = O;
+
+    fn report_state(&mut self, probe: StateProbe<K, O>) {
+        self.probe = Some(probe);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::operator::int_keyed_fold::{IntKeyTable, IntKeyedFold};
+    use crate::operator::{Operator, StreamElement};
+    use crate::test::FakeOperator;
+
+    #[test]
+    fn int_key_table_grows() {
+        let mut table = IntKeyTable::<i64, i64>::new();
+        for i in -1000..1000i64 {
+            table.entry(i % 300, || 0).1 += i;
+        }
+        let res = table.drain().map(|(k, v, _)| (k, v)).sorted().collect_vec();
+        let expected = (-299..300i64)
+            .map(|k| (k, (-1000..1000).filter(|i| i % 300 == k).sum()))
+            .collect_vec();
+        assert_eq!(res, expected);
+        assert!(table.is_empty());
+        assert_eq!(table.drain().count(), 0);
+    }
+
+    #[test]
+    fn test_int_keyed_fold() {
+        let data = (0..10i32).map(|x| (x % 2, x)).collect_vec();
+        let fake_operator = FakeOperator::new(data.into_iter());
+        let mut fold = IntKeyedFold::new(fake_operator, 0, |a: &mut i32, b| *a += b);
+
+        let mut res = vec![];
+        for _ in 0..2 {
+            let item = fold.next();
+            match item {
+                StreamElement::Item(x) => res.push(x),
+                other => panic!("Expecting StreamElement::Item, got {}", other.variant_str()),
+            }
+        }
+
+        assert_eq!(fold.next(), StreamElement::Terminate);
+
+        res.sort_unstable();
+        assert_eq!(res[0].1, 2 + 4 + 6 + 8);
+        assert_eq!(res[1].1, 1 + 3 + 5 + 7 + 9);
+    }
+}
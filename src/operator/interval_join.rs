@@ -1,7 +1,7 @@
 use std::collections::{HashMap, VecDeque};
 use std::fmt::Display;
 
-use crate::block::{BlockStructure, OperatorStructure};
+use crate::block::{BlockStructure, OperatorStructure, TimestampUsage};
 use crate::operator::merge::MergeElement;
 
 use crate::operator::{ExchangeData, ExchangeDataKey, Operator, StreamElement, Timestamp};
@@ -191,10 +191,8 @@ where
     }
 
     fn structure(&self) -> BlockStructure {
-        self.prev
-            .structure()
-            .add_operator(OperatorStructure::new::<(Key, (Out, Out2)), _>(
-                "IntervalJoin",
-            ))
+        let mut operator = OperatorStructure::new::<(Key, (Out, Out2)), _>("IntervalJoin");
+        operator.timestamps = TimestampUsage::Require;
+        self.prev.structure().add_operator(operator)
     }
 }
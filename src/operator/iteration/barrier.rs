@@ -0,0 +1,426 @@
+use std::any::TypeId;
+use std::collections::VecDeque;
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::block::{
+    BlockStructure, Connection, NextStrategy, OperatorReceiver, OperatorStructure, Replication,
+};
+use crate::network::{Coord, NetworkMessage, NetworkReceiver, NetworkSender, ReceiverEndpoint};
+use crate::operator::source::Source;
+use crate::operator::start::{SimpleStartOperator, Start, StartReceiver};
+use crate::operator::{Data, ExchangeData, Operator, StreamElement};
+use crate::scheduler::{BlockId, ExecutionMetadata};
+use crate::stream::Stream;
+
+/// The contribution of a replica to a superstep, sent to the `BarrierLeader`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Contribution<T>(T);
+
+/// The aggregated value of a superstep, sent back to all the replicas.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Aggregate<T>(T);
+
+/// The replica side of [`Stream::barrier_aggregate`].
+///
+/// The elements of each superstep are folded into the contribution of this replica and kept
+/// aside. At the end of the superstep the contribution is sent to the leader and the elements are
+/// emitted only after the aggregated value has been received back.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct BarrierAggregate<Out, T, L, Op>
+where
+    Out: Data,
+    T: ExchangeData,
+    L: Fn(&mut T, &Out) + Send + Clone,
+    Op: Operator<Out = Out>,
+{
+    prev: Op,
+    coord: Coord,
+    init: T,
+    #[derivative(Debug = "ignore")]
+    local: L,
+    /// The id of the block where `BarrierLeader` is.
+    leader_block_id: BlockId,
+    leader_sender: Option<NetworkSender<Contribution<T>>>,
+    aggregate_receiver: Option<NetworkReceiver<Aggregate<T>>>,
+    /// The elements of the current superstep, including the final `FlushAndRestart`.
+    #[derivative(Debug = "ignore")]
+    buffer: VecDeque<StreamElement<Out>>,
+    /// The aggregated value of the superstep being emitted.
+    aggregate: Option<T>,
+}
+
+impl<Out, T, L, Op> Clone for BarrierAggregate<Out, T, L, Op>
+where
+    Out: Data,
+    T: ExchangeData,
+    L: Fn(&mut T, &Out) + Send + Clone,
+    Op: Operator<Out = Out>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            prev: self.prev.clone(),
+            coord: self.coord,
+            init: self.init.clone(),
+            local: self.local.clone(),
+            leader_block_id: self.leader_block_id,
+            leader_sender: None,
+            aggregate_receiver: None,
+            buffer: Default::default(),
+            aggregate: None,
+        }
+    }
+}
+
+impl<Out, T, L, Op> Display for BarrierAggregate<Out, T, L, Op>
+where
+    Out: Data,
+    T: ExchangeData,
+    L: Fn(&mut T, &Out) + Send + Clone,
+    Op: Operator<Out = Out>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} -> BarrierAggregate<{}>",
+            self.prev,
+            std::any::type_name::<T>()
+        )
+    }
+}
+
+impl<Out, T, L, Op> BarrierAggregate<Out, T, L, Op>
+where
+    Out: Data,
+    T: ExchangeData,
+    L: Fn(&mut T, &Out) + Send + Clone,
+    Op: Operator<Out = Out>,
+{
+    /// Send the contribution of this replica to the leader and wait for the aggregated value.
+    fn synchronize(&mut self, contribution: T) -> T {
+        let message =
+            NetworkMessage::new_single(StreamElement::Item(Contribution(contribution)), self.coord);
+        self.leader_sender.as_ref().unwrap().send(message).unwrap();
+        let message = self
+            .aggregate_receiver
+            .as_ref()
+            .unwrap()
+            .recv()
+            .expect("the barrier leader has exited");
+        match message.into_iter().next() {
+            Some(StreamElement::Item(Aggregate(aggregate))) => aggregate,
+            el => unreachable!(
+                "BarrierAggregate received an invalid message: {:?}",
+                el.map(|el| el.variant_str())
+            ),
+        }
+    }
+}
+
+impl<Out, T, L, Op> Operator for BarrierAggregate<Out, T, L, Op>
+where
+    Out: Data,
+    T: ExchangeData,
+    L: Fn(&mut T, &Out) + Send + Clone,
+    Op: Operator<Out = Out>,
+{
+    type Out = (Out, T);
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        self.prev.setup(metadata);
+        let replicas = metadata.network.replicas(self.leader_block_id);
+        assert_eq!(
+            replicas.len(),
+            1,
+            "The BarrierLeader should not be replicated"
+        );
+        let leader = replicas[0];
+        self.leader_sender = Some(
+            metadata
+                .network
+                .get_sender(ReceiverEndpoint::new(leader, metadata.coord.block_id)),
+        );
+        self.aggregate_receiver = Some(
+            metadata
+                .network
+                .get_receiver(ReceiverEndpoint::new(metadata.coord, self.leader_block_id)),
+        );
+        self.coord = metadata.coord;
+    }
+
+    fn next(&mut self) -> StreamElement<(Out, T)> {
+        loop {
+            if let Some(el) = self.buffer.pop_front() {
+                let aggregate = self.aggregate.as_ref().unwrap();
+                return match el {
+                    StreamElement::Item(item) => StreamElement::Item((item, aggregate.clone())),
+                    StreamElement::Timestamped(item, ts) => {
+                        StreamElement::Timestamped((item, aggregate.clone()), ts)
+                    }
+                    StreamElement::Watermark(ts) => StreamElement::Watermark(ts),
+                    StreamElement::FlushAndRestart => {
+                        self.aggregate = None;
+                        StreamElement::FlushAndRestart
+                    }
+                    el => unreachable!("BarrierAggregate buffered {}", el.variant_str()),
+                };
+            }
+
+            // a replica without elements contributes nevertheless, so the superstep ends when
+            // the partition of each replica has ended
+            let mut contribution = self.init.clone();
+            loop {
+                match self.prev.next() {
+                    el @ (StreamElement::Item(_) | StreamElement::Timestamped(_, _)) => {
+                        if let StreamElement::Item(item) | StreamElement::Timestamped(item, _) = &el
+                        {
+                            (self.local)(&mut contribution, item);
+                        }
+                        self.buffer.push_back(el);
+                    }
+                    el @ StreamElement::Watermark(_) => self.buffer.push_back(el),
+                    StreamElement::FlushBatch => {}
+                    StreamElement::FlushAndRestart => {
+                        self.buffer.push_back(StreamElement::FlushAndRestart);
+                        break;
+                    }
+                    StreamElement::Terminate => {
+                        let message =
+                            NetworkMessage::new_single(StreamElement::Terminate, self.coord);
+                        self.leader_sender.as_ref().unwrap().send(message).unwrap();
+                        return StreamElement::Terminate;
+                    }
+                }
+            }
+            self.aggregate = Some(self.synchronize(contribution));
+        }
+    }
+
+    fn structure(&self) -> BlockStructure {
+        let mut operator = OperatorStructure::new::<(Out, T), _>("BarrierAggregate");
+        operator
+            .connections
+            .push(Connection::new::<Contribution<T>, _>(
+                self.leader_block_id,
+                &NextStrategy::only_one(),
+            ));
+        operator
+            .receivers
+            .push(OperatorReceiver::new::<Aggregate<T>>(self.leader_block_id));
+        self.prev.structure().add_operator(operator)
+    }
+}
+
+/// The leader of [`Stream::barrier_aggregate`].
+///
+/// This block receives the contribution of each replica of the synchronized block, the number of
+/// replicas is known from the job graph. When all the contributions of a superstep have been
+/// received they are combined and the result is sent to all the replicas.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct BarrierLeader<T, G>
+where
+    T: ExchangeData,
+    G: Fn(&mut T, T) + Send + Clone,
+{
+    coord: Coord,
+    init: T,
+    #[derivative(Debug = "ignore")]
+    global: G,
+    /// The id of the block whose replicas are synchronized.
+    block_id: BlockId,
+    contribution_receiver: Option<SimpleStartOperator<Contribution<T>>>,
+    /// The number of replicas of the synchronized block.
+    num_replicas: usize,
+    aggregate_senders: Vec<NetworkSender<Aggregate<T>>>,
+}
+
+impl<T, G> Clone for BarrierLeader<T, G>
+where
+    T: ExchangeData,
+    G: Fn(&mut T, T) + Send + Clone,
+{
+    fn clone(&self) -> Self {
+        panic!("BarrierLeader cannot be cloned, replication should be 1");
+    }
+}
+
+impl<T, G> Display for BarrierLeader<T, G>
+where
+    T: ExchangeData,
+    G: Fn(&mut T, T) + Send + Clone,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BarrierLeader<{}>", std::any::type_name::<T>())
+    }
+}
+
+impl<T, G> Operator for BarrierLeader<T, G>
+where
+    T: ExchangeData,
+    G: Fn(&mut T, T) + Send + Clone,
+{
+    type Out = ();
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        self.coord = metadata.coord;
+        self.aggregate_senders = metadata
+            .network
+            .get_senders(metadata.coord)
+            .into_iter()
+            .map(|(_, s)| s)
+            .collect();
+        let mut receiver = Start::single(self.block_id, None);
+        receiver.setup(metadata);
+        self.num_replicas = receiver.receiver().prev_replicas().len();
+        self.contribution_receiver = Some(receiver);
+    }
+
+    fn next(&mut self) -> StreamElement<()> {
+        let rx = self.contribution_receiver.as_mut().unwrap();
+        let mut aggregate = self.init.clone();
+        let mut missing = self.num_replicas;
+        loop {
+            match rx.next() {
+                StreamElement::Item(Contribution(contribution)) => {
+                    (self.global)(&mut aggregate, contribution);
+                    missing -= 1;
+                    log::trace!(
+                        "barrier_aggregate {} {missing} contributions left",
+                        self.coord
+                    );
+                }
+                StreamElement::Terminate => return StreamElement::Terminate,
+                StreamElement::FlushAndRestart | StreamElement::FlushBatch => {}
+                el => unreachable!(
+                    "BarrierLeader received an invalid message: {}",
+                    el.variant_str()
+                ),
+            }
+            if missing == 0 {
+                for sender in &self.aggregate_senders {
+                    let message = NetworkMessage::new_single(
+                        StreamElement::Item(Aggregate(aggregate.clone())),
+                        self.coord,
+                    );
+                    sender.send(message).unwrap();
+                }
+                return StreamElement::FlushAndRestart;
+            }
+        }
+    }
+
+    fn structure(&self) -> BlockStructure {
+        let mut operator = OperatorStructure::new::<(), _>("BarrierLeader");
+        operator
+            .connections
+            .push(Connection::new::<Aggregate<T>, _>(
+                self.block_id,
+                &NextStrategy::only_one(),
+            ));
+        self.contribution_receiver
+            .as_ref()
+            .map(|receiver| receiver.structure())
+            .unwrap_or_default()
+            .add_operator(operator)
+    }
+}
+
+impl<T, G> Source for BarrierLeader<T, G>
+where
+    T: ExchangeData,
+    G: Fn(&mut T, T) + Send + Clone,
+{
+    fn replication(&self) -> Replication {
+        Replication::One
+    }
+}
+
+impl<Out: Data, OperatorChain> Stream<OperatorChain>
+where
+    OperatorChain: Operator<Out = Out> + 'static,
+{
+    /// Synchronize all the replicas of this block at the end of each superstep, handing to each of
+    /// them a value aggregated from the contributions of all the replicas.
+    ///
+    /// This is the same synchronization that happens between two iterations of
+    /// [`Stream::iterate`]: every replica folds its elements with `local`, starting from `init`,
+    /// and sends the result to a single leader. The leader combines the contributions with
+    /// `global`, again starting from `init`, and sends the result back to all the replicas. Each
+    /// element is then emitted paired with the aggregated value.
+    ///
+    /// A superstep ends when the partition of the replica ends, or at the end of each iteration
+    /// when used inside the body of an iteration. The leader waits for the contribution of every
+    /// replica of the block, including the ones that received no elements.
+    ///
+    /// **Note**: the elements of the superstep are kept in memory until the aggregated value is
+    /// received.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+    /// let res = env
+    ///     // each replica computes the sum of its own chunk...
+    ///     .stream_par_iter(|id, peers| (id..100u64).step_by(peers as usize))
+    ///     .barrier_aggregate(0, |acc, &n| *acc += n, |acc, sum| *acc += sum)
+    ///     // ...and uses the total in the second phase
+    ///     .map(|(n, total)| (n, n * 100 / total))
+    ///     .collect_vec();
+    /// env.execute_blocking();
+    ///
+    /// let res = res.get().unwrap();
+    /// assert_eq!(res.len(), 100);
+    /// assert!(res.iter().all(|&(n, share)| share == n * 100 / 4950));
+    /// ```
+    pub fn barrier_aggregate<T, L, G>(
+        self,
+        init: T,
+        local: L,
+        global: G,
+    ) -> Stream<BarrierAggregate<Out, T, L, OperatorChain>>
+    where
+        T: ExchangeData,
+        L: Fn(&mut T, &Out) + Send + Clone + 'static,
+        G: Fn(&mut T, T) + Send + Clone + 'static,
+    {
+        let batch_mode = self.block.batch_mode;
+        let block_id = self.block.id;
+        let leader = BarrierLeader {
+            coord: Default::default(),
+            init: init.clone(),
+            global,
+            block_id,
+            contribution_receiver: None,
+            num_replicas: 0,
+            aggregate_senders: Default::default(),
+        };
+        let leader_block = self
+            .ctx
+            .lock()
+            .new_block(leader, batch_mode, Default::default());
+
+        let mut ctx = self.ctx.lock();
+        let scheduler = ctx.scheduler_mut();
+        scheduler.connect_blocks(block_id, leader_block.id, TypeId::of::<Contribution<T>>());
+        scheduler.connect_blocks(leader_block.id, block_id, TypeId::of::<Aggregate<T>>());
+        let leader_block_id = leader_block.id;
+        scheduler.schedule_block(leader_block);
+        drop(ctx);
+
+        self.add_operator(|prev| BarrierAggregate {
+            prev,
+            coord: Default::default(),
+            init,
+            local,
+            leader_block_id,
+            leader_sender: None,
+            aggregate_receiver: None,
+            buffer: Default::default(),
+            aggregate: None,
+        })
+    }
+}
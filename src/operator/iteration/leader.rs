@@ -235,16 +235,19 @@ where
 
     fn structure(&self) -> BlockStructure {
         let mut operator = OperatorStructure::new::<State, _>("IterationLeader");
-        operator
-            .connections
-            .push(Connection::new::<StateFeedback<State>, _>(
-                self.feedback_senders[0].receiver_endpoint.coord.block_id,
-                &NextStrategy::only_one(),
-            ));
+        // the senders and the receiver are known only after the setup
+        if let Some(sender) = self.feedback_senders.first() {
+            operator
+                .connections
+                .push(Connection::new::<StateFeedback<State>, _>(
+                    sender.receiver_endpoint.coord.block_id,
+                    &NextStrategy::only_one(),
+                ));
+        }
         self.state_update_receiver
             .as_ref()
-            .unwrap()
-            .structure()
+            .map(|receiver| receiver.structure())
+            .unwrap_or_default()
             .add_operator(operator)
     }
 }
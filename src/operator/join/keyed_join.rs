@@ -387,6 +387,38 @@ impl<K: DataKey + ExchangeData + Debug, V1: Data + ExchangeData + Debug, O1> Key
 where
     O1: Operator<Out = (K, V1)> + 'static,
 {
+    /// Given two keyed streams, join them on their keys, keeping also the items without a
+    /// matching key in the other stream.
+    ///
+    /// Like [`KeyedStream::join`], but when a key is present in only one of the two streams its
+    /// items are emitted anyway, with `None` on the other side.
+    ///
+    /// **Note**: this operator will split the current block.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # let mut env = StreamContext::new_local();
+    /// let s1 = env.stream_iter(0..3u32).group_by(|&n| n);
+    /// let s2 = env.stream_iter(1..4u32).group_by(|&n| n);
+    /// let res = s1.join_outer(s2).unkey().collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let mut res = res.get().unwrap();
+    /// res.sort_unstable();
+    /// assert_eq!(
+    ///     res,
+    ///     vec![
+    ///         (0, (Some(0), None)),
+    ///         (1, (Some(1), Some(1))),
+    ///         (2, (Some(2), Some(2))),
+    ///         (3, (None, Some(3))),
+    ///     ]
+    /// );
+    /// ```
+    #[allow(clippy::type_complexity)]
     pub fn join_outer<V2: Data + ExchangeData + Debug, O2>(
         self,
         rhs: KeyedStream<O2>,
@@ -405,6 +437,34 @@ where
         KeyedStream(s)
     }
 
+    /// Given two keyed streams, join them on their keys, emitting the pairs of items of the two
+    /// streams with the same key.
+    ///
+    /// The output is still keyed by the same key. Since both the streams are already partitioned
+    /// by key, the items are not shuffled again: each replica joins the items of the keys it
+    /// already has, unlike [`Stream::join`](crate::Stream::join) which always partitions both
+    /// streams by the join key.
+    ///
+    /// **Note**: this operator will split the current block.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # let mut env = StreamContext::new_local();
+    /// let s1 = env.stream_iter(0..5u32).group_by(|&n| n % 2);
+    /// let s2 = env.stream_iter(0..2u32).group_by(|&n| n % 2);
+    /// let res = s1.join(s2).unkey().collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let mut res = res.get().unwrap();
+    /// res.sort_unstable();
+    /// assert_eq!(
+    ///     res,
+    ///     vec![(0, (0, 0)), (0, (2, 0)), (0, (4, 0)), (1, (1, 1)), (1, (3, 1))]
+    /// );
+    /// ```
     pub fn join<V2: Data + ExchangeData + Debug, O2>(
         self,
         rhs: KeyedStream<O2>,
@@ -1,13 +1,14 @@
 //! Structures for building the join operators.
 //!
-//! The actual operators are [`Stream::join`], [`Stream::left_join`], [`Stream::outer_join`] and
-//! [`Stream::join_with`].
+//! The actual operators are [`Stream::join`], [`Stream::left_join`], [`Stream::outer_join`],
+//! [`Stream::join_with`] and [`Stream::join_with_slice`].
 
 use std::marker::PhantomData;
 
 pub use local_hash::JoinStreamLocalHash;
 pub use local_sort_merge::JoinStreamLocalSortMerge;
 pub use ship::{ShipBroadcastRight, ShipHash, ShipStrategy};
+pub use slice::StaticCollection;
 
 pub use crate::operator::join::ship::{JoinStreamShipBroadcastRight, JoinStreamShipHash};
 use crate::operator::{Data, DataKey, ExchangeData, KeyerFn, Operator};
@@ -17,6 +18,7 @@ mod keyed_join;
 mod local_hash;
 mod local_sort_merge;
 mod ship;
+mod slice;
 
 /// Type alias for a pair of joined items in an inner join.
 pub type InnerJoinTuple<Out1, Out2> = (Out1, Out2);
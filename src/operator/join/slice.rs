@@ -0,0 +1,249 @@
+#![allow(clippy::type_complexity)]
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Display;
+use std::sync::Arc;
+
+use crate::block::{BlockStructure, GroupHasherBuilder, OperatorStructure};
+use crate::operator::{Data, DataKey, Operator, StreamElement, Timestamp};
+use crate::scheduler::ExecutionMetadata;
+use crate::stream::{KeyedStream, Stream};
+
+use super::InnerJoinTuple;
+
+/// A collection available to all the replicas without being sent over the network, used by
+/// [`Stream::join_with_slice`].
+pub trait StaticCollection<T>: Clone + Send + Sync + 'static {
+    /// The items of the collection.
+    fn items(&self) -> &[T];
+}
+
+impl<T: Sync + 'static> StaticCollection<T> for &'static [T] {
+    fn items(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T: Send + Sync + 'static> StaticCollection<T> for Arc<Vec<T>> {
+    fn items(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T: Send + Sync + 'static> StaticCollection<T> for Arc<[T]> {
+    fn items(&self) -> &[T] {
+        self
+    }
+}
+
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct JoinSlice<Key, T, C, Keyer1, Keyer2, Op>
+where
+    Op: Operator,
+{
+    prev: Op,
+    #[derivative(Debug = "ignore")]
+    collection: C,
+    #[derivative(Debug = "ignore")]
+    keyer1: Keyer1,
+    #[derivative(Debug = "ignore")]
+    keyer2: Keyer2,
+    /// The positions in the collection of the items with each key, built in `setup`.
+    #[derivative(Debug = "ignore")]
+    index: HashMap<Key, Vec<usize>, GroupHasherBuilder>,
+    /// The joined pairs not yet emitted.
+    #[derivative(Debug = "ignore")]
+    buffer: VecDeque<StreamElement<(Key, InnerJoinTuple<Op::Out, T>)>>,
+}
+
+impl<Key, T, C, Keyer1, Keyer2, Op> Clone for JoinSlice<Key, T, C, Keyer1, Keyer2, Op>
+where
+    Key: DataKey,
+    T: Data,
+    C: StaticCollection<T>,
+    Keyer1: Fn(&Op::Out) -> Key + Clone + Send,
+    Keyer2: Fn(&T) -> Key + Clone + Send,
+    Op: Operator,
+    Op::Out: Data,
+{
+    fn clone(&self) -> Self {
+        Self::new(
+            self.prev.clone(),
+            self.collection.clone(),
+            self.keyer1.clone(),
+            self.keyer2.clone(),
+        )
+    }
+}
+
+impl<Key, T, C, Keyer1, Keyer2, Op> Display for JoinSlice<Key, T, C, Keyer1, Keyer2, Op>
+where
+    Op: Operator,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} -> JoinSlice<{}, {}>",
+            self.prev,
+            std::any::type_name::<Op::Out>(),
+            std::any::type_name::<T>()
+        )
+    }
+}
+
+impl<Key, T, C, Keyer1, Keyer2, Op> JoinSlice<Key, T, C, Keyer1, Keyer2, Op>
+where
+    Key: DataKey,
+    T: Data,
+    C: StaticCollection<T>,
+    Keyer1: Fn(&Op::Out) -> Key + Clone + Send,
+    Keyer2: Fn(&T) -> Key + Clone + Send,
+    Op: Operator,
+    Op::Out: Data,
+{
+    pub(crate) fn new(prev: Op, collection: C, keyer1: Keyer1, keyer2: Keyer2) -> Self {
+        Self {
+            prev,
+            collection,
+            keyer1,
+            keyer2,
+            index: Default::default(),
+            buffer: Default::default(),
+        }
+    }
+
+    /// Buffer the pairs of `item` with all the items of the collection with the same key.
+    fn probe(&mut self, item: Op::Out, ts: Option<Timestamp>) {
+        let key = (self.keyer1)(&item);
+        let Some(positions) = self.index.get(&key) else {
+            return;
+        };
+        let items = self.collection.items();
+        self.buffer.extend(positions.iter().map(|&i| {
+            let pair = (key.clone(), (item.clone(), items[i].clone()));
+            match ts {
+                Some(ts) => StreamElement::Timestamped(pair, ts),
+                None => StreamElement::Item(pair),
+            }
+        }));
+    }
+}
+
+impl<Key, T, C, Keyer1, Keyer2, Op> Operator for JoinSlice<Key, T, C, Keyer1, Keyer2, Op>
+where
+    Key: DataKey,
+    T: Data,
+    C: StaticCollection<T>,
+    Keyer1: Fn(&Op::Out) -> Key + Clone + Send,
+    Keyer2: Fn(&T) -> Key + Clone + Send,
+    Op: Operator,
+    Op::Out: Data,
+{
+    type Out = (Key, InnerJoinTuple<Op::Out, T>);
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        self.prev.setup(metadata);
+        for (i, item) in self.collection.items().iter().enumerate() {
+            self.index.entry((self.keyer2)(item)).or_default().push(i);
+        }
+    }
+
+    fn next(&mut self) -> StreamElement<Self::Out> {
+        loop {
+            if let Some(el) = self.buffer.pop_front() {
+                return el;
+            }
+            match self.prev.next() {
+                StreamElement::Item(item) => self.probe(item, None),
+                StreamElement::Timestamped(item, ts) => self.probe(item, Some(ts)),
+                StreamElement::Watermark(ts) => return StreamElement::Watermark(ts),
+                StreamElement::FlushBatch => return StreamElement::FlushBatch,
+                StreamElement::FlushAndRestart => return StreamElement::FlushAndRestart,
+                StreamElement::Terminate => return StreamElement::Terminate,
+            }
+        }
+    }
+
+    fn structure(&self) -> BlockStructure {
+        let operator = OperatorStructure::new::<Self::Out, _>("JoinSlice");
+        self.prev.structure().add_operator(operator)
+    }
+}
+
+impl<Out: Data, OperatorChain> Stream<OperatorChain>
+where
+    OperatorChain: Operator<Out = Out> + 'static,
+{
+    /// Join the stream with a collection held in memory, creating the pairs (item from the stream,
+    /// item from the collection) such that the key obtained with `keyer1` on the item of the
+    /// stream is equal to the key obtained with `keyer2` on the item of the collection.
+    ///
+    /// This is an inner join like [`Stream::join`], but the collection is not turned into a
+    /// stream: each replica builds a hash index of the whole collection when it's set up, so the
+    /// stream is not shuffled. Use it for joining with a small reference dataset, passed as a
+    /// `&'static [T]`, an `Arc<Vec<T>>` or an `Arc<[T]>`.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let names = Arc::new(vec![(0, "zero".to_string()), (1, "one".to_string())]);
+    /// let s = env.stream_iter(0..4u32);
+    /// let res = s
+    ///     .join_with_slice(names, |n| n % 2, |(id, _)| *id)
+    ///     .drop_key()
+    ///     .collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let mut res = res.get().unwrap();
+    /// res.sort_unstable();
+    /// assert_eq!(res[0], (0, (0, "zero".to_string())));
+    /// assert_eq!(res[3], (3, (1, "one".to_string())));
+    /// ```
+    pub fn join_with_slice<T, C, Key, Keyer1, Keyer2>(
+        self,
+        collection: C,
+        keyer1: Keyer1,
+        keyer2: Keyer2,
+    ) -> KeyedStream<impl Operator<Out = (Key, InnerJoinTuple<Out, T>)>>
+    where
+        T: Data,
+        C: StaticCollection<T>,
+        Key: DataKey,
+        Keyer1: Fn(&Out) -> Key + Clone + Send + 'static,
+        Keyer2: Fn(&T) -> Key + Clone + Send + 'static,
+    {
+        KeyedStream(self.add_operator(|prev| JoinSlice::new(prev, collection, keyer1, keyer2)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::JoinSlice;
+    use crate::operator::{Operator, StreamElement};
+    use crate::test::{FakeNetworkTopology, FakeOperator};
+
+    static COLORS: [(u8, &str); 3] = [(0, "red"), (1, "green"), (0, "blue")];
+
+    #[test]
+    fn join_slice() {
+        let mut fake = FakeOperator::empty();
+        fake.push(StreamElement::Item(0u8));
+        fake.push(StreamElement::Item(1));
+        fake.push(StreamElement::Item(2));
+        fake.push(StreamElement::FlushAndRestart);
+        let mut op = JoinSlice::new(fake, &COLORS[..], |n: &u8| *n, |(k, _): &(u8, &str)| *k);
+        op.setup(&mut FakeNetworkTopology::<u8>::new(0, 0).metadata());
+
+        assert_eq!(op.next(), StreamElement::Item((0, (0, (0, "red")))));
+        assert_eq!(op.next(), StreamElement::Item((0, (0, (0, "blue")))));
+        assert_eq!(op.next(), StreamElement::Item((1, (1, (1, "green")))));
+        assert_eq!(op.next(), StreamElement::FlushAndRestart);
+        assert_eq!(op.next(), StreamElement::Terminate);
+    }
+}
@@ -3,9 +3,9 @@ use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::fmt::Display;
 
-use crate::block::{BlockStructure, OperatorStructure};
+use crate::block::{BlockStructure, OperatorStructure, StateEntries};
 
-use crate::operator::{Operator, StreamElement, Timestamp};
+use crate::operator::{Operator, ReportState, StateProbe, StreamElement, Timestamp};
 use crate::scheduler::ExecutionMetadata;
 use crate::stream::KeyedItem;
 
@@ -24,6 +24,15 @@ where
     max_watermark: Option<Timestamp>,
     received_end: bool,
     received_end_iter: bool,
+    /// Whether the accumulators are partial results that can be emitted early, merged by a later
+    /// fold, when the state is over the budget.
+    partial: bool,
+    /// The memory that the accumulators can use, set in `setup`.
+    budget: Option<usize>,
+    /// Whether the warning about the state over the budget has been logged.
+    warned: bool,
+    /// Where the size of the accumulators is published, if the state is being reported.
+    probe: Option<StateProbe<<Op::Out as KeyedItem>::Key, O>>,
 }
 
 impl<O: Send + Clone, F: Clone, Op: Clone> Clone for KeyedFold<O, F, Op>
@@ -43,6 +52,10 @@ where
             max_watermark: self.max_watermark,
             received_end: self.received_end,
             received_end_iter: self.received_end_iter,
+            partial: self.partial,
+            budget: self.budget,
+            warned: self.warned,
+            probe: self.probe.clone(),
         }
     }
 }
@@ -82,9 +95,64 @@ where
             max_watermark: None,
             received_end: false,
             received_end_iter: false,
+            partial: false,
+            budget: None,
+            warned: false,
+            probe: None,
         }
     }
 
+    /// Mark the accumulators as partial results, that are emitted before the end of the stream if
+    /// the state goes over the budget of the job.
+    pub(super) fn partial(mut self) -> Self {
+        self.partial = true;
+        self
+    }
+
+    /// Check the size of the state against the budget, returning whether the accumulators must
+    /// be emitted now.
+    ///
+    /// Only the inline bytes of the keys and of the accumulators are counted, the memory they own
+    /// on the heap is not known.
+    fn over_budget(&mut self) -> bool {
+        let Some(budget) = self.budget else {
+            return false;
+        };
+        let size =
+            self.accumulators.len() * std::mem::size_of::<(<Op::Out as KeyedItem>::Key, O)>();
+        if size <= budget {
+            return false;
+        }
+        if !self.partial && !self.warned {
+            log::warn!(
+                "KeyedFold: the state uses at least {size} bytes, over the budget of {budget} bytes"
+            );
+            self.warned = true;
+        }
+        self.partial
+    }
+
+    /// Publish the accumulators held for each key, if the state is being reported.
+    fn publish_state(&self) {
+        if let Some(probe) = &self.probe {
+            probe.publish(self.accumulators.iter().map(|(key, acc)| (key, acc, 1)));
+        }
+    }
+
+    /// Move all the accumulators to the elements ready to be emitted.
+    fn drain_accumulators(&mut self) {
+        // take a reference to move into the closure, avoiding moving "self"
+        let timestamps = &mut self.timestamps;
+        self.ready
+            .extend(self.accumulators.drain().map(|(key, value)| {
+                if let Some(ts) = timestamps.remove(&key) {
+                    StreamElement::Timestamped((key, value), ts)
+                } else {
+                    StreamElement::Item((key, value))
+                }
+            }));
+    }
+
     /// Process a new item, folding it with the accumulator inside the hashmap.
     fn process_item(
         &mut self,
@@ -114,14 +182,23 @@ where
 
     fn setup(&mut self, metadata: &mut ExecutionMetadata) {
         self.prev.setup(metadata);
+        self.budget = metadata.quota.state_budget();
+        if let Some(probe) = &mut self.probe {
+            probe.setup(metadata.coord);
+        }
     }
 
     #[inline]
     fn next(&mut self) -> StreamElement<Self::Out> {
+        // partial results emitted early because of the budget
+        if let Some(elem) = self.ready.pop() {
+            return elem;
+        }
         while !self.received_end {
             match self.prev.next() {
                 StreamElement::Terminate => self.received_end = true,
                 StreamElement::FlushAndRestart => {
+                    self.publish_state();
                     self.received_end = true;
                     self.received_end_iter = true;
                 }
@@ -141,22 +218,18 @@ where
                         .or_insert(ts);
                 }
                 // this block won't sent anything until the stream ends
-                StreamElement::FlushBatch => {}
+                StreamElement::FlushBatch => self.publish_state(),
+            }
+            if self.over_budget() {
+                self.publish_state();
+                self.drain_accumulators();
+                return self.ready.pop().unwrap();
             }
         }
 
         // move all the accumulators into a faster vec
         if !self.accumulators.is_empty() {
-            // take a reference to move into the closure, avoiding moving "self"
-            let timestamps = &mut self.timestamps;
-            self.ready
-                .extend(self.accumulators.drain().map(|(key, value)| {
-                    if let Some(ts) = timestamps.remove(&key) {
-                        StreamElement::Timestamped((key, value), ts)
-                    } else {
-                        StreamElement::Item((key, value))
-                    }
-                }));
+            self.drain_accumulators();
         }
 
         // consume the ready items
@@ -179,19 +252,38 @@ where
     }
 
     fn structure(&self) -> BlockStructure {
-        self.prev
-            .structure()
-            .add_operator(OperatorStructure::new::<Self::Out, _>("KeyedFold"))
+        self.prev.structure().add_operator(
+            OperatorStructure::new::<Self::Out, _>("KeyedFold")
+                .with_state::<Self::Out>(StateEntries::PerKey),
+        )
+    }
+}
+
+impl<O: Send + Clone, F, Op> ReportState for KeyedFold<O, F, Op>
+where
+    F: Fn(&mut O, <Op::Out as KeyedItem>::Value) + Send + Clone,
+    Op: Operator,
+    Op::Out: KeyedItem,
+{
+    type Key = <Op::Out as KeyedItem>::Key;
+    type State = O;
+
+    fn report_state(&mut self, probe: StateProbe<Self::Key, O>) {
+        self.probe = Some(probe);
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use itertools::Itertools;
 
+    use crate::config::QuotaConfig;
     use crate::operator::keyed_fold::KeyedFold;
     use crate::operator::{Operator, StreamElement};
-    use crate::test::FakeOperator;
+    use crate::scheduler::QuotaUsage;
+    use crate::test::{FakeNetworkTopology, FakeOperator};
 
     #[test]
     #[allow(clippy::identity_op)]
@@ -269,4 +361,46 @@ mod tests {
         assert_eq!(keyed_fold.next(), StreamElement::FlushAndRestart);
         assert_eq!(keyed_fold.next(), StreamElement::Terminate);
     }
+
+    #[test]
+    fn test_keyed_fold_budget() {
+        let budget = 3 * std::mem::size_of::<(u8, u32)>() as u64;
+        let quota = Arc::new(QuotaUsage::new(QuotaConfig {
+            max_state_bytes: Some(budget),
+            ..Default::default()
+        }));
+        let data = (0..20u8).map(|x| (x % 5, x as u32)).collect_vec();
+
+        // the local step of a fold emits its partial results when over the budget
+        let fake_operator = FakeOperator::new(data.clone().into_iter());
+        let mut keyed_fold = KeyedFold::new(fake_operator, 0, |a, b| *a += b).partial();
+        let mut t = FakeNetworkTopology::<u8>::new(0, 0);
+        let mut metadata = t.metadata();
+        metadata.quota = quota.clone();
+        keyed_fold.setup(&mut metadata);
+        let mut res = vec![];
+        loop {
+            match keyed_fold.next() {
+                StreamElement::Item(item) => res.push(item),
+                StreamElement::Terminate => break,
+                el => panic!("unexpected element {el:?}"),
+            }
+        }
+        assert!(res.len() > 5);
+        let sums = res.into_iter().into_grouping_map().sum();
+        let expected = data.into_iter().into_grouping_map().sum();
+        assert_eq!(sums, expected);
+
+        // the other folds keep all their state
+        let fake_operator = FakeOperator::new((0..20u8).map(|x| (x % 5, x as u32)));
+        let mut keyed_fold = KeyedFold::new(fake_operator, 0, |a, b| *a += b);
+        let mut metadata = t.metadata();
+        metadata.quota = quota;
+        keyed_fold.setup(&mut metadata);
+        let mut count = 0;
+        while keyed_fold.next() != StreamElement::Terminate {
+            count += 1;
+        }
+        assert_eq!(count, 5);
+    }
 }
@@ -0,0 +1,330 @@
+use std::fmt::Display;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use nanorand::{tls_rng, Rng};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::block::{BlockStructure, OperatorStructure};
+use crate::network::Coord;
+use crate::operator::{Operator, StreamElement};
+use crate::scheduler::ExecutionMetadata;
+use crate::Stream;
+
+/// Identifier of an element sampled for lineage tracing.
+pub type TraceId = u64;
+
+/// An element of a stream with lineage tracing enabled.
+///
+/// Only the sampled elements carry a trace id, which is kept when the element is sent to another
+/// block, so that its path can be followed across the shuffles.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Traced<T> {
+    /// The trace id of the element, if it has been sampled.
+    pub trace: Option<TraceId>,
+    /// The actual element.
+    pub item: T,
+}
+
+impl<T> Traced<T> {
+    /// Transform the element, keeping its trace id.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Traced<U> {
+        Traced {
+            trace: self.trace,
+            item: f(self.item),
+        }
+    }
+
+    /// Drop the trace id, returning the element.
+    pub fn into_inner(self) -> T {
+        self.item
+    }
+}
+
+/// The passage of a traced element through a stage of the job.
+#[derive(Clone, Debug)]
+pub struct LineageEvent {
+    /// The trace id of the element.
+    pub trace: TraceId,
+    /// The name of the stage.
+    pub stage: String,
+    /// The replica that processed the element.
+    pub coord: Coord,
+    /// The instant the element has been seen.
+    pub time: Instant,
+}
+
+/// Collector of the lineage of the sampled elements of a job.
+///
+/// The elements are sampled with [`Stream::sample_lineage`], and each call to
+/// [`Stream::trace_stage`] records their passage through a stage. After the execution the handle
+/// can be used to reconstruct the path of each sampled element and the time it spent between the
+/// stages.
+///
+/// **Note**: only the events of the replicas running in the current process are collected.
+#[derive(Clone, Debug, Default)]
+pub struct Lineage {
+    events: Arc<Mutex<Vec<LineageEvent>>>,
+}
+
+impl Lineage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn record(&self, trace: TraceId, stage: &str, coord: Coord) {
+        self.events.lock().push(LineageEvent {
+            trace,
+            stage: stage.to_string(),
+            coord,
+            time: Instant::now(),
+        });
+    }
+
+    /// The trace ids of all the elements seen so far, in increasing order.
+    pub fn traces(&self) -> Vec<TraceId> {
+        let mut traces: Vec<_> = self.events.lock().iter().map(|e| e.trace).collect();
+        traces.sort_unstable();
+        traces.dedup();
+        traces
+    }
+
+    /// The stages traversed by an element, in the order they have been seen.
+    pub fn path(&self, trace: TraceId) -> Vec<LineageEvent> {
+        let mut path: Vec<_> = self
+            .events
+            .lock()
+            .iter()
+            .filter(|e| e.trace == trace)
+            .cloned()
+            .collect();
+        path.sort_by_key(|e| e.time);
+        path
+    }
+
+    /// The time taken by an element for reaching each stage from the previous one.
+    ///
+    /// The first stage, where the element has been sampled, has a latency of zero.
+    pub fn latencies(&self, trace: TraceId) -> Vec<(String, Duration)> {
+        let path = self.path(trace);
+        let mut prev = path.first().map(|e| e.time);
+        path.into_iter()
+            .map(|e| {
+                let latency = e.time - prev.replace(e.time).unwrap();
+                (e.stage, latency)
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Derivative)]
+#[derivative(Debug)]
+pub struct SampleLineage<Op: Operator> {
+    prev: Op,
+    #[derivative(Debug = "ignore")]
+    lineage: Lineage,
+    stage: String,
+    rate: f64,
+    coord: Option<Coord>,
+}
+
+impl<Op: Operator> Display for SampleLineage<Op> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} -> SampleLineage({})", self.prev, self.stage)
+    }
+}
+
+impl<Op: Operator> Operator for SampleLineage<Op> {
+    type Out = Traced<Op::Out>;
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        self.prev.setup(metadata);
+        self.coord = Some(metadata.coord);
+    }
+
+    #[inline]
+    fn next(&mut self) -> StreamElement<Self::Out> {
+        self.prev.next().map(|item| {
+            let mut rng = tls_rng();
+            let trace = (rng.generate::<f64>() < self.rate).then(|| rng.generate::<TraceId>());
+            if let Some(trace) = trace {
+                let coord = self.coord.expect("setup was not called");
+                self.lineage.record(trace, &self.stage, coord);
+            }
+            Traced { trace, item }
+        })
+    }
+
+    fn structure(&self) -> BlockStructure {
+        self.prev
+            .structure()
+            .add_operator(OperatorStructure::new::<Self::Out, _>("SampleLineage"))
+    }
+}
+
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = "Op: std::fmt::Debug"))]
+pub struct TraceStage<T, Op: Operator<Out = Traced<T>>> {
+    prev: Op,
+    #[derivative(Debug = "ignore")]
+    lineage: Lineage,
+    stage: String,
+    coord: Option<Coord>,
+}
+
+impl<T, Op: Operator<Out = Traced<T>>> Display for TraceStage<T, Op> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} -> TraceStage({})", self.prev, self.stage)
+    }
+}
+
+impl<T: Send, Op: Operator<Out = Traced<T>>> Operator for TraceStage<T, Op> {
+    type Out = Traced<T>;
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        self.prev.setup(metadata);
+        self.coord = Some(metadata.coord);
+    }
+
+    #[inline]
+    fn next(&mut self) -> StreamElement<Self::Out> {
+        let el = self.prev.next();
+        if let StreamElement::Item(t) | StreamElement::Timestamped(t, _) = &el {
+            if let Some(trace) = t.trace {
+                let coord = self.coord.expect("setup was not called");
+                self.lineage.record(trace, &self.stage, coord);
+            }
+        }
+        el
+    }
+
+    fn structure(&self) -> BlockStructure {
+        self.prev
+            .structure()
+            .add_operator(OperatorStructure::new::<Self::Out, _>("TraceStage"))
+    }
+}
+
+impl<Op: Operator + 'static> Stream<Op> {
+    /// Enable lineage tracing on this stream, sampling each element with probability `rate`.
+    ///
+    /// The elements are wrapped in a [`Traced`], and the sampled ones receive a new trace id. The
+    /// sampling is recorded in `lineage` as the first stage of the element, named `stage`. The
+    /// trace ids are carried by the elements until they are unwrapped, and the following stages
+    /// can be recorded with [`Stream::trace_stage`].
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::lineage::{Lineage, Traced};
+    /// # let mut env = StreamContext::new_local();
+    /// let lineage = Lineage::new();
+    /// let res = env
+    ///     .stream_iter(0..100)
+    ///     .sample_lineage(&lineage, "source", 0.1)
+    ///     .map(|t| t.map(|n| n * 2))
+    ///     .shuffle()
+    ///     .trace_stage(&lineage, "doubled")
+    ///     .map(Traced::into_inner)
+    ///     .collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// for trace in lineage.traces() {
+    ///     let stages: Vec<_> = lineage.path(trace).into_iter().map(|e| e.stage).collect();
+    ///     assert_eq!(stages, vec!["source", "doubled"]);
+    /// }
+    /// ```
+    pub fn sample_lineage(
+        self,
+        lineage: &Lineage,
+        stage: impl Into<String>,
+        rate: f64,
+    ) -> Stream<SampleLineage<Op>> {
+        assert!((0.0..=1.0).contains(&rate), "rate must be in [0, 1]");
+        let lineage = lineage.clone();
+        self.add_operator(|prev| SampleLineage {
+            prev,
+            lineage,
+            stage: stage.into(),
+            rate,
+            coord: None,
+        })
+    }
+}
+
+impl<T: Send, Op: Operator<Out = Traced<T>> + 'static> Stream<Op> {
+    /// Record in `lineage` the passage of the sampled elements through the stage named `stage`.
+    ///
+    /// See [`Stream::sample_lineage`] for enabling lineage tracing on a stream.
+    pub fn trace_stage(
+        self,
+        lineage: &Lineage,
+        stage: impl Into<String>,
+    ) -> Stream<TraceStage<T, Op>> {
+        let lineage = lineage.clone();
+        self.add_operator(|prev| TraceStage {
+            prev,
+            lineage,
+            stage: stage.into(),
+            coord: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::RuntimeConfig;
+    use crate::environment::StreamContext;
+    use crate::operator::lineage::{Lineage, Traced};
+    use crate::operator::source::IteratorSource;
+
+    #[test]
+    fn lineage_across_shuffle() {
+        let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        let lineage = Lineage::new();
+        let res = env
+            .stream(IteratorSource::new(0..1000u32))
+            .sample_lineage(&lineage, "source", 0.1)
+            .shuffle()
+            .trace_stage(&lineage, "shuffled")
+            .filter(|t| t.item % 2 == 0)
+            .group_by(|t| t.item % 10)
+            .drop_key()
+            .trace_stage(&lineage, "grouped")
+            .map(Traced::into_inner)
+            .collect_vec();
+        env.execute_blocking();
+
+        let mut res = res.get().unwrap();
+        res.sort_unstable();
+        assert_eq!(res, (0..1000).filter(|n| n % 2 == 0).collect::<Vec<_>>());
+
+        let traces = lineage.traces();
+        assert!(!traces.is_empty());
+        for trace in traces {
+            let stages: Vec<_> = lineage.path(trace).into_iter().map(|e| e.stage).collect();
+            assert!(
+                stages == ["source", "shuffled", "grouped"] || stages == ["source", "shuffled"],
+                "unexpected path {stages:?}"
+            );
+            let latencies = lineage.latencies(trace);
+            assert_eq!(latencies.len(), stages.len());
+            assert!(latencies[0].1.is_zero());
+        }
+    }
+
+    #[test]
+    fn lineage_never_sampled() {
+        let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        let lineage = Lineage::new();
+        env.stream(IteratorSource::new(0..100u32))
+            .sample_lineage(&lineage, "source", 0.0)
+            .trace_stage(&lineage, "next")
+            .for_each(std::mem::drop);
+        env.execute_blocking();
+        assert!(lineage.traces().is_empty());
+    }
+}
@@ -1,7 +1,7 @@
 use std::fmt::Display;
 
 use crate::block::{BlockStructure, OperatorStructure};
-use crate::operator::{Operator, StreamElement};
+use crate::operator::{Operator, ReportState, StateProbe, StreamElement};
 use crate::scheduler::ExecutionMetadata;
 
 #[derive(Derivative)]
@@ -78,6 +78,20 @@ where
     }
 }
 
+/// Mapping the results of a stateful operator does not change the state it holds.
+impl<O: Send, F, Op> ReportState for Map<O, F, Op>
+where
+    F: Fn(Op::Out) -> O + Send + Clone,
+    Op: ReportState,
+{
+    type Key = Op::Key;
+    type State = Op::State;
+
+    fn report_state(&mut self, probe: StateProbe<Self::Key, Self::State>) {
+        self.prev.report_state(probe);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
@@ -6,7 +6,7 @@ use coarsetime::Instant;
 use flume::{Receiver, Sender};
 use futures::{Future, StreamExt};
 
-use crate::block::{BlockStructure, OperatorStructure};
+use crate::block::{BatchTuner, BlockStructure, OperatorStructure};
 use crate::operator::{Data, Operator, StreamElement};
 use crate::scheduler::ExecutionMetadata;
 use crate::BatchMode;
@@ -16,6 +16,7 @@ pub(super) struct Batcher<T> {
     mode: BatchMode,
     buffer: Vec<StreamElement<T>>,
     last_send: Instant,
+    tuner: Option<BatchTuner>,
 }
 
 impl<T> Default for Batcher<T> {
@@ -24,6 +25,7 @@ impl<T> Default for Batcher<T> {
             mode: Default::default(),
             buffer: Default::default(),
             last_send: Default::default(),
+            tuner: None,
         }
     }
 }
@@ -49,6 +51,15 @@ impl<T> Batcher<T> {
                     None
                 }
             }
+            BatchMode::Auto(..) => {
+                self.buffer.push(message);
+                let tuner = self.tuner.as_mut().unwrap();
+                if tuner.enqueue(self.buffer.len()) {
+                    self.flush()
+                } else {
+                    None
+                }
+            }
             BatchMode::Single => Some(vec![message]),
         }
     }
@@ -56,6 +67,9 @@ impl<T> Batcher<T> {
     /// Flush the internal buffer if it's not empty.
     pub(crate) fn flush(&mut self) -> Option<Vec<StreamElement<T>>> {
         if !self.buffer.is_empty() {
+            if let Some(tuner) = self.tuner.as_mut() {
+                tuner.flushed(self.buffer.len());
+            }
             let cap = self.buffer.capacity();
             let new_cap = if self.buffer.len() < cap / 4 {
                 cap / 2
@@ -193,6 +207,7 @@ where
     fn setup(&mut self, metadata: &mut ExecutionMetadata) {
         self.prev.setup(metadata);
         self.batcher.mode = metadata.batch_mode;
+        self.batcher.tuner = BatchTuner::new(metadata.batch_mode);
     }
 
     #[inline]
@@ -56,6 +56,40 @@ where
         })
     }
 
+    /// Merge the items of this stream with the items of any number of other streams with the same
+    /// type.
+    ///
+    /// Unlike chaining [`Stream::merge`], which adds a new block for each merged stream, all the
+    /// streams go into a single new block that receives from all of them at once.
+    ///
+    /// The streams must have the same operator chain type, use [`Stream::into_boxed`] for merging
+    /// streams built with different operators.
+    ///
+    /// **Note**: the order of the resulting items is not specified.
+    ///
+    /// **Note**: this operator will split the current block.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let s1 = env.stream_iter(0..10);
+    /// let s2 = env.stream_iter(10..20);
+    /// let s3 = env.stream_iter(20..30);
+    /// let res = s1.union(vec![s2, s3]).collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let mut res = res.get().unwrap();
+    /// res.sort_unstable();
+    /// assert_eq!(res, (0..30).collect::<Vec<_>>());
+    /// ```
+    pub fn union(self, others: Vec<Stream<Op>>) -> Stream<impl Operator<Out = Op::Out>> {
+        self.union_connection(others)
+    }
+
     pub(crate) fn merge_distinct<Op2>(
         self,
         right: Stream<Op2>,
@@ -3,19 +3,30 @@
 //! The actual operator list can be found from the implemented methods of [`Stream`],
 //! [`KeyedStream`], [`crate::WindowedStream`]
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
 use std::hash::Hash;
+use std::net::ToSocketAddrs;
 use std::ops::{AddAssign, Div};
 
-use flume::{unbounded, Receiver};
+use flume::{bounded, unbounded, Receiver};
 #[cfg(feature = "tokio")]
 use futures::Future;
 use serde::{Deserialize, Serialize};
 
 pub(crate) use start::*;
 
+pub use boxed::BoxedOperator;
+pub use filter_in_set::BloomFilter;
+pub use int_keyed_fold::IntKey;
+pub use queryable_state::{QueryableState, QueryableStateClient};
+#[cfg(feature = "timestamp")]
+pub use resample::{GapFill, ResampledStream};
 pub use rich_map_custom::ElementGenerator;
+pub use sample::Reservoir;
+#[cfg(feature = "timestamp")]
+pub use sessionize::Session;
+pub use state_report::{KeyState, ReportState, StateProbe, StateReport, StateReportHandle};
 
 use crate::block::{group_by_hash, BlockStructure, GroupHasherBuilder, NextStrategy, Replication};
 use crate::scheduler::ExecutionMetadata;
@@ -29,12 +40,17 @@ use self::map_memo::MapMemo;
 use self::sink::collect::Collect;
 use self::sink::collect_channel::CollectChannelSink;
 use self::sink::collect_count::CollectCountSink;
+use self::sink::collect_one::CollectOneSink;
 use self::sink::collect_vec::CollectVecSink;
 use self::sink::for_each::ForEach;
+use self::sink::output_quota::OutputQuota;
+#[cfg(not(feature = "tokio"))]
+use self::sink::publish::{PublishBacklog, PublishSink};
 use self::sink::{StreamOutput, StreamOutputRef};
 #[cfg(feature = "timestamp")]
 use self::{
     add_timestamps::{AddTimestamp, DropTimestamp},
+    asof_join::AsofJoin,
     interval_join::IntervalJoin,
 };
 use self::{
@@ -45,6 +61,7 @@ use self::{
     flatten::{Flatten, KeyedFlatten},
     fold::Fold,
     inspect::Inspect,
+    int_keyed_fold::IntKeyedFold,
     key_by::KeyBy,
     keyed_fold::KeyedFold,
     map::Map,
@@ -58,34 +75,59 @@ use self::{
 
 #[cfg(feature = "timestamp")]
 mod add_timestamps;
+#[cfg(feature = "timestamp")]
+mod asof_join;
 mod batch_mode;
 mod boxed;
+pub mod clock;
+mod cross;
+mod dedup;
+pub mod disk_shuffle;
 pub(crate) mod end;
+mod fallible;
 mod filter;
+mod filter_in_set;
 mod filter_map;
 mod flat_map;
 mod flatten;
 mod fold;
+pub mod graph;
+mod hooks;
 mod inspect;
+pub mod int_keyed_fold;
 #[cfg(feature = "timestamp")]
 mod interval_join;
 pub mod iteration;
 pub mod join;
 mod key_by;
 mod keyed_fold;
+pub mod lineage;
 mod map;
 #[cfg(feature = "tokio")]
 mod map_async;
 mod map_memo;
 mod merge;
+mod named;
+mod queryable_state;
 mod reorder;
 mod replication;
+#[cfg(feature = "timestamp")]
+mod resample;
 mod rich_map;
 mod rich_map_custom;
 mod route;
+mod sample;
+#[cfg(feature = "timestamp")]
+mod sessionize;
 pub mod sink;
+pub mod smoothing;
 pub mod source;
 mod start;
+mod state_report;
+#[cfg(feature = "timestamp")]
+pub mod timestamp;
+#[cfg(feature = "timestamp")]
+pub mod watermark_audit;
 pub mod window;
 mod zip;
 
@@ -111,6 +153,9 @@ impl<Key, Out, T: Fn(&Out) -> Key + Clone + Send + 'static> KeyerFn<Key, Out> fo
 
 /// When using timestamps and watermarks, this type expresses the timestamp of a message or of a
 /// watermark.
+///
+/// It's a signed number of ticks since the Unix epoch, the duration of a tick is chosen by the
+/// user. See the [`timestamp`] module for converting from and to the time types of `std`.
 #[cfg(feature = "timestamp")]
 pub type Timestamp = i64;
 
@@ -357,6 +402,31 @@ where
         self
     }
 
+    /// Set the priority of this stream.
+    ///
+    /// When the stream is merged or joined with a stream with a lower priority, the batches of this
+    /// stream are received first whenever they are ready, so a low-volume stream of control
+    /// messages or alerts is not delayed by the bulk data coming from the other stream.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig, Priority};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let alerts = env.stream_iter(0..10).priority(Priority::High);
+    /// let data = env.stream_iter(100..1000);
+    /// let res = alerts.merge(data).collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// assert_eq!(res.get().unwrap().len(), 910);
+    /// ```
+    pub fn priority(mut self, priority: crate::block::Priority) -> Self {
+        self.block.priority = priority;
+        self
+    }
+
     /// Remove from the stream all the elements for which the provided function returns `None` and
     /// keep the elements that returned `Some(_)`.
     ///
@@ -819,7 +889,7 @@ where
         init: O,
         local: F,
         global: G,
-    ) -> KeyedStream<impl Operator<Out = (K, O)>>
+    ) -> KeyedStream<impl ReportState<Key = K, State = O, Out = (K, O)>>
     where
         Fk: Fn(&Op::Out) -> K + Send + Clone + 'static,
         F: Fn(&mut O, Op::Out) + Send + Clone + 'static,
@@ -838,7 +908,7 @@ where
             // key_by with given keyer
             .add_operator(|prev| KeyBy::new(prev, keyer.clone()))
             // local fold
-            .add_operator(|prev| KeyedFold::new(prev, init.clone(), local))
+            .add_operator(|prev| KeyedFold::new(prev, init.clone(), local).partial())
             // group by key
             .split_block(End::new, next_strategy)
             // global fold
@@ -847,6 +917,62 @@ where
         KeyedStream(new_stream)
     }
 
+    /// Like [`Stream::group_by_fold`], but specialized for fixed-width integer keys (see
+    /// [`IntKey`]).
+    ///
+    /// Both the local and the global aggregations store the accumulators inline in an open
+    /// addressing hash table, avoiding the overhead of the generic hash map used by
+    /// [`Stream::group_by_fold`]. This is faster when there are many elements per key.
+    ///
+    /// **Note**: this operator will retain all the messages of the stream and emit the values only
+    /// when the stream ends. Therefore this is not properly _streaming_.
+    ///
+    /// **Note**: this operator will split the current block.
+    ///
+    /// ## Example
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # let mut env = StreamContext::new_local();
+    /// let s = env.stream_iter(0..5i64);
+    /// let res = s
+    ///     .group_by_fold_int(|&n| n % 2, 0, |acc, value| *acc += value, |acc, value| *acc += value)
+    ///     .collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let mut res = res.get().unwrap();
+    /// res.sort_unstable();
+    /// assert_eq!(res, vec![(0, 0 + 2 + 4), (1, 1 + 3)]);
+    /// ```
+    pub fn group_by_fold_int<K, O, Fk, F, G>(
+        self,
+        keyer: Fk,
+        init: O,
+        local: F,
+        global: G,
+    ) -> KeyedStream<impl ReportState<Key = K, State = O, Out = (K, O)>>
+    where
+        Fk: Fn(&Op::Out) -> K + Send + Clone + 'static,
+        F: Fn(&mut O, Op::Out) + Send + Clone + 'static,
+        G: Fn(&mut O, O) + Send + Clone + 'static,
+        K: IntKey,
+        O: ExchangeData,
+        Op::Out: Clone,
+    {
+        let next_strategy = NextStrategy::GroupBy(
+            move |(key, _): &(K, O)| group_by_hash(&key),
+            Default::default(),
+        );
+
+        let new_stream = self
+            .add_operator(|prev| KeyBy::new(prev, keyer.clone()))
+            .add_operator(|prev| IntKeyedFold::new(prev, init.clone(), local))
+            .split_block(End::new, next_strategy)
+            .add_operator(|prev| IntKeyedFold::new(prev, init.clone(), global));
+
+        KeyedStream(new_stream)
+    }
+
     pub fn fold_scan<O, SL, SG, L, G, F>(
         self,
         local_fold: L,
@@ -1076,7 +1202,7 @@ where
     /// + `Watermark` messages must be sent when no more items with lower timestamp will ever be produced
     /// + `FlushBatch` messages must be forwarded if received
     /// + For each `FlushAndRestart` and `Terminate` message received, the operator must generate
-    ///     one and only one message of the same kind. No other messages of this kind should be created
+    ///   one and only one message of the same kind. No other messages of this kind should be created
     ///
     /// The mapping function is _cloned_ inside each replica, and they will not share state between
     /// each other. If you want that only a single replica handles all the items you may want to
@@ -1138,7 +1264,7 @@ where
     where
         F: FnMut(Op::Out) + Send + Clone + 'static,
     {
-        self.add_operator(|prev| ForEach::new(prev, f))
+        self.add_operator(|prev| ForEach::new(OutputQuota::new(prev), f))
             .finalize_block();
     }
 
@@ -1540,7 +1666,7 @@ where
         self,
         keyer: Fk,
         f: F,
-    ) -> KeyedStream<impl Operator<Out = (K, I)>>
+    ) -> KeyedStream<impl ReportState<Key = K, State = Option<I>, Out = (K, I)>>
     where
         Fk: Fn(&Op::Out) -> K + Send + Clone + 'static,
         F: Fn(&mut I, I) + Send + Clone + 'static,
@@ -1564,7 +1690,7 @@ where
                 }
             },
         )
-        .map(|(_, value)| value.unwrap())
+        .add_operator(|prev| Map::new(prev, |(key, value): (K, Option<I>)| (key, value.unwrap())))
     }
 
     /// Given two streams **with timestamps** join them according to an interval centered around the
@@ -1889,7 +2015,41 @@ where
     pub fn collect_channel(self) -> Receiver<I> {
         let (tx, rx) = unbounded();
         self.replication(Replication::One)
-            .add_operator(|prev| CollectChannelSink::new(prev, tx))
+            .add_operator(|prev| CollectChannelSink::new(OutputQuota::new(prev), tx))
+            .finalize_block();
+        rx
+    }
+
+    /// Close the stream and send resulting items to a bounded channel on a single host.
+    ///
+    /// Like [`Stream::collect_channel`], but the channel holds at most `capacity` items: when it's
+    /// full the stream waits for the receiver to consume them, so the results can be processed as
+    /// they arrive without keeping all of them in memory. The receiver must be consumed while the
+    /// job is running, for example executing the job on another thread. If the receiver is dropped
+    /// the remaining items are discarded.
+    ///
+    /// **Note**: the order of items and keys is unspecified.
+    ///
+    /// **Note**: this operator will split the current block.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let s = env.stream_iter(0..1000u32);
+    /// let rx = s.collect_channel_bounded(16);
+    ///
+    /// let job = std::thread::spawn(move || env.execute_blocking());
+    /// let sum: u32 = rx.iter().sum();
+    /// job.join().unwrap();
+    /// assert_eq!(sum, 499500);
+    /// ```
+    pub fn collect_channel_bounded(self, capacity: usize) -> Receiver<I> {
+        let (tx, rx) = bounded(capacity);
+        self.replication(Replication::One)
+            .add_operator(|prev| CollectChannelSink::new(OutputQuota::new(prev), tx))
             .finalize_block();
         rx
     }
@@ -1919,17 +2079,85 @@ where
     /// ```
     pub fn collect_channel_parallel(self) -> Receiver<I> {
         let (tx, rx) = unbounded();
-        self.add_operator(|prev| CollectChannelSink::new(prev, tx))
+        self.add_operator(|prev| CollectChannelSink::new(OutputQuota::new(prev), tx))
             .finalize_block();
         rx
     }
 
-    /// Close the stream and store all the resulting items into a [`Vec`] on a single host.
+    /// Close the stream and publish its items under the given name, so that other jobs can
+    /// receive them using [`StreamContext::subscribe`](crate::StreamContext::subscribe).
     ///
-    /// If the stream is distributed among multiple replicas, a bottleneck is placed where all the
-    /// replicas sends the items to.
+    /// The items are gathered on a single host, which listens for the subscribers on the given
+    /// address, and are sent with the same protocol used between the hosts of a job. Until the
+    /// first subscriber connects the items are kept in a backlog of
+    /// [`PublishBacklog::default`](crate::operator::sink::PublishBacklog) size, when it is full
+    /// the stream waits for a subscriber. Once connected, the subscribers receive the items as
+    /// they are produced and a slow subscriber slows down the stream. A subscriber joining later
+    /// receives only the items published from that moment.
     ///
-    /// **Note**: the order of items and keys is unspecified.
+    /// See [`Stream::publish_with_backlog`] for choosing what happens when the backlog is full.
+    ///
+    /// **Note**: this operator will split the current block.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// // in the first job
+    /// let env = StreamContext::new_local();
+    /// env.stream_iter(0..10u32).publish("numbers", "127.0.0.1:4000");
+    /// env.execute_blocking();
+    ///
+    /// // in the second job
+    /// let env = StreamContext::new_local();
+    /// let res = env.subscribe::<u32>("numbers", "127.0.0.1:4000").collect_vec();
+    /// env.execute_blocking();
+    /// ```
+    #[cfg(not(feature = "tokio"))]
+    pub fn publish(self, name: impl Into<String>, address: impl ToSocketAddrs) {
+        self.publish_with_backlog(name, address, PublishBacklog::default());
+    }
+
+    /// Close the stream and publish its items under the given name, like [`Stream::publish`],
+    /// choosing what to do with the items produced while nobody is subscribed.
+    ///
+    /// With [`PublishBacklog::Block`] the stream waits for a subscriber when the backlog is full,
+    /// with [`PublishBacklog::DropOldest`] the oldest items are dropped. In both cases the items
+    /// still in the backlog when the stream ends are lost.
+    ///
+    /// **Note**: this operator will split the current block.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::sink::PublishBacklog;
+    /// let env = StreamContext::new_local();
+    /// env.stream_iter(0..10u32)
+    ///     .publish_with_backlog("numbers", "127.0.0.1:4000", PublishBacklog::DropOldest(5));
+    /// env.execute_blocking();
+    /// ```
+    #[cfg(not(feature = "tokio"))]
+    pub fn publish_with_backlog(
+        self,
+        name: impl Into<String>,
+        address: impl ToSocketAddrs,
+        backlog: PublishBacklog,
+    ) {
+        let name = name.into();
+        let address = address
+            .to_socket_addrs()
+            .unwrap_or_else(|e| panic!("publish {name}: invalid address: {e:?}"))
+            .collect();
+        self.replication(Replication::One)
+            .add_operator(|prev| PublishSink::new(OutputQuota::new(prev), name, address, backlog))
+            .finalize_block();
+    }
+
+    /// Close the stream and store the number of its items on a single host.
+    ///
+    /// Each replica counts its own items and sends only the count.
     ///
     /// **Note**: this operator will split the current block.
     ///
@@ -1940,17 +2168,81 @@ where
     /// # use renoir::operator::source::IteratorSource;
     /// # let mut env = StreamContext::new_local();
     /// let s = env.stream_iter(0..10);
-    /// let res = s.collect_vec();
+    /// let res = s.collect_count();
     ///
     /// env.execute_blocking();
     ///
-    /// assert_eq!(res.get().unwrap(), (0..10).collect::<Vec<_>>());
+    /// assert_eq!(res.get().unwrap(), 10);
     /// ```
     pub fn collect_count(self) -> StreamOutput<usize> {
         let output = StreamOutputRef::default();
         self.add_operator(|prev| Fold::new(prev, 0, |acc, _| *acc += 1))
             .replication(Replication::One)
-            .add_operator(|prev| CollectCountSink::new(prev, output.clone()))
+            .add_operator(|prev| CollectCountSink::new(OutputQuota::new(prev), output.clone()))
+            .finalize_block();
+        StreamOutput::from(output)
+    }
+
+    /// Close the stream and store its first element on a single host, or `None` if the stream is
+    /// empty.
+    ///
+    /// Each replica sends only its own first element. If the stream is distributed among multiple
+    /// replicas, the result is the first element of one of them.
+    ///
+    /// **Note**: this operator will split the current block.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let s = env.stream_iter(0..10);
+    /// let res = s.collect_first();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// assert_eq!(res.get().unwrap(), Some(0));
+    /// ```
+    pub fn collect_first(self) -> StreamOutput<Option<I>> {
+        let output = StreamOutputRef::default();
+        self.add_operator(|prev| {
+            Fold::new(prev, None, |acc: &mut Option<I>, item| {
+                acc.get_or_insert(item);
+            })
+        })
+        .replication(Replication::One)
+        .add_operator(|prev| CollectOneSink::new(OutputQuota::new(prev), false, output.clone()))
+        .finalize_block();
+        StreamOutput::from(output)
+    }
+
+    /// Close the stream and store its last element on a single host, or `None` if the stream is
+    /// empty.
+    ///
+    /// Each replica sends only its own last element. If the stream is distributed among multiple
+    /// replicas, the result is the last element of one of them.
+    ///
+    /// **Note**: this operator will split the current block.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let s = env.stream_iter(0..10);
+    /// let res = s.collect_last();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// assert_eq!(res.get().unwrap(), Some(9));
+    /// ```
+    pub fn collect_last(self) -> StreamOutput<Option<I>> {
+        let output = StreamOutputRef::default();
+        self.add_operator(|prev| Fold::new(prev, None, |acc, item| *acc = Some(item)))
+            .replication(Replication::One)
+            .add_operator(|prev| CollectOneSink::new(OutputQuota::new(prev), true, output.clone()))
             .finalize_block();
         StreamOutput::from(output)
     }
@@ -1980,7 +2272,7 @@ where
     pub fn collect_vec(self) -> StreamOutput<Vec<I>> {
         let output = StreamOutputRef::default();
         self.replication(Replication::One)
-            .add_operator(|prev| CollectVecSink::new(prev, output.clone()))
+            .add_operator(|prev| CollectVecSink::new(OutputQuota::new(prev), output.clone()))
             .finalize_block();
         StreamOutput::from(output)
     }
@@ -2010,7 +2302,7 @@ where
     pub fn collect_vec_all(self) -> StreamOutput<Vec<I>> {
         let output = StreamOutputRef::default();
         self.repartition(Replication::Host, NextStrategy::all())
-            .add_operator(|prev| CollectVecSink::new(prev, output.clone()))
+            .add_operator(|prev| CollectVecSink::new(OutputQuota::new(prev), output.clone()))
             .finalize_block();
         StreamOutput::from(output)
     }
@@ -2040,7 +2332,7 @@ where
     pub fn collect<C: FromIterator<I> + Send + 'static>(self) -> StreamOutput<C> {
         let output = StreamOutputRef::default();
         self.replication(Replication::One)
-            .add_operator(|prev| Collect::new(prev, output.clone()))
+            .add_operator(|prev| Collect::new(OutputQuota::new(prev), output.clone()))
             .finalize_block();
         StreamOutput::from(output)
     }
@@ -2069,10 +2361,39 @@ where
     pub fn collect_all<C: FromIterator<I> + Send + 'static>(self) -> StreamOutput<C> {
         let output = StreamOutputRef::default();
         self.repartition(Replication::Host, NextStrategy::all())
-            .add_operator(|prev| Collect::new(prev, output.clone()))
+            .add_operator(|prev| Collect::new(OutputQuota::new(prev), output.clone()))
             .finalize_block();
         StreamOutput::from(output)
     }
+
+    /// Close the stream and store all the distinct items into a [`HashSet`] on a single host.
+    ///
+    /// Each replica drops the items it has already seen before sending them, so each distinct
+    /// item is sent at most once per replica.
+    ///
+    /// **Note**: this operator will split the current block.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let s = env.stream_iter(0..10);
+    /// let res = s.map(|n| n % 3).collect_set();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// assert_eq!(res.get().unwrap(), [0, 1, 2].into_iter().collect());
+    /// ```
+    pub fn collect_set(self) -> StreamOutput<HashSet<I>>
+    where
+        I: Hash + Eq,
+    {
+        let mut seen = HashSet::new();
+        self.rich_filter_map(move |item| seen.insert(item.clone()).then_some(item))
+            .collect()
+    }
 }
 
 impl<Op> Stream<Op>
@@ -2140,7 +2461,6 @@ where
     ///
     /// assert_eq!(res.get().unwrap(), vec![0, 1, 4, 9, 0, 1, 4, 9, 0, 1]);
     /// ```
-
     pub fn map_memo<O: Data + Sync, F>(
         self,
         f: F,
@@ -2372,7 +2692,11 @@ where
     /// res.sort_unstable();
     /// assert_eq!(res, vec![(0, 0 + 2 + 4), (1, 1 + 3)]);
     /// ```
-    pub fn fold<O, F>(self, init: O, f: F) -> KeyedStream<impl Operator<Out = (K, O)>>
+    pub fn fold<O, F>(
+        self,
+        init: O,
+        f: F,
+    ) -> KeyedStream<impl ReportState<Key = K, State = O, Out = (K, O)>>
     where
         F: Fn(&mut O, <Op::Out as KeyedItem>::Value) + Send + Clone + 'static,
         O: Send + Clone,
@@ -2417,7 +2741,10 @@ where
     /// res.sort_unstable();
     /// assert_eq!(res, vec![(0, 0 + 2 + 4), (1, 1 + 3)]);
     /// ```
-    pub fn reduce<F>(self, f: F) -> KeyedStream<impl Operator<Out = (K, I)>>
+    pub fn reduce<F>(
+        self,
+        f: F,
+    ) -> KeyedStream<impl ReportState<Key = K, State = Option<I>, Out = (K, I)>>
     where
         I: Clone + 'static,
         F: Fn(&mut I, I) + Send + Clone + 'static,
@@ -2426,7 +2753,7 @@ where
             None => *acc = Some(value),
             Some(acc) => f(acc, value),
         })
-        .map(|(_, value)| value.unwrap())
+        .add_operator(|prev| Map::new(prev, |(key, value): (K, Option<I>)| (key, value.unwrap())))
     }
 
     /// Map the elements of the stream into new elements.
@@ -2583,7 +2910,7 @@ where
         F: FnMut((K, I)) + Send + Clone + 'static,
     {
         self.0
-            .add_operator(|prev| ForEach::new(prev, f))
+            .add_operator(|prev| ForEach::new(OutputQuota::new(prev), f))
             .finalize_block();
     }
 }
@@ -2594,6 +2921,43 @@ where
     K: ExchangeDataKey,
     I: ExchangeData,
 {
+    /// Partition the stream again according to a new key generated by the `keyer` function from
+    /// the current key and value of each element.
+    ///
+    /// This is the same as `unkey().group_by(...)` followed by dropping the old key, but with a
+    /// single network shuffle: the new key is computed once, on the sending side, and sent along
+    /// with the value.
+    ///
+    /// **Note**: this operator will split the current block.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let s = env.stream_iter(0..6).group_by(|&n| n % 2);
+    /// let res = s.re_key(|&k, &n| k + 10 * (n % 3)).collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let mut res = res.get().unwrap();
+    /// res.sort_unstable(); // the output order is nondeterministic
+    /// assert_eq!(res, vec![(0, 0), (1, 3), (10, 4), (11, 1), (20, 2), (21, 5)]);
+    /// ```
+    pub fn re_key<K2, Fk>(self, keyer: Fk) -> KeyedStream<impl Operator<Out = (K2, I)>>
+    where
+        Fk: Fn(&K, &I) -> K2 + Send + Clone + 'static,
+        K2: ExchangeDataKey,
+    {
+        let next_strategy = NextStrategy::group_by(|(k, _): &(K2, I)| k.clone());
+        let new_stream = self
+            .0
+            .map(move |(k, v)| (keyer(&k, &v), v))
+            .split_block(End::new, next_strategy);
+        KeyedStream(new_stream)
+    }
+
     /// Given two streams **with timestamps** join them according to an interval centered around the
     /// timestamp of the left side.
     ///
@@ -2621,6 +2985,60 @@ where
             .add_operator(|prev| IntervalJoin::new(prev, lower_bound, upper_bound))
     }
 
+    /// Given two streams **with timestamps** join each element on the left with the latest
+    /// element on the right with the same key that is not after it.
+    ///
+    /// An element on the left side with timestamp T is joined with the element on the right with
+    /// the greatest timestamp Q such that `T - tolerance <= Q <= T`. If there is no such element the
+    /// left element is emitted with `None`, so each element of the left side is emitted exactly
+    /// once, with its own timestamp.
+    ///
+    /// This is the typical way of aligning two time series, e.g. attaching to each trade the last
+    /// quote of the same symbol. The elements of the right side are dropped as soon as the
+    /// watermarks guarantee that a newer element replaces them or that they are older than the
+    /// tolerance.
+    ///
+    /// **Note**: this operator will split the current block.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let quotes = env
+    ///     .stream_iter(vec![('a', 0, 10), ('a', 5, 11), ('b', 3, 20)].into_iter())
+    ///     .add_timestamps(|&(_, ts, _)| ts, |_, _| None)
+    ///     .group_by(|&(sym, _, _)| sym)
+    ///     .map(|(_, (_, _, price))| price);
+    /// let trades = env
+    ///     .stream_iter(vec![('a', 4), ('a', 6), ('b', 1)].into_iter())
+    ///     .add_timestamps(|&(_, ts)| ts, |_, _| None)
+    ///     .group_by(|&(sym, _)| sym)
+    ///     .map(|(_, (_, ts))| ts);
+    /// let res = trades.asof_join(quotes, 10).collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let mut res = res.get().unwrap();
+    /// res.sort_unstable();
+    /// assert_eq!(res, vec![('a', (4, Some(10))), ('a', (6, Some(11))), ('b', (1, None))]);
+    /// ```
+    #[cfg(feature = "timestamp")]
+    pub fn asof_join<I2, Op2>(
+        self,
+        right: KeyedStream<Op2>,
+        tolerance: Timestamp,
+    ) -> KeyedStream<impl Operator<Out = (K, (I, Option<I2>))>>
+    where
+        I2: ExchangeData,
+        Op2: Operator<Out = (K, I2)> + 'static,
+    {
+        self.merge_distinct(right)
+            .add_operator(Reorder::new)
+            .add_operator(|prev| AsofJoin::new(prev, tolerance))
+    }
+
     /// Merge the items of this stream with the items of another stream with the same type.
     ///
     /// **Note**: the order of the resulting items is not specified.
@@ -2681,7 +3099,6 @@ where
     /// let s = env.stream_iter(0..5);
     /// let res = s.shuffle();
     /// ```
-
     pub fn shuffle(self) -> Stream<impl Operator<Out = Op::Out>> {
         self.0.split_block(End::new, NextStrategy::random())
     }
@@ -2800,6 +3217,13 @@ where
     pub fn collect_channel(self) -> Receiver<(K, I)> {
         self.unkey().collect_channel()
     }
+
+    /// Close the stream and send resulting items to a bounded channel on a single host.
+    ///
+    /// See [`Stream::collect_channel_bounded`].
+    pub fn collect_channel_bounded(self, capacity: usize) -> Receiver<(K, I)> {
+        self.unkey().collect_channel_bounded(capacity)
+    }
     /// Close the stream and send resulting items to a channel on each single host.
     ///
     /// Each host sends its outputs to the channel without repartitioning.
@@ -2944,6 +3368,32 @@ where
     pub fn collect_all<C: FromIterator<(K, I)> + Send + 'static>(self) -> StreamOutput<C> {
         self.unkey().collect_all()
     }
+
+    /// Close the stream and store the last value of each key into a [`HashMap`] on a single host.
+    ///
+    /// Each replica keeps only the last value of each of its keys, and sends it when the stream
+    /// ends.
+    ///
+    /// **Note**: this operator will split the current block.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let s = env.stream_iter(0..3).group_by(|&n| n % 2);
+    /// let res = s.reduce(|acc, n| *acc += n).collect_map();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let res = res.get().unwrap();
+    /// assert_eq!(res[&0], 0 + 2);
+    /// assert_eq!(res[&1], 1);
+    /// ```
+    pub fn collect_map(self) -> StreamOutput<HashMap<K, I>> {
+        self.reduce(|last, value| *last = value).collect()
+    }
 }
 
 impl<K, I, O, It, Op> KeyedStream<Op>
@@ -0,0 +1,170 @@
+use std::fmt::Display;
+
+use crate::block::BlockStructure;
+use crate::operator::{DataKey, Operator, StreamElement};
+use crate::scheduler::ExecutionMetadata;
+use crate::{KeyedStream, Stream};
+
+/// Operator that attaches a name and/or a stable identifier to the previous operator.
+///
+/// The elements are forwarded unchanged, only the structure and the displayed representation of
+/// the previous operator are changed.
+#[derive(Clone, Debug)]
+pub struct Named<Op: Operator> {
+    prev: Op,
+    name: Option<String>,
+    uid: Option<String>,
+}
+
+impl<Op: Operator> Named<Op> {
+    pub(super) fn name(prev: Op, name: String) -> Self {
+        Self {
+            prev,
+            name: Some(name),
+            uid: None,
+        }
+    }
+
+    pub(super) fn uid(prev: Op, uid: String) -> Self {
+        Self {
+            prev,
+            name: None,
+            uid: Some(uid),
+        }
+    }
+}
+
+impl<Op: Operator> Display for Named<Op> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.prev)?;
+        if let Some(name) = &self.name {
+            write!(f, "[{name}]")?;
+        }
+        if let Some(uid) = &self.uid {
+            write!(f, "[uid={uid}]")?;
+        }
+        Ok(())
+    }
+}
+
+impl<Op: Operator> Operator for Named<Op> {
+    type Out = Op::Out;
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        self.prev.setup(metadata);
+    }
+
+    #[inline]
+    fn next(&mut self) -> StreamElement<Op::Out> {
+        self.prev.next()
+    }
+
+    fn structure(&self) -> BlockStructure {
+        let mut structure = self.prev.structure();
+        if let Some(operator) = structure.operators.last_mut() {
+            if let Some(name) = &self.name {
+                operator.name = Some(name.clone());
+            }
+            if let Some(uid) = &self.uid {
+                operator.uid = Some(uid.clone());
+            }
+        }
+        structure
+    }
+}
+
+impl<Op> Stream<Op>
+where
+    Op: Operator + 'static,
+{
+    /// Give a name to the last operator of the stream.
+    ///
+    /// The name replaces the title of the operator in the [`BlockStructure`] and in the job graph,
+    /// and it is shown in the logs, making it easier to recognize the operators of a large job.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let s = env.stream_iter(0..5).map(|n| n * 2).name("double");
+    /// let res = s.collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// assert_eq!(res.get().unwrap(), vec![0, 2, 4, 6, 8]);
+    /// ```
+    pub fn name(self, name: impl Into<String>) -> Stream<Named<Op>> {
+        let name = name.into();
+        self.add_operator(|prev| Named::name(prev, name))
+    }
+
+    /// Give a stable identifier to the last operator of the stream.
+    ///
+    /// Unlike the title of the operator, the uid does not depend on the code of the job: it is
+    /// meant to identify the same operator (and its state) across different versions of the job.
+    /// For this reason the uids should be unique inside the job, a warning is logged otherwise.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let s = env.stream_iter(0..5).map(|n| n * 2).uid("double-v1");
+    /// let res = s.collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// assert_eq!(res.get().unwrap(), vec![0, 2, 4, 6, 8]);
+    /// ```
+    pub fn uid(self, uid: impl Into<String>) -> Stream<Named<Op>> {
+        let uid = uid.into();
+        self.add_operator(|prev| Named::uid(prev, uid))
+    }
+}
+
+impl<Op, K, I> KeyedStream<Op>
+where
+    K: DataKey,
+    I: Send + 'static,
+    Op: Operator<Out = (K, I)> + 'static,
+{
+    /// Give a name to the last operator of the stream.
+    ///
+    /// See [`Stream::name`] for the details.
+    pub fn name(self, name: impl Into<String>) -> KeyedStream<Named<Op>> {
+        KeyedStream(self.0.name(name))
+    }
+
+    /// Give a stable identifier to the last operator of the stream.
+    ///
+    /// See [`Stream::uid`] for the details.
+    pub fn uid(self, uid: impl Into<String>) -> KeyedStream<Named<Op>> {
+        KeyedStream(self.0.uid(uid))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::operator::named::Named;
+    use crate::operator::Operator;
+    use crate::test::FakeOperator;
+
+    #[test]
+    fn test_named_structure() {
+        let fake = FakeOperator::new(0..3u8);
+        let named = Named::uid(Named::name(fake, "numbers".into()), "numbers-v1".into());
+
+        assert_eq!(
+            named.to_string(),
+            "FakeOperator<u8>[numbers][uid=numbers-v1]"
+        );
+        let structure = named.structure();
+        let operator = structure.operators.last().unwrap();
+        assert_eq!(operator.name.as_deref(), Some("numbers"));
+        assert_eq!(operator.uid.as_deref(), Some("numbers-v1"));
+        assert_eq!(operator.display_name(), "numbers");
+    }
+}
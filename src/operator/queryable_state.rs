@@ -0,0 +1,563 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::{BufReader, ErrorKind, Write};
+use std::marker::PhantomData;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Weak};
+use std::thread::sleep;
+use std::time::Duration;
+
+use dashmap::DashMap;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+
+use crate::block::{group_by_hash, BlockStructure, GroupHasherBuilder, OperatorStructure};
+use crate::config::RuntimeConfig;
+use crate::operator::{Data, DataKey, ExchangeData, ExchangeDataKey, Operator, StreamElement};
+use crate::scheduler::ExecutionMetadata;
+use crate::{CoordUInt, KeyedStream};
+
+type Partition<K, V> = Arc<DashMap<K, V, GroupHasherBuilder>>;
+
+/// How often the endpoint checks whether the state has been dropped.
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A request sent to the endpoint of a host.
+#[derive(Debug, Serialize, Deserialize)]
+enum QueryRequest<K> {
+    /// Ask for the replicas whose partitions are held by the host.
+    Replicas,
+    /// Ask for the value of a key.
+    Get(K),
+}
+
+/// The response of the endpoint of a host.
+#[derive(Debug, Serialize, Deserialize)]
+enum QueryResponse<V> {
+    Replicas {
+        /// The total number of replicas of the block holding the state.
+        num_replicas: usize,
+        /// The global ids of the replicas running in the host.
+        local: Vec<CoordUInt>,
+    },
+    Value(Option<V>),
+}
+
+/// The partitions registered by the replicas, indexed by their global id.
+struct Partitions<K, V> {
+    /// The total number of replicas of the block holding the state.
+    num_replicas: usize,
+    /// The partitions of the replicas running in this process.
+    local: HashMap<CoordUInt, Partition<K, V>, crate::block::CoordHasherBuilder>,
+}
+
+/// Handle for reading the keyed state exposed by a running job, from the same process.
+///
+/// The handle is obtained from [`KeyedStream::queryable_state`] and can be moved to other threads
+/// to query the current value of a key while the computation is still running. After the
+/// computation has ended the handle keeps returning the last value seen for each key.
+///
+/// Each replica owns the partition of the state for the keys it receives: queries are routed to
+/// the replica responsible for the key using the same hash used by
+/// [`Stream::group_by`](crate::Stream::group_by), falling back to a scan of all the partitions if
+/// the stream has been partitioned differently.
+///
+/// The handle reads the state directly from the memory of the replicas running in this process.
+/// To query the state from other processes, or with a remote configuration, use
+/// [`KeyedStream::queryable_state_endpoint`] and [`QueryableStateClient`].
+pub struct QueryableState<K, V> {
+    partitions: Arc<RwLock<Partitions<K, V>>>,
+    /// The address of the endpoint serving the state, if any.
+    address: Option<SocketAddr>,
+}
+
+impl<K, V> Clone for QueryableState<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            partitions: self.partitions.clone(),
+            address: self.address,
+        }
+    }
+}
+
+impl<K: DataKey + Sync, V: Data + Sync> QueryableState<K, V> {
+    fn new() -> Self {
+        Self {
+            partitions: Arc::new(RwLock::new(Partitions {
+                num_replicas: 0,
+                local: Default::default(),
+            })),
+            address: None,
+        }
+    }
+
+    /// The address the endpoint of this process is listening on, if the state has been exposed
+    /// with [`KeyedStream::queryable_state_endpoint`].
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.address
+    }
+
+    /// Register the partition of the replica with the given global id.
+    fn register(&self, global_id: CoordUInt, num_replicas: usize) -> Partition<K, V> {
+        let mut partitions = self.partitions.write();
+        partitions.num_replicas = num_replicas;
+        partitions.local.entry(global_id).or_default().clone()
+    }
+
+    /// Get the current value associated with the key, if any replica has seen it.
+    pub fn get(&self, key: &K) -> Option<V> {
+        let partitions = self.partitions.read();
+        if partitions.num_replicas == 0 {
+            return None;
+        }
+        let owner = (group_by_hash(key) % partitions.num_replicas as u64) as CoordUInt;
+        if let Some(value) = partitions
+            .local
+            .get(&owner)
+            .and_then(|p| p.get(key).map(|v| v.clone()))
+        {
+            return Some(value);
+        }
+        partitions
+            .local
+            .iter()
+            .filter(|(&id, _)| id != owner)
+            .find_map(|(_, p)| p.get(key).map(|v| v.clone()))
+    }
+
+    /// Answer the queries received on the given listener, until all the handles are dropped.
+    fn serve(partitions: Weak<RwLock<Partitions<K, V>>>, listener: TcpListener)
+    where
+        K: ExchangeDataKey,
+        V: ExchangeData,
+    {
+        loop {
+            match listener.accept() {
+                Ok((stream, peer)) => {
+                    let partitions = partitions.clone();
+                    std::thread::Builder::new()
+                        .name(format!("query-{peer}"))
+                        .spawn(move || {
+                            if let Err(e) = Self::answer(partitions, stream) {
+                                log::debug!("queryable state: connection with {peer} closed: {e}");
+                            }
+                        })
+                        .expect("failed to spawn the queryable state connection");
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                    if partitions.strong_count() == 0 {
+                        break;
+                    }
+                    sleep(ACCEPT_POLL_INTERVAL);
+                }
+                Err(e) => {
+                    log::warn!("queryable state: failed to accept connection: {e:?}");
+                    sleep(ACCEPT_POLL_INTERVAL);
+                }
+            }
+        }
+        log::debug!("queryable state: endpoint stopped");
+    }
+
+    /// Answer the queries of a client, until it disconnects or the state is dropped.
+    fn answer(
+        partitions: Weak<RwLock<Partitions<K, V>>>,
+        stream: TcpStream,
+    ) -> Result<(), Box<bincode::ErrorKind>>
+    where
+        K: ExchangeDataKey,
+        V: ExchangeData,
+    {
+        stream.set_nonblocking(false)?;
+        stream.set_nodelay(true)?;
+        let mut reader = BufReader::new(&stream);
+        let mut buf = Vec::new();
+        loop {
+            let request: QueryRequest<K> = bincode::deserialize_from(&mut reader)?;
+            let Some(partitions) = partitions.upgrade() else {
+                return Ok(());
+            };
+            let state = QueryableState {
+                partitions,
+                address: None,
+            };
+            let response = match request {
+                QueryRequest::Replicas => {
+                    let partitions = state.partitions.read();
+                    QueryResponse::Replicas {
+                        num_replicas: partitions.num_replicas,
+                        local: partitions.local.keys().copied().collect(),
+                    }
+                }
+                QueryRequest::Get(key) => QueryResponse::Value(state.get(&key)),
+            };
+            buf.clear();
+            bincode::serialize_into(&mut buf, &response)?;
+            (&stream).write_all(&buf)?;
+        }
+    }
+
+    /// Take a snapshot of all the entries currently visible, in an unspecified order.
+    pub fn snapshot(&self) -> Vec<(K, V)> {
+        let partitions = self.partitions.read();
+        partitions
+            .local
+            .values()
+            .flat_map(|p| {
+                p.iter()
+                    .map(|e| (e.key().clone(), e.value().clone()))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// The number of keys currently visible.
+    pub fn len(&self) -> usize {
+        self.partitions.read().local.values().map(|p| p.len()).sum()
+    }
+
+    /// Whether no key is currently visible.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Client for the state exposed by [`KeyedStream::queryable_state_endpoint`], possibly by a job
+/// running on other hosts.
+///
+/// The client connects to the endpoint of every host of the job and learns which replicas each
+/// host is running. A key is looked up on the host running the replica responsible for it, using
+/// the same hash used by [`Stream::group_by`](crate::Stream::group_by), and on the other hosts
+/// only if it is not found there.
+pub struct QueryableStateClient<K, V> {
+    hosts: Vec<(SocketAddr, BufReader<TcpStream>)>,
+    /// The index in `hosts` of the host running each replica, indexed by global id.
+    owners: Vec<usize>,
+    _marker: PhantomData<(K, V)>,
+}
+
+impl<K: ExchangeDataKey, V: ExchangeData> QueryableStateClient<K, V> {
+    /// Connect to the endpoints of all the hosts running the job.
+    pub fn connect<A: ToSocketAddrs>(
+        addresses: impl IntoIterator<Item = A>,
+    ) -> Result<Self, Box<bincode::ErrorKind>> {
+        let mut hosts = Vec::new();
+        for address in addresses {
+            let stream = TcpStream::connect(address)?;
+            stream.set_nodelay(true)?;
+            hosts.push((stream.peer_addr()?, BufReader::new(stream)));
+        }
+        Ok(Self {
+            hosts,
+            owners: Vec::new(),
+            _marker: PhantomData,
+        })
+    }
+
+    /// Send a request to a host and wait for its response.
+    fn request(
+        &mut self,
+        host: usize,
+        request: &QueryRequest<K>,
+    ) -> Result<QueryResponse<V>, Box<bincode::ErrorKind>> {
+        let (_, reader) = &mut self.hosts[host];
+        let buf = bincode::serialize(request)?;
+        reader.get_mut().write_all(&buf)?;
+        bincode::deserialize_from(reader)
+    }
+
+    /// Ask the hosts which replicas they are running. The job may not have started yet, in that
+    /// case the routing is left empty.
+    fn update_routing(&mut self) -> Result<(), Box<bincode::ErrorKind>> {
+        let mut owners = Vec::new();
+        for host in 0..self.hosts.len() {
+            let QueryResponse::Replicas {
+                num_replicas,
+                local,
+            } = self.request(host, &QueryRequest::Replicas)?
+            else {
+                return Err(Box::new(bincode::ErrorKind::Custom(format!(
+                    "unexpected response from {}",
+                    self.hosts[host].0
+                ))));
+            };
+            owners.resize(num_replicas, usize::MAX);
+            for id in local {
+                owners[id as usize] = host;
+            }
+        }
+        if owners.iter().all(|&host| host != usize::MAX) {
+            self.owners = owners;
+        }
+        Ok(())
+    }
+
+    /// Get the current value associated with the key, if any replica has seen it.
+    pub fn get(&mut self, key: &K) -> Result<Option<V>, Box<bincode::ErrorKind>> {
+        if self.owners.is_empty() {
+            self.update_routing()?;
+        }
+        let owner = if self.owners.is_empty() {
+            None
+        } else {
+            Some(self.owners[(group_by_hash(key) % self.owners.len() as u64) as usize])
+        };
+        let request = QueryRequest::Get(key.clone());
+        let order = owner
+            .into_iter()
+            .chain((0..self.hosts.len()).filter(|&host| Some(host) != owner));
+        for host in order.collect::<Vec<_>>() {
+            match self.request(host, &request)? {
+                QueryResponse::Value(Some(value)) => return Ok(Some(value)),
+                QueryResponse::Value(None) => {}
+                QueryResponse::Replicas { .. } => {
+                    return Err(Box::new(bincode::ErrorKind::Custom(format!(
+                        "unexpected response from {}",
+                        self.hosts[host].0
+                    ))))
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct QueryableStateOperator<K, V, Op>
+where
+    K: DataKey + Sync,
+    V: Data + Sync,
+    Op: Operator<Out = (K, V)>,
+{
+    prev: Op,
+    #[derivative(Debug = "ignore")]
+    handle: QueryableState<K, V>,
+    #[derivative(Debug = "ignore")]
+    partition: Option<Partition<K, V>>,
+}
+
+impl<K, V, Op> Clone for QueryableStateOperator<K, V, Op>
+where
+    K: DataKey + Sync,
+    V: Data + Sync,
+    Op: Operator<Out = (K, V)>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            prev: self.prev.clone(),
+            handle: self.handle.clone(),
+            partition: None,
+        }
+    }
+}
+
+impl<K, V, Op> Display for QueryableStateOperator<K, V, Op>
+where
+    K: DataKey + Sync,
+    V: Data + Sync,
+    Op: Operator<Out = (K, V)>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} -> QueryableState<{}>",
+            self.prev,
+            std::any::type_name::<(K, V)>()
+        )
+    }
+}
+
+impl<K, V, Op> Operator for QueryableStateOperator<K, V, Op>
+where
+    K: DataKey + Sync,
+    V: Data + Sync,
+    Op: Operator<Out = (K, V)>,
+{
+    type Out = (K, V);
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        self.prev.setup(metadata);
+        self.partition = Some(
+            self.handle
+                .register(metadata.global_id, metadata.replicas.len()),
+        );
+    }
+
+    #[inline]
+    fn next(&mut self) -> StreamElement<(K, V)> {
+        let el = self.prev.next();
+        if let StreamElement::Item((k, v)) | StreamElement::Timestamped((k, v), _) = &el {
+            let partition = self.partition.as_ref().expect("setup was not called");
+            partition.insert(k.clone(), v.clone());
+        }
+        el
+    }
+
+    fn structure(&self) -> BlockStructure {
+        self.prev
+            .structure()
+            .add_operator(OperatorStructure::new::<(K, V), _>("QueryableState"))
+    }
+}
+
+impl<K, V, Op> KeyedStream<Op>
+where
+    K: DataKey + Sync,
+    V: Data + Sync,
+    Op: Operator<Out = (K, V)> + 'static,
+{
+    /// Expose the latest value of each key of this stream, so that it can be read while the
+    /// computation is running.
+    ///
+    /// The elements are forwarded unchanged, while the last value seen for each key is stored in
+    /// the partition of the replica that processed it. The returned [`QueryableState`] can be
+    /// used to look up the keys from outside of the job.
+    ///
+    /// This is typically used after an operator that emits the updated state for each incoming
+    /// element (e.g. [`KeyedStream::rich_map`]).
+    ///
+    /// **Note**: the state can be queried only from the process running the job, so this panics
+    /// with a remote configuration. Use [`KeyedStream::queryable_state_endpoint`] for querying it
+    /// from other processes.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let s = env.stream_iter(0..10).group_by(|&n| n % 2);
+    /// let (s, state) = s
+    ///     .rich_map({
+    ///         let mut count = 0;
+    ///         move |_| {
+    ///             count += 1;
+    ///             count
+    ///         }
+    ///     })
+    ///     .queryable_state();
+    /// s.for_each(std::mem::drop);
+    ///
+    /// env.execute_blocking();
+    ///
+    /// assert_eq!(state.get(&0), Some(5));
+    /// assert_eq!(state.get(&1), Some(5));
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn queryable_state(
+        self,
+    ) -> (
+        KeyedStream<QueryableStateOperator<K, V, Op>>,
+        QueryableState<K, V>,
+    ) {
+        assert!(
+            !matches!(self.0.ctx.lock().config, RuntimeConfig::Remote(_)),
+            "queryable_state is local-only, use queryable_state_endpoint with a remote configuration"
+        );
+        let handle = QueryableState::new();
+        let h = handle.clone();
+        let stream = self.add_operator(|prev| QueryableStateOperator {
+            prev,
+            handle: h,
+            partition: None,
+        });
+        (stream, handle)
+    }
+}
+
+impl<K, V, Op> KeyedStream<Op>
+where
+    K: ExchangeDataKey + Sync,
+    V: ExchangeData + Sync,
+    Op: Operator<Out = (K, V)> + 'static,
+{
+    /// Expose the latest value of each key of this stream like [`KeyedStream::queryable_state`],
+    /// and answer the queries of other processes on the given address.
+    ///
+    /// Every host of the job listens on the address for the queries of a
+    /// [`QueryableStateClient`], answering with the state of the replicas it runs. The endpoint
+    /// stays open until the returned handle and the job have been dropped.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::QueryableStateClient;
+    /// # let mut env = StreamContext::new_local();
+    /// let s = env.stream_iter(0..10).group_by(|&n| n % 2);
+    /// let (s, state) = s.reduce(|a, b| *a += b).queryable_state_endpoint("127.0.0.1:0");
+    /// s.for_each(std::mem::drop);
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let mut client = QueryableStateClient::connect(state.local_addr()).unwrap();
+    /// assert_eq!(client.get(&0).unwrap(), Some(0 + 2 + 4 + 6 + 8));
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn queryable_state_endpoint(
+        self,
+        address: impl ToSocketAddrs,
+    ) -> (
+        KeyedStream<QueryableStateOperator<K, V, Op>>,
+        QueryableState<K, V>,
+    ) {
+        let listener = TcpListener::bind(address)
+            .unwrap_or_else(|e| panic!("queryable state: failed to bind the endpoint: {e:?}"));
+        listener
+            .set_nonblocking(true)
+            .expect("cannot set the listener to non blocking");
+        log::debug!("queryable state listening on {:?}", listener.local_addr());
+
+        let mut handle = QueryableState::new();
+        handle.address = listener.local_addr().ok();
+        let partitions = Arc::downgrade(&handle.partitions);
+        std::thread::Builder::new()
+            .name("queryable-state".into())
+            .spawn(move || QueryableState::serve(partitions, listener))
+            .expect("failed to spawn the queryable state endpoint");
+
+        let h = handle.clone();
+        let stream = self.add_operator(|prev| QueryableStateOperator {
+            prev,
+            handle: h,
+            partition: None,
+        });
+        (stream, handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::RuntimeConfig;
+    use crate::environment::StreamContext;
+    use crate::operator::source::IteratorSource;
+
+    #[test]
+    fn queryable_state_last_value() {
+        let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        let source = IteratorSource::new(0..100u32);
+        let (s, state) = env
+            .stream(source)
+            .group_by(|n| n % 10)
+            .rich_map({
+                let mut count = 0u32;
+                move |_| {
+                    count += 1;
+                    count
+                }
+            })
+            .queryable_state();
+        s.for_each(std::mem::drop);
+        assert!(state.get(&3).is_none());
+        env.execute_blocking();
+
+        for k in 0..10 {
+            assert_eq!(state.get(&k), Some(10));
+        }
+        assert_eq!(state.len(), 10);
+        let mut snapshot = state.snapshot();
+        snapshot.sort_unstable();
+        assert_eq!(snapshot, (0..10).map(|k| (k, 10)).collect::<Vec<_>>());
+    }
+}
@@ -2,7 +2,7 @@ use std::cmp::Ordering;
 use std::collections::VecDeque;
 use std::fmt::Display;
 
-use crate::block::{BlockStructure, OperatorStructure};
+use crate::block::{BlockStructure, OperatorStructure, StateEntries};
 use crate::operator::{Operator, StreamElement, Timestamp};
 use crate::scheduler::ExecutionMetadata;
 
@@ -146,9 +146,10 @@ where
     }
 
     fn structure(&self) -> BlockStructure {
-        self.prev
-            .structure()
-            .add_operator(OperatorStructure::new::<Op::Out, _>("Reorder"))
+        self.prev.structure().add_operator(
+            OperatorStructure::new::<Op::Out, _>("Reorder")
+                .with_state::<Op::Out>(StateEntries::PerElement),
+        )
     }
 }
 
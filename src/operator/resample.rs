@@ -0,0 +1,372 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt::Display;
+
+use crate::block::{BlockStructure, OperatorStructure, TimestampUsage};
+use crate::operator::{Data, DataKey, Operator, StreamElement, Timestamp};
+use crate::scheduler::ExecutionMetadata;
+use crate::stream::KeyedItem;
+use crate::KeyedStream;
+
+/// How the buckets without events are filled by [`ResampledStream::agg_fill`].
+///
+/// Only the empty buckets between two non-empty buckets of the same key are filled: a key is not
+/// emitted before its first event or after its last one.
+#[derive(Clone, Debug)]
+pub enum GapFill<A> {
+    /// The empty buckets are not emitted.
+    Skip,
+    /// The empty buckets get the value of the previous bucket.
+    Forward,
+    /// The empty buckets get a constant value.
+    Value(A),
+    /// The empty buckets get a value interpolated between the previous and the next bucket.
+    ///
+    /// The function receives the two values and the position of the empty bucket between them, in
+    /// the range `(0, 1)`.
+    Interpolate(fn(&A, &A, f64) -> A),
+}
+
+impl<A: Default> GapFill<A> {
+    /// Fill the empty buckets with the default value of the aggregate, e.g. zero for numbers.
+    pub fn zero() -> Self {
+        GapFill::Value(A::default())
+    }
+}
+
+impl GapFill<f64> {
+    /// Fill the empty buckets with the linear interpolation of the previous and the next bucket.
+    pub fn linear() -> Self {
+        GapFill::Interpolate(|a, b, t| a + (b - a) * t)
+    }
+}
+
+/// The bucket containing the timestamp `ts`, identified by its start.
+fn bucket_of(ts: Timestamp, interval: Timestamp) -> Timestamp {
+    ts.div_euclid(interval) * interval
+}
+
+#[derive(Derivative)]
+#[derivative(Clone, Debug)]
+pub struct Resample<K, I, A, F, Op>
+where
+    K: DataKey,
+    I: Data,
+    A: Data,
+    F: Fn(&mut A, I) + Send + Clone + 'static,
+    Op: Operator<Out = (K, I)>,
+{
+    prev: Op,
+    interval: Timestamp,
+    init: A,
+    #[derivative(Debug = "ignore")]
+    fold: F,
+    #[derivative(Debug = "ignore")]
+    fill: GapFill<A>,
+    /// For each key, the buckets not closed yet, indexed by their start.
+    #[derivative(Debug = "ignore")]
+    open: HashMap<K, BTreeMap<Timestamp, A>, crate::block::GroupHasherBuilder>,
+    /// For each key, the start and the value of the last emitted bucket.
+    #[derivative(Debug = "ignore")]
+    last: HashMap<K, (Timestamp, A), crate::block::GroupHasherBuilder>,
+    #[derivative(Debug = "ignore")]
+    ready: VecDeque<StreamElement<(K, (Timestamp, A))>>,
+    /// The last watermark received.
+    watermark: Option<Timestamp>,
+}
+
+impl<K, I, A, F, Op> Display for Resample<K, I, A, F, Op>
+where
+    K: DataKey,
+    I: Data,
+    A: Data,
+    F: Fn(&mut A, I) + Send + Clone + 'static,
+    Op: Operator<Out = (K, I)>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} -> Resample<{} -> {}, {}>",
+            self.prev,
+            std::any::type_name::<I>(),
+            std::any::type_name::<A>(),
+            self.interval
+        )
+    }
+}
+
+impl<K, I, A, F, Op> Resample<K, I, A, F, Op>
+where
+    K: DataKey,
+    I: Data,
+    A: Data,
+    F: Fn(&mut A, I) + Send + Clone + 'static,
+    Op: Operator<Out = (K, I)>,
+{
+    fn new(prev: Op, interval: Timestamp, init: A, fold: F, fill: GapFill<A>) -> Self {
+        assert!(interval > 0, "the interval of resample must be positive");
+        Self {
+            prev,
+            interval,
+            init,
+            fold,
+            fill,
+            open: Default::default(),
+            last: Default::default(),
+            ready: Default::default(),
+            watermark: None,
+        }
+    }
+
+    /// Emit the buckets whose last instant is not after `watermark`, or all of them if it's
+    /// `None`, preceded by the filled empty buckets.
+    fn close(&mut self, watermark: Option<Timestamp>) {
+        // the emitted elements must come after the previous watermark
+        let min_ts = self.watermark.map(|w| w + 1).unwrap_or(Timestamp::MIN);
+        let interval = self.interval;
+        let ready = &mut self.ready;
+        let emit = |key: &K, start: Timestamp, value: A, ready: &mut VecDeque<_>| {
+            let ts = (start + interval - 1).max(min_ts);
+            ready.push_back(StreamElement::Timestamped(
+                (key.clone(), (start, value)),
+                ts,
+            ));
+        };
+
+        for (key, buckets) in self.open.iter_mut() {
+            let closed = match watermark {
+                // the buckets starting after `watermark - interval` may still receive events
+                Some(w) => {
+                    let open = buckets.split_off(&w.saturating_sub(interval).saturating_add(2));
+                    std::mem::replace(buckets, open)
+                }
+                None => std::mem::take(buckets),
+            };
+
+            for (start, value) in closed {
+                if let Some((last_start, last_value)) = self.last.get(key) {
+                    let gaps = (last_start + interval..start).step_by(interval as usize);
+                    let steps = ((start - last_start) / interval) as f64;
+                    for (i, gap) in gaps.enumerate() {
+                        let filled = match &self.fill {
+                            GapFill::Skip => break,
+                            GapFill::Forward => last_value.clone(),
+                            GapFill::Value(v) => v.clone(),
+                            GapFill::Interpolate(f) => {
+                                f(last_value, &value, (i + 1) as f64 / steps)
+                            }
+                        };
+                        emit(key, gap, filled, ready);
+                    }
+                }
+                emit(key, start, value.clone(), ready);
+                self.last.insert(key.clone(), (start, value));
+            }
+        }
+        self.open.retain(|_, buckets| !buckets.is_empty());
+    }
+}
+
+impl<K, I, A, F, Op> Operator for Resample<K, I, A, F, Op>
+where
+    K: DataKey,
+    I: Data,
+    A: Data,
+    F: Fn(&mut A, I) + Send + Clone + 'static,
+    Op: Operator<Out = (K, I)>,
+{
+    type Out = (K, (Timestamp, A));
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        self.prev.setup(metadata);
+    }
+
+    fn next(&mut self) -> StreamElement<Self::Out> {
+        while self.ready.is_empty() {
+            match self.prev.next() {
+                StreamElement::Timestamped((key, item), ts) => {
+                    if self.watermark.is_some_and(|w| ts <= w) {
+                        log::warn!("Resample: dropping late element with timestamp {ts}");
+                        continue;
+                    }
+                    let bucket = bucket_of(ts, self.interval);
+                    let value = self
+                        .open
+                        .entry(key)
+                        .or_default()
+                        .entry(bucket)
+                        .or_insert_with(|| self.init.clone());
+                    (self.fold)(value, item);
+                }
+                StreamElement::Watermark(ts) => {
+                    self.close(Some(ts));
+                    self.watermark = Some(ts);
+                    self.ready.push_back(StreamElement::Watermark(ts));
+                }
+                StreamElement::FlushAndRestart => {
+                    self.close(None);
+                    self.last.clear();
+                    self.watermark = None;
+                    self.ready.push_back(StreamElement::FlushAndRestart);
+                }
+                StreamElement::Item(_) => panic!("Resample only supports timestamped streams"),
+                StreamElement::FlushBatch => return StreamElement::FlushBatch,
+                StreamElement::Terminate => return StreamElement::Terminate,
+            }
+        }
+        self.ready.pop_front().unwrap()
+    }
+
+    fn structure(&self) -> BlockStructure {
+        let mut operator = OperatorStructure::new::<Self::Out, _>("Resample");
+        operator.timestamps = TimestampUsage::Require;
+        self.prev.structure().add_operator(operator)
+    }
+}
+
+/// A keyed stream divided in buckets of fixed duration, obtained with [`KeyedStream::resample`].
+pub struct ResampledStream<Op>
+where
+    Op: Operator,
+    Op::Out: KeyedItem,
+{
+    inner: KeyedStream<Op>,
+    interval: Timestamp,
+}
+
+impl<K, I, Op> ResampledStream<Op>
+where
+    K: DataKey,
+    I: Data,
+    Op: Operator<Out = (K, I)> + 'static,
+{
+    /// Aggregate the events of each key inside each bucket, starting from `init` and folding them
+    /// with `fold`. The empty buckets are not emitted.
+    ///
+    /// See [`ResampledStream::agg_fill`].
+    pub fn agg<A, F>(
+        self,
+        init: A,
+        fold: F,
+    ) -> KeyedStream<impl Operator<Out = (K, (Timestamp, A))>>
+    where
+        A: Data,
+        F: Fn(&mut A, I) + Send + Clone + 'static,
+    {
+        self.agg_fill(init, fold, GapFill::Skip)
+    }
+
+    /// Aggregate the events of each key inside each bucket, starting from `init` and folding them
+    /// with `fold`, and fill the empty buckets between the events of a key according to `fill`.
+    ///
+    /// For each key and bucket the stream contains the start of the bucket and the aggregated
+    /// value. A bucket is emitted when the watermark passes its end, with the timestamp of its last
+    /// instant; the filled buckets are emitted together with the next non-empty bucket of the key.
+    /// The events arriving after a watermark that closed their bucket are dropped.
+    pub fn agg_fill<A, F>(
+        self,
+        init: A,
+        fold: F,
+        fill: GapFill<A>,
+    ) -> KeyedStream<impl Operator<Out = (K, (Timestamp, A))>>
+    where
+        A: Data,
+        F: Fn(&mut A, I) + Send + Clone + 'static,
+    {
+        let interval = self.interval;
+        self.inner
+            .add_operator(|prev| Resample::new(prev, interval, init, fold, fill))
+    }
+}
+
+impl<K, I, Op> KeyedStream<Op>
+where
+    K: DataKey,
+    I: Data,
+    Op: Operator<Out = (K, I)> + 'static,
+{
+    /// Divide the timeline in buckets of duration `interval` for aggregating the events of each
+    /// key, producing a regular time series from irregular events.
+    ///
+    /// The stream must have timestamps and watermarks: the buckets are aligned to multiples of
+    /// `interval` and are closed by the watermarks.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::GapFill;
+    /// # let mut env = StreamContext::new(RuntimeConfig::local(1).unwrap());
+    /// let s = env
+    ///     .stream_iter(vec![(0, 1.0), (3, 2.0), (12, 5.0)].into_iter())
+    ///     .add_timestamps(|&(ts, _)| ts, |_, _| None)
+    ///     .group_by(|_| 's')
+    ///     .map(|(_, (_, value))| value);
+    /// let res = s
+    ///     .resample(5)
+    ///     .agg_fill(0.0, |sum, x| *sum += x, GapFill::linear())
+    ///     .collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let res = res.get().unwrap();
+    /// assert_eq!(res, vec![('s', (0, 3.0)), ('s', (5, 4.0)), ('s', (10, 5.0))]);
+    /// ```
+    pub fn resample(self, interval: Timestamp) -> ResampledStream<Op> {
+        ResampledStream {
+            inner: self,
+            interval,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::operator::resample::{GapFill, Resample};
+    use crate::operator::{Operator, StreamElement};
+    use crate::test::FakeOperator;
+
+    #[test]
+    fn resample_gap_fill() {
+        let fills = [
+            (GapFill::Skip, vec![]),
+            (GapFill::Forward, vec![1, 1]),
+            (GapFill::Value(0), vec![0, 0]),
+            (
+                GapFill::Interpolate(|a, b, t| a + ((b - a) as f64 * t) as i32),
+                vec![3, 5],
+            ),
+        ];
+        for (fill, filled) in fills {
+            let mut fake = FakeOperator::empty();
+            fake.push(StreamElement::Timestamped(('a', 1), 1));
+            fake.push(StreamElement::Timestamped(('b', 9), 3));
+            fake.push(StreamElement::Watermark(12));
+            fake.push(StreamElement::Timestamped(('a', 7), 31));
+            // late element, its bucket has been closed
+            fake.push(StreamElement::Timestamped(('a', 100), 8));
+            fake.push(StreamElement::Watermark(40));
+            fake.push(StreamElement::FlushAndRestart);
+            fake.push(StreamElement::Terminate);
+
+            let mut resample = Resample::new(fake, 10, 0, |acc: &mut i32, x| *acc += x, fill);
+            let mut res = Vec::new();
+            loop {
+                match resample.next() {
+                    StreamElement::Timestamped(item, ts) => res.push((item, ts)),
+                    StreamElement::Terminate => break,
+                    _ => {}
+                }
+            }
+
+            let mut expected = vec![(('a', (0, 1)), 9), (('b', (0, 9)), 9)];
+            for (i, value) in filled.into_iter().enumerate() {
+                let start = 10 * (i as i64 + 1);
+                expected.push((('a', (start, value)), start + 9));
+            }
+            expected.push((('a', (30, 7)), 39));
+            res.sort_unstable();
+            expected.sort_unstable();
+            assert_eq!(res, expected);
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use std::fmt::Display;
 use std::marker::PhantomData;
 
-use crate::block::{BlockStructure, OperatorStructure};
+use crate::block::{BlockStructure, OperatorStructure, TimestampUsage};
 use crate::operator::{Operator, StreamElement};
 use crate::scheduler::ExecutionMetadata;
 
@@ -92,8 +92,9 @@ where
     }
 
     fn structure(&self) -> BlockStructure {
-        self.prev
-            .structure()
-            .add_operator(OperatorStructure::new::<O, _>("RichMapCustom"))
+        let mut operator = OperatorStructure::new::<O, _>("RichMapCustom");
+        // the generator has access to the raw elements and may assign timestamps
+        operator.timestamps = TimestampUsage::Assign;
+        self.prev.structure().add_operator(operator)
     }
 }
@@ -0,0 +1,440 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use nanorand::{tls_rng, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::operator::{Data, ExchangeData, ExchangeDataKey, Operator};
+use crate::stream::{KeyedStream, Stream};
+
+/// An item kept in a [`Reservoir`] with its random priority.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Entry<T> {
+    priority: f64,
+    item: T,
+}
+
+impl<T> PartialEq for Entry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for Entry<T> {}
+
+impl<T> PartialOrd for Entry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Entry<T> {
+    /// Reversed, so that the top of the heap is the entry with the lowest priority.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.total_cmp(&self.priority)
+    }
+}
+
+/// A weighted random sample of at most `n` items, without replacement.
+///
+/// Each item gets a random priority `ln(u) / weight`, with `u` uniform in `(0, 1]`, and the
+/// reservoir keeps the `n` items with the highest priority (the A-Res algorithm by Efraimidis and
+/// Spirakis). Since the priorities don't depend on the other items, two reservoirs built from
+/// disjoint parts of a stream can be merged into the reservoir of the whole stream: this is how
+/// the replicas of [`Stream::sample_weighted`] combine their samples.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Reservoir<T> {
+    capacity: usize,
+    entries: BinaryHeap<Entry<T>>,
+}
+
+impl<T> Reservoir<T> {
+    /// Create an empty reservoir keeping at most `capacity` items.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: BinaryHeap::with_capacity(capacity),
+        }
+    }
+
+    /// Offer an item to the reservoir with uniform weight.
+    pub fn insert(&mut self, item: T) {
+        self.insert_weighted(item, 1.0);
+    }
+
+    /// Offer an item to the reservoir: the probability of keeping it is proportional to `weight`.
+    ///
+    /// Items with a weight that is not positive and finite are never sampled.
+    pub fn insert_weighted(&mut self, item: T, weight: f64) {
+        if !(weight > 0.0 && weight.is_finite()) {
+            return;
+        }
+        let u = 1.0 - tls_rng().generate::<f64>();
+        self.push(Entry {
+            priority: u.ln() / weight,
+            item,
+        });
+    }
+
+    /// Merge the sample of another part of the stream into this reservoir.
+    pub fn merge(&mut self, other: Reservoir<T>) {
+        for entry in other.entries {
+            self.push(entry);
+        }
+    }
+
+    fn push(&mut self, entry: Entry<T>) {
+        if self.entries.len() < self.capacity {
+            self.entries.push(entry);
+        } else if let Some(mut lowest) = self.entries.peek_mut() {
+            if entry.priority > lowest.priority {
+                *lowest = entry;
+            }
+        }
+    }
+
+    /// The number of items in the sample.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the sample is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The sampled items, in no particular order.
+    pub fn into_items(self) -> Vec<T> {
+        self.entries.into_iter().map(|e| e.item).collect()
+    }
+}
+
+impl<Op> Stream<Op>
+where
+    Op: Operator + 'static,
+{
+    /// Keep each item of the stream independently with probability `fraction` (Bernoulli
+    /// sampling).
+    ///
+    /// The size of the sample is not fixed: on average it's `fraction` times the size of the
+    /// stream. Use [`Stream::reservoir_sample`] for a sample of fixed size. The sampling is done
+    /// by each replica on its items, so this does not split the block.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let s = env.stream_iter(0..10_000u32);
+    /// let res = s.sample(0.1).collect_count();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let count = res.get().unwrap();
+    /// assert!(count > 500 && count < 1500);
+    /// ```
+    pub fn sample(self, fraction: f64) -> Stream<impl Operator<Out = Op::Out>> {
+        assert!(
+            (0.0..=1.0).contains(&fraction),
+            "the fraction of sample must be between 0 and 1"
+        );
+        self.filter(move |_| tls_rng().generate::<f64>() < fraction)
+    }
+}
+
+impl<Op> Stream<Op>
+where
+    Op: Operator + 'static,
+    Op::Out: ExchangeData,
+{
+    /// Sample `n` items of the stream uniformly, without replacement.
+    ///
+    /// Each replica builds a local [`Reservoir`] of its items, then the local reservoirs are
+    /// merged by a single replica. The merge keeps the `n` items with the highest random priority
+    /// over all the reservoirs, so it takes into account how many items each replica has seen:
+    /// every item of the stream has the same probability of being in the sample, even if the
+    /// replicas receive a different number of items. If the stream has less than `n` items, all
+    /// of them are emitted.
+    ///
+    /// **Note**: this operator will retain the sample of the stream and emit it only when the
+    /// stream ends. Therefore this is not properly _streaming_.
+    ///
+    /// **Note**: this operator will split the current block.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let s = env.stream_iter(0..100u32);
+    /// let res = s.reservoir_sample(10).collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// assert_eq!(res.get().unwrap().len(), 10);
+    /// ```
+    pub fn reservoir_sample(self, n: usize) -> Stream<impl Operator<Out = Op::Out>> {
+        self.fold_assoc(
+            Reservoir::new(n),
+            |reservoir, item| reservoir.insert(item),
+            |reservoir, other| reservoir.merge(other),
+        )
+        .flat_map(Reservoir::into_items)
+    }
+
+    /// Sample `n` items of the stream without replacement, with a probability proportional to the
+    /// weight returned by `weight_fn`. Items with a weight that is not positive and finite are
+    /// never sampled.
+    ///
+    /// Each replica builds a local [`Reservoir`], and only the local samples are sent to the single
+    /// replica that merges them, so the stream is not shuffled.
+    ///
+    /// **Note**: this operator will retain the sample of the stream and emit it only when the
+    /// stream ends. Therefore this is not properly _streaming_.
+    ///
+    /// **Note**: this operator will split the current block.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let s = env.stream_iter(0..100u32);
+    /// // only the multiples of 10 can be sampled
+    /// let res = s
+    ///     .sample_weighted(|&n| if n % 10 == 0 { 1.0 } else { 0.0 }, 5)
+    ///     .collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let res = res.get().unwrap();
+    /// assert_eq!(res.len(), 5);
+    /// assert!(res.iter().all(|n| n % 10 == 0));
+    /// ```
+    pub fn sample_weighted<F>(self, weight_fn: F, n: usize) -> Stream<impl Operator<Out = Op::Out>>
+    where
+        F: Fn(&Op::Out) -> f64 + Send + Clone + 'static,
+    {
+        self.fold_assoc(
+            Reservoir::new(n),
+            move |reservoir, item| {
+                let weight = weight_fn(&item);
+                reservoir.insert_weighted(item, weight);
+            },
+            |reservoir, other| reservoir.merge(other),
+        )
+        .flat_map(Reservoir::into_items)
+    }
+
+    /// Partition the stream using the `keyer` function and sample `n_per_key` items of each
+    /// partition uniformly, without replacement.
+    ///
+    /// Like [`Stream::group_by_fold`], each replica first samples its items locally, then only the
+    /// local samples of each key are shuffled and merged. Use [`KeyedStream::sample_stratified`]
+    /// if the stream is already partitioned.
+    ///
+    /// **Note**: this operator will retain the sample of each key and emit it only when the
+    /// stream ends. Therefore this is not properly _streaming_.
+    ///
+    /// **Note**: this operator will split the current block.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # let mut env = StreamContext::new_local();
+    /// // the key `true` is much rarer than `false`
+    /// let s = env.stream_iter(0..1000u32);
+    /// let res = s.group_by_sample(|&n| n % 100 == 0, 5).collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let res = res.get().unwrap();
+    /// assert_eq!(res.iter().filter(|(k, _)| *k).count(), 5);
+    /// assert_eq!(res.iter().filter(|(k, _)| !*k).count(), 5);
+    /// ```
+    pub fn group_by_sample<K, Fk>(
+        self,
+        keyer: Fk,
+        n_per_key: usize,
+    ) -> KeyedStream<impl Operator<Out = (K, Op::Out)>>
+    where
+        Fk: Fn(&Op::Out) -> K + Send + Clone + 'static,
+        K: ExchangeDataKey,
+    {
+        self.group_by_fold(
+            keyer,
+            Reservoir::new(n_per_key),
+            |reservoir, item| reservoir.insert(item),
+            |reservoir, other| reservoir.merge(other),
+        )
+        .flat_map(|(_, reservoir)| reservoir.into_items())
+    }
+}
+
+impl<K, V, Op> KeyedStream<Op>
+where
+    K: ExchangeDataKey,
+    V: Data,
+    Op: Operator<Out = (K, V)> + 'static,
+{
+    /// Sample `n_per_key` items of each key uniformly, without replacement.
+    ///
+    /// This is useful for building a balanced dataset from a skewed stream: the rare keys keep all
+    /// their items, while the frequent ones are downsampled.
+    ///
+    /// **Note**: this operator will retain the sample of each key and emit it only when the
+    /// stream ends. Therefore this is not properly _streaming_.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let s = env.stream_iter(0..100u32).group_by(|&n| n % 50 == 0);
+    /// let res = s.sample_stratified(3).collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let res = res.get().unwrap();
+    /// // the key `true` has only the items 0 and 50
+    /// assert_eq!(res.iter().filter(|(k, _)| *k).count(), 2);
+    /// assert_eq!(res.iter().filter(|(k, _)| !*k).count(), 3);
+    /// ```
+    pub fn sample_stratified(self, n_per_key: usize) -> KeyedStream<impl Operator<Out = (K, V)>> {
+        self.fold(Reservoir::new(n_per_key), |reservoir, item| {
+            reservoir.insert(item)
+        })
+        .flat_map(|(_, reservoir)| reservoir.into_items())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Reservoir;
+    use crate::config::RuntimeConfig;
+    use crate::environment::StreamContext;
+    use crate::operator::source::IteratorSource;
+
+    #[test]
+    fn reservoir_merge() {
+        let mut a = Reservoir::new(4);
+        let mut b = Reservoir::new(4);
+        for n in 0..100u32 {
+            a.insert_weighted(n, if n == 7 { 1e9 } else { 1.0 });
+            b.insert_weighted(n + 100, 0.0);
+        }
+        b.insert(200);
+        assert_eq!(a.len(), 4);
+        assert_eq!(b.len(), 1);
+
+        a.merge(b);
+        let items = a.into_items();
+        assert_eq!(items.len(), 4);
+        assert!(items.contains(&7));
+        assert!(items.iter().all(|&n| n < 100 || n == 200));
+    }
+
+    #[test]
+    fn sample_weighted_stream() {
+        let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        let source = IteratorSource::new(0..1000u32);
+        let res = env
+            .stream(source)
+            .sample_weighted(|&n| (n % 2) as f64, 100)
+            .collect_vec();
+        let few = env
+            .stream(IteratorSource::new(0..10u32))
+            .sample_weighted(|_| 1.0, 100)
+            .collect_vec();
+        env.execute_blocking();
+
+        let mut res = res.get().unwrap();
+        res.sort_unstable();
+        res.dedup();
+        assert_eq!(res.len(), 100);
+        assert!(res.iter().all(|n| n % 2 == 1));
+        let mut few = few.get().unwrap();
+        few.sort_unstable();
+        assert_eq!(few, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn sample_bernoulli() {
+        let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        let half = env
+            .stream_par_iter(0..10_000u32)
+            .sample(0.5)
+            .collect_count();
+        let none = env.stream_par_iter(0..100u32).sample(0.0).collect_count();
+        let all = env.stream_par_iter(0..100u32).sample(1.0).collect_count();
+        env.execute_blocking();
+
+        let half = half.get().unwrap();
+        assert!((4500..5500).contains(&half), "{half} items sampled");
+        assert_eq!(none.get().unwrap(), 0);
+        assert_eq!(all.get().unwrap(), 100);
+    }
+
+    #[test]
+    fn reservoir_sample_uniform() {
+        let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        // the first replica gets 9000 items, the others 1000 in total
+        let res = env
+            .stream_par_iter(|id, _| match id as u32 {
+                0 => 0..9000,
+                id => 9000 + (id - 1) * 334..9000 + id * 334,
+            })
+            .reservoir_sample(500)
+            .collect_vec();
+        env.execute_blocking();
+
+        let mut res = res.get().unwrap();
+        res.sort_unstable();
+        res.dedup();
+        assert_eq!(res.len(), 500);
+        // about 10% of the sample comes from the small replicas
+        let small = res.iter().filter(|&&n| n >= 9000).count();
+        assert!(
+            (20..80).contains(&small),
+            "{small} items from the small replicas"
+        );
+    }
+
+    #[test]
+    fn sample_stratified_keys() {
+        let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        let keyed = env
+            .stream(IteratorSource::new(0..1000u32))
+            .group_by(|n| n % 3)
+            .sample_stratified(10)
+            .collect_vec();
+        let local = env
+            .stream(IteratorSource::new(0..1000u32))
+            .group_by_sample(|&n| n < 4, 10)
+            .collect_vec();
+        env.execute_blocking();
+
+        let mut keyed = keyed.get().unwrap();
+        keyed.sort_unstable();
+        keyed.dedup();
+        assert_eq!(keyed.len(), 30);
+        for k in 0..3 {
+            assert_eq!(keyed.iter().filter(|(key, _)| *key == k).count(), 10);
+        }
+        assert!(keyed.iter().all(|(k, n)| *k == n % 3));
+
+        let mut local = local.get().unwrap();
+        local.sort_unstable();
+        local.dedup();
+        // the key `true` has only 4 items
+        assert_eq!(local.iter().filter(|(k, _)| *k).count(), 4);
+        assert_eq!(local.iter().filter(|(k, _)| !*k).count(), 10);
+        assert!(local.iter().all(|(k, n)| *k == (*n < 4)));
+    }
+}
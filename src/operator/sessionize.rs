@@ -0,0 +1,314 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::block::{BlockStructure, OperatorStructure, StateEntries, TimestampUsage};
+use crate::operator::{Data, DataKey, Operator, ReportState, StateProbe, StreamElement, Timestamp};
+use crate::scheduler::ExecutionMetadata;
+use crate::KeyedStream;
+
+/// A session of activity of a key, produced by [`KeyedStream::sessionize`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Session {
+    /// The timestamp of the first event of the session.
+    pub start: Timestamp,
+    /// The timestamp of the last event of the session.
+    pub end: Timestamp,
+    /// The time between the first and the last event, `end - start`.
+    pub duration: Timestamp,
+    /// The number of events in the session.
+    pub count: usize,
+}
+
+impl Session {
+    fn new(ts: Timestamp) -> Self {
+        Self {
+            start: ts,
+            end: ts,
+            duration: 0,
+            count: 1,
+        }
+    }
+
+    /// Whether an event with timestamp `ts` belongs to this session.
+    fn touches(&self, ts: Timestamp, gap: Timestamp) -> bool {
+        self.start.saturating_sub(gap) <= ts && ts <= self.end.saturating_add(gap)
+    }
+
+    fn merge(&mut self, other: &Session) {
+        self.start = self.start.min(other.start);
+        self.end = self.end.max(other.end);
+        self.duration = self.end - self.start;
+        self.count += other.count;
+    }
+}
+
+#[derive(Derivative)]
+#[derivative(Clone, Debug)]
+pub struct Sessionize<K, I, Op>
+where
+    K: DataKey,
+    I: Data,
+    Op: Operator<Out = (K, I)>,
+{
+    prev: Op,
+    gap: Timestamp,
+    /// For each key, the sessions that can still be extended by a new event.
+    #[derivative(Debug = "ignore")]
+    open: HashMap<K, Vec<Session>, crate::block::GroupHasherBuilder>,
+    #[derivative(Debug = "ignore")]
+    ready: VecDeque<StreamElement<(K, Session)>>,
+    /// The last watermark received.
+    watermark: Option<Timestamp>,
+    /// Where the open sessions are published, if the state is being reported.
+    probe: Option<StateProbe<K, Vec<Session>>>,
+}
+
+impl<K, I, Op> Display for Sessionize<K, I, Op>
+where
+    K: DataKey,
+    I: Data,
+    Op: Operator<Out = (K, I)>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} -> Sessionize<{}, {}>",
+            self.prev,
+            std::any::type_name::<I>(),
+            self.gap
+        )
+    }
+}
+
+impl<K, I, Op> Sessionize<K, I, Op>
+where
+    K: DataKey,
+    I: Data,
+    Op: Operator<Out = (K, I)>,
+{
+    fn new(prev: Op, gap: Timestamp) -> Self {
+        assert!(gap >= 0, "the gap of sessionize must not be negative");
+        Self {
+            prev,
+            gap,
+            open: Default::default(),
+            ready: Default::default(),
+            watermark: None,
+            probe: None,
+        }
+    }
+
+    /// Publish the sessions open for each key, if the state is being reported.
+    fn publish_state(&self) {
+        if let Some(probe) = &self.probe {
+            probe.publish(
+                self.open
+                    .iter()
+                    .map(|(key, sessions)| (key, sessions, sessions.len())),
+            );
+        }
+    }
+
+    /// Add an event to the sessions of `key`, merging all the sessions it connects.
+    fn insert(&mut self, key: K, ts: Timestamp) {
+        let gap = self.gap;
+        let sessions = self.open.entry(key).or_default();
+        let mut merged = Session::new(ts);
+        sessions.retain(|session| {
+            let touches = session.touches(ts, gap);
+            if touches {
+                merged.merge(session);
+            }
+            !touches
+        });
+        sessions.push(merged);
+    }
+
+    /// Emit the sessions that no event after `watermark` can extend, or all of them if it's
+    /// `None`.
+    ///
+    /// A session is emitted with the timestamp of its end plus the gap, the instant at which it's
+    /// known to be over.
+    fn close(&mut self, watermark: Option<Timestamp>) {
+        let gap = self.gap;
+        let ready = &mut self.ready;
+        self.open.retain(|key, sessions| {
+            sessions.retain(|session| {
+                let ts = session.end.saturating_add(gap);
+                if watermark.is_some_and(|w| ts > w) {
+                    return true;
+                }
+                ready.push_back(StreamElement::Timestamped((key.clone(), *session), ts));
+                false
+            });
+            !sessions.is_empty()
+        });
+    }
+}
+
+impl<K, I, Op> Operator for Sessionize<K, I, Op>
+where
+    K: DataKey,
+    I: Data,
+    Op: Operator<Out = (K, I)>,
+{
+    type Out = (K, Session);
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        self.prev.setup(metadata);
+        if let Some(probe) = &mut self.probe {
+            probe.setup(metadata.coord);
+        }
+    }
+
+    fn next(&mut self) -> StreamElement<Self::Out> {
+        while self.ready.is_empty() {
+            match self.prev.next() {
+                StreamElement::Timestamped((key, _), ts) => {
+                    if self.watermark.is_some_and(|w| ts <= w) {
+                        log::warn!("Sessionize: dropping late element with timestamp {ts}");
+                        continue;
+                    }
+                    self.insert(key, ts);
+                }
+                StreamElement::Watermark(ts) => {
+                    self.close(Some(ts));
+                    self.publish_state();
+                    self.watermark = Some(ts);
+                    self.ready.push_back(StreamElement::Watermark(ts));
+                }
+                StreamElement::FlushAndRestart => {
+                    self.publish_state();
+                    self.close(None);
+                    self.watermark = None;
+                    self.ready.push_back(StreamElement::FlushAndRestart);
+                }
+                StreamElement::Item(_) => panic!("Sessionize only supports timestamped streams"),
+                StreamElement::FlushBatch => {
+                    self.publish_state();
+                    return StreamElement::FlushBatch;
+                }
+                StreamElement::Terminate => return StreamElement::Terminate,
+            }
+        }
+        self.ready.pop_front().unwrap()
+    }
+
+    fn structure(&self) -> BlockStructure {
+        let mut operator = OperatorStructure::new::<Self::Out, _>("Sessionize")
+            .with_state::<(K, Vec<Session>)>(StateEntries::PerKey);
+        operator.timestamps = TimestampUsage::Require;
+        self.prev.structure().add_operator(operator)
+    }
+}
+
+impl<K, I, Op> ReportState for Sessionize<K, I, Op>
+where
+    K: DataKey,
+    I: Data,
+    Op: Operator<Out = (K, I)>,
+{
+    type Key = K;
+    type State = Vec<Session>;
+
+    fn report_state(&mut self, probe: StateProbe<K, Vec<Session>>) {
+        self.probe = Some(probe);
+    }
+}
+
+impl<K, I, Op> KeyedStream<Op>
+where
+    K: DataKey,
+    I: Data,
+    Op: Operator<Out = (K, I)> + 'static,
+{
+    /// Group the events of each key in sessions of activity, emitting one [`Session`] per session
+    /// with its start, end, duration and number of events.
+    ///
+    /// Two events of the same key belong to the same session if their timestamps are at most
+    /// `gap` apart. The stream must have timestamps and watermarks: a session is emitted when the
+    /// watermark passes its end plus `gap`, with that timestamp. The events arriving after a
+    /// watermark are dropped if their timestamp is not after it.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::Session;
+    /// # let mut env = StreamContext::new(RuntimeConfig::local(1).unwrap());
+    /// let s = env
+    ///     .stream_iter(vec![('a', 1), ('a', 4), ('b', 5), ('a', 20)].into_iter())
+    ///     .add_timestamps(|&(_, ts)| ts, |_, _| None)
+    ///     .group_by(|&(user, _)| user);
+    /// let res = s.sessionize(5).collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let mut res = res.get().unwrap();
+    /// res.sort_by_key(|(user, session)| (*user, session.start));
+    /// let session = |start, end, count| Session { start, end, duration: end - start, count };
+    /// assert_eq!(
+    ///     res,
+    ///     vec![('a', session(1, 4, 2)), ('a', session(20, 20, 1)), ('b', session(5, 5, 1))]
+    /// );
+    /// ```
+    pub fn sessionize(
+        self,
+        gap: Timestamp,
+    ) -> KeyedStream<impl ReportState<Key = K, State = Vec<Session>, Out = (K, Session)>> {
+        self.add_operator(|prev| Sessionize::new(prev, gap))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::operator::sessionize::{Session, Sessionize};
+    use crate::operator::{Operator, StreamElement};
+    use crate::test::FakeOperator;
+
+    #[test]
+    fn sessionize_out_of_order() {
+        let mut fake = FakeOperator::empty();
+        fake.push(StreamElement::Timestamped(('a', ()), 10));
+        fake.push(StreamElement::Timestamped(('a', ()), 30));
+        fake.push(StreamElement::Timestamped(('b', ()), 1));
+        // bridges the two sessions of `a`
+        fake.push(StreamElement::Timestamped(('a', ()), 20));
+        fake.push(StreamElement::Watermark(35));
+        fake.push(StreamElement::Timestamped(('a', ()), 45));
+        // late element
+        fake.push(StreamElement::Timestamped(('b', ()), 3));
+        fake.push(StreamElement::Watermark(50));
+        fake.push(StreamElement::Timestamped(('a', ()), 52));
+        fake.push(StreamElement::FlushAndRestart);
+        fake.push(StreamElement::Terminate);
+
+        let mut sessionize = Sessionize::new(fake, 10);
+        let mut res = Vec::new();
+        loop {
+            match sessionize.next() {
+                StreamElement::Timestamped(item, ts) => res.push((item, ts)),
+                StreamElement::Watermark(ts) => res.push((('w', Session::new(0)), ts)),
+                StreamElement::Terminate => break,
+                _ => {}
+            }
+        }
+
+        let session = |start, end, count| Session {
+            start,
+            end,
+            duration: end - start,
+            count,
+        };
+        let expected = vec![
+            (('b', session(1, 1, 1)), 11),
+            (('w', Session::new(0)), 35),
+            (('a', session(10, 30, 3)), 40),
+            (('w', Session::new(0)), 50),
+            (('a', session(45, 52, 2)), 62),
+        ];
+        assert_eq!(res, expected);
+    }
+}
@@ -1,4 +1,5 @@
-use apache_avro::{AvroSchema, Schema, Writer};
+use apache_avro::{AvroSchema, Codec, Schema, Writer};
+use nanorand::{tls_rng, Rng};
 use serde::Serialize;
 use std::fs::File;
 use std::io::{BufWriter, Write};
@@ -10,28 +11,59 @@ use crate::operator::{ExchangeData, Operator};
 use crate::scheduler::ExecutionMetadata;
 use crate::{CoordUInt, Replication, Stream};
 
+use super::output_quota::OutputQuota;
 use super::writer::{sequential_path, WriteOperator, WriterOperator};
 
-// #[derive(Debug)]
+/// Sink that writes the items into Avro object container files, with the schema derived from the
+/// type of the items (see [`AvroSchema`]).
+///
+/// Each replica writes its own file, with a single header and the items appended in blocks
+/// compressed with the chosen [`Codec`].
 pub struct AvroSink<T> {
     _t: PhantomData<T>,
     writer: Option<BufWriter<File>>,
     schema: Schema,
+    codec: Codec,
+    /// The sync marker of the file, written in the header and after each block.
+    marker: [u8; 16],
+    /// Whether the header has already been written to the file.
+    has_header: bool,
 }
 
 impl<T> AvroSink<T>
 where
     T: AvroSchema + Serialize,
 {
+    /// Create a new sink that writes uncompressed blocks.
     pub fn new() -> Self {
         Self {
             _t: PhantomData,
             writer: None,
             schema: T::get_schema(),
+            codec: Codec::Null,
+            marker: [0; 16],
+            has_header: false,
         }
     }
 }
 
+impl<T> Default for AvroSink<T>
+where
+    T: AvroSchema + Serialize,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> AvroSink<T> {
+    /// The codec used for compressing the blocks of the files.
+    pub fn codec(mut self, codec: Codec) -> Self {
+        self.codec = codec;
+        self
+    }
+}
+
 impl<T: Serialize> WriteOperator<T> for AvroSink<T>
 where
     T: AvroSchema + Serialize + Send,
@@ -39,12 +71,27 @@ where
     type Destination = PathBuf;
 
     fn write(&mut self, items: &mut impl Iterator<Item = T>) {
+        let mut items = items.peekable();
+        if items.peek().is_none() {
+            return;
+        }
         let w = self.writer.as_mut().unwrap();
-        let mut w = Writer::new(&self.schema, w);
+        // the header is written only by the first writer, the others append to the same file
+        let mut w = if self.has_header {
+            Writer::append_to_with_codec(&self.schema, w, self.codec, self.marker)
+        } else {
+            Writer::builder()
+                .schema(&self.schema)
+                .writer(w)
+                .codec(self.codec)
+                .marker(self.marker)
+                .build()
+        };
         for item in items {
             w.append_ser(item).expect("failed to write to avro");
         }
         w.flush().unwrap();
+        self.has_header = true;
     }
 
     fn flush(&mut self) { /* already flushes in write */
@@ -72,6 +119,8 @@ where
 
         let buf_writer = BufWriter::new(file);
         self.writer = Some(buf_writer);
+        tls_rng().fill_bytes(&mut self.marker);
+        self.has_header = false;
     }
 }
 
@@ -81,6 +130,9 @@ impl<T> Clone for AvroSink<T> {
             _t: PhantomData,
             writer: None,
             schema: self.schema.clone(),
+            codec: self.codec,
+            marker: [0; 16],
+            has_header: false,
         }
     }
 }
@@ -98,7 +150,42 @@ where
 
         self.add_operator(|prev| {
             let writer = AvroSink::new();
-            WriterOperator::new(prev, writer, make_destination)
+            WriterOperator::new(OutputQuota::new(prev), writer, make_destination)
+        })
+        .finalize_block();
+    }
+
+    /// Write output to avro files using `sink`, see [`AvroSink`]. A file is created for each
+    /// replica of the current block, with the path returned by `make_path` from the global id of
+    /// the replica.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::sink::AvroSink;
+    /// # use apache_avro::{AvroSchema, Codec};
+    /// # use serde::Serialize;
+    /// # let mut env = StreamContext::new_local();
+    /// #[derive(AvroSchema, Serialize, Clone)]
+    /// struct Event {
+    ///     id: u32,
+    /// }
+    ///
+    /// let sink = AvroSink::new().codec(Codec::Deflate);
+    /// env.stream_iter((0..100).map(|id| Event { id }))
+    ///     .write_avro_with(sink, |replica| format!("/data/events-{replica}.avro").into());
+    /// env.execute_blocking();
+    /// ```
+    pub fn write_avro_with<F: FnOnce(CoordUInt) -> PathBuf + Clone + Send + 'static>(
+        self,
+        sink: AvroSink<Op::Out>,
+        make_path: F,
+    ) {
+        let make_destination = |metadata: &ExecutionMetadata| (make_path)(metadata.global_id);
+
+        self.add_operator(|prev| {
+            WriterOperator::new(OutputQuota::new(prev), sink, make_destination)
         })
         .finalize_block();
     }
@@ -117,7 +204,9 @@ where
     pub fn write_avro_seq(self, template_path: PathBuf) {
         self.add_operator(|prev| {
             let writer = AvroSink::new();
-            WriterOperator::new(prev, writer, |m| sequential_path(template_path, m))
+            WriterOperator::new(OutputQuota::new(prev), writer, |m| {
+                sequential_path(template_path, m)
+            })
         })
         .finalize_block();
     }
@@ -133,8 +222,49 @@ where
         self.repartition(Replication::One, NextStrategy::only_one())
             .add_operator(|prev| {
                 let writer = AvroSink::new();
-                WriterOperator::new(prev, writer, move |_| path)
+                WriterOperator::new(OutputQuota::new(prev), writer, move |_| path)
             })
             .finalize_block();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use apache_avro::{from_value, AvroSchema, Codec, Reader};
+    use serde::{Deserialize, Serialize};
+    use std::fs::File;
+
+    use super::AvroSink;
+    use crate::operator::sink::writer::WriteOperator;
+
+    #[derive(AvroSchema, Serialize, Deserialize, Clone, Debug, PartialEq)]
+    struct Event {
+        id: u32,
+        name: String,
+    }
+
+    #[test]
+    fn avro_sink_batches() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.avro");
+        let events: Vec<_> = (0..10)
+            .map(|id| Event {
+                id,
+                name: format!("event {id}"),
+            })
+            .collect();
+
+        let mut sink = AvroSink::<Event>::new().codec(Codec::Deflate);
+        sink.setup(path.clone());
+        sink.write(&mut events[..4].iter().cloned());
+        sink.write(&mut std::iter::empty());
+        sink.write(&mut events[4..].iter().cloned());
+        sink.finalize();
+
+        let reader = Reader::new(File::open(path).unwrap()).unwrap();
+        let read: Vec<Event> = reader
+            .map(|value| from_value(&value.unwrap()).unwrap())
+            .collect();
+        assert_eq!(read, events);
+    }
+}
@@ -1,7 +1,7 @@
 use std::fmt::Display;
 use std::marker::PhantomData;
 
-use crate::block::{BlockStructure, OperatorKind, OperatorStructure};
+use crate::block::{BlockStructure, Boundedness, OperatorKind, OperatorStructure};
 use crate::operator::sink::StreamOutputRef;
 use crate::operator::{ExchangeData, Operator, StreamElement};
 use crate::scheduler::ExecutionMetadata;
@@ -73,6 +73,7 @@ where
     fn structure(&self) -> BlockStructure {
         let mut operator = OperatorStructure::new::<Out, _>("Collect");
         operator.kind = OperatorKind::Sink;
+        operator.boundedness = Boundedness::RequireBounded;
         self.prev.structure().add_operator(operator)
     }
 }
@@ -89,7 +90,7 @@ where
 
 #[cfg(test)]
 mod qtests {
-    use std::collections::HashSet;
+    use std::collections::{HashMap, HashSet};
 
     use crate::config::RuntimeConfig;
     use crate::environment::StreamContext;
@@ -112,4 +113,23 @@ mod qtests {
         env.execute_blocking();
         assert_eq!(res.get().unwrap(), (0..10).collect::<HashSet<_>>());
     }
+
+    #[test]
+    fn collect_set_dedup() {
+        let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        let source = source::IteratorSource::new(0..100u8);
+        let res = env.stream(source).shuffle().map(|n| n % 5).collect_set();
+        env.execute_blocking();
+        assert_eq!(res.get().unwrap(), (0..5).collect::<HashSet<_>>());
+    }
+
+    #[test]
+    fn collect_map_last() {
+        let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        let source = source::IteratorSource::new(0..100u8);
+        let res = env.stream(source).group_by(|n| n % 5).collect_map();
+        env.execute_blocking();
+        let expected: HashMap<_, _> = (95..100).map(|n| (n % 5, n)).collect();
+        assert_eq!(res.get().unwrap(), expected);
+    }
 }
@@ -86,4 +86,19 @@ mod tests {
         }
         assert_eq!(v, (0..10).collect_vec());
     }
+
+    #[test]
+    fn collect_channel_bounded() {
+        let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        let source = source::IteratorSource::new(0..1000u32);
+        let rx = env.stream(source).collect_channel_bounded(4);
+        let job = std::thread::spawn(move || env.execute_blocking());
+        let mut v = Vec::new();
+        while let Ok(x) = rx.recv() {
+            assert!(rx.len() <= 4);
+            v.push(x)
+        }
+        job.join().unwrap();
+        assert_eq!(v, (0..1000).collect_vec());
+    }
 }
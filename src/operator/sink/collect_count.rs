@@ -1,6 +1,6 @@
 use std::fmt::Display;
 
-use crate::block::{BlockStructure, OperatorKind, OperatorStructure};
+use crate::block::{BlockStructure, Boundedness, OperatorKind, OperatorStructure};
 
 use crate::operator::sink::StreamOutputRef;
 use crate::operator::{Operator, StreamElement};
@@ -67,6 +67,7 @@ where
     fn structure(&self) -> BlockStructure {
         let mut operator = OperatorStructure::new::<usize, _>("CollectCountSink");
         operator.kind = OperatorKind::Sink;
+        operator.boundedness = Boundedness::RequireBounded;
         self.prev.structure().add_operator(operator)
     }
 }
@@ -0,0 +1,136 @@
+use std::fmt::Display;
+
+use crate::block::{BlockStructure, Boundedness, OperatorKind, OperatorStructure};
+use crate::operator::sink::StreamOutputRef;
+use crate::operator::{Data, Operator, StreamElement};
+use crate::scheduler::ExecutionMetadata;
+
+/// Sink that keeps one of the partial results computed by each replica, the first or the last it
+/// receives.
+#[derive(Debug)]
+pub struct CollectOneSink<T, PreviousOperators>
+where
+    PreviousOperators: Operator<Out = Option<T>>,
+{
+    prev: PreviousOperators,
+    /// Whether a result replaces the one received before it.
+    last: bool,
+    result: Option<T>,
+    output: StreamOutputRef<Option<T>>,
+}
+
+impl<T, PreviousOperators> CollectOneSink<T, PreviousOperators>
+where
+    PreviousOperators: Operator<Out = Option<T>>,
+{
+    pub(crate) fn new(
+        prev: PreviousOperators,
+        last: bool,
+        output: StreamOutputRef<Option<T>>,
+    ) -> Self {
+        Self {
+            prev,
+            last,
+            result: None,
+            output,
+        }
+    }
+}
+
+impl<T, PreviousOperators> Display for CollectOneSink<T, PreviousOperators>
+where
+    PreviousOperators: Operator<Out = Option<T>>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = if self.last { "Last" } else { "First" };
+        write!(f, "{} -> Collect{name}Sink", self.prev)
+    }
+}
+
+impl<T: Data, PreviousOperators> Operator for CollectOneSink<T, PreviousOperators>
+where
+    PreviousOperators: Operator<Out = Option<T>>,
+{
+    type Out = ();
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        self.prev.setup(metadata);
+    }
+
+    fn next(&mut self) -> StreamElement<()> {
+        match self.prev.next() {
+            StreamElement::Item(item) | StreamElement::Timestamped(item, _) => {
+                if item.is_some() && (self.last || self.result.is_none()) {
+                    self.result = item;
+                }
+                StreamElement::Item(())
+            }
+            StreamElement::Watermark(w) => StreamElement::Watermark(w),
+            StreamElement::Terminate => {
+                *self.output.lock().unwrap() = Some(self.result.take());
+                StreamElement::Terminate
+            }
+            StreamElement::FlushBatch => StreamElement::FlushBatch,
+            StreamElement::FlushAndRestart => StreamElement::FlushAndRestart,
+        }
+    }
+
+    fn structure(&self) -> BlockStructure {
+        let name = if self.last {
+            "CollectLastSink"
+        } else {
+            "CollectFirstSink"
+        };
+        let mut operator = OperatorStructure::new::<Option<T>, _>(name);
+        operator.kind = OperatorKind::Sink;
+        operator.boundedness = Boundedness::RequireBounded;
+        self.prev.structure().add_operator(operator)
+    }
+}
+
+impl<T, PreviousOperators> Clone for CollectOneSink<T, PreviousOperators>
+where
+    PreviousOperators: Operator<Out = Option<T>>,
+{
+    fn clone(&self) -> Self {
+        panic!("CollectOneSink cannot be cloned, replication should be 1");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::RuntimeConfig;
+    use crate::environment::StreamContext;
+    use crate::operator::source;
+
+    #[test]
+    fn collect_first_last() {
+        let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        let first = env
+            .stream(source::IteratorSource::new(0..10u8))
+            .collect_first();
+        let last = env
+            .stream(source::IteratorSource::new(0..10u8))
+            .map(|n| n * 2)
+            .collect_last();
+        let empty = env
+            .stream(source::IteratorSource::new(0..10u8))
+            .filter(|_| false)
+            .collect_first();
+        env.execute_blocking();
+        assert_eq!(first.get().unwrap(), Some(0));
+        assert_eq!(last.get().unwrap(), Some(18));
+        assert_eq!(empty.get().unwrap(), None);
+    }
+
+    #[test]
+    fn collect_last_shuffled() {
+        let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        let last = env
+            .stream(source::IteratorSource::new(0..100u8))
+            .shuffle()
+            .collect_last();
+        env.execute_blocking();
+        assert!(last.get().unwrap().is_some());
+    }
+}
@@ -1,6 +1,6 @@
 use std::fmt::Display;
 
-use crate::block::{BlockStructure, OperatorKind, OperatorStructure};
+use crate::block::{BlockStructure, Boundedness, OperatorKind, OperatorStructure};
 use crate::operator::sink::StreamOutputRef;
 use crate::operator::{ExchangeData, Operator, StreamElement};
 use crate::scheduler::ExecutionMetadata;
@@ -71,6 +71,7 @@ where
     fn structure(&self) -> BlockStructure {
         let mut operator = OperatorStructure::new::<Out, _>("CollectVecSink");
         operator.kind = OperatorKind::Sink;
+        operator.boundedness = Boundedness::RequireBounded;
         self.prev.structure().add_operator(operator)
     }
 }
@@ -9,6 +9,7 @@ use crate::operator::{ExchangeData, Operator};
 use crate::scheduler::ExecutionMetadata;
 use crate::{CoordUInt, Replication, Stream};
 
+use super::output_quota::OutputQuota;
 use super::writer::{sequential_path, WriteOperator, WriterOperator};
 
 // #[derive(Debug)]
@@ -107,7 +108,7 @@ where
 
         self.add_operator(|prev| {
             let writer = CsvWriteOp::new(append);
-            WriterOperator::new(prev, writer, make_destination)
+            WriterOperator::new(OutputQuota::new(prev), writer, make_destination)
         })
         .finalize_block();
     }
@@ -126,7 +127,9 @@ where
     pub fn write_csv_seq(self, template_path: PathBuf, append: bool) {
         self.add_operator(|prev| {
             let writer = CsvWriteOp::new(append);
-            WriterOperator::new(prev, writer, |m| sequential_path(template_path, m))
+            WriterOperator::new(OutputQuota::new(prev), writer, |m| {
+                sequential_path(template_path, m)
+            })
         })
         .finalize_block();
     }
@@ -142,7 +145,7 @@ where
         self.repartition(Replication::One, NextStrategy::only_one())
             .add_operator(|prev| {
                 let writer = CsvWriteOp::new(append);
-                WriterOperator::new(prev, writer, move |_| path)
+                WriterOperator::new(OutputQuota::new(prev), writer, move |_| path)
             })
             .finalize_block();
     }
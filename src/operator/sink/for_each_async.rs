@@ -0,0 +1,262 @@
+use std::fmt::Display;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Wake, Waker};
+use std::thread::{self, Thread};
+
+use crate::block::{BlockStructure, OperatorKind, OperatorStructure};
+use crate::operator::{Operator, StreamElement};
+use crate::scheduler::ExecutionMetadata;
+use crate::Stream;
+
+use super::output_quota::OutputQuota;
+
+type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Wake the worker thread waiting for the futures.
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct ForEachAsync<F, Fut, Op>
+where
+    F: Fn(Op::Out) -> Fut + Send + Clone,
+    Fut: Future<Output = ()> + Send + 'static,
+    Op: Operator,
+{
+    prev: Op,
+    #[derivative(Debug = "ignore")]
+    f: F,
+    concurrency: usize,
+    /// The futures still running.
+    #[derivative(Debug = "ignore")]
+    in_flight: Vec<BoxFuture>,
+    /// The waker of the worker thread, set by the first call to `next`.
+    #[derivative(Debug = "ignore")]
+    waker: Option<Waker>,
+    /// The runtime the futures are polled in, if the job is executed by tokio.
+    #[cfg(feature = "tokio")]
+    #[derivative(Debug = "ignore")]
+    runtime: Option<tokio::runtime::Handle>,
+}
+
+impl<F, Fut, Op> ForEachAsync<F, Fut, Op>
+where
+    F: Fn(Op::Out) -> Fut + Send + Clone,
+    Fut: Future<Output = ()> + Send + 'static,
+    Op: Operator,
+{
+    pub(crate) fn new(prev: Op, f: F, concurrency: usize) -> Self {
+        assert!(
+            concurrency > 0,
+            "the concurrency of for_each_async must be positive"
+        );
+        Self {
+            prev,
+            f,
+            concurrency,
+            in_flight: Vec::new(),
+            waker: None,
+            #[cfg(feature = "tokio")]
+            runtime: None,
+        }
+    }
+
+    /// Poll all the futures still running once, dropping the completed ones.
+    fn poll_in_flight(&mut self) {
+        #[cfg(feature = "tokio")]
+        let _guard = self.runtime.as_ref().map(|runtime| runtime.enter());
+        let waker = self.waker.as_ref().expect("setup was not called");
+        let mut cx = Context::from_waker(waker);
+        self.in_flight
+            .retain_mut(|fut| fut.as_mut().poll(&mut cx).is_pending());
+    }
+
+    /// Wait until at most `max` futures are running.
+    fn wait_in_flight(&mut self, max: usize) {
+        loop {
+            self.poll_in_flight();
+            if self.in_flight.len() <= max {
+                return;
+            }
+            thread::park();
+        }
+    }
+}
+
+impl<F, Fut, Op> Clone for ForEachAsync<F, Fut, Op>
+where
+    F: Fn(Op::Out) -> Fut + Send + Clone,
+    Fut: Future<Output = ()> + Send + 'static,
+    Op: Operator,
+{
+    fn clone(&self) -> Self {
+        Self::new(self.prev.clone(), self.f.clone(), self.concurrency)
+    }
+}
+
+impl<F, Fut, Op> Display for ForEachAsync<F, Fut, Op>
+where
+    F: Fn(Op::Out) -> Fut + Send + Clone,
+    Fut: Future<Output = ()> + Send + 'static,
+    Op: Operator,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} -> ForEachAsync", self.prev)
+    }
+}
+
+impl<F, Fut, Op> Operator for ForEachAsync<F, Fut, Op>
+where
+    F: Fn(Op::Out) -> Fut + Send + Clone,
+    Fut: Future<Output = ()> + Send + 'static,
+    Op: Operator,
+{
+    type Out = ();
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        self.prev.setup(metadata);
+        #[cfg(feature = "tokio")]
+        {
+            self.runtime = tokio::runtime::Handle::try_current().ok();
+        }
+    }
+
+    fn next(&mut self) -> StreamElement<()> {
+        // setup is called by the thread that starts the job, not by the worker of the replica
+        if self.waker.is_none() {
+            self.waker = Some(Arc::new(ThreadWaker(thread::current())).into());
+        }
+        loop {
+            match self.prev.next() {
+                StreamElement::Item(t) | StreamElement::Timestamped(t, _) => {
+                    self.wait_in_flight(self.concurrency - 1);
+                    self.in_flight.push(Box::pin((self.f)(t)));
+                    // start the new future right away
+                    self.poll_in_flight();
+                }
+                StreamElement::Watermark(w) => return StreamElement::Watermark(w),
+                el => {
+                    // all the items before a flush must have been processed
+                    self.wait_in_flight(0);
+                    return el.variant();
+                }
+            }
+        }
+    }
+
+    fn structure(&self) -> BlockStructure {
+        let mut operator = OperatorStructure::new::<Op::Out, _>("ForEachAsyncSink");
+        operator.kind = OperatorKind::Sink;
+        self.prev.structure().add_operator(operator)
+    }
+}
+
+impl<Op> Stream<Op>
+where
+    Op: Operator + 'static,
+{
+    /// Apply the given async function to all the elements of the stream, consuming the stream.
+    ///
+    /// Each replica runs at most `concurrency` futures at the same time, and waits for all of
+    /// them to complete when the stream is flushed or terminates. The futures are polled by the
+    /// thread of the replica: if the job is executed by tokio, they are polled inside its runtime.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use std::sync::atomic::{AtomicU64, Ordering};
+    /// # use std::sync::Arc;
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let total = Arc::new(AtomicU64::new(0));
+    /// let sum = total.clone();
+    /// let s = env.stream_iter(0..10u64);
+    /// // e.g. post each item to an HTTP API
+    /// s.for_each_async(4, move |n| {
+    ///     let sum = sum.clone();
+    ///     async move {
+    ///         sum.fetch_add(n, Ordering::Relaxed);
+    ///     }
+    /// });
+    ///
+    /// env.execute_blocking();
+    ///
+    /// assert_eq!(total.load(Ordering::Relaxed), 45);
+    /// ```
+    pub fn for_each_async<F, Fut>(self, concurrency: usize, f: F)
+    where
+        F: Fn(Op::Out) -> Fut + Send + Clone + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.add_operator(|prev| ForEachAsync::new(OutputQuota::new(prev), f, concurrency))
+            .finalize_block();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::task::{Context, Poll};
+
+    use super::ForEachAsync;
+    use crate::operator::{Operator, StreamElement};
+    use crate::test::{FakeNetworkTopology, FakeOperator};
+
+    /// A future that completes after being polled the given number of times.
+    struct Yield(usize);
+
+    impl Future for Yield {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            self.0 -= 1;
+            if self.0 == 0 {
+                return Poll::Ready(());
+            }
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    #[test]
+    fn for_each_async_concurrency() {
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+        let done = Arc::new(Mutex::new(vec![]));
+        let fake = FakeOperator::new(0..10u8);
+        let (r, m, d) = (running.clone(), max_running.clone(), done.clone());
+        let mut op = ForEachAsync::new(
+            fake,
+            move |n| {
+                let (r, m, d) = (r.clone(), m.clone(), d.clone());
+                async move {
+                    m.fetch_max(r.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                    Yield(10).await;
+                    r.fetch_sub(1, Ordering::SeqCst);
+                    d.lock().unwrap().push(n);
+                }
+            },
+            3,
+        );
+        op.setup(&mut FakeNetworkTopology::<u8>::new(0, 0).metadata());
+
+        assert_eq!(op.next(), StreamElement::Terminate);
+        let mut done = done.lock().unwrap().clone();
+        done.sort_unstable();
+        assert_eq!(done, (0..10).collect::<Vec<_>>());
+        assert_eq!(max_running.load(Ordering::SeqCst), 3);
+        assert_eq!(running.load(Ordering::SeqCst), 0);
+    }
+}
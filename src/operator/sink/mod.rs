@@ -10,11 +10,23 @@ pub(super) mod avro;
 pub(super) mod collect;
 pub(super) mod collect_channel;
 pub(super) mod collect_count;
+pub(super) mod collect_one;
 pub(super) mod collect_vec;
 pub(super) mod csv;
 pub(super) mod for_each;
+pub(super) mod for_each_async;
+pub(super) mod output_quota;
+#[cfg(not(feature = "tokio"))]
+pub(super) mod publish;
+pub(super) mod socket;
 pub(super) mod writer;
 
+#[cfg(feature = "avro")]
+pub use avro::AvroSink;
+#[cfg(not(feature = "tokio"))]
+pub use publish::PublishBacklog;
+pub use socket::{JsonEncoder, SocketSink};
+
 pub(crate) type StreamOutputRef<Out> = Arc<Mutex<Option<Out>>>;
 
 /// The result of a stream after the execution.
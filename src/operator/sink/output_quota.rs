@@ -0,0 +1,96 @@
+use std::fmt::Display;
+use std::sync::Arc;
+
+use crate::block::BlockStructure;
+use crate::operator::{Operator, StreamElement};
+use crate::scheduler::{ExecutionMetadata, QuotaUsage};
+
+/// Count the items that reach a sink against the output quota of the job.
+///
+/// The operator is transparent: it is not shown in the structure of the block.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct OutputQuota<PreviousOperators: Operator> {
+    prev: PreviousOperators,
+    #[derivative(Debug = "ignore")]
+    quota: Option<Arc<QuotaUsage>>,
+}
+
+impl<PreviousOperators: Operator> Clone for OutputQuota<PreviousOperators> {
+    fn clone(&self) -> Self {
+        Self {
+            prev: self.prev.clone(),
+            quota: None,
+        }
+    }
+}
+
+impl<PreviousOperators: Operator> OutputQuota<PreviousOperators> {
+    pub(crate) fn new(prev: PreviousOperators) -> Self {
+        Self { prev, quota: None }
+    }
+}
+
+impl<PreviousOperators: Operator> Display for OutputQuota<PreviousOperators> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.prev.fmt(f)
+    }
+}
+
+impl<PreviousOperators: Operator> Operator for OutputQuota<PreviousOperators> {
+    type Out = PreviousOperators::Out;
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        self.prev.setup(metadata);
+        self.quota = Some(metadata.quota.clone());
+    }
+
+    fn next(&mut self) -> StreamElement<Self::Out> {
+        let el = self.prev.next();
+        if matches!(
+            el,
+            StreamElement::Item(_) | StreamElement::Timestamped(_, _)
+        ) {
+            let quota = self.quota.as_ref().expect("setup was not called");
+            quota.add_output_rows(1);
+        }
+        el
+    }
+
+    fn structure(&self) -> BlockStructure {
+        self.prev.structure()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::config::QuotaConfig;
+    use crate::operator::sink::output_quota::OutputQuota;
+    use crate::operator::{Operator, StreamElement};
+    use crate::scheduler::QuotaUsage;
+    use crate::test::{FakeNetworkTopology, FakeOperator};
+
+    #[test]
+    fn output_quota() {
+        let fake = FakeOperator::new(0..3u32);
+        let mut quota = OutputQuota::new(fake);
+        let mut t = FakeNetworkTopology::<u32>::new(0, 0);
+        let mut metadata = t.metadata();
+        let usage = Arc::new(QuotaUsage::new(QuotaConfig {
+            max_output_rows: Some(3),
+            ..Default::default()
+        }));
+        metadata.quota = usage.clone();
+        quota.setup(&mut metadata);
+
+        assert_eq!(quota.next(), StreamElement::Item(0));
+        assert_eq!(quota.next(), StreamElement::Item(1));
+        assert_eq!(quota.next(), StreamElement::Item(2));
+        assert_eq!(quota.next(), StreamElement::Terminate);
+
+        let res = std::panic::catch_unwind(|| usage.add_output_rows(1));
+        assert!(res.is_err());
+    }
+}
@@ -0,0 +1,510 @@
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::io::{BufReader, ErrorKind, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread::sleep;
+use std::time::Duration;
+
+use flume::{Receiver, Sender};
+
+use crate::block::{BlockStructure, OperatorKind, OperatorStructure};
+use crate::network::{
+    remote_recv, remote_send, Coord, DemuxCoord, NetworkMessage, ReceiverEndpoint,
+};
+use crate::operator::{ExchangeData, Operator, StreamElement};
+use crate::scheduler::ExecutionMetadata;
+
+/// Timeout for receiving the subscription request of a new subscriber.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+/// How often the listener checks whether the sink is still running.
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// What a [`PublishSink`] does with the items published while no subscriber is connected.
+///
+/// The items are kept in a backlog and sent to the first subscribers that connect. Once a
+/// subscriber is connected the backlog is not used anymore: the sink sends the items as they
+/// arrive, waiting for the slowest subscriber.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PublishBacklog {
+    /// Keep up to this many items, then stop the stream until a subscriber connects.
+    Block(usize),
+    /// Keep up to this many items, then drop the oldest ones.
+    DropOldest(usize),
+}
+
+impl PublishBacklog {
+    fn capacity(&self) -> usize {
+        match self {
+            PublishBacklog::Block(n) | PublishBacklog::DropOldest(n) => *n,
+        }
+    }
+}
+
+impl Default for PublishBacklog {
+    fn default() -> Self {
+        PublishBacklog::Block(64 * 1024)
+    }
+}
+
+/// A job subscribed to the published stream.
+struct Subscriber {
+    stream: TcpStream,
+    peer: String,
+    /// The endpoint of the subscriber, as it sent in its request.
+    endpoint: ReceiverEndpoint,
+    /// The number of messages already sent to this subscriber.
+    sequence: u64,
+}
+
+/// Sink that sends the elements of the stream to the jobs subscribed to it.
+///
+/// The subscribers connect to the address the sink is listening on and request the stream by
+/// name. The elements are sent with the same framing used between the hosts of a job, in batches
+/// with a sequence number and a checksum, and the stream is closed with a `Terminate`.
+///
+/// The connections are accepted by a separate thread, so the sink never waits for a handshake.
+/// While nobody is connected the elements are kept in a backlog, see [`PublishBacklog`].
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct PublishSink<Op>
+where
+    Op: Operator,
+    Op::Out: ExchangeData,
+{
+    prev: Op,
+    coord: Option<Coord>,
+    name: String,
+    address: Vec<SocketAddr>,
+    backlog_mode: PublishBacklog,
+    /// The subscribers whose handshake has been completed by the listener.
+    #[derivative(Debug = "ignore")]
+    incoming: Option<Receiver<Subscriber>>,
+    #[derivative(Debug = "ignore")]
+    subscribers: Vec<Subscriber>,
+    #[derivative(Debug = "ignore")]
+    batch: Vec<StreamElement<Op::Out>>,
+    batch_size: usize,
+    /// The elements flushed while no subscriber was connected.
+    #[derivative(Debug = "ignore")]
+    backlog: VecDeque<StreamElement<Op::Out>>,
+    /// The number of elements dropped from the backlog.
+    dropped: usize,
+}
+
+impl<Op> PublishSink<Op>
+where
+    Op: Operator,
+    Op::Out: ExchangeData,
+{
+    pub(crate) fn new(
+        prev: Op,
+        name: String,
+        address: Vec<SocketAddr>,
+        backlog_mode: PublishBacklog,
+    ) -> Self {
+        Self {
+            prev,
+            coord: None,
+            name,
+            address,
+            backlog_mode,
+            incoming: None,
+            subscribers: Vec::new(),
+            batch: Vec::new(),
+            batch_size: 1,
+            backlog: VecDeque::new(),
+            dropped: 0,
+        }
+    }
+
+    /// Add the subscribers that completed the handshake since the last call. If `wait` is set
+    /// and nobody is connected, wait for the first subscriber.
+    fn accept_subscribers(&mut self, wait: bool) {
+        let incoming = self.incoming.as_ref().expect("setup was not called");
+        if wait && self.subscribers.is_empty() {
+            log::warn!(
+                "publish {}: backlog full, waiting for a subscriber",
+                self.name
+            );
+            let subscriber = incoming
+                .recv()
+                .expect("the listener of the publisher stopped");
+            self.subscribers.push(subscriber);
+        }
+        self.subscribers.extend(incoming.try_iter());
+    }
+
+    /// Send the buffered elements to all the subscribers, or keep them in the backlog if nobody is
+    /// connected.
+    fn flush(&mut self) {
+        if self.batch.is_empty() {
+            return;
+        }
+        self.accept_subscribers(false);
+        if self.subscribers.is_empty() {
+            self.backlog.extend(self.batch.drain(..));
+            let capacity = self.backlog_mode.capacity();
+            if self.backlog.len() <= capacity {
+                return;
+            }
+            match self.backlog_mode {
+                PublishBacklog::Block(_) => self.accept_subscribers(true),
+                PublishBacklog::DropOldest(_) => {
+                    let excess = self.backlog.len() - capacity;
+                    self.backlog.drain(..excess);
+                    if self.dropped == 0 {
+                        log::warn!(
+                            "publish {}: backlog full, dropping the oldest elements",
+                            self.name
+                        );
+                    }
+                    self.dropped += excess;
+                    return;
+                }
+            }
+        }
+        if !self.backlog.is_empty() {
+            // the first subscribers receive what was published before they connected
+            self.batch.splice(0..0, self.backlog.drain(..));
+        }
+
+        let name = &self.name;
+        let coord = self.coord.unwrap();
+        let batch = &self.batch;
+        let mut buf = Vec::new();
+        self.subscribers.retain_mut(|subscriber| {
+            buf.clear();
+            let message = NetworkMessage::new_batch(batch.clone(), coord);
+            remote_send(
+                message,
+                subscriber.endpoint,
+                &mut buf,
+                &subscriber.peer,
+                subscriber.sequence,
+                true,
+            );
+            subscriber.sequence += 1;
+            // the write blocks while the subscriber is not keeping up
+            let res = subscriber
+                .stream
+                .write_all(&buf)
+                .and_then(|_| subscriber.stream.flush());
+            match res {
+                Ok(_) => true,
+                Err(e) => {
+                    log::warn!(
+                        "publish {name}: dropping subscriber {}: {e:?}",
+                        subscriber.peer
+                    );
+                    false
+                }
+            }
+        });
+        self.batch.clear();
+    }
+}
+
+/// Accept the connections of the subscribers, until the sink is dropped.
+fn listen(name: String, coord: Coord, listener: TcpListener, subscribers: Sender<Subscriber>) {
+    while !subscribers.is_disconnected() {
+        match listener.accept() {
+            Ok((stream, peer)) => {
+                let name = name.clone();
+                let subscribers = subscribers.clone();
+                std::thread::Builder::new()
+                    .name(format!("publish-{peer}"))
+                    .spawn(move || {
+                        if let Some(subscriber) = handshake(&name, coord, stream, peer) {
+                            let _ = subscribers.send(subscriber);
+                        }
+                    })
+                    .expect("failed to spawn the handshake thread");
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => sleep(ACCEPT_POLL_INTERVAL),
+            Err(e) => {
+                log::warn!("publish {name}: failed to accept subscriber: {e:?}");
+                sleep(ACCEPT_POLL_INTERVAL);
+            }
+        }
+    }
+    log::debug!("publish {name}: listener stopped");
+}
+
+/// Receive the subscription request of a new subscriber, keeping the connection only if it asks
+/// for this stream.
+fn handshake(name: &str, coord: Coord, stream: TcpStream, peer: SocketAddr) -> Option<Subscriber> {
+    let peer = peer.to_string();
+    let res = stream
+        .set_nonblocking(false)
+        .and_then(|_| stream.set_nodelay(true))
+        .and_then(|_| stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT)));
+    if let Err(e) = res {
+        log::warn!("publish {name}: cannot configure {peer}: {e:?}");
+        return None;
+    }
+    let demux_coord = DemuxCoord::from(ReceiverEndpoint::new(coord, coord.block_id));
+    let mut reader = BufReader::new(&stream);
+    let mut sequence = 0;
+    let request = match remote_recv::<String, _>(demux_coord, &mut reader, &peer, &mut sequence) {
+        Ok(Some((_, request))) => request,
+        Ok(None) => {
+            log::warn!("publish {name}: {peer} disconnected before subscribing");
+            return None;
+        }
+        Err(e) => {
+            log::warn!("publish {name}: invalid subscription request: {e}");
+            return None;
+        }
+    };
+    let sender = request.sender();
+    match request.into_iter().next() {
+        Some(StreamElement::Item(requested)) if requested == name => {}
+        requested => {
+            log::warn!("publish {name}: {peer} sent an invalid request: {requested:?}");
+            return None;
+        }
+    }
+    if let Err(e) = stream.set_read_timeout(None) {
+        log::warn!("publish {name}: cannot configure {peer}: {e:?}");
+        return None;
+    }
+    log::debug!("publish {name}: {peer} subscribed");
+    Some(Subscriber {
+        stream,
+        peer,
+        endpoint: ReceiverEndpoint::new(sender, coord.block_id),
+        sequence: 0,
+    })
+}
+
+impl<Op> Display for PublishSink<Op>
+where
+    Op: Operator,
+    Op::Out: ExchangeData,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} -> PublishSink({})", self.prev, self.name)
+    }
+}
+
+impl<Op> Operator for PublishSink<Op>
+where
+    Op: Operator,
+    Op::Out: ExchangeData,
+{
+    type Out = ();
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        self.prev.setup(metadata);
+        self.coord = Some(metadata.coord);
+        self.batch_size = metadata.batch_mode.max_size();
+        let listener = TcpListener::bind(&self.address[..]).unwrap_or_else(|e| {
+            panic!(
+                "publish {}: failed to bind {:?}: {e:?}",
+                self.name, self.address
+            )
+        });
+        listener
+            .set_nonblocking(true)
+            .expect("cannot set the listener to non blocking");
+        log::debug!(
+            "publish {} listening on {:?}",
+            self.name,
+            listener.local_addr()
+        );
+        let (tx, rx) = flume::unbounded();
+        let name = self.name.clone();
+        let coord = metadata.coord;
+        std::thread::Builder::new()
+            .name(format!("publish-{}", self.name))
+            .spawn(move || listen(name, coord, listener, tx))
+            .expect("failed to spawn the publish listener");
+        self.incoming = Some(rx);
+    }
+
+    fn next(&mut self) -> StreamElement<()> {
+        match self.prev.next() {
+            el @ (StreamElement::Item(_)
+            | StreamElement::Timestamped(_, _)
+            | StreamElement::Watermark(_)) => {
+                let ret = el.variant();
+                self.batch.push(el);
+                if self.batch.len() >= self.batch_size {
+                    self.flush();
+                }
+                ret
+            }
+            StreamElement::FlushBatch => {
+                self.flush();
+                StreamElement::FlushBatch
+            }
+            StreamElement::FlushAndRestart => {
+                self.flush();
+                StreamElement::FlushAndRestart
+            }
+            StreamElement::Terminate => {
+                self.batch.push(StreamElement::Terminate);
+                // the stream is over, never wait for a subscriber
+                self.accept_subscribers(false);
+                if self.subscribers.is_empty() {
+                    log::warn!(
+                        "publish {}: terminated without subscribers, {} elements not delivered",
+                        self.name,
+                        self.backlog.len() + self.batch.len() - 1 + self.dropped
+                    );
+                    self.batch.clear();
+                } else {
+                    self.flush();
+                    if self.dropped > 0 {
+                        log::warn!(
+                            "publish {}: {} elements dropped while no subscriber was connected",
+                            self.name,
+                            self.dropped
+                        );
+                    }
+                }
+                self.backlog.clear();
+                self.subscribers.clear();
+                // stops the listener
+                self.incoming = None;
+                StreamElement::Terminate
+            }
+        }
+    }
+
+    fn structure(&self) -> BlockStructure {
+        let mut operator = OperatorStructure::new::<Op::Out, _>("PublishSink");
+        operator.kind = OperatorKind::Sink;
+        self.prev.structure().add_operator(operator)
+    }
+}
+
+impl<Op> Clone for PublishSink<Op>
+where
+    Op: Operator,
+    Op::Out: ExchangeData,
+{
+    fn clone(&self) -> Self {
+        panic!("PublishSink cannot be cloned, replication should be 1");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufReader, Read};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::config::RuntimeConfig;
+    use crate::environment::StreamContext;
+    use crate::operator::sink::PublishBacklog;
+    use crate::BatchMode;
+
+    fn free_port() -> u16 {
+        TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    #[test]
+    fn publish_subscribe() {
+        let address = ("127.0.0.1", free_port());
+
+        // the items are sent one at a time until the subscriber has received some of them, then
+        // the publisher terminates
+        let (tx, rx) = std::sync::mpsc::channel();
+        let publisher = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        publisher
+            .stream_iter(rx.into_iter())
+            .batch_mode(BatchMode::fixed(1))
+            .map(|n: u32| n * 2)
+            .publish("numbers", address);
+        let publisher = std::thread::spawn(move || publisher.execute_blocking());
+
+        let received = Arc::new(AtomicBool::new(false));
+        let subscriber = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        let res = subscriber
+            .subscribe::<u32>("numbers", address)
+            .inspect({
+                let received = received.clone();
+                move |_| received.store(true, Ordering::Release)
+            })
+            .shuffle()
+            .collect_vec();
+        let subscriber = std::thread::spawn(move || subscriber.execute_blocking());
+
+        let mut sent = 0;
+        while !received.load(Ordering::Acquire) {
+            tx.send(sent).unwrap();
+            sent += 1;
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        for n in sent..sent + 1000 {
+            tx.send(n).unwrap();
+        }
+        drop(tx);
+        publisher.join().unwrap();
+        subscriber.join().unwrap();
+
+        let mut res = res.get().unwrap();
+        res.sort_unstable();
+        assert_eq!(res, (0..sent + 1000).map(|n| n * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn publish_blocks_until_subscribed() {
+        let address = ("127.0.0.1", free_port());
+
+        // the backlog is much smaller than the stream, nothing is lost anyway
+        let publisher = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        publisher.stream_iter(0..10_000u32).publish_with_backlog(
+            "numbers",
+            address,
+            PublishBacklog::Block(10),
+        );
+        let publisher = std::thread::spawn(move || publisher.execute_blocking());
+        std::thread::sleep(Duration::from_millis(100));
+
+        let subscriber = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        let res = subscriber
+            .subscribe::<u32>("numbers", address)
+            .collect_vec();
+        subscriber.execute_blocking();
+        publisher.join().unwrap();
+
+        assert_eq!(res.get().unwrap(), (0..10_000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn publish_without_subscribers() {
+        let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        env.stream_iter(0..1000u32).publish_with_backlog(
+            "numbers",
+            ("127.0.0.1", free_port()),
+            PublishBacklog::DropOldest(10),
+        );
+        // nobody subscribes, but the job terminates anyway
+        env.execute_blocking();
+    }
+
+    #[test]
+    #[should_panic]
+    fn subscribe_publisher_crashed() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        // a publisher that accepts the subscription and disconnects without terminating
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut request = [0; 16];
+            BufReader::new(&stream).read_exact(&mut request).unwrap();
+        });
+
+        let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        env.subscribe::<u32>("numbers", address).for_each(|_| {});
+        env.execute_blocking();
+    }
+}
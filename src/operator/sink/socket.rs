@@ -0,0 +1,247 @@
+use serde::Serialize;
+use std::io::{BufWriter, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::thread::sleep;
+use std::time::Duration;
+
+use crate::operator::Operator;
+use crate::scheduler::ExecutionMetadata;
+use crate::Stream;
+
+use super::output_quota::OutputQuota;
+use super::writer::{WriteOperator, WriterOperator};
+
+/// To avoid spamming the connections, wait this timeout before trying again. If the connection
+/// fails again this timeout will be doubled up to `RETRY_MAX_TIMEOUT`.
+const RETRY_INITIAL_TIMEOUT: Duration = Duration::from_millis(8);
+/// Maximum timeout between connection attempts.
+const RETRY_MAX_TIMEOUT: Duration = Duration::from_secs(1);
+/// The encoded items are sent as soon as the buffer reaches this size, even without a flush.
+const SEND_BUFFER_SIZE: usize = 64 << 10;
+
+/// The encoder used by [`SocketSink::json`].
+pub type JsonEncoder<T> = fn(&T, &mut Vec<u8>);
+
+/// Sink that encodes the items and writes them to a TCP endpoint.
+///
+/// Each replica opens its own connection to the endpoint. The encoded items are buffered and sent
+/// when the stream is flushed. When sending fails the sink reconnects and sends the buffer again,
+/// so the endpoint may receive part of a buffer twice. Note that TCP may report a broken
+/// connection only on a later write: the items sent just before the endpoint fails can be lost.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct SocketSink<F> {
+    address: Vec<SocketAddr>,
+    #[derivative(Debug = "ignore")]
+    encoder: F,
+    max_attempts: usize,
+    #[derivative(Debug = "ignore")]
+    stream: Option<BufWriter<TcpStream>>,
+    #[derivative(Debug = "ignore")]
+    buffer: Vec<u8>,
+}
+
+impl<T: Serialize> SocketSink<JsonEncoder<T>> {
+    /// Create a new sink writing to `address` the items serialized as JSON, one per line.
+    pub fn json(address: impl ToSocketAddrs) -> Self {
+        Self::new(address, |item, buf| {
+            serde_json::to_writer(&mut *buf, item).expect("SocketSink: cannot serialize the item");
+            buf.push(b'\n');
+        })
+    }
+}
+
+impl<F> SocketSink<F> {
+    /// Create a new sink writing to `address` the items encoded by `encoder`, which appends the
+    /// bytes of an item to the buffer.
+    pub fn new(address: impl ToSocketAddrs, encoder: F) -> Self {
+        let address = address
+            .to_socket_addrs()
+            .unwrap_or_else(|e| panic!("SocketSink: invalid address: {e:?}"))
+            .collect();
+        Self {
+            address,
+            encoder,
+            max_attempts: 32,
+            stream: None,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// The number of consecutive failed attempts to connect and send the buffer after which the
+    /// sink panics. The default is 32.
+    pub fn max_attempts(mut self, max_attempts: usize) -> Self {
+        assert!(
+            max_attempts > 0,
+            "the max_attempts of SocketSink must be positive"
+        );
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Write the buffer to the connection, opening it if needed.
+    fn try_send(&mut self) -> std::io::Result<()> {
+        let stream = match self.stream.as_mut() {
+            Some(stream) => stream,
+            None => {
+                let stream = TcpStream::connect(&self.address[..])?;
+                log::debug!("SocketSink: connected to {:?}", self.address);
+                self.stream.insert(BufWriter::new(stream))
+            }
+        };
+        stream.write_all(&self.buffer)?;
+        stream.flush()
+    }
+
+    /// Send the buffer, reconnecting when the connection fails.
+    fn send(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let mut retry_delay = RETRY_INITIAL_TIMEOUT;
+        for attempt in 1..=self.max_attempts {
+            match self.try_send() {
+                Ok(()) => {
+                    self.buffer.clear();
+                    return;
+                }
+                Err(e) if attempt == self.max_attempts => panic!(
+                    "SocketSink: error while sending to {:?} after {attempt} attempts: {e:?}",
+                    self.address
+                ),
+                Err(e) => {
+                    log::warn!(
+                        "SocketSink: error while sending to {:?} ({attempt}), reconnecting: {e:?}",
+                        self.address
+                    );
+                    self.stream = None;
+                    sleep(retry_delay);
+                    retry_delay = (2 * retry_delay).min(RETRY_MAX_TIMEOUT);
+                }
+            }
+        }
+    }
+}
+
+impl<F: Clone> Clone for SocketSink<F> {
+    fn clone(&self) -> Self {
+        Self {
+            address: self.address.clone(),
+            encoder: self.encoder.clone(),
+            max_attempts: self.max_attempts,
+            stream: None,
+            buffer: Vec::new(),
+        }
+    }
+}
+
+impl<T, F> WriteOperator<T> for SocketSink<F>
+where
+    T: Serialize,
+    F: Fn(&T, &mut Vec<u8>) + Clone + Send,
+{
+    type Destination = ();
+
+    fn setup(&mut self, _destination: ()) {}
+
+    fn write(&mut self, items: &mut impl Iterator<Item = T>) {
+        for item in items {
+            (self.encoder)(&item, &mut self.buffer);
+            if self.buffer.len() >= SEND_BUFFER_SIZE {
+                self.send();
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        self.send();
+    }
+
+    fn finalize(&mut self) {
+        self.send();
+        self.stream = None;
+    }
+}
+
+impl<Op: Operator> Stream<Op>
+where
+    Op: 'static,
+    Op::Out: Serialize,
+{
+    /// Write the items of the stream to a TCP endpoint using `sink`, see [`SocketSink`].
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::sink::SocketSink;
+    /// # let mut env = StreamContext::new_local();
+    /// env.stream_iter(0..100)
+    ///     .write_socket(SocketSink::json("127.0.0.1:9000"));
+    /// env.execute_blocking();
+    /// ```
+    pub fn write_socket<F>(self, sink: SocketSink<F>)
+    where
+        F: Fn(&Op::Out, &mut Vec<u8>) + Clone + Send + 'static,
+    {
+        let make_destination = |_: &ExecutionMetadata| ();
+
+        self.add_operator(|prev| {
+            WriterOperator::new(OutputQuota::new(prev), sink, make_destination)
+        })
+        .finalize_block();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::SocketSink;
+    use crate::operator::sink::writer::WriteOperator;
+
+    #[test]
+    fn socket_sink_json() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let mut content = String::new();
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.read_to_string(&mut content).unwrap();
+            content
+        });
+
+        let mut sink = SocketSink::json(address);
+        WriteOperator::<(u32, &str)>::setup(&mut sink, ());
+        sink.write(&mut [(1, "a"), (2, "b")].into_iter());
+        WriteOperator::<(u32, &str)>::flush(&mut sink);
+        sink.write(&mut [(3, "c")].into_iter());
+        WriteOperator::<(u32, &str)>::finalize(&mut sink);
+        drop(sink);
+
+        assert_eq!(server.join().unwrap(), "[1,\"a\"]\n[2,\"b\"]\n[3,\"c\"]\n");
+    }
+
+    #[test]
+    fn socket_sink_encoder() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let mut content = vec![];
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.read_to_end(&mut content).unwrap();
+            content
+        });
+
+        let mut sink = SocketSink::new(address, |n: &u16, buf: &mut Vec<u8>| {
+            buf.extend(n.to_be_bytes())
+        });
+        sink.write(&mut (1..3u16));
+        WriteOperator::<u16>::finalize(&mut sink);
+        drop(sink);
+
+        assert_eq!(server.join().unwrap(), vec![0, 1, 0, 2]);
+    }
+}
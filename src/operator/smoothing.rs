@@ -0,0 +1,395 @@
+//! Stateful operators for smoothing, tracking and monitoring numeric time series.
+//!
+//! Each accumulator keeps the state of a single series, the methods on [`KeyedStream`] keep a
+//! separate accumulator for each key.
+
+use std::collections::VecDeque;
+
+use crate::operator::{Data, DataKey, Operator};
+use crate::KeyedStream;
+
+/// The state of a numeric series, updated with each new value of the series.
+pub trait Accumulator: Clone + Send + 'static {
+    type Out: Data;
+
+    /// Add a new value to the series, returning the current output of the accumulator.
+    fn update(&mut self, value: f64) -> Self::Out;
+}
+
+/// Exponentially weighted moving average.
+///
+/// Each new value `x` moves the average `m` towards it: `m = m + alpha * (x - m)`. The first
+/// value of the series is taken as the initial average.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ewma {
+    alpha: f64,
+    mean: Option<f64>,
+}
+
+impl Ewma {
+    /// Create an average with smoothing factor `alpha`, in the range `(0, 1]`: the higher the
+    /// factor, the faster the older values are forgotten.
+    pub fn new(alpha: f64) -> Self {
+        assert!(
+            alpha > 0.0 && alpha <= 1.0,
+            "the smoothing factor of Ewma must be in (0, 1], got {alpha}"
+        );
+        Self { alpha, mean: None }
+    }
+
+    /// Create an average whose weights halve every `half_life` values.
+    pub fn with_half_life(half_life: f64) -> Self {
+        assert!(half_life > 0.0, "the half life of Ewma must be positive");
+        Self::new(1.0 - 0.5f64.powf(1.0 / half_life))
+    }
+
+    /// The current average, `None` if no value has been added.
+    pub fn mean(&self) -> Option<f64> {
+        self.mean
+    }
+}
+
+impl Accumulator for Ewma {
+    type Out = f64;
+
+    fn update(&mut self, value: f64) -> f64 {
+        let mean = match self.mean {
+            // the difference form avoids the cancellation of `alpha * x + (1 - alpha) * m`
+            Some(mean) => mean + self.alpha * (value - mean),
+            None => value,
+        };
+        self.mean = Some(mean);
+        mean
+    }
+}
+
+/// Maximum drawdown: the largest relative drop from a peak of the series to a later value.
+///
+/// The drop from the peak `p` to the value `x` is `(p - x) / p`, the output is the largest drop
+/// seen so far, between `0` and `1` for positive series. The drops from non-positive peaks are
+/// ignored.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MaxDrawdown {
+    peak: Option<f64>,
+    max_drawdown: f64,
+}
+
+impl MaxDrawdown {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Accumulator for MaxDrawdown {
+    type Out = f64;
+
+    fn update(&mut self, value: f64) -> f64 {
+        let peak = self.peak.map_or(value, |p| p.max(value));
+        self.peak = Some(peak);
+        if peak > 0.0 {
+            self.max_drawdown = self.max_drawdown.max((peak - value) / peak);
+        }
+        self.max_drawdown
+    }
+}
+
+/// Rate of change: the change of each value relative to the previous one, `(x - prev) / |prev|`.
+///
+/// The output is `None` for the first value of the series and when the previous value is zero.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RateOfChange {
+    prev: Option<f64>,
+}
+
+impl RateOfChange {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Accumulator for RateOfChange {
+    type Out = Option<f64>;
+
+    fn update(&mut self, value: f64) -> Option<f64> {
+        let rate = self
+            .prev
+            .filter(|&prev| prev != 0.0)
+            .map(|prev| (value - prev) / prev.abs());
+        self.prev = Some(value);
+        rate
+    }
+}
+
+/// Standard score of each value against the mean and standard deviation of the last `window`
+/// values of the series: `(x - mean) / std`.
+///
+/// The output is `None` until the window holds at least two values, and infinite when the window
+/// is constant and the value differs from it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ZScore {
+    window: usize,
+    values: VecDeque<f64>,
+}
+
+impl ZScore {
+    pub fn new(window: usize) -> Self {
+        assert!(
+            window >= 2,
+            "the window of ZScore must hold at least two values"
+        );
+        Self {
+            window,
+            values: VecDeque::with_capacity(window),
+        }
+    }
+}
+
+/// Distance of `value` from `center` in units of `spread`, infinite if the spread is zero.
+fn score(value: f64, center: f64, spread: f64) -> f64 {
+    if spread > 0.0 {
+        (value - center) / spread
+    } else if value == center {
+        0.0
+    } else {
+        f64::INFINITY.copysign(value - center)
+    }
+}
+
+/// Push `value` in the rolling `window`, dropping the oldest value if it is full.
+fn push_window(values: &mut VecDeque<f64>, window: usize, value: f64) {
+    if values.len() == window {
+        values.pop_front();
+    }
+    values.push_back(value);
+}
+
+impl Accumulator for ZScore {
+    type Out = Option<f64>;
+
+    fn update(&mut self, value: f64) -> Option<f64> {
+        let res = (self.values.len() >= 2).then(|| {
+            let n = self.values.len() as f64;
+            let mean = self.values.iter().sum::<f64>() / n;
+            // two passes over the window: the sum of squares minus the squared sum is unstable
+            let var = self.values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0);
+            score(value, mean, var.sqrt())
+        });
+        push_window(&mut self.values, self.window, value);
+        res
+    }
+}
+
+/// Robust score of each value against the median and the median absolute deviation (MAD) of the
+/// last `window` values of the series: `0.6745 * (x - median) / MAD`.
+///
+/// The constant makes the score comparable with [`ZScore`] for normally distributed values, while
+/// the median makes it insensitive to the outliers in the window. The output is `None` until the
+/// window holds at least two values, and infinite when more than half of the window is equal to
+/// the median and the value differs from it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MadScore {
+    window: usize,
+    values: VecDeque<f64>,
+}
+
+impl MadScore {
+    pub fn new(window: usize) -> Self {
+        assert!(
+            window >= 2,
+            "the window of MadScore must hold at least two values"
+        );
+        Self {
+            window,
+            values: VecDeque::with_capacity(window),
+        }
+    }
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_unstable_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+impl Accumulator for MadScore {
+    type Out = Option<f64>;
+
+    fn update(&mut self, value: f64) -> Option<f64> {
+        let res = (self.values.len() >= 2).then(|| {
+            let mut values: Vec<_> = self.values.iter().copied().collect();
+            let center = median(&mut values);
+            values.iter_mut().for_each(|x| *x = (*x - center).abs());
+            let mad = median(&mut values);
+            0.6745 * score(value, center, mad)
+        });
+        push_window(&mut self.values, self.window, value);
+        res
+    }
+}
+
+impl<K, I, Op> KeyedStream<Op>
+where
+    K: DataKey,
+    I: Into<f64> + Send + 'static,
+    Op: Operator<Out = (K, I)> + 'static,
+{
+    /// Replace each value with the output of an [`Accumulator`] that has seen all the previous
+    /// values of the same key.
+    ///
+    /// The accumulator is cloned for each key, the values of each key are processed in the order
+    /// they arrive.
+    pub fn accumulate<A: Accumulator>(
+        self,
+        mut acc: A,
+    ) -> KeyedStream<impl Operator<Out = (K, A::Out)>> {
+        self.rich_map(move |(_, value): (&K, I)| acc.update(value.into()))
+    }
+
+    /// Replace each value with the exponentially weighted moving average of the values of its key,
+    /// see [`Ewma`].
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # let mut env = StreamContext::new(RuntimeConfig::local(1).unwrap());
+    /// let s = env.stream_iter(vec![(0, 2.0), (1, 1.0), (0, 4.0)].into_iter());
+    /// let res = s.group_by(|&(k, _)| k).map(|(_, (_, v))| v).ewma(0.5).collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let mut res = res.get().unwrap();
+    /// res.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    /// assert_eq!(res, vec![(0, 2.0), (0, 3.0), (1, 1.0)]);
+    /// ```
+    pub fn ewma(self, alpha: f64) -> KeyedStream<impl Operator<Out = (K, f64)>> {
+        self.accumulate(Ewma::new(alpha))
+    }
+
+    /// Replace each value with the maximum drawdown of the values of its key up to it, see
+    /// [`MaxDrawdown`].
+    pub fn max_drawdown(self) -> KeyedStream<impl Operator<Out = (K, f64)>> {
+        self.accumulate(MaxDrawdown::new())
+    }
+
+    /// Replace each value with its change relative to the previous value of the same key, see
+    /// [`RateOfChange`].
+    pub fn rate_of_change(self) -> KeyedStream<impl Operator<Out = (K, Option<f64>)>> {
+        self.accumulate(RateOfChange::new())
+    }
+
+    /// Tag each value with whether it is an anomaly: a value whose score, computed by `scorer`
+    /// from the previous values of its key, exceeds `threshold` in absolute value.
+    ///
+    /// The values without a score (the first ones of each key) are never anomalies.
+    pub fn tag_anomalies<A>(
+        self,
+        mut scorer: A,
+        threshold: f64,
+    ) -> KeyedStream<impl Operator<Out = (K, (I, bool))>>
+    where
+        I: Data + Copy,
+        A: Accumulator<Out = Option<f64>>,
+    {
+        self.rich_map(move |(_, value): (&K, I)| {
+            let anomaly = scorer
+                .update(value.into())
+                .is_some_and(|score| score.abs() > threshold);
+            (value, anomaly)
+        })
+    }
+
+    /// Tag each value with whether its [`ZScore`] against the previous `window` values of its key
+    /// exceeds `threshold` in absolute value.
+    ///
+    /// The anomalies can be separated from the other values with
+    /// [`split`](KeyedStream::split) or [`filter`](KeyedStream::filter).
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # let mut env = StreamContext::new(RuntimeConfig::local(1).unwrap());
+    /// let s = env.stream_iter(vec![10.0, 11.0, 9.0, 10.0, 50.0, 10.0].into_iter());
+    /// let res = s
+    ///     .group_by(|_| ())
+    ///     .detect_anomalies(4, 3.0)
+    ///     .filter(|(_, (_, anomaly))| *anomaly)
+    ///     .drop_key()
+    ///     .collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// assert_eq!(res.get().unwrap(), vec![(50.0, true)]);
+    /// ```
+    pub fn detect_anomalies(
+        self,
+        window: usize,
+        threshold: f64,
+    ) -> KeyedStream<impl Operator<Out = (K, (I, bool))>>
+    where
+        I: Data + Copy,
+    {
+        self.tag_anomalies(ZScore::new(window), threshold)
+    }
+
+    /// Like [`detect_anomalies`](KeyedStream::detect_anomalies), but using the robust
+    /// [`MadScore`], which is not skewed by the anomalies in the window.
+    pub fn detect_anomalies_mad(
+        self,
+        window: usize,
+        threshold: f64,
+    ) -> KeyedStream<impl Operator<Out = (K, (I, bool))>>
+    where
+        I: Data + Copy,
+    {
+        self.tag_anomalies(MadScore::new(window), threshold)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Accumulator, Ewma, MadScore, MaxDrawdown, RateOfChange, ZScore};
+
+    fn run<A: Accumulator>(mut acc: A, values: &[f64]) -> Vec<A::Out> {
+        values.iter().map(|&v| acc.update(v)).collect()
+    }
+
+    #[test]
+    fn smoothing_accumulators() {
+        assert_eq!(run(Ewma::new(0.5), &[4.0, 0.0, 2.0]), vec![4.0, 2.0, 2.0]);
+        assert_eq!(run(Ewma::new(1.0), &[4.0, 0.0]), vec![4.0, 0.0]);
+        let half = Ewma::with_half_life(1.0);
+        assert_eq!(run(half, &[0.0, 8.0]), vec![0.0, 4.0]);
+
+        assert_eq!(
+            run(MaxDrawdown::new(), &[10.0, 5.0, 20.0, 15.0, 4.0]),
+            vec![0.0, 0.5, 0.5, 0.5, 0.8]
+        );
+
+        assert_eq!(
+            run(RateOfChange::new(), &[2.0, 3.0, 0.0, 1.0, -2.0]),
+            vec![None, Some(0.5), Some(-1.0), None, Some(-3.0)]
+        );
+    }
+
+    #[test]
+    fn anomaly_scores() {
+        let z = run(ZScore::new(3), &[1.0, 3.0, 2.0, 5.0]);
+        assert_eq!(z, vec![None, None, Some(0.0), Some(3.0)]);
+
+        let z = run(ZScore::new(2), &[1.0, 1.0, 1.0, 0.0]);
+        assert_eq!(z, vec![None, None, Some(0.0), Some(f64::NEG_INFINITY)]);
+
+        let mad = run(MadScore::new(5), &[1.0, 2.0, 3.0, 100.0, 2.0, 6.0]);
+        assert_eq!(mad[..2], [None, None]);
+        assert_eq!(mad[2], Some(0.6745 * 3.0));
+        assert_eq!(mad[4], Some(0.6745 * -0.5));
+        // the outlier in the window does not inflate the spread
+        assert_eq!(mad[5], Some(0.6745 * 4.0));
+    }
+}
@@ -2,7 +2,7 @@ use std::fmt::Display;
 
 use futures::{Stream, StreamExt};
 
-use crate::block::{BlockStructure, OperatorKind, OperatorStructure, Replication};
+use crate::block::{BlockStructure, OperatorKind, OperatorStructure, Replication, TimestampUsage};
 use crate::operator::source::Source;
 use crate::operator::{Operator, StreamElement};
 use crate::scheduler::ExecutionMetadata;
@@ -100,6 +100,7 @@ where
     fn structure(&self) -> BlockStructure {
         let mut operator = OperatorStructure::new::<Self::Out, _>("AsyncStreamSource");
         operator.kind = OperatorKind::Source;
+        operator.timestamps = TimestampUsage::Drop;
         BlockStructure::default().add_operator(operator)
     }
 }
@@ -1,13 +1,14 @@
+use std::collections::VecDeque;
 use std::fmt::Display;
 use std::fs::File;
-use std::io::{BufReader, Read};
+use std::io::{self, BufReader, Cursor, Read, Seek, SeekFrom};
 use std::path::PathBuf;
 
 use apache_avro::types::Value;
 use apache_avro::{from_value, Reader};
 use serde::Deserialize;
 
-use crate::block::{BlockStructure, OperatorKind, OperatorStructure, Replication};
+use crate::block::{BlockStructure, OperatorKind, OperatorStructure, Replication, TimestampUsage};
 use crate::operator::source::Source;
 use crate::operator::{Operator, StreamElement};
 use crate::scheduler::ExecutionMetadata;
@@ -18,21 +19,170 @@ pub trait MakeReader: Send + Clone {
     fn make_reader(&self, index: CoordUInt, peers: CoordUInt) -> Self::Reader;
 }
 
+/// Length of the sync marker that follows each block of an Avro object container file.
+const SYNC_SIZE: i64 = 16;
+
+/// Opens an Avro object container file, giving to each replica a share of its blocks.
 #[derive(Clone)]
 pub struct MakeFileReader {
     path: PathBuf,
 }
 
 impl MakeReader for MakeFileReader {
-    type Reader = BufReader<File>;
-    fn make_reader(&self, _: CoordUInt, _: CoordUInt) -> Self::Reader {
-        let file = File::options()
-            .read(true)
-            .write(false)
-            .open(&self.path)
-            .expect("could not open file");
-        BufReader::new(file)
+    type Reader = AvroBlocksReader;
+    fn make_reader(&self, index: CoordUInt, peers: CoordUInt) -> Self::Reader {
+        let open = || {
+            File::options()
+                .read(true)
+                .write(false)
+                .open(&self.path)
+                .expect("could not open file")
+        };
+        let mut file = BufReader::new(open());
+        let (header, blocks) = scan_container(&mut file).unwrap_or_else(|e| {
+            panic!("failed to read avro container file {:?}: {e:?}", self.path)
+        });
+
+        // each replica reads a contiguous range of blocks
+        let start = blocks.len() * index as usize / peers as usize;
+        let end = blocks.len() * (index as usize + 1) / peers as usize;
+        let ranges = blocks[start..end].iter().copied().collect();
+
+        file.rewind().expect("could not rewind file");
+        let mut header_bytes = vec![0; header as usize];
+        file.read_exact(&mut header_bytes)
+            .expect("failed to read avro header");
+
+        AvroBlocksReader {
+            header: Cursor::new(header_bytes),
+            file,
+            ranges,
+            remaining: 0,
+        }
+    }
+}
+
+/// Reader of the header of an Avro object container file followed by some of its blocks, that
+/// form a valid container file with their records.
+pub struct AvroBlocksReader {
+    header: Cursor<Vec<u8>>,
+    file: BufReader<File>,
+    /// The byte ranges of the blocks still to be read.
+    ranges: VecDeque<(u64, u64)>,
+    /// Bytes left in the current block.
+    remaining: u64,
+}
+
+impl Read for AvroBlocksReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.header.read(buf)?;
+        if n > 0 {
+            return Ok(n);
+        }
+        while self.remaining == 0 {
+            let Some((start, end)) = self.ranges.pop_front() else {
+                return Ok(0);
+            };
+            self.file.seek(SeekFrom::Start(start))?;
+            self.remaining = end - start;
+        }
+        let len = buf.len().min(self.remaining as usize);
+        let n = self.file.read(&mut buf[..len])?;
+        if n == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        self.remaining -= n as u64;
+        Ok(n)
+    }
+}
+
+/// Read a zig-zag encoded long, returning `None` at the end of the file.
+fn read_long(r: &mut impl Read) -> io::Result<Option<i64>> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    loop {
+        let mut byte = [0u8];
+        if r.read(&mut byte)? == 0 {
+            return if shift == 0 {
+                Ok(None)
+            } else {
+                Err(io::ErrorKind::UnexpectedEof.into())
+            };
+        }
+        value |= ((byte[0] & 0x7f) as u64) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(Some((value >> 1) as i64 ^ -((value & 1) as i64)));
+        }
+        shift += 7;
+        if shift > 63 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid long"));
+        }
+    }
+}
+
+fn expect_long(r: &mut impl Read) -> io::Result<i64> {
+    read_long(r)?.ok_or_else(|| io::ErrorKind::UnexpectedEof.into())
+}
+
+/// Skip `len` bytes, checking that they are not past the end of the file.
+fn skip(r: &mut BufReader<File>, len: i64) -> io::Result<()> {
+    if len < 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "negative length",
+        ));
+    }
+    let pos = r.stream_position()? + len as u64;
+    if pos > r.get_ref().metadata()?.len() {
+        return Err(io::ErrorKind::UnexpectedEof.into());
     }
+    r.seek_relative(len)
+}
+
+/// Find the size of the header of an Avro object container file and the byte ranges of its
+/// blocks, without decoding them.
+fn scan_container(r: &mut BufReader<File>) -> io::Result<(u64, Vec<(u64, u64)>)> {
+    let mut magic = [0u8; 4];
+    r.read_exact(&mut magic)?;
+    if &magic != b"Obj\x01" {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not an avro container file",
+        ));
+    }
+    // the metadata is a map of bytes, encoded as a sequence of blocks of entries
+    loop {
+        let count = expect_long(r)?;
+        if count == 0 {
+            break;
+        }
+        if count < 0 {
+            // the block is prefixed by its size in bytes
+            let size = expect_long(r)?;
+            skip(r, size)?;
+            continue;
+        }
+        for _ in 0..count {
+            let key = expect_long(r)?;
+            skip(r, key)?;
+            let value = expect_long(r)?;
+            skip(r, value)?;
+        }
+    }
+    skip(r, SYNC_SIZE)?;
+    let header = r.stream_position()?;
+
+    let mut blocks = Vec::new();
+    loop {
+        let start = r.stream_position()?;
+        if read_long(r)?.is_none() {
+            break;
+        }
+        let size = expect_long(r)?;
+        skip(r, size + SYNC_SIZE)?;
+        blocks.push((start, r.stream_position()?));
+    }
+    Ok((header, blocks))
 }
 
 impl<T, R> MakeReader for T
@@ -62,6 +212,11 @@ impl<R: MakeReader> Display for AvroSource<R> {
 }
 
 impl AvroSource<MakeFileReader> {
+    /// Create a new source that reads the records of an Avro object container file.
+    ///
+    /// The records are decoded using the schema embedded in the file. With more than one replica
+    /// the blocks of the file are divided between them, so each replica has to have the **same**
+    /// file in the same path.
     pub fn from_file<P: Into<PathBuf> + Send + Clone>(replication: Replication, path: P) -> Self {
         Self {
             replication,
@@ -127,6 +282,7 @@ impl<R: MakeReader> Operator for AvroSource<R> {
     fn structure(&self) -> BlockStructure {
         let mut operator = OperatorStructure::new::<Value, _>("AvroSource");
         operator.kind = OperatorKind::Source;
+        operator.timestamps = TimestampUsage::Drop;
         BlockStructure::default().add_operator(operator)
     }
 }
@@ -183,3 +339,71 @@ where
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use apache_avro::{AvroSchema, Writer};
+    use itertools::Itertools;
+    use serde::{Deserialize, Serialize};
+
+    use crate::config::RuntimeConfig;
+    use crate::environment::StreamContext;
+    use crate::operator::source::MakeReader;
+    use crate::Replication;
+
+    use super::MakeFileReader;
+
+    #[derive(Clone, Debug, Serialize, Deserialize, AvroSchema)]
+    struct Record {
+        num: i64,
+        s: String,
+    }
+
+    fn write_container(path: &std::path::Path, records: i64, block_len: i64) {
+        let schema = Record::get_schema();
+        let file = std::fs::File::create(path).unwrap();
+        let mut writer = Writer::new(&schema, file);
+        writer
+            .add_user_metadata("created_by".into(), b"test")
+            .unwrap();
+        for num in 0..records {
+            let s = format!("{num:o}");
+            writer.append_ser(Record { num, s }).unwrap();
+            if num % block_len == block_len - 1 {
+                writer.flush().unwrap();
+            }
+        }
+        writer.flush().unwrap();
+    }
+
+    #[test]
+    fn avro_file_blocks() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.avro");
+        write_container(&path, 95, 10);
+
+        let make = MakeFileReader { path: path.clone() };
+        let counts = (0..3)
+            .map(|i| {
+                apache_avro::Reader::new(make.make_reader(i, 3))
+                    .unwrap()
+                    .count()
+            })
+            .collect_vec();
+        assert_eq!(counts, vec![30, 30, 35]);
+
+        for replicas in [1, 4, 16] {
+            let env = StreamContext::new(RuntimeConfig::local(replicas).unwrap());
+            let res = env
+                .stream_avro_file(Replication::Unlimited, &path)
+                .from_avro_value::<Record>()
+                .collect_vec();
+            env.execute_blocking();
+
+            let res = res.get().unwrap();
+            assert!(res.iter().all(|r| r.s == format!("{:o}", r.num)));
+            let nums = res.into_iter().map(|r| r.num).sorted().collect_vec();
+            assert_eq!(nums, (0..95).collect_vec());
+        }
+    }
+}
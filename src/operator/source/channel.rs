@@ -2,7 +2,7 @@ use std::fmt::Display;
 
 use flume::{bounded, Receiver, RecvError, Sender, TryRecvError};
 
-use crate::block::{BlockStructure, OperatorKind, OperatorStructure, Replication};
+use crate::block::{BlockStructure, OperatorKind, OperatorStructure, Replication, TimestampUsage};
 use crate::operator::source::Source;
 use crate::operator::{Operator, StreamElement};
 use crate::scheduler::ExecutionMetadata;
@@ -117,6 +117,7 @@ impl<Out: Send + core::fmt::Debug> Operator for ChannelSource<Out> {
     fn structure(&self) -> BlockStructure {
         let mut operator = OperatorStructure::new::<Out, _>("ChannelSource");
         operator.kind = OperatorKind::Source;
+        operator.timestamps = TimestampUsage::Drop;
         BlockStructure::default().add_operator(operator)
     }
 }
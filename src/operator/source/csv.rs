@@ -3,12 +3,17 @@ use std::fs::File;
 use std::io;
 use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
 use std::marker::PhantomData;
-use std::path::PathBuf;
+use std::ops::{Bound, RangeBounds};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use csv::{ByteRecord, Reader, ReaderBuilder, Terminator, Trim};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::block::{BlockStructure, OperatorKind, OperatorStructure, Replication};
+use crate::block::{BlockStructure, OperatorKind, OperatorStructure, Replication, TimestampUsage};
+use crate::operator::source::file_set::{open_file, FileFormat};
+use crate::operator::source::object_store::{ObjectReader, ObjectStore};
+use crate::operator::source::reader::SharedReader;
 use crate::operator::source::Source;
 use crate::operator::{Data, Operator, StreamElement};
 use crate::scheduler::ExecutionMetadata;
@@ -25,6 +30,10 @@ impl<R: Read> LimitedReader<R> {
     fn new(inner: R, remaining: usize) -> Self {
         Self { inner, remaining }
     }
+
+    fn into_inner(self) -> R {
+        self.inner
+    }
 }
 
 impl<R: Read> Read for LimitedReader<R> {
@@ -41,6 +50,79 @@ impl<R: Read> Read for LimitedReader<R> {
     }
 }
 
+/// Where a CSV file is read from.
+#[derive(Clone, Debug)]
+enum CsvLocation {
+    /// A file in the local file system.
+    File(PathBuf),
+    /// An object of an object store, with its URI.
+    Object(Arc<dyn ObjectStore>, String),
+    /// A reader, read sequentially by a single replica.
+    Reader(SharedReader),
+}
+
+impl CsvLocation {
+    /// Open the file, returning the reader and the size of the file.
+    ///
+    /// The size of a reader is unknown, `u64::MAX` is returned instead.
+    fn open(&self) -> (CsvInput, u64) {
+        let opened = match self {
+            CsvLocation::File(path) => File::open(path).and_then(|file| {
+                let size = file.metadata()?.len();
+                Ok((CsvInput::File(file), size))
+            }),
+            CsvLocation::Object(store, uri) => {
+                ObjectReader::new(store.clone(), uri.clone()).map(|reader| {
+                    let size = reader.size();
+                    (CsvInput::Object(reader), size)
+                })
+            }
+            CsvLocation::Reader(reader) => Ok((CsvInput::Reader(reader.take()), u64::MAX)),
+        };
+        opened.unwrap_or_else(|err| panic!("CsvSource: error while opening {self}: {err:?}"))
+    }
+}
+
+impl Display for CsvLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CsvLocation::File(path) => write!(f, "{}", path.display()),
+            CsvLocation::Object(_, uri) => write!(f, "{uri}"),
+            CsvLocation::Reader(_) => write!(f, "<reader>"),
+        }
+    }
+}
+
+/// The reader of a CSV file opened from a [`CsvLocation`].
+enum CsvInput {
+    File(File),
+    Object(ObjectReader),
+    Reader(Box<dyn Read + Send>),
+}
+
+impl Read for CsvInput {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            CsvInput::File(file) => file.read(buf),
+            CsvInput::Object(reader) => reader.read(buf),
+            CsvInput::Reader(reader) => reader.read(buf),
+        }
+    }
+}
+
+impl Seek for CsvInput {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match self {
+            CsvInput::File(file) => file.seek(pos),
+            CsvInput::Object(reader) => reader.seek(pos),
+            CsvInput::Reader(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "a reader cannot be seeked",
+            )),
+        }
+    }
+}
+
 /// Options for the CSV parser.
 #[derive(Clone)]
 struct CsvOptions {
@@ -66,6 +148,25 @@ struct CsvOptions {
     has_headers: bool,
 }
 
+impl CsvOptions {
+    /// A `ReaderBuilder` configured with these options.
+    fn builder(&self) -> ReaderBuilder {
+        let mut builder = ReaderBuilder::new();
+        builder
+            .comment(self.comment)
+            .delimiter(self.delimiter)
+            .double_quote(self.double_quote)
+            .escape(self.escape)
+            .flexible(self.flexible)
+            .quote(self.quote)
+            .quoting(self.quoting)
+            .terminator(self.terminator)
+            .trim(self.trim)
+            .has_headers(self.has_headers);
+        builder
+    }
+}
+
 impl Default for CsvOptions {
     fn default() -> Self {
         Self {
@@ -83,16 +184,145 @@ impl Default for CsvOptions {
     }
 }
 
+/// A chunk of consecutive records of a CSV file, with the minimum and maximum values of the
+/// indexed columns.
+///
+/// The zones are built by [`CsvSource::build_zone_map`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CsvZone {
+    /// Offset of the first byte of the chunk.
+    start: u64,
+    /// Offset of the first byte after the chunk.
+    end: u64,
+    /// Number of records in the chunk.
+    rows: usize,
+    /// For each indexed column, the minimum and maximum of its values, or `None` if some of them
+    /// are not numbers.
+    bounds: Vec<(usize, Option<(f64, f64)>)>,
+}
+
+impl CsvZone {
+    fn new(start: u64, columns: &[usize]) -> Self {
+        Self {
+            start,
+            end: start,
+            rows: 0,
+            bounds: columns
+                .iter()
+                .map(|&c| (c, Some((f64::INFINITY, f64::NEG_INFINITY))))
+                .collect(),
+        }
+    }
+
+    fn add(&mut self, record: &ByteRecord) {
+        self.rows += 1;
+        for (column, bounds) in self.bounds.iter_mut() {
+            let value = record
+                .get(*column)
+                .and_then(|field| std::str::from_utf8(field).ok())
+                .and_then(|field| field.trim().parse::<f64>().ok())
+                .filter(|value| !value.is_nan());
+            *bounds = match (*bounds, value) {
+                (Some((min, max)), Some(value)) => Some((min.min(value), max.max(value))),
+                _ => None,
+            };
+        }
+    }
+
+    /// The number of records in the chunk.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// The minimum and maximum values of the field at index `column` inside the chunk.
+    ///
+    /// Returns `None` if the column is not indexed or some of its values are not numbers.
+    pub fn bounds(&self, column: usize) -> Option<(f64, f64)> {
+        self.bounds
+            .iter()
+            .find(|(c, _)| *c == column)
+            .and_then(|(_, bounds)| *bounds)
+    }
+
+    /// Whether the chunk may contain a record whose field at index `column` is inside `range`.
+    ///
+    /// If the bounds of the column are not known this is always `true`.
+    pub fn may_contain(&self, column: usize, range: impl RangeBounds<f64>) -> bool {
+        let Some((min, max)) = self.bounds(column) else {
+            return true;
+        };
+        let above_start = match range.start_bound() {
+            Bound::Included(&start) => max >= start,
+            Bound::Excluded(&start) => max > start,
+            Bound::Unbounded => true,
+        };
+        let below_end = match range.end_bound() {
+            Bound::Included(&end) => min <= end,
+            Bound::Excluded(&end) => min < end,
+            Bound::Unbounded => true,
+        };
+        above_start && below_end
+    }
+}
+
+/// Index of a CSV file, dividing it into chunks that record the minimum and maximum values of
+/// some numeric columns.
+///
+/// The index is built with a pass over the whole file by [`CsvSource::build_zone_map`], and can
+/// be stored next to the file with [`CsvZoneMap::save`] so that it is computed only once. A
+/// [`CsvSource`] using the index with [`CsvSource::zone_map`] skips the chunks that cannot
+/// contain the records it is looking for.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CsvZoneMap {
+    /// The size of the indexed file, used for detecting a stale index.
+    file_size: u64,
+    zones: Vec<CsvZone>,
+}
+
+impl CsvZoneMap {
+    /// The chunks of the file, in order.
+    pub fn zones(&self) -> &[CsvZone] {
+        &self.zones
+    }
+
+    /// Write the index to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let file = File::create(path)?;
+        serde_json::to_writer(io::BufWriter::new(file), self)?;
+        Ok(())
+    }
+
+    /// Read an index previously written with [`CsvZoneMap::save`].
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+        Ok(serde_json::from_reader(BufReader::new(file))?)
+    }
+}
+
+/// The chunks of the file selected by the zone map.
+#[derive(Clone, Debug)]
+struct CsvChunks {
+    file_size: u64,
+    ranges: Vec<(u64, u64)>,
+}
+
 /// Source that reads and parses a CSV file.
 ///
-/// The file is divided in chunks and is read concurrently by multiple replicas.
+/// The file is divided in chunks and is read concurrently by multiple replicas. The file can be
+/// either a local file or an object of an [`ObjectStore`], see [`CsvSource::from_object_store`].
 pub struct CsvSource<Out: Data + for<'a> Deserialize<'a>> {
-    /// Path of the file.
-    path: PathBuf,
+    /// Where the file is read from.
+    location: CsvLocation,
     /// Reader used to parse the CSV file.
-    csv_reader: Option<Reader<LimitedReader<BufReader<File>>>>,
+    csv_reader: Option<Reader<LimitedReader<BufReader<CsvInput>>>>,
     /// Options to customize the CSV parser.
     options: CsvOptions,
+    /// The chunks to read, if only some of them can contain the records to emit.
+    chunks: Option<Arc<CsvChunks>>,
+    /// Header of the file, set to each new reader.
+    header: Option<ByteRecord>,
+    /// Byte ranges still to be read by this replica, in reverse order.
+    ranges: Vec<(u64, u64)>,
     /// Whether the reader has terminated its job.
     terminated: bool,
     _out: PhantomData<Out>,
@@ -139,10 +369,60 @@ impl<Out: Data + for<'a> Deserialize<'a>> CsvSource<Out> {
     /// let s = env.stream(source);
     /// ```
     pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self::with_location(CsvLocation::File(path.into()))
+    }
+
+    /// Create a new source that reads and parse the lines of a CSV object from an object store.
+    ///
+    /// The object is partitioned between the replicas like a local file: each replica reads only
+    /// its byte range, using ranged requests to the store. All the replicas must be able to reach
+    /// the store, so the dataset doesn't need to be copied to every host.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::{CsvSource, HttpObjectStore};
+    /// # let mut env = StreamContext::new_local();
+    /// let store = HttpObjectStore::new().s3_endpoint("http://minio.local:9000");
+    /// let source = CsvSource::<(String, u64)>::from_object_store(store, "s3://datasets/huge.csv");
+    /// let s = env.stream(source);
+    /// ```
+    pub fn from_object_store<S: ObjectStore>(store: S, uri: impl Into<String>) -> Self {
+        Self::with_location(CsvLocation::Object(Arc::new(store), uri.into()))
+    }
+
+    /// Create a new source that reads and parse the lines of a CSV from any reader, like the
+    /// standard input, a pipe or an in-memory buffer.
+    ///
+    /// A reader can only be read sequentially, so it is not partitioned: the source has a single
+    /// replica and zone maps cannot be used.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::CsvSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let source = CsvSource::<(char, u64)>::from_reader(&b"what,count\na,1\nb,2\n"[..]);
+    /// let res = env.stream(source).collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// assert_eq!(res.get().unwrap(), vec![('a', 1), ('b', 2)]);
+    /// ```
+    pub fn from_reader(reader: impl Read + Send + 'static) -> Self {
+        Self::with_location(CsvLocation::Reader(SharedReader::new(reader)))
+    }
+
+    fn with_location(location: CsvLocation) -> Self {
         Self {
-            path: path.into(),
+            location,
             csv_reader: None,
             options: Default::default(),
+            chunks: None,
+            header: None,
+            ranges: Default::default(),
             terminated: false,
             _out: PhantomData,
             buf: ByteRecord::new(),
@@ -258,11 +538,150 @@ impl<Out: Data + for<'a> Deserialize<'a>> CsvSource<Out> {
         self.options.has_headers = has_headers;
         self
     }
+
+    /// Index the file, dividing it into chunks of `chunk_rows` records and recording the minimum
+    /// and maximum values of the fields at the indices in `columns`.
+    ///
+    /// This reads the whole file using the current options of the parser, which should be set
+    /// before building the index. A column whose values are not all numbers is not indexed in the
+    /// chunks containing the other values.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use renoir::operator::source::{CsvSource, CsvZoneMap};
+    /// let source = CsvSource::<(String, f64)>::new("/datasets/huge.csv");
+    /// let zone_map = source.build_zone_map(&[1], 10_000);
+    /// zone_map.save("/datasets/huge.csv.zones").unwrap();
+    /// ```
+    pub fn build_zone_map(&self, columns: &[usize], chunk_rows: usize) -> CsvZoneMap {
+        assert!(
+            chunk_rows > 0,
+            "CsvSource: chunks must contain at least one row"
+        );
+        assert!(
+            !matches!(self.location, CsvLocation::Reader(_)),
+            "CsvSource: cannot build the zone map of a reader"
+        );
+        let (file, file_size) = self.location.open();
+        let mut csv_reader = self.options.builder().from_reader(BufReader::new(file));
+
+        let mut zones = Vec::new();
+        let mut current: Option<CsvZone> = None;
+        let mut record = ByteRecord::new();
+        while csv_reader
+            .read_byte_record(&mut record)
+            .unwrap_or_else(|e| panic!("Error while reading CSV file: {:?}", e))
+        {
+            let position = record.position().unwrap().byte();
+            if current.as_ref().is_some_and(|z| z.rows == chunk_rows) {
+                let mut zone = current.take().unwrap();
+                zone.end = position;
+                zones.push(zone);
+            }
+            current
+                .get_or_insert_with(|| CsvZone::new(position, columns))
+                .add(&record);
+        }
+        if let Some(mut zone) = current {
+            zone.end = file_size;
+            zones.push(zone);
+        }
+
+        CsvZoneMap { file_size, zones }
+    }
+
+    /// Read only the chunks of the file for which `filter` returns `true`.
+    ///
+    /// The filter receives the zones of a `zone_map` built for this file, and should return
+    /// `false` only if the chunk cannot contain any record that is needed; the records of the
+    /// selected chunks are all emitted, so the stream should still be filtered afterwards. The
+    /// selected chunks are divided between the replicas.
+    ///
+    /// **Note**: the zone map must be built for the current content of the file, the source panics
+    /// if the size of the file has changed.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::{CsvSource, CsvZoneMap};
+    /// # let mut env = StreamContext::new_local();
+    /// let zone_map = CsvZoneMap::load("/datasets/huge.csv.zones").unwrap();
+    /// let source = CsvSource::<(String, f64)>::new("/datasets/huge.csv")
+    ///     .zone_map(&zone_map, |zone| zone.may_contain(1, 100.0..));
+    /// let s = env.stream(source).filter(|(_, value)| *value >= 100.0);
+    /// ```
+    pub fn zone_map(mut self, zone_map: &CsvZoneMap, filter: impl Fn(&CsvZone) -> bool) -> Self {
+        // the zones are kept separate, so that they can be divided between the replicas
+        let ranges: Vec<(u64, u64)> = zone_map
+            .zones
+            .iter()
+            .filter(|zone| filter(zone))
+            .map(|zone| (zone.start, zone.end))
+            .collect();
+        log::debug!(
+            "CsvSource: reading {} zones of {} selected by the zone map",
+            ranges.len(),
+            self.location
+        );
+        self.chunks = Some(Arc::new(CsvChunks {
+            file_size: zone_map.file_size,
+            ranges,
+        }));
+        self
+    }
+
+    /// Start reading the byte range from `start` to `end`.
+    fn open_range(&mut self, mut buf_reader: BufReader<CsvInput>, (start, end): (u64, u64)) {
+        buf_reader
+            .seek(SeekFrom::Start(start))
+            .expect("Error while seeking BufReader to start");
+
+        // Limit the number of bytes to be read
+        self.set_reader(LimitedReader::new(buf_reader, (end - start) as usize));
+    }
+
+    fn set_reader(&mut self, limited_reader: LimitedReader<BufReader<CsvInput>>) {
+        let mut csv_reader = self.options.builder().from_reader(limited_reader);
+        if let Some(header) = &self.header {
+            // set the headers of the CSV file
+            csv_reader.set_byte_headers(header.clone());
+        }
+        self.csv_reader = Some(csv_reader);
+    }
+}
+
+/// Divide the `ranges` of a file into `instances` parts of similar size, returning the part with
+/// index `global_id`.
+///
+/// The ranges are not split, the adjacent ranges that end up in the same part are merged.
+fn split_ranges(ranges: &[(u64, u64)], global_id: u64, instances: u64) -> Vec<(u64, u64)> {
+    let total: u64 = ranges.iter().map(|(start, end)| end - start).sum();
+    let part_start = total * global_id / instances;
+    let part_end = total * (global_id + 1) / instances;
+
+    // each range belongs to the part containing its first byte, so that it is read only once
+    let mut offset = 0;
+    let mut part: Vec<(u64, u64)> = Vec::new();
+    for &(start, end) in ranges {
+        if (part_start..part_end).contains(&offset) {
+            match part.last_mut() {
+                Some((_, last_end)) if *last_end == start => *last_end = end,
+                _ => part.push((start, end)),
+            }
+        }
+        offset += end - start;
+    }
+    part
 }
 
 impl<Out: Data + for<'a> Deserialize<'a>> Source for CsvSource<Out> {
     fn replication(&self) -> Replication {
-        Replication::Unlimited
+        match self.location {
+            CsvLocation::Reader(_) => Replication::One,
+            _ => Replication::Unlimited,
+        }
     }
 }
 
@@ -273,19 +692,7 @@ impl<Out: Data + for<'a> Deserialize<'a>> Operator for CsvSource<Out> {
         let global_id = metadata.global_id;
         let instances = metadata.replicas.len();
 
-        let file = File::options()
-            .read(true)
-            .write(false)
-            .open(&self.path)
-            .unwrap_or_else(|err| {
-                panic!(
-                    "CsvSource: error while opening file {:?}: {:?}",
-                    self.path, err
-                )
-            });
-
-        let file_size = file.metadata().unwrap().len();
-
+        let (file, file_size) = self.location.open();
         let mut buf_reader = BufReader::new(file);
 
         let last_byte_terminator = match self.options.terminator {
@@ -304,67 +711,8 @@ impl<Out: Data + for<'a> Deserialize<'a>> Operator for CsvSource<Out> {
             0
         };
 
-        // Calculate start and end offset of this replica
-        let body_size = file_size - header_size;
-        let range_size = body_size / instances as u64;
-        let mut start = header_size + range_size * global_id;
-        let mut end = if global_id as usize == instances - 1 {
-            file_size
-        } else {
-            start + range_size
-        };
-
-        // Align start byte
-        if global_id != 0 {
-            // Seek reader to the first byte to be read
-            buf_reader
-                .seek(SeekFrom::Start(start))
-                .expect("Error while seeking BufReader to start");
-            // discard first line
-            let mut buf = Vec::new();
-            start += buf_reader
-                .read_until(last_byte_terminator, &mut buf)
-                .expect("Error while reading first line from file") as u64;
-        }
-
-        // Align end byte
-        if global_id as usize != instances - 1 {
-            // Seek reader to the last byte to be read
-            buf_reader
-                .seek(SeekFrom::Start(end))
-                .expect("Error while seeking BufReader to end");
-            // get to the end of the line
-            let mut buf = Vec::new();
-            end += buf_reader
-                .read_until(last_byte_terminator, &mut buf)
-                .expect("Error while reading last line from file") as u64;
-        }
-
-        // Rewind BufReader to the start
-        buf_reader
-            .seek(SeekFrom::Start(start))
-            .expect("Error while rewinding BufReader");
-
-        // Limit the number of bytes to be read
-        let limited_reader = LimitedReader::new(buf_reader, (end - start) as usize);
-
-        // Create csv::Reader
-        let mut csv_reader = ReaderBuilder::new()
-            .comment(self.options.comment)
-            .delimiter(self.options.delimiter)
-            .double_quote(self.options.double_quote)
-            .escape(self.options.escape)
-            .flexible(self.options.flexible)
-            .quote(self.options.quote)
-            .quoting(self.options.quoting)
-            .terminator(self.options.terminator)
-            .trim(self.options.trim)
-            .has_headers(self.options.has_headers)
-            .from_reader(limited_reader);
-
         if self.options.has_headers {
-            // set the headers of the CSV file
-            csv_reader.set_byte_headers(
+            self.header = Some(
                 Reader::from_reader(header.as_slice())
                     .byte_headers()
                     .unwrap()
@@ -372,7 +720,71 @@ impl<Out: Data + for<'a> Deserialize<'a>> Operator for CsvSource<Out> {
             );
         }
 
-        self.csv_reader = Some(csv_reader);
+        if let CsvLocation::Reader(_) = self.location {
+            assert!(
+                self.chunks.is_none(),
+                "CsvSource: zone maps cannot be used with a reader"
+            );
+            // the reader is not partitioned, read it until the end
+            self.set_reader(LimitedReader::new(buf_reader, usize::MAX));
+            return;
+        }
+
+        self.ranges = match &self.chunks {
+            Some(chunks) => {
+                assert_eq!(
+                    chunks.file_size, file_size,
+                    "CsvSource: the zone map of {} is stale, the size of the file has changed",
+                    self.location
+                );
+                split_ranges(&chunks.ranges, global_id, instances as u64)
+            }
+            None => {
+                // Calculate start and end offset of this replica
+                let body_size = file_size - header_size;
+                let range_size = body_size / instances as u64;
+                let mut start = header_size + range_size * global_id;
+                let mut end = if global_id as usize == instances - 1 {
+                    file_size
+                } else {
+                    start + range_size
+                };
+
+                // Align start byte
+                if global_id != 0 {
+                    // Seek reader to the first byte to be read
+                    buf_reader
+                        .seek(SeekFrom::Start(start))
+                        .expect("Error while seeking BufReader to start");
+                    // discard first line
+                    let mut buf = Vec::new();
+                    start += buf_reader
+                        .read_until(last_byte_terminator, &mut buf)
+                        .expect("Error while reading first line from file")
+                        as u64;
+                }
+
+                // Align end byte
+                if global_id as usize != instances - 1 {
+                    // Seek reader to the last byte to be read
+                    buf_reader
+                        .seek(SeekFrom::Start(end))
+                        .expect("Error while seeking BufReader to end");
+                    // get to the end of the line
+                    let mut buf = Vec::new();
+                    end += buf_reader
+                        .read_until(last_byte_terminator, &mut buf)
+                        .expect("Error while reading last line from file")
+                        as u64;
+                }
+
+                vec![(start, end)]
+            }
+        };
+        self.ranges.reverse();
+
+        let range = self.ranges.pop().unwrap_or((file_size, file_size));
+        self.open_range(buf_reader, range);
     }
 
     fn next(&mut self) -> StreamElement<Out> {
@@ -392,10 +804,17 @@ impl<Out: Data + for<'a> Deserialize<'a>> Operator for CsvSource<Out> {
                     .expect("csv does not match type");
                 StreamElement::Item(item)
             }
-            Ok(false) => {
-                self.terminated = true;
-                StreamElement::FlushAndRestart
-            }
+            Ok(false) => match self.ranges.pop() {
+                Some(range) => {
+                    let buf_reader = self.csv_reader.take().unwrap().into_inner().into_inner();
+                    self.open_range(buf_reader, range);
+                    self.next()
+                }
+                None => {
+                    self.terminated = true;
+                    StreamElement::FlushAndRestart
+                }
+            },
             Err(e) => panic!("Error while reading CSV file: {:?}", e),
         }
     }
@@ -403,6 +822,7 @@ impl<Out: Data + for<'a> Deserialize<'a>> Operator for CsvSource<Out> {
     fn structure(&self) -> BlockStructure {
         let mut operator = OperatorStructure::new::<Out, _>("CSVSource");
         operator.kind = OperatorKind::Source;
+        operator.timestamps = TimestampUsage::Drop;
         BlockStructure::default().add_operator(operator)
     }
 }
@@ -414,9 +834,12 @@ impl<Out: Data + for<'a> Deserialize<'a>> Clone for CsvSource<Out> {
             "CsvSource must be cloned before calling setup"
         );
         Self {
-            path: self.path.clone(),
+            location: self.location.clone(),
             csv_reader: None,
             options: self.options.clone(),
+            chunks: self.chunks.clone(),
+            header: None,
+            ranges: Default::default(),
             terminated: false,
             _out: PhantomData,
             buf: ByteRecord::new(),
@@ -424,6 +847,93 @@ impl<Out: Data + for<'a> Deserialize<'a>> Clone for CsvSource<Out> {
     }
 }
 
+/// Read the files of a [`FileSetSource`](super::FileSetSource) as CSV, deserializing each record
+/// into `Out`.
+///
+/// By default it is assumed that the delimiter is `,` and each file has headers.
+pub struct CsvFormat<Out> {
+    options: CsvOptions,
+    _out: PhantomData<fn() -> Out>,
+}
+
+impl<Out> CsvFormat<Out> {
+    pub fn new() -> Self {
+        Self {
+            options: Default::default(),
+            _out: PhantomData,
+        }
+    }
+
+    /// The field delimiter to use when parsing CSV, see [`CsvSource::delimiter`].
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.options.delimiter = delimiter;
+        self
+    }
+
+    /// Whether the number of fields in records is allowed to change or not, see
+    /// [`CsvSource::flexible`].
+    pub fn flexible(mut self, flexible: bool) -> Self {
+        self.options.flexible = flexible;
+        self
+    }
+
+    /// Whether fields are trimmed of leading and trailing whitespace or not, see
+    /// [`CsvSource::trim`].
+    pub fn trim(mut self, trim: Trim) -> Self {
+        self.options.trim = trim;
+        self
+    }
+
+    /// Whether to treat the first row of each file as a special header row, see
+    /// [`CsvSource::has_headers`].
+    pub fn has_headers(mut self, has_headers: bool) -> Self {
+        self.options.has_headers = has_headers;
+        self
+    }
+}
+
+impl<Out> Default for CsvFormat<Out> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Out> Clone for CsvFormat<Out> {
+    fn clone(&self) -> Self {
+        Self {
+            options: self.options.clone(),
+            _out: PhantomData,
+        }
+    }
+}
+
+/// The records of a CSV file, see [`CsvFormat`].
+pub struct CsvRecords<Out> {
+    records: csv::DeserializeRecordsIntoIter<BufReader<File>, Out>,
+}
+
+impl<Out: for<'a> Deserialize<'a>> Iterator for CsvRecords<Out> {
+    type Item = Out;
+
+    fn next(&mut self) -> Option<Out> {
+        self.records
+            .next()
+            .map(|record| record.unwrap_or_else(|e| panic!("Error while reading CSV file: {e:?}")))
+    }
+}
+
+impl<Out: Data + for<'a> Deserialize<'a>> FileFormat for CsvFormat<Out> {
+    type Out = Out;
+    type Reader = CsvRecords<Out>;
+
+    fn open(&self, path: &Path) -> CsvRecords<Out> {
+        let reader = self.options.builder().from_reader(open_file(path));
+        CsvRecords {
+            records: reader.into_deserialize(),
+        }
+    }
+}
+
 impl crate::StreamContext {
     /// Convenience method, creates a `CsvSource` and makes a stream using `StreamContext::stream`
     pub fn stream_csv<T: Data + for<'a> Deserialize<'a>>(
@@ -443,9 +953,10 @@ mod tests {
     use serde::{Deserialize, Serialize};
     use tempfile::NamedTempFile;
 
+    use super::split_ranges;
     use crate::config::RuntimeConfig;
     use crate::environment::StreamContext;
-    use crate::operator::source::CsvSource;
+    use crate::operator::source::{CsvSource, CsvZoneMap, HttpObjectStore};
 
     #[test]
     fn csv_without_headers() {
@@ -500,4 +1011,153 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn csv_from_reader() {
+        let mut data = "a,b\n".to_string();
+        for i in 0..100 {
+            data += &format!("{},{}\n", i, i + 1);
+        }
+
+        let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        let source = CsvSource::<(i32, i32)>::from_reader(std::io::Cursor::new(data));
+        let res = env.stream(source).shuffle().collect_vec();
+        env.execute_blocking();
+
+        let mut res = res.get().unwrap();
+        res.sort_unstable();
+        assert_eq!(res, (0..100).map(|x| (x, x + 1)).collect_vec());
+    }
+
+    #[test]
+    fn csv_zone_map() {
+        let file = NamedTempFile::new().unwrap();
+        writeln!(file.as_file(), "a,b").unwrap();
+        for i in 0..1000 {
+            writeln!(file.as_file(), "{},x{}", i, i % 7).unwrap();
+        }
+
+        let source = CsvSource::<(i32, String)>::new(file.path());
+        let zone_map = source.build_zone_map(&[0, 1], 100);
+        assert_eq!(zone_map.zones().len(), 10);
+        for (i, zone) in zone_map.zones().iter().enumerate() {
+            let first = (i * 100) as f64;
+            assert_eq!(zone.rows(), 100);
+            assert_eq!(zone.bounds(0), Some((first, first + 99.0)));
+            assert_eq!(zone.bounds(1), None);
+            assert!(zone.may_contain(1, 0.0..1.0));
+        }
+
+        let sidecar = NamedTempFile::new().unwrap();
+        zone_map.save(sidecar.path()).unwrap();
+        assert_eq!(CsvZoneMap::load(sidecar.path()).unwrap(), zone_map);
+
+        for replicas in [1, 3, 4] {
+            let env = StreamContext::new(RuntimeConfig::local(replicas).unwrap());
+            let source = CsvSource::<(i32, String)>::new(file.path()).zone_map(&zone_map, |z| {
+                z.may_contain(0, 250.0..=420.0) || z.may_contain(0, 950.0..)
+            });
+            let res = env.stream(source).collect_vec();
+            env.execute_blocking();
+
+            let res = res
+                .get()
+                .unwrap()
+                .into_iter()
+                .map(|(a, _)| a)
+                .sorted()
+                .collect_vec();
+            let expected = (200..500).chain(900..1000).collect_vec();
+            assert_eq!(res, expected);
+        }
+    }
+
+    #[test]
+    fn csv_split_ranges() {
+        // a contiguous selection of zones is divided between the replicas
+        let zones = [(10, 20), (20, 30), (30, 40), (40, 50)];
+        let parts = (0..4).map(|i| split_ranges(&zones, i, 4)).collect_vec();
+        assert_eq!(
+            parts,
+            vec![
+                vec![(10, 20)],
+                vec![(20, 30)],
+                vec![(30, 40)],
+                vec![(40, 50)]
+            ]
+        );
+
+        // the adjacent zones assigned to the same replica are read as a single range
+        let parts = (0..2).map(|i| split_ranges(&zones, i, 2)).collect_vec();
+        assert_eq!(parts, vec![vec![(10, 30)], vec![(30, 50)]]);
+        let parts = (0..2)
+            .map(|i| split_ranges(&[(0, 10), (20, 30), (30, 40)], i, 2))
+            .collect_vec();
+        assert_eq!(parts, vec![vec![(0, 10), (20, 30)], vec![(30, 40)]]);
+    }
+
+    /// Serve `content` over HTTP, supporting `HEAD` and ranged `GET` requests.
+    fn serve_http(content: Vec<u8>) -> String {
+        use std::io::{BufRead, BufReader};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request = String::new();
+                reader.read_line(&mut request).unwrap();
+                let mut range = 0..content.len();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim_end().is_empty() {
+                        break;
+                    }
+                    if let Some(bytes) = line.trim_end().strip_prefix("Range: bytes=") {
+                        let (start, end) = bytes.split_once('-').unwrap();
+                        range = start.parse().unwrap()..end.parse::<usize>().unwrap() + 1;
+                    }
+                }
+                if request.starts_with("HEAD") {
+                    write!(
+                        stream,
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n",
+                        content.len()
+                    )
+                    .unwrap();
+                } else {
+                    write!(
+                        stream,
+                        "HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\n\r\n",
+                        range.len()
+                    )
+                    .unwrap();
+                    stream.write_all(&content[range]).unwrap();
+                }
+            }
+        });
+        format!("http://{address}/data.csv")
+    }
+
+    #[test]
+    fn csv_object_store() {
+        let mut content = Vec::new();
+        writeln!(content, "a,b").unwrap();
+        for i in 0..1000 {
+            writeln!(content, "{},{}", i, i + 1).unwrap();
+        }
+        let uri = serve_http(content);
+
+        for replicas in [1, 3, 4] {
+            let env = StreamContext::new(RuntimeConfig::local(replicas).unwrap());
+            let source = CsvSource::<(i32, i32)>::from_object_store(HttpObjectStore::new(), &uri);
+            let res = env.stream(source).collect_vec();
+            env.execute_blocking();
+
+            let res = res.get().unwrap().into_iter().sorted().collect_vec();
+            assert_eq!(res, (0..1000).map(|x| (x, x + 1)).collect_vec());
+        }
+    }
 }
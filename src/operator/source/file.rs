@@ -6,7 +6,7 @@ use std::io::{BufReader, SeekFrom};
 use std::path::PathBuf;
 
 use crate::block::Replication;
-use crate::block::{BlockStructure, OperatorKind, OperatorStructure};
+use crate::block::{BlockStructure, OperatorKind, OperatorStructure, TimestampUsage};
 use crate::network::Coord;
 use crate::operator::source::Source;
 use crate::operator::{Operator, StreamElement};
@@ -148,6 +148,7 @@ impl Operator for FileSource {
     fn structure(&self) -> BlockStructure {
         let mut operator = OperatorStructure::new::<String, _>("FileSource");
         operator.kind = OperatorKind::Source;
+        operator.timestamps = TimestampUsage::Drop;
         BlockStructure::default().add_operator(operator)
     }
 }
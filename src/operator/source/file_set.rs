@@ -0,0 +1,514 @@
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use serde::Deserialize;
+
+use crate::block::{BlockStructure, OperatorKind, OperatorStructure, Replication, TimestampUsage};
+use crate::operator::source::Source;
+use crate::operator::{Data, Operator, StreamElement};
+use crate::scheduler::ExecutionMetadata;
+use crate::Stream;
+
+/// The format of the files read by a [`FileSetSource`].
+///
+/// The format is cloned in each replica of the source, which opens a new reader for each file it
+/// takes from the queue.
+pub trait FileFormat: Clone + Send + 'static {
+    type Out: Data;
+    type Reader: Iterator<Item = Self::Out> + Send;
+
+    /// Open the file at `path`, the reader yields all its records.
+    fn open(&self, path: &Path) -> Self::Reader;
+}
+
+/// Open a file for reading, panicking with a message naming the source if it fails.
+pub(crate) fn open_file(path: &Path) -> BufReader<File> {
+    let file = File::open(path)
+        .unwrap_or_else(|err| panic!("FileSetSource: error while opening file {path:?}: {err:?}"));
+    BufReader::new(file)
+}
+
+/// Read the files as text, one record for each line.
+///
+/// The lines are emitted including their terminator, like [`FileSource`](super::FileSource).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LinesFormat;
+
+/// The lines of a text file, see [`LinesFormat`].
+pub struct Lines {
+    reader: BufReader<File>,
+}
+
+impl Iterator for Lines {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) => None,
+            Ok(_) => Some(line),
+            Err(e) => panic!("Error while reading file: {e:?}"),
+        }
+    }
+}
+
+impl FileFormat for LinesFormat {
+    type Out = String;
+    type Reader = Lines;
+
+    fn open(&self, path: &Path) -> Lines {
+        Lines {
+            reader: open_file(path),
+        }
+    }
+}
+
+/// Read the files as [JSON Lines](https://jsonlines.org/), deserializing each value into `Out`.
+pub struct JsonLinesFormat<Out> {
+    _out: PhantomData<fn() -> Out>,
+}
+
+impl<Out> JsonLinesFormat<Out> {
+    pub fn new() -> Self {
+        Self { _out: PhantomData }
+    }
+}
+
+impl<Out> Default for JsonLinesFormat<Out> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Out> Clone for JsonLinesFormat<Out> {
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
+
+/// The values of a JSON Lines file, see [`JsonLinesFormat`].
+pub struct JsonLines<Out> {
+    values: serde_json::StreamDeserializer<'static, serde_json::de::IoRead<BufReader<File>>, Out>,
+}
+
+impl<Out: for<'a> Deserialize<'a>> Iterator for JsonLines<Out> {
+    type Item = Out;
+
+    fn next(&mut self) -> Option<Out> {
+        self.values
+            .next()
+            .map(|value| value.unwrap_or_else(|e| panic!("Error while reading JSON file: {e:?}")))
+    }
+}
+
+impl<Out: Data + for<'a> Deserialize<'a>> FileFormat for JsonLinesFormat<Out> {
+    type Out = Out;
+    type Reader = JsonLines<Out>;
+
+    fn open(&self, path: &Path) -> JsonLines<Out> {
+        JsonLines {
+            values: serde_json::Deserializer::from_reader(open_file(path)).into_iter(),
+        }
+    }
+}
+
+/// Whether `pattern` contains a wildcard.
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+/// Match a file name against a pattern where `*` matches any sequence of characters and `?` a
+/// single character.
+fn glob_match(pattern: &[u8], name: &[u8]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => {
+            glob_match(&pattern[1..], name) || (!name.is_empty() && glob_match(pattern, &name[1..]))
+        }
+        (Some(b'?'), Some(_)) => glob_match(&pattern[1..], &name[1..]),
+        (Some(p), Some(n)) if p == n => glob_match(&pattern[1..], &name[1..]),
+        _ => false,
+    }
+}
+
+/// Whether the file should be ignored when listing a directory: hidden files and the files
+/// starting with `_` (e.g. `_SUCCESS` markers).
+fn is_hidden(name: &str) -> bool {
+    name.starts_with('.') || name.starts_with('_')
+}
+
+/// Collect the regular files inside `dir` and its subdirectories.
+fn list_dir(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in read_dir(dir) {
+        let path = entry.path();
+        if entry.file_name().to_str().is_some_and(is_hidden) {
+            continue;
+        }
+        if path.is_dir() {
+            list_dir(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+/// Collect the files inside `dir` matching the remaining components of a glob pattern.
+///
+/// The component `**` matches any number of directories.
+fn list_glob(dir: &Path, components: &[String], files: &mut Vec<PathBuf>) {
+    let Some((component, rest)) = components.split_first() else {
+        return;
+    };
+    if component == "**" {
+        // match zero directories...
+        list_glob(dir, rest, files);
+        // ...or one more
+        for entry in read_dir(dir) {
+            let path = entry.path();
+            if path.is_dir() && !entry.file_name().to_str().is_some_and(is_hidden) {
+                list_glob(&path, components, files);
+            }
+        }
+        return;
+    }
+    if !is_glob(component) {
+        let path = dir.join(component);
+        match rest.is_empty() {
+            true if path.is_file() => files.push(path),
+            false if path.is_dir() => list_glob(&path, rest, files),
+            _ => {}
+        }
+        return;
+    }
+    for entry in read_dir(dir) {
+        let name = entry.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        if !glob_match(component.as_bytes(), name.as_bytes()) {
+            continue;
+        }
+        let path = entry.path();
+        if rest.is_empty() {
+            if path.is_file() {
+                files.push(path);
+            }
+        } else if path.is_dir() {
+            list_glob(&path, rest, files);
+        }
+    }
+}
+
+fn read_dir(dir: &Path) -> impl Iterator<Item = std::fs::DirEntry> {
+    let entries = std::fs::read_dir(dir).unwrap_or_else(|err| {
+        panic!("FileSetSource: error while reading directory {dir:?}: {err:?}")
+    });
+    entries.map(|entry| entry.expect("FileSetSource: cannot read directory entry"))
+}
+
+/// List the files selected by `pattern`, sorted by path.
+fn list_files(pattern: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let glob = pattern.to_str().is_some_and(is_glob);
+    if !glob && pattern.is_dir() {
+        list_dir(pattern, &mut files);
+    } else if !glob {
+        files.push(pattern.to_path_buf());
+    } else {
+        // the longest prefix without wildcards is the directory where the search starts
+        let mut base = PathBuf::new();
+        let mut components = Vec::new();
+        for component in pattern.components() {
+            let part = component.as_os_str().to_str().unwrap();
+            if components.is_empty() && !is_glob(part) {
+                base.push(component);
+            } else {
+                components.push(part.to_string());
+            }
+        }
+        if base.as_os_str().is_empty() {
+            base.push(".");
+        }
+        list_glob(&base, &components, &mut files);
+    }
+    files.sort_unstable();
+    files.dedup();
+    files
+}
+
+/// Source that reads a set of files selected by a glob pattern or a directory.
+///
+/// The files are read whole by a single replica each, using a [`FileFormat`] to turn them into
+/// records. The files are divided between the hosts, then the replicas of each host take the next
+/// file to read from a shared queue, so a replica that finishes early moves on to the remaining
+/// files. Each host has to see the **same** files in the same paths.
+///
+/// The pattern can be:
+/// - a directory: all the files inside it and its subdirectories are read, except the ones whose
+///   name starts with `.` or `_`;
+/// - a glob pattern: `*` matches any sequence of characters inside a path component, `?` any
+///   single character and `**` any number of directories (e.g. `data/**/part-*.csv`);
+/// - the path of a single file.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct FileSetSource<F: FileFormat> {
+    pattern: PathBuf,
+    #[derivative(Debug = "ignore")]
+    format: F,
+    /// The files still to be read by the replicas of this host, filled by the first replica that
+    /// is set up.
+    queue: Arc<Mutex<Option<VecDeque<PathBuf>>>>,
+    #[derivative(Debug = "ignore")]
+    reader: Option<F::Reader>,
+    terminated: bool,
+}
+
+impl<F: FileFormat> Display for FileSetSource<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "FileSetSource<{}>({})",
+            std::any::type_name::<F::Out>(),
+            self.pattern.display()
+        )
+    }
+}
+
+impl<F: FileFormat> FileSetSource<F> {
+    /// Create a new source that reads the files matching `pattern` with the given format.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::{CsvFormat, FileSetSource};
+    /// # let mut env = StreamContext::new_local();
+    /// let source = FileSetSource::new("/datasets/part-*.csv", CsvFormat::<(String, u64)>::new());
+    /// let s = env.stream(source);
+    /// ```
+    pub fn new<P: Into<PathBuf>>(pattern: P, format: F) -> Self {
+        Self {
+            pattern: pattern.into(),
+            format,
+            queue: Default::default(),
+            reader: None,
+            terminated: false,
+        }
+    }
+}
+
+impl<F: FileFormat> Source for FileSetSource<F> {
+    fn replication(&self) -> Replication {
+        Replication::Unlimited
+    }
+}
+
+impl<F: FileFormat> Operator for FileSetSource<F> {
+    type Out = F::Out;
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        let mut queue = self.queue.lock();
+        if queue.is_some() {
+            return;
+        }
+
+        let mut hosts = metadata
+            .replicas
+            .iter()
+            .map(|c| c.host_id)
+            .collect::<Vec<_>>();
+        hosts.sort_unstable();
+        hosts.dedup();
+        let host_index = hosts
+            .iter()
+            .position(|&h| h == metadata.coord.host_id)
+            .unwrap();
+
+        let files = list_files(&self.pattern);
+        log::debug!(
+            "FileSetSource: {} files match {:?}",
+            files.len(),
+            self.pattern
+        );
+        // all the hosts agree on the order of the files
+        *queue = Some(
+            files
+                .into_iter()
+                .skip(host_index)
+                .step_by(hosts.len())
+                .collect(),
+        );
+    }
+
+    fn next(&mut self) -> StreamElement<Self::Out> {
+        if self.terminated {
+            return StreamElement::Terminate;
+        }
+        loop {
+            if let Some(item) = self.reader.as_mut().and_then(|r| r.next()) {
+                return StreamElement::Item(item);
+            }
+            let next_file = self
+                .queue
+                .lock()
+                .as_mut()
+                .expect("FileSetSource was not initialized")
+                .pop_front();
+            match next_file {
+                Some(path) => self.reader = Some(self.format.open(&path)),
+                None => {
+                    self.reader = None;
+                    self.terminated = true;
+                    return StreamElement::FlushAndRestart;
+                }
+            }
+        }
+    }
+
+    fn structure(&self) -> BlockStructure {
+        let mut operator = OperatorStructure::new::<Self::Out, _>("FileSetSource");
+        operator.kind = OperatorKind::Source;
+        operator.timestamps = TimestampUsage::Drop;
+        BlockStructure::default().add_operator(operator)
+    }
+}
+
+impl<F: FileFormat> Clone for FileSetSource<F> {
+    fn clone(&self) -> Self {
+        assert!(
+            self.reader.is_none(),
+            "FileSetSource must be cloned before calling setup"
+        );
+        // the clones are the replicas of the source, they share the queue of the files
+        Self {
+            pattern: self.pattern.clone(),
+            format: self.format.clone(),
+            queue: self.queue.clone(),
+            reader: None,
+            terminated: false,
+        }
+    }
+}
+
+impl crate::StreamContext {
+    /// Convenience method, creates a `FileSetSource` and makes a stream using `StreamContext::stream`
+    pub fn stream_file_set<F: FileFormat, P: Into<PathBuf>>(
+        &self,
+        pattern: P,
+        format: F,
+    ) -> Stream<FileSetSource<F>> {
+        let source = FileSetSource::new(pattern, format);
+        self.stream(source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use itertools::Itertools;
+    use serde::{Deserialize, Serialize};
+
+    use super::{glob_match, list_files};
+    use crate::config::RuntimeConfig;
+    use crate::environment::StreamContext;
+    use crate::operator::source::{CsvFormat, FileSetSource, JsonLinesFormat, LinesFormat};
+
+    #[test]
+    fn file_set_glob() {
+        assert!(glob_match(b"part-*.csv", b"part-0001.csv"));
+        assert!(glob_match(b"part-?.csv", b"part-1.csv"));
+        assert!(!glob_match(b"part-?.csv", b"part-10.csv"));
+        assert!(!glob_match(b"part-*.csv", b"part-1.json"));
+
+        let dir = tempfile::tempdir().unwrap();
+        for sub in ["a", "b/c"] {
+            fs::create_dir_all(dir.path().join(sub)).unwrap();
+            fs::write(dir.path().join(sub).join("part-1.csv"), "").unwrap();
+            fs::write(dir.path().join(sub).join("part-2.json"), "").unwrap();
+            fs::write(dir.path().join(sub).join("_SUCCESS"), "").unwrap();
+        }
+        let relative = |files: Vec<_>| {
+            files
+                .into_iter()
+                .map(|f: std::path::PathBuf| f.strip_prefix(dir.path()).unwrap().to_path_buf())
+                .map(|f| f.to_str().unwrap().to_string())
+                .collect_vec()
+        };
+
+        assert_eq!(
+            relative(list_files(&dir.path().join("*/part-*.csv"))),
+            vec!["a/part-1.csv"]
+        );
+        assert_eq!(
+            relative(list_files(&dir.path().join("**/part-*.csv"))),
+            vec!["a/part-1.csv", "b/c/part-1.csv"]
+        );
+        assert_eq!(
+            relative(list_files(dir.path())),
+            vec![
+                "a/part-1.csv",
+                "a/part-2.json",
+                "b/c/part-1.csv",
+                "b/c/part-2.json"
+            ]
+        );
+    }
+
+    #[test]
+    fn file_set_formats() {
+        #[derive(Clone, Serialize, Deserialize)]
+        struct T {
+            a: i32,
+            b: i32,
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        for part in 0..10 {
+            let csv = (0..10)
+                .map(|i| format!("{},{}\n", part * 10 + i, i))
+                .join("");
+            fs::write(
+                dir.path().join(format!("part-{part}.csv")),
+                format!("a,b\n{csv}"),
+            )
+            .unwrap();
+            let json = (0..10)
+                .map(|i| format!("{{\"a\":{},\"b\":{}}}\n", part * 10 + i, i))
+                .join("");
+            fs::write(dir.path().join(format!("part-{part}.json")), json).unwrap();
+        }
+
+        let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        let csv = env
+            .stream_file_set(dir.path().join("part-*.csv"), CsvFormat::<T>::new())
+            .map(|t| t.a)
+            .collect_vec();
+        let json = env
+            .stream(FileSetSource::new(
+                dir.path().join("part-*.json"),
+                JsonLinesFormat::<T>::new(),
+            ))
+            .map(|t| t.a)
+            .collect_vec();
+        let lines = env.stream_file_set(dir.path(), LinesFormat).collect_count();
+        env.execute_blocking();
+
+        let expected = (0..100).collect_vec();
+        assert_eq!(
+            csv.get().unwrap().into_iter().sorted().collect_vec(),
+            expected
+        );
+        assert_eq!(
+            json.get().unwrap().into_iter().sorted().collect_vec(),
+            expected
+        );
+        assert_eq!(lines.get().unwrap(), 210);
+    }
+}
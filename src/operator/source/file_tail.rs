@@ -0,0 +1,402 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Display;
+use std::fs::{File, Metadata};
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::block::{
+    BlockStructure, Boundedness, OperatorKind, OperatorStructure, Replication, TimestampUsage,
+};
+use crate::operator::source::Source;
+use crate::operator::{Operator, StreamElement};
+use crate::scheduler::ExecutionMetadata;
+use crate::Stream;
+
+/// The identity of a file, used for detecting when a path is replaced by a new file.
+#[cfg(unix)]
+fn file_id(metadata: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// A file being followed.
+struct TailedFile {
+    reader: BufReader<File>,
+    id: Option<(u64, u64)>,
+    /// The last line read, not terminated yet.
+    partial: String,
+}
+
+impl TailedFile {
+    fn open(path: &Path, from_end: bool) -> Option<Self> {
+        let file = File::open(path).ok()?;
+        let id = file.metadata().ok().and_then(|m| file_id(&m));
+        let mut reader = BufReader::new(file);
+        if from_end {
+            reader.seek(SeekFrom::End(0)).ok()?;
+        }
+        Some(Self {
+            reader,
+            id,
+            partial: String::new(),
+        })
+    }
+
+    /// Read the complete lines appended to the file.
+    fn read_lines(&mut self, path: &Path, lines: &mut VecDeque<String>) {
+        loop {
+            match self.reader.read_line(&mut self.partial) {
+                Ok(0) => break,
+                Ok(_) if self.partial.ends_with('\n') => {
+                    lines.push_back(std::mem::take(&mut self.partial))
+                }
+                // the rest of the line has not been written yet
+                Ok(_) => break,
+                Err(e) => {
+                    log::warn!("FileTailSource: error reading {}: {e:?}", path.display());
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Source that follows a text file, like `tail -f`, emitting the lines appended to it.
+///
+/// The file is checked for new lines every poll interval. A line is emitted only once its
+/// terminator has been written, including the terminator. When the file is rotated, i.e. the path
+/// refers to a new file, the rest of the old file is read before following the new one; when the
+/// file is truncated it is read again from the start. The path can also be a directory, in which
+/// case all the files in it are followed, including the ones created later.
+///
+/// The file can only be read sequentially, so the source has a single replica. By default the
+/// source never terminates, see [`FileTailSource::idle_timeout`].
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct FileTailSource {
+    path: PathBuf,
+    from_end: bool,
+    poll_interval: Duration,
+    idle_timeout: Option<Duration>,
+    /// The files being followed, by path.
+    #[derivative(Debug = "ignore")]
+    files: BTreeMap<PathBuf, TailedFile>,
+    #[derivative(Debug = "ignore")]
+    lines: VecDeque<String>,
+    /// Whether the files existing at the start have been opened.
+    started: bool,
+    last_line: Instant,
+    /// Whether lines have been emitted since the last `FlushBatch`.
+    unflushed: bool,
+    terminated: bool,
+}
+
+impl Display for FileTailSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "FileTailSource<{}>", self.path.display())
+    }
+}
+
+impl FileTailSource {
+    /// Create a new source that follows the file, or the files of the directory, at `path`.
+    ///
+    /// The content already in the files is emitted, use [`FileTailSource::from_end`] for emitting
+    /// only the new lines. The path doesn't need to exist when the source starts.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::FileTailSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let source = FileTailSource::new("/var/log/nginx/access.log").from_end();
+    /// let errors = env
+    ///     .stream(source)
+    ///     .filter(|line| line.contains(" 500 "))
+    ///     .for_each(|line| print!("{line}"));
+    /// ```
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            from_end: false,
+            poll_interval: Duration::from_millis(100),
+            idle_timeout: None,
+            files: Default::default(),
+            lines: Default::default(),
+            started: false,
+            last_line: Instant::now(),
+            unflushed: false,
+            terminated: false,
+        }
+    }
+
+    /// Skip the content of the files existing when the source starts, emitting only the lines
+    /// appended later.
+    pub fn from_end(mut self) -> Self {
+        self.from_end = true;
+        self
+    }
+
+    /// How often the files are checked for new lines.
+    ///
+    /// The default is 100ms.
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Terminate the source after no line has been appended for `timeout`.
+    ///
+    /// By default the source never terminates.
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// The files currently at the path.
+    fn paths(&self) -> Vec<PathBuf> {
+        match std::fs::read_dir(&self.path) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+                .map(|entry| entry.path())
+                .collect(),
+            Err(_) if self.path.is_file() => vec![self.path.clone()],
+            Err(_) => vec![],
+        }
+    }
+
+    /// Read the new lines of all the files, following the rotations.
+    fn poll(&mut self) {
+        // the files existing at the start may be skipped, the new ones are read from the start
+        let from_end = self.from_end && !self.started;
+        self.started = true;
+        for path in self.paths() {
+            if self.files.contains_key(&path) {
+                continue;
+            }
+            let Some(file) = TailedFile::open(&path, from_end) else {
+                continue;
+            };
+            // a file of the directory renamed by the rotation is still followed from where it was
+            let renamed = self
+                .files
+                .iter()
+                .find(|(_, f)| f.id.is_some() && f.id == file.id)
+                .map(|(old, _)| old.clone());
+            match renamed {
+                Some(old) => {
+                    let file = self.files.remove(&old).unwrap();
+                    self.files.insert(path, file);
+                }
+                None => {
+                    self.files.insert(path, file);
+                }
+            }
+        }
+
+        for (path, file) in self.files.iter_mut() {
+            file.read_lines(path, &mut self.lines);
+            let Ok(metadata) = std::fs::metadata(path) else {
+                // the file has been moved and the new one is not there yet
+                continue;
+            };
+            let rotated = file.id.is_some() && file_id(&metadata) != file.id;
+            let position = file.reader.stream_position().unwrap_or(0);
+            if rotated {
+                // the old file is complete, its last line will never be terminated
+                if !file.partial.is_empty() {
+                    self.lines.push_back(std::mem::take(&mut file.partial));
+                }
+                log::debug!("FileTailSource: {} rotated", path.display());
+                if let Some(mut new) = TailedFile::open(path, false) {
+                    new.read_lines(path, &mut self.lines);
+                    *file = new;
+                }
+            } else if metadata.len() < position {
+                log::debug!("FileTailSource: {} truncated", path.display());
+                file.partial.clear();
+                if file.reader.seek(SeekFrom::Start(0)).is_ok() {
+                    file.read_lines(path, &mut self.lines);
+                }
+            }
+        }
+    }
+}
+
+impl Source for FileTailSource {
+    fn replication(&self) -> Replication {
+        Replication::One
+    }
+}
+
+impl Operator for FileTailSource {
+    type Out = String;
+
+    fn setup(&mut self, _metadata: &mut ExecutionMetadata) {
+        self.last_line = Instant::now();
+    }
+
+    fn next(&mut self) -> StreamElement<String> {
+        if self.terminated {
+            return StreamElement::Terminate;
+        }
+        if let Some(line) = self.lines.pop_front() {
+            self.unflushed = true;
+            return StreamElement::Item(line);
+        }
+
+        loop {
+            let poll_start = Instant::now();
+            self.poll();
+            if let Some(line) = self.lines.pop_front() {
+                self.last_line = Instant::now();
+                self.unflushed = true;
+                return StreamElement::Item(line);
+            }
+            if self
+                .idle_timeout
+                .is_some_and(|timeout| self.last_line.elapsed() >= timeout)
+            {
+                self.terminated = true;
+                return StreamElement::FlushAndRestart;
+            }
+            if std::mem::take(&mut self.unflushed) {
+                // let the next operators process the lines received so far
+                return StreamElement::FlushBatch;
+            }
+            // nothing to flush, wait for new lines without bothering the next operators
+            std::thread::sleep(self.poll_interval.saturating_sub(poll_start.elapsed()));
+        }
+    }
+
+    fn structure(&self) -> BlockStructure {
+        let mut operator = OperatorStructure::new::<String, _>("FileTailSource");
+        operator.kind = OperatorKind::Source;
+        operator.timestamps = TimestampUsage::Drop;
+        if self.idle_timeout.is_none() {
+            operator.boundedness = Boundedness::Unbounded;
+        }
+        BlockStructure::default().add_operator(operator)
+    }
+}
+
+impl Clone for FileTailSource {
+    fn clone(&self) -> Self {
+        assert!(
+            !self.started,
+            "FileTailSource must be cloned before calling setup"
+        );
+        Self {
+            from_end: self.from_end,
+            poll_interval: self.poll_interval,
+            idle_timeout: self.idle_timeout,
+            ..Self::new(self.path.clone())
+        }
+    }
+}
+
+impl crate::StreamContext {
+    /// Convenience method, creates a `FileTailSource` and makes a stream using
+    /// `StreamContext::stream`
+    pub fn stream_file_tail(&self, path: impl Into<PathBuf>) -> Stream<FileTailSource> {
+        let source = FileTailSource::new(path);
+        self.stream(source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{File, OpenOptions};
+    use std::io::Write;
+    use std::path::Path;
+    use std::time::Duration;
+
+    use super::FileTailSource;
+    use crate::operator::{Operator, StreamElement};
+    use crate::test::FakeNetworkTopology;
+
+    fn append(path: &Path, content: &str) {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+    }
+
+    /// Read the lines until the source has nothing new to emit.
+    fn read_available(source: &mut FileTailSource) -> Vec<String> {
+        let mut res = vec![];
+        loop {
+            match source.next() {
+                StreamElement::Item(line) => res.push(line),
+                StreamElement::FlushBatch => return res,
+                other => panic!("unexpected {}", other.variant_str()),
+            }
+        }
+    }
+
+    #[test]
+    fn file_tail_follows_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        append(&path, "old\n");
+
+        let mut source = FileTailSource::new(&path)
+            .from_end()
+            .poll_interval(Duration::ZERO);
+        source.setup(&mut FakeNetworkTopology::<u32>::new(0, 0).metadata());
+        source.poll();
+        assert!(source.lines.is_empty());
+
+        // a line is emitted only once it is complete
+        append(&path, "a\nb");
+        assert_eq!(read_available(&mut source), vec!["a\n"]);
+        append(&path, "c\n");
+        assert_eq!(read_available(&mut source), vec!["bc\n"]);
+
+        // rotation: the rest of the old file is read before the new one
+        append(&path, "d\n");
+        std::fs::rename(&path, dir.path().join("app.log.1")).unwrap();
+        append(&path, "e\n");
+        assert_eq!(read_available(&mut source), vec!["d\n", "e\n"]);
+
+        // truncation: the file is read again from the start
+        File::create(&path).unwrap();
+        source.poll();
+        assert!(source.lines.is_empty());
+        append(&path, "f\n");
+        assert_eq!(read_available(&mut source), vec!["f\n"]);
+    }
+
+    #[test]
+    fn file_tail_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        append(&dir.path().join("a.log"), "a1\n");
+
+        let mut source = FileTailSource::new(dir.path()).poll_interval(Duration::ZERO);
+        source.setup(&mut FakeNetworkTopology::<u32>::new(0, 0).metadata());
+        assert_eq!(read_available(&mut source), vec!["a1\n"]);
+
+        append(&dir.path().join("b.log"), "b1\n");
+        append(&dir.path().join("a.log"), "a2\n");
+        let mut lines = read_available(&mut source);
+        lines.sort();
+        assert_eq!(lines, vec!["a2\n", "b1\n"]);
+
+        // the renamed file is not read again
+        append(&dir.path().join("a.log"), "a3\n");
+        std::fs::rename(dir.path().join("a.log"), dir.path().join("a.log.1")).unwrap();
+        append(&dir.path().join("a.log"), "a4\n");
+        let mut lines = read_available(&mut source);
+        lines.sort();
+        assert_eq!(lines, vec!["a3\n", "a4\n"]);
+    }
+}
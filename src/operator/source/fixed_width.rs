@@ -0,0 +1,231 @@
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::PathBuf;
+
+use crate::block::{BlockStructure, OperatorKind, OperatorStructure, Replication, TimestampUsage};
+use crate::operator::source::Source;
+use crate::operator::{Data, Operator, StreamElement};
+use crate::scheduler::ExecutionMetadata;
+use crate::Stream;
+
+/// Source that reads a binary file made of records of the same size.
+///
+/// The records are divided in chunks and are read concurrently by multiple replicas, like
+/// [`FileSource`](super::FileSource) does with the lines. Since the records have a fixed size, the
+/// boundaries of the chunks are always on the boundaries of the records.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct FixedWidthSource<Out, F>
+where
+    F: Fn(&[u8]) -> Out + Clone + Send + 'static,
+    Out: Data,
+{
+    path: PathBuf,
+    record_len: usize,
+    #[derivative(Debug = "ignore")]
+    decode: F,
+    // reader is initialized in `setup`, before it is None
+    #[derivative(Debug = "ignore")]
+    reader: Option<BufReader<File>>,
+    buf: Vec<u8>,
+    /// The number of records this replica still has to read.
+    remaining: u64,
+    terminated: bool,
+}
+
+impl<Out: Data, F> Display for FixedWidthSource<Out, F>
+where
+    F: Fn(&[u8]) -> Out + Clone + Send + 'static,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "FixedWidthSource<{}>", std::any::type_name::<Out>())
+    }
+}
+
+impl<Out: Data, F> FixedWidthSource<Out, F>
+where
+    F: Fn(&[u8]) -> Out + Clone + Send + 'static,
+{
+    /// Create a new source that reads the records of `record_len` bytes of a binary file,
+    /// converting each one with `decode`.
+    ///
+    /// The file is partitioned into as many chunks as replicas, each replica has to have the
+    /// **same** file in the same path. It is guaranteed that each record of the file is emitted by
+    /// exactly one replica.
+    ///
+    /// **Note**: the size of the file must be a multiple of `record_len`.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::FixedWidthSource;
+    /// # let mut env = StreamContext::new_local();
+    /// // records made of a little-endian u32 id followed by a f64 value
+    /// let source = FixedWidthSource::new("/datasets/points.bin", 12, |record| {
+    ///     let id = u32::from_le_bytes(record[..4].try_into().unwrap());
+    ///     let value = f64::from_le_bytes(record[4..].try_into().unwrap());
+    ///     (id, value)
+    /// });
+    /// let s = env.stream(source);
+    /// ```
+    pub fn new<P>(path: P, record_len: usize, decode: F) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        assert!(record_len > 0, "The records must not be empty");
+        Self {
+            path: path.into(),
+            record_len,
+            decode,
+            reader: None,
+            buf: vec![0; record_len],
+            remaining: 0,
+            terminated: false,
+        }
+    }
+}
+
+impl<Out: Data, F> Source for FixedWidthSource<Out, F>
+where
+    F: Fn(&[u8]) -> Out + Clone + Send + 'static,
+{
+    fn replication(&self) -> Replication {
+        Replication::Unlimited
+    }
+}
+
+impl<Out: Data, F> Operator for FixedWidthSource<Out, F>
+where
+    F: Fn(&[u8]) -> Out + Clone + Send + 'static,
+{
+    type Out = Out;
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        let global_id = metadata.global_id;
+        let instances = metadata.replicas.len() as u64;
+
+        let file = File::open(&self.path).unwrap_or_else(|err| {
+            panic!(
+                "FixedWidthSource: error while opening file {:?}: {:?}",
+                self.path, err
+            )
+        });
+        let file_size = file.metadata().unwrap().len();
+        let record_len = self.record_len as u64;
+        assert_eq!(
+            file_size % record_len,
+            0,
+            "FixedWidthSource: the size of {:?} ({file_size} bytes) is not a multiple of the record length ({record_len} bytes)",
+            self.path,
+        );
+
+        let records = file_size / record_len;
+        let range_size = records / instances;
+        let start = range_size * global_id;
+        self.remaining = if global_id == instances - 1 {
+            records - start
+        } else {
+            range_size
+        };
+
+        let mut reader = BufReader::new(file);
+        // Seek reader to the first record to be read
+        reader
+            .seek(SeekFrom::Start(start * record_len))
+            .expect("seek file");
+        self.reader = Some(reader);
+    }
+
+    fn next(&mut self) -> StreamElement<Out> {
+        if self.terminated {
+            return StreamElement::Terminate;
+        }
+        if self.remaining == 0 {
+            self.terminated = true;
+            return StreamElement::FlushAndRestart;
+        }
+        self.reader
+            .as_mut()
+            .expect("BufReader was not initialized")
+            .read_exact(&mut self.buf)
+            .unwrap_or_else(|e| panic!("Error while reading file {:?}: {e:?}", self.path));
+        self.remaining -= 1;
+        StreamElement::Item((self.decode)(&self.buf))
+    }
+
+    fn structure(&self) -> BlockStructure {
+        let mut operator = OperatorStructure::new::<Out, _>("FixedWidthSource");
+        operator.kind = OperatorKind::Source;
+        operator.timestamps = TimestampUsage::Drop;
+        BlockStructure::default().add_operator(operator)
+    }
+}
+
+impl<Out: Data, F> Clone for FixedWidthSource<Out, F>
+where
+    F: Fn(&[u8]) -> Out + Clone + Send + 'static,
+{
+    fn clone(&self) -> Self {
+        assert!(
+            self.reader.is_none(),
+            "FixedWidthSource must be cloned before calling setup"
+        );
+        Self::new(self.path.clone(), self.record_len, self.decode.clone())
+    }
+}
+
+impl crate::StreamContext {
+    /// Convenience method, creates a `FixedWidthSource` and makes a stream using
+    /// `StreamContext::stream`
+    pub fn stream_fixed_width<Out, F, P>(
+        &self,
+        path: P,
+        record_len: usize,
+        decode: F,
+    ) -> Stream<FixedWidthSource<Out, F>>
+    where
+        F: Fn(&[u8]) -> Out + Clone + Send + 'static,
+        Out: Data,
+        P: Into<PathBuf>,
+    {
+        let source = FixedWidthSource::new(path, record_len, decode);
+        self.stream(source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use crate::config::RuntimeConfig;
+    use crate::environment::StreamContext;
+
+    #[test]
+    fn fixed_width_split_across_replicas() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        for i in 0..1000u32 {
+            file.write_all(&i.to_le_bytes()).unwrap();
+            file.write_all(&(i as u16 * 2).to_be_bytes()).unwrap();
+        }
+        file.flush().unwrap();
+
+        for replicas in [1, 3, 4] {
+            let env = StreamContext::new(RuntimeConfig::local(replicas).unwrap());
+            let res = env
+                .stream_fixed_width(file.path(), 6, |record| {
+                    let a = u32::from_le_bytes(record[..4].try_into().unwrap());
+                    let b = u16::from_be_bytes(record[4..].try_into().unwrap());
+                    (a, b)
+                })
+                .collect_vec();
+            env.execute_blocking();
+
+            let mut res = res.get().unwrap();
+            res.sort_unstable();
+            let expected: Vec<_> = (0..1000u32).map(|i| (i, i as u16 * 2)).collect();
+            assert_eq!(res, expected);
+        }
+    }
+}
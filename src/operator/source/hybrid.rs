@@ -0,0 +1,274 @@
+use std::fmt::Display;
+
+use crate::block::{BlockStructure, OperatorKind, OperatorStructure, Replication, TimestampUsage};
+use crate::operator::source::Source;
+use crate::operator::{Operator, StreamElement, Timestamp};
+use crate::scheduler::ExecutionMetadata;
+use crate::Stream;
+
+/// Source that reads the history of a stream from a bounded source and then continues with the
+/// live elements of an unbounded one.
+///
+/// The elements of the history with a timestamp before the switch point are emitted first, then
+/// the elements of the live source from the switch point on: the elements of each source on the
+/// wrong side of the switch point are dropped, so the two sources can overlap. The elements are
+/// emitted with their timestamp, taken from the element itself if the source emits it timestamped
+/// or computed with the timestamp function otherwise.
+///
+/// The watermarks of the history are capped to the switch point and the ones of the live source
+/// are emitted only when they move the watermark forward. When the history ends a watermark at the
+/// switch point is emitted, since all the elements before it have been read.
+///
+/// Each replica reads both sources, which are set up when the job starts.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct HybridSource<H, L, F>
+where
+    H: Source,
+    L: Source<Out = H::Out>,
+    F: Fn(&H::Out) -> Timestamp + Clone + Send + 'static,
+{
+    history: H,
+    live: L,
+    switch_at: Timestamp,
+    #[derivative(Debug = "ignore")]
+    timestamp: F,
+    /// Whether the history has ended and the elements are taken from the live source.
+    is_live: bool,
+    last_watermark: Option<Timestamp>,
+}
+
+impl<H, L, F> Display for HybridSource<H, L, F>
+where
+    H: Source,
+    L: Source<Out = H::Out>,
+    F: Fn(&H::Out) -> Timestamp + Clone + Send + 'static,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "HybridSource({}, {}, switch_at: {})",
+            self.history, self.live, self.switch_at
+        )
+    }
+}
+
+impl<H, L, F> HybridSource<H, L, F>
+where
+    H: Source,
+    L: Source<Out = H::Out>,
+    F: Fn(&H::Out) -> Timestamp + Clone + Send + 'static,
+{
+    /// Create a new source that reads `history` up to the timestamp `switch_at` (excluded) and
+    /// then `live` from it on.
+    ///
+    /// `timestamp` computes the timestamp of the elements emitted without one; it can also be an
+    /// offset inside the stream, like the sequence number of the element.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::{HybridSource, IteratorSource};
+    /// # let mut env = StreamContext::new_local();
+    /// // the history stored up to now, and the new events that also include the last ones stored
+    /// let history = IteratorSource::new(0..1000i64);
+    /// let live = IteratorSource::new(990..2000i64);
+    /// let res = env
+    ///     .stream(HybridSource::new(history, live, 995, |&n| n))
+    ///     .collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// assert_eq!(res.get().unwrap(), (0..2000).collect::<Vec<_>>());
+    /// ```
+    pub fn new(history: H, live: L, switch_at: Timestamp, timestamp: F) -> Self {
+        Self {
+            history,
+            live,
+            switch_at,
+            timestamp,
+            is_live: false,
+            last_watermark: None,
+        }
+    }
+
+    /// Emit the watermark `ts` if it moves the watermark forward.
+    fn watermark(&mut self, ts: Timestamp) -> Option<StreamElement<H::Out>> {
+        if self.last_watermark.is_some_and(|last| last >= ts) {
+            return None;
+        }
+        self.last_watermark = Some(ts);
+        Some(StreamElement::Watermark(ts))
+    }
+}
+
+impl<H, L, F> Source for HybridSource<H, L, F>
+where
+    H: Source,
+    L: Source<Out = H::Out>,
+    F: Fn(&H::Out) -> Timestamp + Clone + Send + 'static,
+{
+    fn replication(&self) -> Replication {
+        self.history
+            .replication()
+            .intersect(self.live.replication())
+    }
+}
+
+impl<H, L, F> Operator for HybridSource<H, L, F>
+where
+    H: Source,
+    L: Source<Out = H::Out>,
+    F: Fn(&H::Out) -> Timestamp + Clone + Send + 'static,
+{
+    type Out = H::Out;
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        self.history.setup(metadata);
+        self.live.setup(metadata);
+    }
+
+    fn next(&mut self) -> StreamElement<Self::Out> {
+        loop {
+            let el = if self.is_live {
+                self.live.next()
+            } else {
+                self.history.next()
+            };
+            let (item, ts) = match el {
+                StreamElement::Item(item) => {
+                    let ts = (self.timestamp)(&item);
+                    (item, ts)
+                }
+                StreamElement::Timestamped(item, ts) => (item, ts),
+                StreamElement::Watermark(ts) => {
+                    let ts = if self.is_live {
+                        ts
+                    } else {
+                        ts.min(self.switch_at)
+                    };
+                    match self.watermark(ts) {
+                        Some(el) => return el,
+                        None => continue,
+                    }
+                }
+                StreamElement::FlushBatch => return StreamElement::FlushBatch,
+                StreamElement::FlushAndRestart | StreamElement::Terminate if !self.is_live => {
+                    log::debug!("HybridSource: history ended, switching to the live source");
+                    self.is_live = true;
+                    match self.watermark(self.switch_at) {
+                        Some(el) => return el,
+                        None => continue,
+                    }
+                }
+                el => return el,
+            };
+            if (ts < self.switch_at) != self.is_live {
+                return StreamElement::Timestamped(item, ts);
+            }
+        }
+    }
+
+    fn structure(&self) -> BlockStructure {
+        let mut operator = OperatorStructure::new::<Self::Out, _>("HybridSource");
+        operator.kind = OperatorKind::Source;
+        operator.timestamps = TimestampUsage::Assign;
+        if let Some(live) = self.live.structure().operators.last() {
+            operator.boundedness = live.boundedness;
+        }
+        BlockStructure::default().add_operator(operator)
+    }
+}
+
+impl<H, L, F> Clone for HybridSource<H, L, F>
+where
+    H: Source,
+    L: Source<Out = H::Out>,
+    F: Fn(&H::Out) -> Timestamp + Clone + Send + 'static,
+{
+    fn clone(&self) -> Self {
+        Self::new(
+            self.history.clone(),
+            self.live.clone(),
+            self.switch_at,
+            self.timestamp.clone(),
+        )
+    }
+}
+
+impl crate::StreamContext {
+    /// Convenience method, creates a `HybridSource` and makes a stream using `StreamContext::stream`
+    pub fn stream_hybrid<H, L, F>(
+        &self,
+        history: H,
+        live: L,
+        switch_at: Timestamp,
+        timestamp: F,
+    ) -> Stream<HybridSource<H, L, F>>
+    where
+        H: Source + 'static,
+        L: Source<Out = H::Out> + 'static,
+        F: Fn(&H::Out) -> Timestamp + Clone + Send + 'static,
+    {
+        let source = HybridSource::new(history, live, switch_at, timestamp);
+        self.stream(source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::operator::source::HybridSource;
+    use crate::operator::{Operator, StreamElement};
+    use crate::test::{FakeNetworkTopology, FakeOperator};
+
+    #[test]
+    fn hybrid_source_switch() {
+        let mut history = FakeOperator::empty();
+        for i in 0..6 {
+            history.push(StreamElement::Item(i));
+            if i % 2 == 1 {
+                history.push(StreamElement::Watermark(i));
+            }
+        }
+        history.push(StreamElement::FlushAndRestart);
+        let mut live = FakeOperator::empty();
+        live.push(StreamElement::Timestamped(2, 2));
+        live.push(StreamElement::Watermark(2));
+        live.push(StreamElement::Timestamped(4, 4));
+        live.push(StreamElement::FlushBatch);
+        live.push(StreamElement::Timestamped(6, 6));
+        live.push(StreamElement::Watermark(6));
+        live.push(StreamElement::FlushAndRestart);
+
+        let mut source = HybridSource::new(history, live, 4, |&n| n);
+        let mut topology = FakeNetworkTopology::<i64>::new(0, 0);
+        source.setup(&mut topology.metadata());
+
+        let mut res = Vec::new();
+        loop {
+            match source.next() {
+                StreamElement::Terminate => break,
+                el => res.push(el),
+            }
+        }
+        assert_eq!(
+            res,
+            vec![
+                StreamElement::Timestamped(0, 0),
+                StreamElement::Timestamped(1, 1),
+                StreamElement::Watermark(1),
+                StreamElement::Timestamped(2, 2),
+                StreamElement::Timestamped(3, 3),
+                StreamElement::Watermark(3),
+                // the watermark of the history is capped at the switch point
+                StreamElement::Watermark(4),
+                StreamElement::Timestamped(4, 4),
+                StreamElement::FlushBatch,
+                StreamElement::Timestamped(6, 6),
+                StreamElement::Watermark(6),
+                StreamElement::FlushAndRestart,
+            ]
+        );
+    }
+}
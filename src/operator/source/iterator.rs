@@ -1,6 +1,8 @@
 use std::fmt::Display;
 
-use crate::block::{BlockStructure, OperatorKind, OperatorStructure, Replication};
+use crate::block::{
+    BlockStructure, Boundedness, OperatorKind, OperatorStructure, Replication, TimestampUsage,
+};
 use crate::operator::source::Source;
 use crate::operator::{Operator, StreamElement};
 use crate::scheduler::ExecutionMetadata;
@@ -96,6 +98,11 @@ where
     fn structure(&self) -> BlockStructure {
         let mut operator = OperatorStructure::new::<Self::Out, _>("IteratorSource");
         operator.kind = OperatorKind::Source;
+        operator.timestamps = TimestampUsage::Drop;
+        // an iterator with no upper bound that has at least `usize::MAX` elements never ends
+        if self.inner.size_hint() == (usize::MAX, None) {
+            operator.boundedness = Boundedness::Unbounded;
+        }
         BlockStructure::default().add_operator(operator)
     }
 }
@@ -7,8 +7,20 @@ pub use async_stream::*;
 pub use avro::*;
 pub use channel::*;
 pub use file::*;
+pub use file_set::*;
+pub use file_tail::*;
+pub use fixed_width::*;
+#[cfg(feature = "timestamp")]
+pub use hybrid::*;
 pub use iterator::*;
+pub use nexmark::*;
+pub use object_store::{HttpObjectStore, ObjectStore};
 pub use parallel_iterator::*;
+pub use partitioned_file::*;
+pub use reader::ReaderSource;
+pub use split::*;
+#[cfg(not(feature = "tokio"))]
+pub use subscribe::*;
 
 use crate::{block::Replication, operator::Operator};
 
@@ -19,8 +31,20 @@ mod avro;
 mod channel;
 mod csv;
 mod file;
+mod file_set;
+mod file_tail;
+mod fixed_width;
+#[cfg(feature = "timestamp")]
+mod hybrid;
 mod iterator;
+mod nexmark;
+mod object_store;
 mod parallel_iterator;
+mod partitioned_file;
+mod reader;
+mod split;
+#[cfg(not(feature = "tokio"))]
+mod subscribe;
 
 /// This trait marks all the operators that can be used as sinks.
 pub trait Source: Operator {
@@ -0,0 +1,401 @@
+use std::time::{Duration, Instant};
+
+use nanorand::{Rng, WyRand};
+use serde::{Deserialize, Serialize};
+
+use crate::operator::source::{IntoParallelSource, ParallelIteratorSource};
+use crate::{CoordUInt, Stream};
+
+/// The number of most recent people that can sell auctions and make bids.
+const ACTIVE_PEOPLE: u64 = 1000;
+/// The number of most recent auctions that can receive bids.
+const ACTIVE_AUCTIONS: u64 = 100;
+/// Only one auction every this many is hot.
+const HOT_AUCTION_STRIDE: u64 = 100;
+/// Only one person every this many is a hot bidder or seller.
+const HOT_PERSON_STRIDE: u64 = 100;
+/// The id of the first category of the auctions.
+const FIRST_CATEGORY_ID: u64 = 10;
+/// The number of categories of the auctions.
+const NUM_CATEGORIES: u64 = 5;
+
+const FIRST_NAMES: [&str; 8] = [
+    "Peter", "Paul", "Luke", "John", "Saul", "Vicky", "Kate", "Julie",
+];
+const LAST_NAMES: [&str; 8] = [
+    "Shultz", "Abrams", "Spencer", "White", "Bartels", "Walton", "Smith", "Jones",
+];
+const US_STATES: [&str; 6] = ["AZ", "CA", "ID", "OR", "WA", "WY"];
+const US_CITIES: [&str; 10] = [
+    "Phoenix",
+    "Los Angeles",
+    "San Francisco",
+    "Boise",
+    "Portland",
+    "Bend",
+    "Redmond",
+    "Seattle",
+    "Kent",
+    "Cheyenne",
+];
+
+/// A person registered to the auction site.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NexmarkPerson {
+    pub id: u64,
+    pub name: String,
+    pub email_address: String,
+    pub city: String,
+    pub state: String,
+    /// The time of the registration, in milliseconds.
+    pub date_time: u64,
+}
+
+/// An item put up for auction by a person.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NexmarkAuction {
+    pub id: u64,
+    pub item_name: String,
+    pub initial_bid: u64,
+    pub reserve: u64,
+    /// The time the auction was opened, in milliseconds.
+    pub date_time: u64,
+    /// The time the auction closes, in milliseconds.
+    pub expires: u64,
+    /// The id of the person selling the item.
+    pub seller: u64,
+    pub category: u64,
+}
+
+/// A bid of a person for an auction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NexmarkBid {
+    pub auction: u64,
+    pub bidder: u64,
+    pub price: u64,
+    /// The time of the bid, in milliseconds.
+    pub date_time: u64,
+}
+
+/// An event of the auction site of the Nexmark benchmark, generated by [`NexmarkGenerator`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NexmarkEvent {
+    Person(NexmarkPerson),
+    Auction(NexmarkAuction),
+    Bid(NexmarkBid),
+}
+
+impl NexmarkEvent {
+    /// The time of the event, in milliseconds.
+    pub fn timestamp(&self) -> u64 {
+        match self {
+            NexmarkEvent::Person(p) => p.date_time,
+            NexmarkEvent::Auction(a) => a.date_time,
+            NexmarkEvent::Bid(b) => b.date_time,
+        }
+    }
+}
+
+/// Generator of the events of an auction site, like the ones of the Nexmark benchmark: people
+/// register, open auctions and bid for them.
+///
+/// The events are generated in parallel by the replicas of a [`ParallelIteratorSource`], see
+/// [`StreamContext::stream_nexmark`](crate::StreamContext::stream_nexmark). Each event only depends
+/// on its index and on the seed, so the same configuration generates the same events with any
+/// number of replicas, although in a different order.
+///
+/// The time of the `i`-th event is `i / events_per_second` seconds: the events of each replica are
+/// sorted by time, and can be used with event time windows after
+/// [`Stream::add_timestamps`](crate::Stream::add_timestamps).
+#[derive(Clone, Debug)]
+pub struct NexmarkGenerator {
+    num_events: u64,
+    events_per_second: u64,
+    real_time: bool,
+    person_proportion: u64,
+    auction_proportion: u64,
+    bid_proportion: u64,
+    hot_auction_ratio: u64,
+    hot_bidder_ratio: u64,
+    hot_seller_ratio: u64,
+    seed: u64,
+}
+
+impl NexmarkGenerator {
+    /// Create a generator of `num_events` events, with the default configuration of Nexmark.
+    pub fn new(num_events: u64) -> Self {
+        Self {
+            num_events,
+            events_per_second: 10_000,
+            real_time: false,
+            person_proportion: 1,
+            auction_proportion: 3,
+            bid_proportion: 46,
+            hot_auction_ratio: 2,
+            hot_bidder_ratio: 4,
+            hot_seller_ratio: 4,
+            seed: 0,
+        }
+    }
+
+    /// The rate of the events, which sets their time. The default is 10000.
+    pub fn events_per_second(mut self, events_per_second: u64) -> Self {
+        assert!(
+            events_per_second > 0,
+            "the events_per_second of NexmarkGenerator must be positive"
+        );
+        self.events_per_second = events_per_second;
+        self
+    }
+
+    /// Emit the events when their time comes, starting from when the source is set up, instead
+    /// of as fast as possible. The default is `false`.
+    pub fn real_time(mut self, real_time: bool) -> Self {
+        self.real_time = real_time;
+        self
+    }
+
+    /// The number of people, auctions and bids in each group of consecutive events. The default is
+    /// 1 person, 3 auctions and 46 bids.
+    pub fn proportions(mut self, person: u64, auction: u64, bid: u64) -> Self {
+        assert!(
+            person > 0 && person + auction + bid > 0,
+            "NexmarkGenerator needs at least a person in each group of events"
+        );
+        self.person_proportion = person;
+        self.auction_proportion = auction;
+        self.bid_proportion = bid;
+        self
+    }
+
+    /// The skew of the bids and of the sellers: `r - 1` out of `r` bids are for a hot auction, by
+    /// a hot bidder, and `r - 1` out of `r` auctions are sold by a hot seller. A ratio of 1 picks
+    /// all of them uniformly from the active ones. The defaults are 2, 4 and 4.
+    pub fn hot_ratios(mut self, auction: u64, bidder: u64, seller: u64) -> Self {
+        assert!(
+            auction > 0 && bidder > 0 && seller > 0,
+            "the hot ratios of NexmarkGenerator must be positive"
+        );
+        self.hot_auction_ratio = auction;
+        self.hot_bidder_ratio = bidder;
+        self.hot_seller_ratio = seller;
+        self
+    }
+
+    /// The seed of the random choices. The default is 0.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    fn events_per_group(&self) -> u64 {
+        self.person_proportion + self.auction_proportion + self.bid_proportion
+    }
+
+    /// The time of the event with the given index, in milliseconds.
+    fn date_time(&self, index: u64) -> u64 {
+        (index as u128 * 1000 / self.events_per_second as u128) as u64
+    }
+
+    /// The number of people registered in the events before the one with the given index.
+    fn people_before(&self, index: u64) -> u64 {
+        let (group, offset) = (
+            index / self.events_per_group(),
+            index % self.events_per_group(),
+        );
+        group * self.person_proportion + offset.min(self.person_proportion)
+    }
+
+    /// The number of auctions opened in the events before the one with the given index.
+    fn auctions_before(&self, index: u64) -> u64 {
+        let (group, offset) = (
+            index / self.events_per_group(),
+            index % self.events_per_group(),
+        );
+        let offset = offset.saturating_sub(self.person_proportion);
+        group * self.auction_proportion + offset.min(self.auction_proportion)
+    }
+
+    /// Pick an active person, a hot one with probability `(ratio - 1) / ratio`.
+    fn pick_person(&self, rng: &mut WyRand, index: u64, ratio: u64) -> u64 {
+        // there is at least a person before any auction or bid
+        let people = self.people_before(index).max(1);
+        if rng.generate_range(0..ratio) > 0 {
+            (people - 1) / HOT_PERSON_STRIDE * HOT_PERSON_STRIDE
+        } else {
+            people - 1 - rng.generate_range(0..people.min(ACTIVE_PEOPLE))
+        }
+    }
+
+    /// Generate the event with the given index.
+    fn event(&self, index: u64) -> NexmarkEvent {
+        let mut rng = WyRand::new_seed(self.seed ^ index.wrapping_mul(0x9e3779b97f4a7c15));
+        let date_time = self.date_time(index);
+        let offset = index % self.events_per_group();
+        if offset < self.person_proportion {
+            let id = self.people_before(index);
+            let first = FIRST_NAMES[rng.generate_range(0..FIRST_NAMES.len())];
+            let last = LAST_NAMES[rng.generate_range(0..LAST_NAMES.len())];
+            NexmarkEvent::Person(NexmarkPerson {
+                id,
+                name: format!("{first} {last}"),
+                email_address: format!("{}@{}.com", first.to_lowercase(), last.to_lowercase()),
+                city: US_CITIES[rng.generate_range(0..US_CITIES.len())].to_string(),
+                state: US_STATES[rng.generate_range(0..US_STATES.len())].to_string(),
+                date_time,
+            })
+        } else if offset < self.person_proportion + self.auction_proportion {
+            let id = self.auctions_before(index);
+            let initial_bid = 100 + rng.generate_range(0..10_000u64);
+            NexmarkEvent::Auction(NexmarkAuction {
+                id,
+                item_name: format!("item-{id}"),
+                initial_bid,
+                reserve: initial_bid + rng.generate_range(0..10_000u64),
+                date_time,
+                expires: date_time + 1000 + rng.generate_range(0..60_000u64),
+                seller: self.pick_person(&mut rng, index, self.hot_seller_ratio),
+                category: FIRST_CATEGORY_ID + rng.generate_range(0..NUM_CATEGORIES),
+            })
+        } else {
+            // the bids before the first auction are for the auction 0
+            let auctions = self.auctions_before(index).max(1);
+            let auction = if rng.generate_range(0..self.hot_auction_ratio) > 0 {
+                (auctions - 1) / HOT_AUCTION_STRIDE * HOT_AUCTION_STRIDE
+            } else {
+                auctions - 1 - rng.generate_range(0..auctions.min(ACTIVE_AUCTIONS))
+            };
+            // prices from 100 to 100 millions, with a long tail
+            let exponent = rng.generate::<u32>() as f64 / u32::MAX as f64 * 6.0;
+            NexmarkEvent::Bid(NexmarkBid {
+                auction,
+                bidder: self.pick_person(&mut rng, index, self.hot_bidder_ratio),
+                price: 10f64.powf(exponent) as u64 * 100,
+                date_time,
+            })
+        }
+    }
+}
+
+/// The events generated by a replica of a [`NexmarkGenerator`].
+pub struct NexmarkIter {
+    generator: NexmarkGenerator,
+    next: u64,
+    step: u64,
+    start: Instant,
+}
+
+impl Iterator for NexmarkIter {
+    type Item = NexmarkEvent;
+
+    fn next(&mut self) -> Option<NexmarkEvent> {
+        if self.next >= self.generator.num_events {
+            return None;
+        }
+        let event = self.generator.event(self.next);
+        self.next += self.step;
+        if self.generator.real_time {
+            let due = self.start + Duration::from_millis(event.timestamp());
+            std::thread::sleep(due.saturating_duration_since(Instant::now()));
+        }
+        Some(event)
+    }
+}
+
+impl IntoParallelSource for NexmarkGenerator {
+    type Iter = NexmarkIter;
+
+    fn generate_iterator(self, index: CoordUInt, peers: CoordUInt) -> Self::Iter {
+        NexmarkIter {
+            generator: self,
+            next: index,
+            step: peers,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl crate::StreamContext {
+    /// Convenience method, creates a [`ParallelIteratorSource`] of the events of a
+    /// [`NexmarkGenerator`].
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::{NexmarkEvent, NexmarkGenerator};
+    /// # let mut env = StreamContext::new_local();
+    /// let generator = NexmarkGenerator::new(10_000).hot_ratios(10, 4, 4);
+    /// let bids = env
+    ///     .stream_nexmark(generator)
+    ///     .filter_map(|e| match e {
+    ///         NexmarkEvent::Bid(b) => Some(b),
+    ///         _ => None,
+    ///     })
+    ///     .collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// // 46 out of 50 events are bids
+    /// assert_eq!(bids.get().unwrap().len(), 9200);
+    /// ```
+    pub fn stream_nexmark(
+        &self,
+        generator: NexmarkGenerator,
+    ) -> Stream<ParallelIteratorSource<NexmarkGenerator>> {
+        self.stream_par_iter(generator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{NexmarkEvent, NexmarkGenerator};
+    use crate::operator::source::IntoParallelSource;
+
+    #[test]
+    fn nexmark_replicas() {
+        // one event per millisecond, so the events can be sorted by time
+        let generator = NexmarkGenerator::new(1000).events_per_second(1000).seed(42);
+        let single: Vec<_> = generator.clone().generate_iterator(0, 1).collect();
+        let mut parallel: Vec<_> = (0..3)
+            .flat_map(|i| generator.clone().generate_iterator(i, 3))
+            .collect();
+        parallel.sort_by_key(|e| e.timestamp());
+        assert_eq!(single.len(), 1000);
+        assert_eq!(single, parallel);
+    }
+
+    #[test]
+    fn nexmark_events() {
+        let generator = NexmarkGenerator::new(5000).events_per_second(1000);
+        let events: Vec<_> = generator.generate_iterator(0, 1).collect();
+        assert_eq!(events[4999].timestamp(), 4999);
+
+        let mut people = 0;
+        let mut auctions = 0;
+        let mut bids_per_auction: HashMap<u64, usize> = HashMap::new();
+        for event in events {
+            match event {
+                NexmarkEvent::Person(p) => {
+                    assert_eq!(p.id, people);
+                    people += 1;
+                }
+                NexmarkEvent::Auction(a) => {
+                    assert_eq!(a.id, auctions);
+                    assert!(a.seller < people && a.reserve >= a.initial_bid);
+                    auctions += 1;
+                }
+                NexmarkEvent::Bid(b) => {
+                    assert!(b.auction < auctions && b.bidder < people);
+                    *bids_per_auction.entry(b.auction).or_default() += 1;
+                }
+            }
+        }
+        assert_eq!((people, auctions), (100, 300));
+        // about half of the bids are for the hot auctions 0, 100 and 200
+        let hot: usize = [0, 100, 200].iter().map(|a| bids_per_auction[a]).sum();
+        assert!(hot > 4600 * 4 / 10, "{hot} bids for the hot auctions");
+    }
+}
@@ -0,0 +1,368 @@
+//! Access to the files stored in an object store (e.g. S3, GCS or a plain HTTP server).
+
+use std::fmt::Debug;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Maximum number of bytes requested with a single ranged read by [`ObjectReader`].
+const RANGE_WINDOW: u64 = 8 << 20;
+/// Default timeout of [`HttpObjectStore`] for connecting and for each read or write.
+const DEFAULT_HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A service that stores objects identified by an URI and allows reading a byte range of them.
+///
+/// The sources reading from an object store split the objects by byte ranges between their
+/// replicas, exactly like they do with local files, and each replica requests only the ranges it
+/// has to read.
+///
+/// renoir provides [`HttpObjectStore`] for reading the objects served over plain HTTP, other
+/// stores (or authenticated access to S3 and GCS) can be added by implementing this trait on top
+/// of their client library.
+pub trait ObjectStore: Debug + Send + Sync + 'static {
+    /// The size in bytes of the object.
+    fn size(&self, uri: &str) -> io::Result<u64>;
+
+    /// Read the bytes of the object inside `range`.
+    ///
+    /// The returned reader must yield exactly the bytes of the range.
+    fn get_range(&self, uri: &str, range: Range<u64>) -> io::Result<Box<dyn Read + Send>>;
+}
+
+/// An [`ObjectStore`] that reads the objects using HTTP `HEAD` and ranged `GET` requests.
+///
+/// The `http://` URIs are requested as they are. The `s3://bucket/key` and `gs://bucket/key` URIs
+/// are mapped to `endpoint/bucket/key` using the endpoint set with
+/// [`HttpObjectStore::s3_endpoint`] or [`HttpObjectStore::gcs_endpoint`], so the objects can be
+/// read through a path-style HTTP gateway (e.g. MinIO or a local proxy).
+///
+/// The server must honor the ranged requests (answering `206 Partial Content`) and must not use a
+/// chunked transfer encoding, otherwise the read fails.
+///
+/// **Note**: the requests are not authenticated and TLS is not supported, only plain `http://`
+/// endpoints can be used. Implement [`ObjectStore`] with a full HTTP client for anything else.
+#[derive(Clone, Debug)]
+pub struct HttpObjectStore {
+    s3_endpoint: Option<String>,
+    gcs_endpoint: Option<String>,
+    timeout: Duration,
+}
+
+impl Default for HttpObjectStore {
+    fn default() -> Self {
+        Self {
+            s3_endpoint: None,
+            gcs_endpoint: None,
+            timeout: DEFAULT_HTTP_TIMEOUT,
+        }
+    }
+}
+
+impl HttpObjectStore {
+    /// Create a store that can read only `http://` URIs.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The timeout for connecting to the server and for each read or write on the connection.
+    ///
+    /// The default is 30 seconds.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The HTTP endpoint used for reading the `s3://` URIs.
+    pub fn s3_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.s3_endpoint = Some(endpoint.into());
+        self
+    }
+
+    /// The HTTP endpoint used for reading the `gs://` URIs.
+    pub fn gcs_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.gcs_endpoint = Some(endpoint.into());
+        self
+    }
+
+    /// The `http://` URL of the object with the given URI.
+    fn url(&self, uri: &str) -> io::Result<String> {
+        let (scheme, rest) = uri
+            .split_once("://")
+            .ok_or_else(|| invalid_input(format!("invalid object URI: {uri}")))?;
+        let endpoint = match scheme {
+            "http" => return Ok(uri.to_string()),
+            "s3" => &self.s3_endpoint,
+            "gs" => &self.gcs_endpoint,
+            _ => return Err(invalid_input(format!("unsupported URI scheme: {uri}"))),
+        };
+        let endpoint = endpoint
+            .as_ref()
+            .ok_or_else(|| invalid_input(format!("no endpoint configured for {scheme}://")))?;
+        Ok(format!("{}/{}", endpoint.trim_end_matches('/'), rest))
+    }
+
+    /// Send a request for the object and parse the response headers.
+    fn request(
+        &self,
+        method: &str,
+        uri: &str,
+        range: Option<&Range<u64>>,
+    ) -> io::Result<HttpResponse> {
+        let url = self.url(uri)?;
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or_else(|| invalid_input(format!("only http:// is supported: {url}")))?;
+        let (authority, path) = match rest.find('/') {
+            Some(i) => rest.split_at(i),
+            None => (rest, "/"),
+        };
+        let address = if authority.contains(':') {
+            authority.to_string()
+        } else {
+            format!("{authority}:80")
+        };
+
+        let mut stream = self.connect(&address)?;
+        let mut request =
+            format!("{method} {path} HTTP/1.1\r\nHost: {authority}\r\nConnection: close\r\n");
+        if let Some(range) = range {
+            request += &format!("Range: bytes={}-{}\r\n", range.start, range.end - 1);
+        }
+        request += "\r\n";
+        stream.write_all(request.as_bytes())?;
+
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let status = line
+            .split_whitespace()
+            .nth(1)
+            .and_then(|s| s.parse::<u16>().ok())
+            .ok_or_else(|| invalid_data(format!("invalid HTTP response: {line:?}")))?;
+
+        let mut content_length = None;
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+                break;
+            }
+            let Some((name, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.parse::<u64>().ok();
+            } else if name.eq_ignore_ascii_case("transfer-encoding")
+                && !value.eq_ignore_ascii_case("identity")
+            {
+                return Err(invalid_data(format!(
+                    "unsupported transfer encoding for {url}: {value}"
+                )));
+            }
+        }
+
+        if !(200..300).contains(&status) {
+            return Err(io::Error::other(format!(
+                "{method} {url} failed with status {status}"
+            )));
+        }
+        Ok(HttpResponse {
+            status,
+            content_length,
+            body: reader,
+        })
+    }
+}
+
+impl HttpObjectStore {
+    /// Connect to the first reachable address of `address`, with the configured timeouts.
+    fn connect(&self, address: &str) -> io::Result<TcpStream> {
+        let mut error = invalid_input(format!("no address found for {address}"));
+        for address in address.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&address, self.timeout) {
+                Ok(stream) => {
+                    stream.set_read_timeout(Some(self.timeout))?;
+                    stream.set_write_timeout(Some(self.timeout))?;
+                    return Ok(stream);
+                }
+                Err(e) => error = e,
+            }
+        }
+        Err(error)
+    }
+}
+
+struct HttpResponse {
+    status: u16,
+    content_length: Option<u64>,
+    body: BufReader<TcpStream>,
+}
+
+impl ObjectStore for HttpObjectStore {
+    fn size(&self, uri: &str) -> io::Result<u64> {
+        self.request("HEAD", uri, None)?
+            .content_length
+            .ok_or_else(|| invalid_data(format!("the size of {uri} is not known")))
+    }
+
+    fn get_range(&self, uri: &str, range: Range<u64>) -> io::Result<Box<dyn Read + Send>> {
+        if range.is_empty() {
+            return Ok(Box::new(io::empty()));
+        }
+        let response = self.request("GET", uri, Some(&range))?;
+        let len = range.end - range.start;
+        if response.status != 206 {
+            // the server ignored the range, reading the whole object for each range is not viable
+            return Err(invalid_data(format!(
+                "the ranged request for {uri} was answered with status {}",
+                response.status
+            )));
+        }
+        if let Some(content_length) = response.content_length.filter(|&l| l != len) {
+            return Err(invalid_data(format!(
+                "the range {range:?} of {uri} has {content_length} bytes instead of {len}"
+            )));
+        }
+        Ok(Box::new(response.body.take(len)))
+    }
+}
+
+/// A reader over an object of an [`ObjectStore`] that supports seeking.
+///
+/// The object is read with ranged requests of at most a fixed number of bytes, a new request is
+/// made after seeking or when the current range is exhausted.
+pub(crate) struct ObjectReader {
+    store: Arc<dyn ObjectStore>,
+    uri: String,
+    size: u64,
+    /// The offset of the next byte to read.
+    position: u64,
+    /// The reader of the current range and the offset where it ends.
+    current: Option<(Box<dyn Read + Send>, u64)>,
+}
+
+impl ObjectReader {
+    pub(crate) fn new(store: Arc<dyn ObjectStore>, uri: String) -> io::Result<Self> {
+        let size = store.size(&uri)?;
+        Ok(Self {
+            store,
+            uri,
+            size,
+            position: 0,
+            current: None,
+        })
+    }
+
+    /// The size in bytes of the object.
+    pub(crate) fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+impl Read for ObjectReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.size || buf.is_empty() {
+            return Ok(0);
+        }
+        if !matches!(&self.current, Some((_, end)) if self.position < *end) {
+            let end = self.size.min(self.position + RANGE_WINDOW);
+            let reader = self.store.get_range(&self.uri, self.position..end)?;
+            self.current = Some((reader, end));
+        }
+        let (reader, _) = self.current.as_mut().unwrap();
+        let n = reader.read(buf)?;
+        if n == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("{}: the range ended before the expected size", self.uri),
+            ));
+        }
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for ObjectReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.size.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        }
+        .ok_or_else(|| invalid_input("seek to a negative position".to_string()))?;
+        if position != self.position {
+            self.current = None;
+            self.position = position;
+        }
+        Ok(position)
+    }
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::time::Duration;
+
+    use super::{HttpObjectStore, ObjectStore};
+
+    /// Answer each request with `response`, after reading its headers.
+    fn serve(response: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        format!("http://{address}/data")
+    }
+
+    #[test]
+    fn http_range() {
+        let uri = serve("HTTP/1.1 206 Partial Content\r\nContent-Length: 3\r\n\r\nabc");
+        let mut body = String::new();
+        HttpObjectStore::new()
+            .get_range(&uri, 10..13)
+            .unwrap()
+            .read_to_string(&mut body)
+            .unwrap();
+        assert_eq!(body, "abc");
+    }
+
+    #[test]
+    fn http_range_ignored() {
+        let uri = serve("HTTP/1.1 200 OK\r\nContent-Length: 6\r\n\r\nabcdef");
+        let err = HttpObjectStore::new().get_range(&uri, 2..4).err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn http_timeout() {
+        // the connection is accepted by the OS, but nobody answers the request
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let uri = format!("http://{}/data", listener.local_addr().unwrap());
+        let store = HttpObjectStore::new().timeout(Duration::from_millis(100));
+        let err = store.size(&uri).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+        ));
+    }
+}
@@ -1,7 +1,7 @@
 use std::fmt::Display;
 use std::ops::Range;
 
-use crate::block::{BlockStructure, OperatorKind, OperatorStructure, Replication};
+use crate::block::{BlockStructure, OperatorKind, OperatorStructure, Replication, TimestampUsage};
 use crate::operator::source::Source;
 use crate::operator::{Operator, StreamElement};
 use crate::scheduler::ExecutionMetadata;
@@ -185,6 +185,7 @@ where
         let mut operator =
             OperatorStructure::new::<<S::Iter as Iterator>::Item, _>("ParallelIteratorSource");
         operator.kind = OperatorKind::Source;
+        operator.timestamps = TimestampUsage::Drop;
         BlockStructure::default().add_operator(operator)
     }
 }
@@ -0,0 +1,292 @@
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::block::{BlockStructure, OperatorKind, OperatorStructure, Replication, TimestampUsage};
+use crate::operator::source::Source;
+use crate::operator::{Operator, StreamElement};
+use crate::scheduler::ExecutionMetadata;
+use crate::Stream;
+
+/// The partition columns of a file, derived from the `key=value` directories in its path.
+///
+/// For example, the file `root/date=2024-01-01/country=it/data.txt` has the columns `date` and
+/// `country`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Partition {
+    columns: Vec<(String, String)>,
+}
+
+impl Partition {
+    /// The value of a partition column, if the path has it.
+    pub fn get(&self, column: &str) -> Option<&str> {
+        self.columns
+            .iter()
+            .find(|(c, _)| c == column)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// All the partition columns, in the order they appear in the path.
+    pub fn columns(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.columns.iter().map(|(c, v)| (c.as_str(), v.as_str()))
+    }
+
+    /// Parse a path component in the `key=value` form.
+    fn parse_component(name: &str) -> Option<(String, String)> {
+        let (column, value) = name.split_once('=')?;
+        (!column.is_empty()).then(|| (column.to_string(), value.to_string()))
+    }
+}
+
+type ColumnFilter = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// Source that reads line-by-line the text files inside a directory partitioned by the values of
+/// some columns, skipping the partitions that cannot match the filters.
+///
+/// The directories named `key=value` define the partition columns of the files inside of them.
+/// Each filter added with [`PartitionedFileSource::filter_partition`] is checked as soon as the
+/// corresponding directory is found, so the directories that don't match are never visited; the
+/// files whose path lacks a filtered column are skipped.
+///
+/// The files are divided between the replicas, each replica has to see the **same** directory in
+/// the same path. Each line is emitted together with the partition of its file.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct PartitionedFileSource {
+    root: PathBuf,
+    #[derivative(Debug = "ignore")]
+    filters: Vec<(String, ColumnFilter)>,
+    /// The files assigned to this replica, initialized in `setup`.
+    files: Vec<(PathBuf, Partition)>,
+    #[derivative(Debug = "ignore")]
+    reader: Option<(BufReader<File>, Partition)>,
+    terminated: bool,
+}
+
+impl Display for PartitionedFileSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PartitionedFileSource<{}>", self.root.display())
+    }
+}
+
+impl PartitionedFileSource {
+    /// Create a new source that reads all the files inside the `root` directory.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::PartitionedFileSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let source = PartitionedFileSource::new("/datasets/events")
+    ///     .filter_partition("date", |date| date >= "2024-01-01");
+    /// let s = env.stream(source);
+    /// ```
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        Self {
+            root: root.into(),
+            filters: Default::default(),
+            files: Default::default(),
+            reader: None,
+            terminated: false,
+        }
+    }
+
+    /// Read only the files whose value of the partition column `column` satisfies `filter`.
+    pub fn filter_partition<F>(mut self, column: impl Into<String>, filter: F) -> Self
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        self.filters.push((column.into(), Arc::new(filter)));
+        self
+    }
+
+    /// Whether the partition may still match all the filters.
+    fn matches(&self, column: &str, value: &str) -> bool {
+        self.filters
+            .iter()
+            .filter(|(c, _)| c == column)
+            .all(|(_, f)| f(value))
+    }
+
+    /// Collect the files inside `dir` that can match the filters.
+    fn list_files(&self, dir: &Path, partition: &Partition, files: &mut Vec<(PathBuf, Partition)>) {
+        let entries = std::fs::read_dir(dir).unwrap_or_else(|err| {
+            panic!("PartitionedFileSource: error while reading directory {dir:?}: {err:?}")
+        });
+        for entry in entries {
+            let entry = entry.expect("PartitionedFileSource: cannot read directory entry");
+            let path = entry.path();
+            if path.is_dir() {
+                let mut partition = partition.clone();
+                let name = entry.file_name();
+                if let Some((column, value)) = name.to_str().and_then(Partition::parse_component) {
+                    if !self.matches(&column, &value) {
+                        log::debug!("PartitionedFileSource: pruning {path:?}");
+                        continue;
+                    }
+                    partition.columns.push((column, value));
+                }
+                self.list_files(&path, &partition, files);
+            } else if self.filters.iter().all(|(c, _)| partition.get(c).is_some()) {
+                files.push((path, partition.clone()));
+            }
+        }
+    }
+}
+
+impl Source for PartitionedFileSource {
+    fn replication(&self) -> Replication {
+        Replication::Unlimited
+    }
+}
+
+impl Operator for PartitionedFileSource {
+    type Out = (Partition, String);
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        let global_id = metadata.global_id as usize;
+        let instances = metadata.replicas.len();
+
+        let mut files = vec![];
+        self.list_files(&self.root, &Partition::default(), &mut files);
+        // all the replicas must agree on the order of the files
+        files.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        self.files = files
+            .into_iter()
+            .skip(global_id)
+            .step_by(instances)
+            .rev()
+            .collect();
+    }
+
+    fn next(&mut self) -> StreamElement<Self::Out> {
+        if self.terminated {
+            return StreamElement::Terminate;
+        }
+        loop {
+            if let Some((reader, partition)) = self.reader.as_mut() {
+                let mut line = String::new();
+                match reader.read_line(&mut line) {
+                    Ok(len) if len > 0 => return StreamElement::Item((partition.clone(), line)),
+                    Ok(_) => self.reader = None,
+                    Err(e) => panic!("Error while reading file: {e:?}"),
+                }
+            }
+            match self.files.pop() {
+                Some((path, partition)) => {
+                    let file = File::open(&path).unwrap_or_else(|err| {
+                        panic!("PartitionedFileSource: error while opening file {path:?}: {err:?}")
+                    });
+                    self.reader = Some((BufReader::new(file), partition));
+                }
+                None => {
+                    self.terminated = true;
+                    return StreamElement::FlushAndRestart;
+                }
+            }
+        }
+    }
+
+    fn structure(&self) -> BlockStructure {
+        let mut operator = OperatorStructure::new::<Self::Out, _>("PartitionedFileSource");
+        operator.kind = OperatorKind::Source;
+        operator.timestamps = TimestampUsage::Drop;
+        BlockStructure::default().add_operator(operator)
+    }
+}
+
+impl Clone for PartitionedFileSource {
+    fn clone(&self) -> Self {
+        assert!(
+            self.reader.is_none() && self.files.is_empty(),
+            "PartitionedFileSource must be cloned before calling setup"
+        );
+        Self {
+            root: self.root.clone(),
+            filters: self.filters.clone(),
+            files: Default::default(),
+            reader: None,
+            terminated: false,
+        }
+    }
+}
+
+impl crate::StreamContext {
+    /// Convenience method, creates a `PartitionedFileSource` reading all the partitions and makes
+    /// a stream using `StreamContext::stream`
+    pub fn stream_partitioned_files<P: Into<PathBuf>>(
+        &self,
+        root: P,
+    ) -> Stream<PartitionedFileSource> {
+        let source = PartitionedFileSource::new(root);
+        self.stream(source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use itertools::Itertools;
+
+    use crate::config::RuntimeConfig;
+    use crate::environment::StreamContext;
+    use crate::operator::source::PartitionedFileSource;
+
+    fn write_partitions(root: &std::path::Path) {
+        for date in ["2024-01-01", "2024-01-02", "2024-01-03"] {
+            for country in ["it", "fr"] {
+                let dir = root
+                    .join(format!("date={date}"))
+                    .join(format!("country={country}"));
+                fs::create_dir_all(&dir).unwrap();
+                fs::write(dir.join("data.txt"), format!("{date} {country}\n")).unwrap();
+            }
+        }
+        fs::write(root.join("unpartitioned.txt"), "none\n").unwrap();
+    }
+
+    #[test]
+    fn partitioned_file_all() {
+        let dir = tempfile::tempdir().unwrap();
+        write_partitions(dir.path());
+
+        let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        let res = env
+            .stream_partitioned_files(dir.path())
+            .map(|(p, line)| (p.columns().count(), line))
+            .collect_vec();
+        env.execute_blocking();
+
+        let res = res.get().unwrap().into_iter().sorted().collect_vec();
+        assert_eq!(res.len(), 7);
+        assert_eq!(res[0], (0, "none\n".to_string()));
+    }
+
+    #[test]
+    fn partitioned_file_pruning() {
+        let dir = tempfile::tempdir().unwrap();
+        write_partitions(dir.path());
+
+        let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        let source = PartitionedFileSource::new(dir.path())
+            .filter_partition("date", |d| d >= "2024-01-02")
+            .filter_partition("country", |c| c == "it");
+        let res = env
+            .stream(source)
+            .map(|(p, line)| {
+                assert_eq!(line, format!("{} {}\n", p.get("date").unwrap(), "it"));
+                line
+            })
+            .collect_vec();
+        env.execute_blocking();
+
+        let res = res.get().unwrap().into_iter().sorted().collect_vec();
+        assert_eq!(res, vec!["2024-01-02 it\n", "2024-01-03 it\n"]);
+    }
+}
@@ -0,0 +1,240 @@
+use std::fmt::Display;
+use std::io::{self, BufRead, BufReader, Read};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::block::{BlockStructure, OperatorKind, OperatorStructure, Replication, TimestampUsage};
+use crate::operator::source::Source;
+use crate::operator::{Data, Operator, StreamElement};
+use crate::scheduler::ExecutionMetadata;
+use crate::Stream;
+
+/// A reader shared between the clones of a source, taken by the only replica that reads it.
+#[derive(Clone)]
+pub(crate) struct SharedReader(Arc<Mutex<Option<Box<dyn Read + Send>>>>);
+
+impl SharedReader {
+    pub(crate) fn new(reader: impl Read + Send + 'static) -> Self {
+        Self(Arc::new(Mutex::new(Some(Box::new(reader)))))
+    }
+
+    /// Take the reader, panicking if it has already been taken by a previous execution.
+    pub(crate) fn take(&self) -> Box<dyn Read + Send> {
+        self.0
+            .lock()
+            .take()
+            .expect("the reader of the source has already been consumed")
+    }
+}
+
+impl std::fmt::Debug for SharedReader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SharedReader")
+    }
+}
+
+/// Read the next record from the reader, given the delimiter of the source.
+type Decoder<Out> = fn(&mut BufReader<Box<dyn Read + Send>>, u8) -> io::Result<Option<Out>>;
+
+/// Source that reads the records of any [`Read`], like the standard input, a pipe or an in-memory
+/// buffer.
+///
+/// A reader can only be read sequentially, so the source has a single replica. The records are
+/// framed according to the constructor: [`ReaderSource::lines`], [`ReaderSource::delimited`] or
+/// [`ReaderSource::length_delimited`].
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct ReaderSource<Out: Data> {
+    #[derivative(Debug = "ignore")]
+    shared: SharedReader,
+    #[derivative(Debug = "ignore")]
+    reader: Option<BufReader<Box<dyn Read + Send>>>,
+    #[derivative(Debug = "ignore")]
+    decode: Decoder<Out>,
+    delimiter: u8,
+    terminated: bool,
+}
+
+impl<Out: Data> Display for ReaderSource<Out> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ReaderSource<{}>", std::any::type_name::<Out>())
+    }
+}
+
+impl ReaderSource<String> {
+    /// Create a new source that reads the lines of `reader`.
+    ///
+    /// The lines are emitted including their terminator, like [`FileSource`](super::FileSource).
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::ReaderSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let source = ReaderSource::lines(&b"hello\nworld\n"[..]);
+    /// let res = env.stream(source).collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// assert_eq!(res.get().unwrap(), vec!["hello\n", "world\n"]);
+    /// ```
+    pub fn lines(reader: impl Read + Send + 'static) -> Self {
+        Self::with_decoder(reader, |reader, _| {
+            let mut line = String::new();
+            Ok((reader.read_line(&mut line)? > 0).then_some(line))
+        })
+    }
+
+    /// Create a new source that reads the lines of the standard input of the process.
+    pub fn stdin() -> Self {
+        Self::lines(io::stdin())
+    }
+}
+
+impl ReaderSource<Vec<u8>> {
+    /// Create a new source that reads the records of `reader` separated by `delimiter`.
+    ///
+    /// The records are emitted without the delimiter, the last one may not be followed by it.
+    pub fn delimited(reader: impl Read + Send + 'static, delimiter: u8) -> Self {
+        let mut source = Self::with_decoder(reader, |reader, delimiter| {
+            let mut record = Vec::new();
+            if reader.read_until(delimiter, &mut record)? == 0 {
+                return Ok(None);
+            }
+            if record.last() == Some(&delimiter) {
+                record.pop();
+            }
+            Ok(Some(record))
+        });
+        source.delimiter = delimiter;
+        source
+    }
+
+    /// Create a new source that reads the records of `reader`, each one preceded by its length as
+    /// a 32-bit big-endian integer.
+    pub fn length_delimited(reader: impl Read + Send + 'static) -> Self {
+        Self::with_decoder(reader, |reader, _| {
+            let mut len = [0; 4];
+            match reader.read_exact(&mut len) {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+                Err(e) => return Err(e),
+            }
+            let mut record = vec![0; u32::from_be_bytes(len) as usize];
+            reader.read_exact(&mut record)?;
+            Ok(Some(record))
+        })
+    }
+}
+
+impl<Out: Data> ReaderSource<Out> {
+    fn with_decoder(reader: impl Read + Send + 'static, decode: Decoder<Out>) -> Self {
+        Self {
+            shared: SharedReader::new(reader),
+            reader: None,
+            decode,
+            delimiter: b'\n',
+            terminated: false,
+        }
+    }
+}
+
+impl<Out: Data> Source for ReaderSource<Out> {
+    fn replication(&self) -> Replication {
+        Replication::One
+    }
+}
+
+impl<Out: Data> Operator for ReaderSource<Out> {
+    type Out = Out;
+
+    fn setup(&mut self, _metadata: &mut ExecutionMetadata) {
+        self.reader = Some(BufReader::new(self.shared.take()));
+    }
+
+    fn next(&mut self) -> StreamElement<Out> {
+        if self.terminated {
+            return StreamElement::Terminate;
+        }
+        let reader = self
+            .reader
+            .as_mut()
+            .expect("ReaderSource was not initialized");
+        match (self.decode)(reader, self.delimiter) {
+            Ok(Some(record)) => StreamElement::Item(record),
+            Ok(None) => {
+                self.terminated = true;
+                StreamElement::FlushAndRestart
+            }
+            Err(e) => panic!("Error while reading from the reader: {e:?}"),
+        }
+    }
+
+    fn structure(&self) -> BlockStructure {
+        let mut operator = OperatorStructure::new::<Out, _>("ReaderSource");
+        operator.kind = OperatorKind::Source;
+        operator.timestamps = TimestampUsage::Drop;
+        BlockStructure::default().add_operator(operator)
+    }
+}
+
+impl<Out: Data> Clone for ReaderSource<Out> {
+    fn clone(&self) -> Self {
+        assert!(
+            self.reader.is_none(),
+            "ReaderSource must be cloned before calling setup"
+        );
+        Self {
+            shared: self.shared.clone(),
+            reader: None,
+            decode: self.decode,
+            delimiter: self.delimiter,
+            terminated: false,
+        }
+    }
+}
+
+impl crate::StreamContext {
+    /// Convenience method, creates a `ReaderSource` reading the lines of the standard input and
+    /// makes a stream using `StreamContext::stream`
+    pub fn stream_stdin(&self) -> Stream<ReaderSource<String>> {
+        self.stream(ReaderSource::stdin())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::ReaderSource;
+    use crate::operator::{Data, Operator, StreamElement};
+    use crate::test::FakeNetworkTopology;
+
+    fn read_all<Out: Data>(mut source: ReaderSource<Out>) -> Vec<Out> {
+        source.setup(&mut FakeNetworkTopology::<u32>::new(0, 0).metadata());
+        let mut res = Vec::new();
+        while let StreamElement::Item(record) = source.next() {
+            res.push(record);
+        }
+        assert!(matches!(source.next(), StreamElement::Terminate));
+        res
+    }
+
+    #[test]
+    fn reader_source_framing() {
+        let source = ReaderSource::delimited(Cursor::new(b"a,bc,,d".to_vec()), b',');
+        let expected: Vec<&[u8]> = vec![b"a", b"bc", b"", b"d"];
+        assert_eq!(read_all(source), expected);
+
+        let mut data = Vec::new();
+        for record in [&b"hello"[..], b"", b"world!"] {
+            data.extend((record.len() as u32).to_be_bytes());
+            data.extend(record);
+        }
+        let source = ReaderSource::length_delimited(Cursor::new(data));
+        let expected: Vec<&[u8]> = vec![b"hello", b"", b"world!"];
+        assert_eq!(read_all(source), expected);
+    }
+}
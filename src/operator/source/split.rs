@@ -0,0 +1,394 @@
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::block::{BlockStructure, OperatorKind, OperatorStructure, Replication, TimestampUsage};
+use crate::operator::source::Source;
+use crate::operator::{Data, Operator, StreamElement};
+use crate::scheduler::ExecutionMetadata;
+use crate::Stream;
+
+/// A part of the input of a source that can be read independently of the others, like a file, a
+/// byte range of a file or a partition of a topic.
+///
+/// The splits are read by a [`SplitSource`], which assigns them to its replicas while the job is
+/// running.
+pub trait SourceSplit: Send + 'static {
+    type Out: Data;
+    type Reader: Iterator<Item = Self::Out> + Send;
+
+    /// An estimate of the cost of reading this split, like its size in bytes.
+    ///
+    /// It is used to balance the splits between the hosts and to read the larger ones first.
+    fn size(&self) -> u64 {
+        1
+    }
+
+    /// Start reading this split, the reader yields all its records.
+    fn open(self) -> Self::Reader;
+}
+
+/// Divide the splits with the given `sizes` between `num_hosts` hosts, returning the indices of
+/// the ones assigned to the host with index `host_index`, largest first.
+///
+/// Each split goes to the host with the smallest total size so far, starting from the largest
+/// split. The result only depends on the sizes, so all the hosts agree on the assignment.
+fn assign_splits(sizes: &[u64], num_hosts: usize, host_index: usize) -> Vec<usize> {
+    let mut order: Vec<_> = (0..sizes.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(sizes[i]));
+    let mut loads = vec![0; num_hosts];
+    let mut assigned = Vec::new();
+    for i in order {
+        let (host, _) = loads
+            .iter()
+            .enumerate()
+            .min_by_key(|&(host, load)| (*load, host))
+            .unwrap();
+        loads[host] += sizes[i];
+        if host == host_index {
+            assigned.push(i);
+        }
+    }
+    assigned
+}
+
+/// Source that reads a set of [`SourceSplit`]s.
+///
+/// The splits are enumerated by each host when the job starts and are divided between the hosts
+/// balancing their sizes. The replicas of each host then take the next split to read from a
+/// shared queue, so when the sizes of the splits are skewed a replica that finishes early moves
+/// on to the remaining splits instead of waiting for the others. Each host has to enumerate the
+/// **same** splits in the same order.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct SplitSource<S: SourceSplit> {
+    #[derivative(Debug = "ignore")]
+    enumerate: Arc<dyn Fn() -> Vec<S> + Send + Sync>,
+    /// The splits still to be read by the replicas of this host, filled by the first replica that
+    /// is set up.
+    #[derivative(Debug = "ignore")]
+    queue: Arc<Mutex<Option<VecDeque<S>>>>,
+    #[derivative(Debug = "ignore")]
+    reader: Option<S::Reader>,
+    terminated: bool,
+}
+
+impl<S: SourceSplit> Display for SplitSource<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SplitSource<{}>", std::any::type_name::<S::Out>())
+    }
+}
+
+impl<S: SourceSplit> SplitSource<S> {
+    /// Create a new source that reads the splits returned by `enumerate`.
+    ///
+    /// `enumerate` is called once on each host when the job starts.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::{LineRangeSplit, SplitSource};
+    /// # let mut env = StreamContext::new_local();
+    /// let source = SplitSource::new(|| {
+    ///     let mut splits = LineRangeSplit::of_file("/datasets/huge.txt", 64 << 20);
+    ///     splits.extend(LineRangeSplit::of_file("/datasets/small.txt", 64 << 20));
+    ///     splits
+    /// });
+    /// let s = env.stream(source);
+    /// ```
+    pub fn new<F>(enumerate: F) -> Self
+    where
+        F: Fn() -> Vec<S> + Send + Sync + 'static,
+    {
+        Self {
+            enumerate: Arc::new(enumerate),
+            queue: Default::default(),
+            reader: None,
+            terminated: false,
+        }
+    }
+}
+
+impl<S: SourceSplit> Source for SplitSource<S> {
+    fn replication(&self) -> Replication {
+        Replication::Unlimited
+    }
+}
+
+impl<S: SourceSplit> Operator for SplitSource<S> {
+    type Out = S::Out;
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        let mut queue = self.queue.lock();
+        if queue.is_some() {
+            return;
+        }
+
+        let mut hosts = metadata
+            .replicas
+            .iter()
+            .map(|c| c.host_id)
+            .collect::<Vec<_>>();
+        hosts.sort_unstable();
+        hosts.dedup();
+        let host_index = hosts
+            .iter()
+            .position(|&h| h == metadata.coord.host_id)
+            .unwrap();
+
+        let splits = (self.enumerate)();
+        let sizes: Vec<_> = splits.iter().map(|s| s.size()).collect();
+        let assigned = assign_splits(&sizes, hosts.len(), host_index);
+        log::debug!(
+            "{}: host {} reads {} of {} splits",
+            metadata.coord,
+            metadata.coord.host_id,
+            assigned.len(),
+            splits.len()
+        );
+        let mut splits: Vec<_> = splits.into_iter().map(Some).collect();
+        *queue = Some(
+            assigned
+                .into_iter()
+                .map(|i| splits[i].take().unwrap())
+                .collect(),
+        );
+    }
+
+    fn next(&mut self) -> StreamElement<Self::Out> {
+        if self.terminated {
+            return StreamElement::Terminate;
+        }
+        loop {
+            if let Some(item) = self.reader.as_mut().and_then(|r| r.next()) {
+                return StreamElement::Item(item);
+            }
+            let next_split = self
+                .queue
+                .lock()
+                .as_mut()
+                .expect("SplitSource was not initialized")
+                .pop_front();
+            match next_split {
+                Some(split) => self.reader = Some(split.open()),
+                None => {
+                    self.reader = None;
+                    self.terminated = true;
+                    return StreamElement::FlushAndRestart;
+                }
+            }
+        }
+    }
+
+    fn structure(&self) -> BlockStructure {
+        let mut operator = OperatorStructure::new::<Self::Out, _>("SplitSource");
+        operator.kind = OperatorKind::Source;
+        operator.timestamps = TimestampUsage::Drop;
+        BlockStructure::default().add_operator(operator)
+    }
+}
+
+impl<S: SourceSplit> Clone for SplitSource<S> {
+    fn clone(&self) -> Self {
+        assert!(
+            self.reader.is_none(),
+            "SplitSource must be cloned before calling setup"
+        );
+        // the clones are the replicas of the source, they share the queue of the splits
+        Self {
+            enumerate: self.enumerate.clone(),
+            queue: self.queue.clone(),
+            reader: None,
+            terminated: false,
+        }
+    }
+}
+
+/// A byte range of a text file, read line-by-line.
+///
+/// Like in [`FileSource`](super::FileSource), a line belongs to the range containing the byte
+/// before its start, so the ranges of a file can be read independently and each line is emitted
+/// exactly once. The lines are emitted including their terminator.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LineRangeSplit {
+    pub path: PathBuf,
+    pub start: u64,
+    pub end: u64,
+}
+
+impl LineRangeSplit {
+    /// Divide the file at `path` into ranges of `chunk_size` bytes.
+    pub fn of_file<P: Into<PathBuf>>(path: P, chunk_size: u64) -> Vec<Self> {
+        assert!(chunk_size > 0, "The chunks must not be empty");
+        let path = path.into();
+        let file_size = std::fs::metadata(&path)
+            .unwrap_or_else(|err| panic!("LineRangeSplit: cannot read size of {path:?}: {err:?}"))
+            .len();
+        (0..file_size.div_ceil(chunk_size).max(1))
+            .map(|i| Self {
+                path: path.clone(),
+                start: i * chunk_size,
+                end: ((i + 1) * chunk_size).min(file_size),
+            })
+            .collect()
+    }
+}
+
+/// The lines of a [`LineRangeSplit`].
+pub struct LineRange {
+    reader: BufReader<File>,
+    current: u64,
+    end: u64,
+    terminated: bool,
+}
+
+impl Iterator for LineRange {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        if self.terminated || self.current > self.end {
+            return None;
+        }
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) => None,
+            Ok(len) => {
+                self.current += len as u64;
+                Some(line)
+            }
+            Err(e) => panic!("Error while reading file: {e:?}"),
+        }
+    }
+}
+
+impl SourceSplit for LineRangeSplit {
+    type Out = String;
+    type Reader = LineRange;
+
+    fn size(&self) -> u64 {
+        self.end - self.start
+    }
+
+    fn open(self) -> LineRange {
+        let file = File::open(&self.path).unwrap_or_else(|err| {
+            panic!(
+                "LineRangeSplit: error while opening file {:?}: {:?}",
+                self.path, err
+            )
+        });
+        let mut reader = BufReader::new(file);
+        reader.seek(SeekFrom::Start(self.start)).expect("seek file");
+        let mut current = self.start;
+        if self.start != 0 {
+            // discard first line, it belongs to the previous range
+            let mut v = Vec::new();
+            current += reader
+                .read_until(b'\n', &mut v)
+                .expect("Cannot read line from file") as u64;
+        }
+        LineRange {
+            reader,
+            current,
+            end: self.end,
+            // an empty range contains no line
+            terminated: self.start == self.end,
+        }
+    }
+}
+
+impl crate::StreamContext {
+    /// Convenience method, creates a `SplitSource` and makes a stream using `StreamContext::stream`
+    pub fn stream_splits<S, F>(&self, enumerate: F) -> Stream<SplitSource<S>>
+    where
+        S: SourceSplit,
+        F: Fn() -> Vec<S> + Send + Sync + 'static,
+    {
+        let source = SplitSource::new(enumerate);
+        self.stream(source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use itertools::Itertools;
+
+    use super::{assign_splits, LineRangeSplit, SourceSplit};
+    use crate::config::RuntimeConfig;
+    use crate::environment::StreamContext;
+
+    #[test]
+    fn split_assignment_balances_hosts() {
+        let sizes = [100, 10, 10, 10, 10, 10, 50, 40];
+        let hosts = (0..2).map(|h| assign_splits(&sizes, 2, h)).collect_vec();
+        assert_eq!(hosts[0], vec![0, 2, 4]);
+        assert_eq!(hosts[1], vec![6, 7, 1, 3, 5]);
+        assert_eq!(
+            hosts[0].iter().map(|&i| sizes[i]).sum::<u64>(),
+            hosts[1].iter().map(|&i| sizes[i]).sum::<u64>()
+        );
+        assert_eq!(assign_splits(&sizes, 1, 0), vec![0, 6, 7, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn line_range_splits() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        for i in 0..100 {
+            writeln!(file, "{}", "x".repeat(i % 7)).unwrap();
+        }
+        file.flush().unwrap();
+
+        for chunk_size in [1, 3, 7, 64, 1 << 20] {
+            let lines = LineRangeSplit::of_file(file.path(), chunk_size)
+                .into_iter()
+                .flat_map(|split| split.open())
+                .collect_vec();
+            assert_eq!(lines.len(), 100, "chunk size {chunk_size}");
+            for (i, line) in lines.into_iter().enumerate() {
+                assert_eq!(line, format!("{}\n", "x".repeat(i % 7)));
+            }
+        }
+    }
+
+    #[test]
+    fn split_source_skewed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut paths = Vec::new();
+        for (f, lines) in [1000, 10, 3, 0, 200].into_iter().enumerate() {
+            let path = dir.path().join(format!("part-{f}.txt"));
+            let content = (0..lines).map(|i| format!("{f}-{i}\n")).join("");
+            std::fs::write(&path, content).unwrap();
+            paths.push(path);
+        }
+
+        for replicas in [1, 3, 8] {
+            let env = StreamContext::new(RuntimeConfig::local(replicas).unwrap());
+            let paths = paths.clone();
+            let res = env
+                .stream_splits(move || {
+                    paths
+                        .iter()
+                        .flat_map(|p| LineRangeSplit::of_file(p, 512))
+                        .collect()
+                })
+                .collect_vec();
+            env.execute_blocking();
+
+            let res = res.get().unwrap().into_iter().sorted().collect_vec();
+            let expected = [1000, 10, 3, 0, 200]
+                .into_iter()
+                .enumerate()
+                .flat_map(|(f, lines)| (0..lines).map(move |i| format!("{f}-{i}\n")))
+                .sorted()
+                .collect_vec();
+            assert_eq!(res, expected);
+        }
+    }
+}
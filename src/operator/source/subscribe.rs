@@ -0,0 +1,184 @@
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::io::BufReader;
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::thread::sleep;
+use std::time::Duration;
+
+use crate::block::{BlockStructure, OperatorKind, OperatorStructure, Replication};
+use crate::network::{
+    remote_recv, remote_send, Coord, DemuxCoord, NetworkMessage, ReceiverEndpoint,
+};
+use crate::operator::source::Source;
+use crate::operator::{ExchangeData, Operator, StreamElement};
+use crate::scheduler::ExecutionMetadata;
+use crate::Stream;
+
+/// Maximum number of attempts to make for connecting to the publisher.
+const CONNECT_ATTEMPTS: usize = 32;
+/// To avoid spamming the connections, wait this timeout before trying again. If the connection
+/// fails again this timeout will be doubled up to `RETRY_MAX_TIMEOUT`.
+const RETRY_INITIAL_TIMEOUT: Duration = Duration::from_millis(8);
+/// Maximum timeout between connection attempts.
+const RETRY_MAX_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Source that receives the elements of a stream published by another job.
+///
+/// The other job should close one of its streams with
+/// [`Stream::publish`](crate::Stream::publish), using the same name and address.
+///
+/// This source is **not parallel**, all the elements are received by a single replica.
+///
+/// If the publisher disconnects before terminating its stream the source panics, instead of
+/// ending the stream with partial results.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct SubscribeSource<Out: ExchangeData> {
+    name: String,
+    address: Vec<SocketAddr>,
+    coord: Option<Coord>,
+    #[derivative(Debug = "ignore")]
+    reader: Option<BufReader<TcpStream>>,
+    /// The number of messages received from the publisher.
+    sequence: u64,
+    buffer: VecDeque<StreamElement<Out>>,
+    terminated: bool,
+}
+
+impl<Out: ExchangeData> Display for SubscribeSource<Out> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "SubscribeSource<{}>({})",
+            std::any::type_name::<Out>(),
+            self.name
+        )
+    }
+}
+
+impl<Out: ExchangeData> SubscribeSource<Out> {
+    /// Create a new source that subscribes to the stream with the given name, published at the
+    /// given address.
+    ///
+    /// The connection is established when the job starts, retrying until the publisher is ready.
+    /// The source terminates when the publisher terminates.
+    pub fn new<A: ToSocketAddrs>(name: impl Into<String>, address: A) -> Self {
+        let name = name.into();
+        let address = address
+            .to_socket_addrs()
+            .unwrap_or_else(|e| panic!("subscribe {name}: invalid address: {e:?}"))
+            .collect();
+        Self {
+            name,
+            address,
+            coord: None,
+            reader: None,
+            sequence: 0,
+            buffer: VecDeque::new(),
+            terminated: false,
+        }
+    }
+
+    /// Connect to the publisher and ask for the stream.
+    fn connect(&self) -> BufReader<TcpStream> {
+        let coord = self.coord.expect("setup was not called");
+        let mut retry_delay = RETRY_INITIAL_TIMEOUT;
+        for attempt in 1..=CONNECT_ATTEMPTS {
+            match TcpStream::connect(&self.address[..]) {
+                Ok(mut stream) => {
+                    let request =
+                        NetworkMessage::new_single(StreamElement::Item(self.name.clone()), coord);
+                    let dest = ReceiverEndpoint::new(Coord::default(), coord.block_id);
+                    let address = format!("{:?}", self.address);
+                    remote_send(request, dest, &mut stream, &address, 0, true);
+                    log::debug!("subscribe {} connected to {:?}", self.name, self.address);
+                    return BufReader::new(stream);
+                }
+                Err(e) => {
+                    log::debug!(
+                        "subscribe {} failed to connect to {:?} ({attempt}): {e:?}",
+                        self.name,
+                        self.address
+                    );
+                }
+            }
+            sleep(retry_delay);
+            retry_delay = (2 * retry_delay).min(RETRY_MAX_TIMEOUT);
+        }
+        panic!(
+            "subscribe {}: failed to connect to {:?} after {CONNECT_ATTEMPTS} attempts",
+            self.name, self.address
+        );
+    }
+}
+
+impl<Out: ExchangeData> Source for SubscribeSource<Out> {
+    fn replication(&self) -> Replication {
+        Replication::One
+    }
+}
+
+impl<Out: ExchangeData> Operator for SubscribeSource<Out> {
+    type Out = Out;
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        self.coord = Some(metadata.coord);
+    }
+
+    fn next(&mut self) -> StreamElement<Out> {
+        if self.terminated {
+            return StreamElement::Terminate;
+        }
+        if let Some(el) = self.buffer.pop_front() {
+            if matches!(el, StreamElement::Terminate) {
+                self.terminated = true;
+                return StreamElement::FlushAndRestart;
+            }
+            return el;
+        }
+
+        if self.reader.is_none() {
+            self.reader = Some(self.connect());
+        }
+        let coord = self.coord.unwrap();
+        let demux_coord = DemuxCoord::from(ReceiverEndpoint::new(coord, coord.block_id));
+        let address = format!("{:?}", self.address);
+        let reader = self.reader.as_mut().unwrap();
+        match remote_recv::<Out, _>(demux_coord, reader, &address, &mut self.sequence) {
+            Ok(Some((_, batch))) => self.buffer.extend(batch),
+            Ok(None) => panic!(
+                "subscribe {}: publisher at {address} disconnected without terminating",
+                self.name
+            ),
+            Err(e) => panic!("subscribe {}: {e}", self.name),
+        }
+        // the batch has been received, let the downstream operators process it
+        StreamElement::FlushBatch
+    }
+
+    fn structure(&self) -> BlockStructure {
+        let mut operator = OperatorStructure::new::<Out, _>("SubscribeSource");
+        operator.kind = OperatorKind::Source;
+        BlockStructure::default().add_operator(operator)
+    }
+}
+
+impl<Out: ExchangeData> Clone for SubscribeSource<Out> {
+    fn clone(&self) -> Self {
+        // Since this is a non-parallel source, we don't want the other replicas to emit any value
+        panic!("SubscribeSource cannot be cloned, replication should be 1");
+    }
+}
+
+impl crate::StreamContext {
+    /// Convenience method, creates a `SubscribeSource` and makes a stream using
+    /// `StreamContext::stream`
+    pub fn subscribe<Out: ExchangeData>(
+        &self,
+        name: impl Into<String>,
+        address: impl ToSocketAddrs,
+    ) -> Stream<SubscribeSource<Out>> {
+        let source = SubscribeSource::new(name, address);
+        self.stream(source)
+    }
+}
@@ -2,9 +2,10 @@ use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
-use crate::block::{BlockStructure, OperatorReceiver, OperatorStructure};
+use crate::block::{BlockStructure, OperatorReceiver, OperatorStructure, Priority};
 use crate::channel::{RecvTimeoutError, SelectResult};
 use crate::network::{Coord, NetworkMessage};
+use crate::operator::start::cache::MessageCache;
 use crate::operator::start::{SimpleStartReceiver, StartReceiver};
 use crate::operator::{Data, ExchangeData, StreamElement};
 use crate::scheduler::{BlockId, ExecutionMetadata};
@@ -40,11 +41,13 @@ struct SideReceiver<Out: ExchangeData, Item: ExchangeData> {
     /// Whether this side is cached (i.e. after it is fully read, it's just replayed indefinitely).
     cached: bool,
     /// The content of the cache, if any.
-    cache: Vec<NetworkMessage<Item>>,
+    cache: MessageCache<Item>,
     /// Whether the cache has been fully populated.
     cache_full: bool,
     /// The index of the first element to return from the cache.
     cache_pointer: usize,
+    /// The priority of the stream coming from this side.
+    priority: Priority,
 }
 
 impl<Out: ExchangeData, Item: ExchangeData> SideReceiver<Out, Item> {
@@ -58,11 +61,13 @@ impl<Out: ExchangeData, Item: ExchangeData> SideReceiver<Out, Item> {
             cache: Default::default(),
             cache_full: false,
             cache_pointer: 0,
+            priority: Priority::Normal,
         }
     }
 
     fn setup(&mut self, metadata: &mut ExecutionMetadata) {
         self.receiver.setup(metadata);
+        self.priority = metadata.prev_priority(self.receiver.previous_block_id);
         self.instances = self.receiver.prev_replicas().len();
         self.missing_flush_and_restart = self.instances;
         self.missing_terminate = self.instances;
@@ -111,7 +116,7 @@ impl<Out: ExchangeData, Item: ExchangeData> SideReceiver<Out, Item> {
             // sure that when the cache ends the counter is zero.
             self.missing_flush_and_restart = 0;
         }
-        self.cache[self.cache_pointer - 1].clone()
+        self.cache.get(self.cache_pointer - 1)
     }
 }
 
@@ -180,7 +185,7 @@ impl<OutL: ExchangeData, OutR: ExchangeData> BinaryStartReceiver<OutL, OutR> {
             .collect::<Vec<_>>();
         let message = NetworkMessage::new_batch(data, sender);
         if side.cached {
-            side.cache.push(message.clone());
+            side.cache.push(&message);
             // the elements are already out, ignore the cache for this round
             side.cache_pointer = side.cache.len();
         }
@@ -260,24 +265,38 @@ impl<OutL: ExchangeData, OutR: ExchangeData> BinaryStartReceiver<OutL, OutR> {
         } else {
             let left_terminated = self.left.is_terminated();
             let right_terminated = self.right.is_terminated();
+            let left_priority = self.left.priority;
+            let right_priority = self.right.priority;
             let left = self.left.receiver.receiver.as_mut().unwrap();
             let right = self.right.receiver.receiver.as_mut().unwrap();
 
-            let data = match (left_terminated, right_terminated, timeout) {
-                (false, false, Some(timeout)) => left.select_timeout(right, timeout),
-                (false, false, None) => Ok(left.select(right)),
+            // the side with the higher priority is read first if it has something ready
+            let prioritized = if left_priority > right_priority && !left_terminated {
+                left.try_recv().ok().map(|m| SelectResult::A(Ok(m)))
+            } else if right_priority > left_priority && !right_terminated {
+                right.try_recv().ok().map(|m| SelectResult::B(Ok(m)))
+            } else {
+                None
+            };
 
-                (true, false, Some(timeout)) => {
-                    right.recv_timeout(timeout).map(|r| SelectResult::B(Ok(r)))
-                }
-                (false, true, Some(timeout)) => {
-                    left.recv_timeout(timeout).map(|r| SelectResult::A(Ok(r)))
-                }
+            let data = match prioritized {
+                Some(data) => Ok(data),
+                None => match (left_terminated, right_terminated, timeout) {
+                    (false, false, Some(timeout)) => left.select_timeout(right, timeout),
+                    (false, false, None) => Ok(left.select(right)),
+
+                    (true, false, Some(timeout)) => {
+                        right.recv_timeout(timeout).map(|r| SelectResult::B(Ok(r)))
+                    }
+                    (false, true, Some(timeout)) => {
+                        left.recv_timeout(timeout).map(|r| SelectResult::A(Ok(r)))
+                    }
 
-                (true, false, None) => Ok(SelectResult::B(right.recv())),
-                (false, true, None) => Ok(SelectResult::A(left.recv())),
+                    (true, false, None) => Ok(SelectResult::B(right.recv())),
+                    (false, true, None) => Ok(SelectResult::A(left.recv())),
 
-                (true, true, _) => Err(RecvTimeoutError::Disconnected),
+                    (true, true, _) => Err(RecvTimeoutError::Disconnected),
+                },
             };
 
             match data {
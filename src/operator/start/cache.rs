@@ -0,0 +1,89 @@
+//! The cache of the messages received by a cached side of a binary start block.
+//!
+//! With the `compressed-cache` feature each message is serialized and compressed with deflate, and
+//! decompressed only when it is replayed.
+
+use crate::network::NetworkMessage;
+use crate::operator::ExchangeData;
+
+/// The messages received by a cached side, replayed at every iteration.
+#[derive(Clone, Debug)]
+pub(crate) struct MessageCache<T> {
+    #[cfg(not(feature = "compressed-cache"))]
+    messages: Vec<NetworkMessage<T>>,
+    #[cfg(feature = "compressed-cache")]
+    messages: Vec<Vec<u8>>,
+    #[cfg(feature = "compressed-cache")]
+    _t: std::marker::PhantomData<T>,
+}
+
+impl<T> Default for MessageCache<T> {
+    fn default() -> Self {
+        Self {
+            messages: Default::default(),
+            #[cfg(feature = "compressed-cache")]
+            _t: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T: ExchangeData> MessageCache<T> {
+    /// The number of messages in the cache.
+    pub(crate) fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    #[cfg(not(feature = "compressed-cache"))]
+    pub(crate) fn push(&mut self, message: &NetworkMessage<T>) {
+        self.messages.push(message.clone());
+    }
+
+    #[cfg(not(feature = "compressed-cache"))]
+    pub(crate) fn get(&self, index: usize) -> NetworkMessage<T> {
+        self.messages[index].clone()
+    }
+
+    #[cfg(feature = "compressed-cache")]
+    pub(crate) fn push(&mut self, message: &NetworkMessage<T>) {
+        let mut encoder = libflate::deflate::Encoder::new(Vec::new());
+        bincode::serialize_into(&mut encoder, message)
+            .expect("MessageCache: cannot serialize the message");
+        let compressed = encoder
+            .finish()
+            .into_result()
+            .expect("MessageCache: cannot compress the message");
+        self.messages.push(compressed);
+    }
+
+    #[cfg(feature = "compressed-cache")]
+    pub(crate) fn get(&self, index: usize) -> NetworkMessage<T> {
+        let decoder = libflate::deflate::Decoder::new(&self.messages[index][..]);
+        bincode::deserialize_from(decoder).expect("MessageCache: corrupted message")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MessageCache;
+    use crate::network::{Coord, NetworkMessage};
+    use crate::operator::StreamElement;
+
+    #[test]
+    fn message_cache_replay() {
+        let sender = Coord::new(0, 0, 0);
+        let messages = vec![
+            NetworkMessage::new_batch((0..100u32).map(StreamElement::Item).collect(), sender),
+            NetworkMessage::new_single(StreamElement::FlushAndRestart, sender),
+        ];
+        let mut cache = MessageCache::default();
+        for message in &messages {
+            cache.push(message);
+        }
+        assert_eq!(cache.len(), 2);
+        for _ in 0..2 {
+            for (i, message) in messages.iter().enumerate() {
+                assert_eq!(&cache.get(i), message);
+            }
+        }
+    }
+}
@@ -1,9 +1,11 @@
 use std::fmt::{Debug, Display};
+use std::iter::Peekable;
 use std::sync::Arc;
 use std::time::Duration;
 
 pub(crate) use binary::*;
 pub(crate) use simple::*;
+pub(crate) use union::*;
 
 #[cfg(feature = "timestamp")]
 use super::Timestamp;
@@ -17,7 +19,9 @@ use crate::operator::{ExchangeData, Operator, StreamElement};
 use crate::scheduler::{BlockId, ExecutionMetadata};
 
 mod binary;
+mod cache;
 mod simple;
+mod union;
 mod watermark_frontier;
 
 /// Trait that abstract the receiving part of the `Start`.
@@ -49,17 +53,23 @@ pub(crate) trait StartReceiver: Clone {
     fn structure(&self) -> BlockStructure;
 }
 
+/// The messages of the batch being read, peekable for coalescing the consecutive watermarks.
+type BatchIter<Out> = Peekable<NetworkDataIterator<StreamElement<Out>>>;
+
 pub(crate) type BinaryStartOperator<OutL, OutR> = Start<BinaryStartReceiver<OutL, OutR>>;
 
 pub(crate) type SimpleStartOperator<Out> = Start<SimpleStartReceiver<Out>>;
 
+pub(crate) type UnionStartOperator<Out> = Start<UnionStartReceiver<Out>>;
+
 /// Each block should start with a `Start` operator, whose task is to read from the network,
 /// receive from the previous operators and handle the watermark frontier.
 ///
 /// There are different kinds of `Start`, the main difference is in the number of previous
 /// blocks. With a `SimpleStartReceiver` the block is able to receive from the replicas of a
 /// single block of the job graph. If the block needs the data from multiple blocks it should use
-/// `MultipleStartReceiver` which is able to handle 2 previous blocks.
+/// `MultipleStartReceiver` which is able to handle 2 previous blocks. A `UnionStartReceiver`
+/// receives from any number of previous blocks with the same type, without telling them apart.
 ///
 /// Following operators will receive the messages in an unspecified order but the watermark property
 /// is followed. Note that the timestamps of the messages are not sorted, it's only guaranteed that
@@ -75,7 +85,7 @@ pub(crate) struct Start<Receiver: StartReceiver + Send> {
     receiver: Receiver,
 
     /// Inner iterator over batch items, contains coordinate of the sender
-    batch_iter: Option<(Coord, NetworkDataIterator<StreamElement<Receiver::Out>>)>,
+    batch_iter: Option<(Coord, BatchIter<Receiver::Out>)>,
 
     /// The number of `StreamElement::Terminate` messages yet to be received. When this value
     /// reaches zero this operator will emit the terminate.
@@ -86,10 +96,11 @@ pub(crate) struct Start<Receiver: StartReceiver + Send> {
     /// `missing_flush_and_restart`.
     num_previous_replicas: usize,
 
-    /// Whether the previous blocks timed out and the last batch has been flushed.
+    /// Whether nothing has been emitted since the last flush of the batches.
     ///
-    /// The next time `next()` is called it will not wait the timeout asked by the batch mode.
-    already_timed_out: bool,
+    /// Another `FlushBatch` would be redundant, so `next()` will not wait the timeout asked by the
+    /// batch mode.
+    flushed: bool,
 
     /// The current frontier of the watermarks from the previous replicas.
     watermark_frontier: WatermarkFrontier,
@@ -111,7 +122,7 @@ impl<Receiver: StartReceiver + Send> Clone for Start<Receiver> {
             missing_terminate: self.missing_terminate,
             missing_flush_and_restart: self.missing_flush_and_restart,
             num_previous_replicas: self.num_previous_replicas,
-            already_timed_out: self.already_timed_out,
+            flushed: self.flushed,
             watermark_frontier: self.watermark_frontier.clone(),
             wait_for_state: self.wait_for_state,
             state_lock: self.state_lock.clone(),
@@ -157,6 +168,16 @@ impl<OutL: ExchangeData, OutR: ExchangeData> Start<BinaryStartReceiver<OutL, Out
     }
 }
 
+impl<Out: ExchangeData> Start<UnionStartReceiver<Out>> {
+    /// Create a `Start` able to receive data from any number of previous blocks with the same type.
+    pub(crate) fn union(
+        previous_block_ids: Vec<BlockId>,
+        state_lock: Option<Arc<IterationStateLock>>,
+    ) -> UnionStartOperator<Out> {
+        Start::new(UnionStartReceiver::new(previous_block_ids), state_lock)
+    }
+}
+
 impl<Receiver: StartReceiver + Send> Start<Receiver> {
     fn new(receiver: Receiver, state_lock: Option<Arc<IterationStateLock>>) -> Self {
         Self {
@@ -170,7 +191,7 @@ impl<Receiver: StartReceiver + Send> Start<Receiver> {
             missing_flush_and_restart: Default::default(),
             num_previous_replicas: 0,
 
-            already_timed_out: Default::default(),
+            flushed: true,
 
             watermark_frontier: Default::default(),
 
@@ -227,6 +248,7 @@ where
                 // this iteration has ended, before starting the next one wait for the state update
                 self.wait_for_state = true;
                 self.state_generation += 2;
+                self.flushed = true;
                 return StreamElement::FlushAndRestart;
             }
 
@@ -241,7 +263,16 @@ where
                         match item {
                             StreamElement::Watermark(ts) => {
                                 // update the frontier and return a watermark if necessary
-                                match self.watermark_frontier.update(sender, ts) {
+                                let mut frontier = self.watermark_frontier.update(sender, ts);
+                                // the following watermarks of the batch supersede this one, emit
+                                // only the last frontier
+                                while let Some(StreamElement::Watermark(ts)) = inner
+                                    .next_if(|item| matches!(item, StreamElement::Watermark(_)))
+                                {
+                                    frontier =
+                                        self.watermark_frontier.update(sender, ts).or(frontier);
+                                }
+                                match frontier {
                                     Some(ts) => StreamElement::Watermark(ts), // ts is safe
                                     None => continue,
                                 }
@@ -277,12 +308,14 @@ where
                     }
                     self.wait_for_state = false;
                 }
+                self.flushed = matches!(msg, StreamElement::FlushBatch);
                 return msg;
             }
 
             // Receive next batch
-            let net_msg = match (self.already_timed_out, self.max_delay) {
-                // check the timeout only if there is one and the last time we didn't timed out
+            let net_msg = match (self.flushed, self.max_delay) {
+                // check the timeout only if there is one and something has been emitted since the
+                // last flush
                 (false, Some(max_delay)) => {
                     match self.receiver.recv_timeout(max_delay) {
                         Ok(net_msg) => net_msg,
@@ -290,7 +323,7 @@ where
                             // timed out: tell the block to flush the current batch
                             // next time we wait indefinitely without the timeout since the batch is
                             // currently empty
-                            self.already_timed_out = true;
+                            self.flushed = true;
                             // this is a fake batch, and its sender is meaningless and will be
                             // forget immediately
                             NetworkMessage::new_single(
@@ -300,13 +333,10 @@ where
                         }
                     }
                 }
-                _ => {
-                    self.already_timed_out = false;
-                    self.receiver.recv()
-                }
+                _ => self.receiver.recv(),
             };
 
-            self.batch_iter = Some((net_msg.sender(), net_msg.into_iter()));
+            self.batch_iter = Some((net_msg.sender(), net_msg.into_iter().peekable()));
         }
     }
 
@@ -327,6 +357,9 @@ where
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
+    use crate::block::Priority;
     use crate::network::NetworkMessage;
     use crate::operator::{BinaryElement, Operator, Start, StreamElement, Timestamp};
     use crate::test::FakeNetworkTopology;
@@ -374,6 +407,40 @@ mod tests {
         assert_eq!(StreamElement::Terminate, start_block.next());
     }
 
+    #[test]
+    fn test_single_no_repeated_flush_batch() {
+        let mut t = FakeNetworkTopology::new(1, 2);
+        let (from1, sender1) = t.senders_mut()[0].pop().unwrap();
+        let (from2, sender2) = t.senders_mut()[0].pop().unwrap();
+
+        let mut start_block = Start::single(sender1.receiver_endpoint.prev_block_id, None);
+        start_block.setup(&mut t.metadata());
+
+        sender1
+            .send(NetworkMessage::new_batch(
+                vec![StreamElement::Item(42), StreamElement::FlushAndRestart],
+                from1,
+            ))
+            .unwrap();
+
+        assert_eq!(StreamElement::Item(42), start_block.next());
+        assert_eq!(StreamElement::FlushBatch, start_block.next());
+
+        // nothing has been emitted since the last flush: waiting longer than the batch timeout
+        // must not produce another FlushBatch
+        let delayed = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(300));
+            sender2
+                .send(NetworkMessage::new_batch(
+                    vec![StreamElement::FlushAndRestart],
+                    from2,
+                ))
+                .unwrap();
+        });
+        assert_eq!(StreamElement::FlushAndRestart, start_block.next());
+        delayed.join().unwrap();
+    }
+
     #[test]
     #[cfg(feature = "timestamp")]
     fn test_single_watermark() {
@@ -422,6 +489,42 @@ mod tests {
         assert_eq!(StreamElement::Watermark(ts(110)), start_block.next());
     }
 
+    #[test]
+    #[cfg(feature = "timestamp")]
+    fn test_single_coalesce_watermarks() {
+        let mut t = FakeNetworkTopology::new(1, 2);
+        let (from1, sender1) = t.senders_mut()[0].pop().unwrap();
+        let (from2, sender2) = t.senders_mut()[0].pop().unwrap();
+
+        let mut start_block = Start::single(sender1.receiver_endpoint.prev_block_id, None);
+        start_block.setup(&mut t.metadata());
+
+        sender1
+            .send(NetworkMessage::new_batch(
+                vec![
+                    StreamElement::Watermark(ts(10)),
+                    StreamElement::Watermark(ts(20)),
+                ],
+                from1,
+            ))
+            .unwrap();
+        sender2
+            .send(NetworkMessage::new_batch(
+                vec![
+                    StreamElement::Watermark(ts(5)),
+                    StreamElement::Watermark(ts(15)),
+                    StreamElement::Watermark(ts(30)),
+                    StreamElement::Timestamped(42, ts(40)),
+                ],
+                from2,
+            ))
+            .unwrap();
+
+        // the frontier advances three times, but only the last one is emitted
+        assert_eq!(StreamElement::Watermark(ts(20)), start_block.next());
+        assert_eq!(StreamElement::Timestamped(42, ts(40)), start_block.next());
+    }
+
     #[test]
     #[cfg(feature = "timestamp")]
     fn test_multiple_no_cache() {
@@ -487,6 +590,51 @@ mod tests {
         assert_eq!(StreamElement::FlushAndRestart, start_block.next());
     }
 
+    #[test]
+    fn test_multiple_priority() {
+        let mut t = FakeNetworkTopology::new(2, 1);
+        let (from1, sender1) = t.senders_mut()[0].pop().unwrap();
+        let (from2, sender2) = t.senders_mut()[1].pop().unwrap();
+
+        let mut start_block = Start::multiple(from1.block_id, from2.block_id, false, false, None);
+        let mut metadata = t.metadata();
+        metadata.prev_priorities = vec![
+            (from1.block_id, Priority::Normal),
+            (from2.block_id, Priority::High),
+        ];
+        start_block.setup(&mut metadata);
+
+        for i in 0..10 {
+            sender1
+                .send(NetworkMessage::new_batch(
+                    vec![StreamElement::Item(i)],
+                    from1,
+                ))
+                .unwrap();
+        }
+        sender2
+            .send(NetworkMessage::new_batch(
+                vec![StreamElement::Item(100)],
+                from2,
+            ))
+            .unwrap();
+
+        // the batch of the right side is received before all the ones already waiting on the left
+        assert_eq!(
+            StreamElement::Item(BinaryElement::Right(100)),
+            start_block.next()
+        );
+        let mut left: Vec<i32> = Vec::new();
+        while left.len() < 10 {
+            match start_block.next() {
+                StreamElement::Item(BinaryElement::Left(i)) => left.push(i),
+                StreamElement::FlushBatch => {}
+                el => panic!("unexpected element {el:?}"),
+            }
+        }
+        assert_eq!(left, (0..10).collect::<Vec<_>>());
+    }
+
     #[test]
     fn test_multiple_cache() {
         let mut t = FakeNetworkTopology::new(2, 1);
@@ -0,0 +1,101 @@
+use std::time::Duration;
+
+use crate::block::{BlockStructure, OperatorReceiver, OperatorStructure};
+use crate::channel::RecvTimeoutError;
+use crate::network::{Coord, NetworkMessage, NetworkReceiver};
+use crate::operator::start::{SimpleStartReceiver, StartReceiver};
+use crate::operator::ExchangeData;
+use crate::scheduler::{BlockId, ExecutionMetadata};
+
+/// This receiver is able to receive data from any number of previous blocks with the same type.
+///
+/// Unlike `BinaryStartReceiver` the elements are not wrapped: all the receivers are selected at
+/// once, and the messages are forwarded as if they came from the replicas of a single block.
+#[derive(Clone, Debug)]
+pub(crate) struct UnionStartReceiver<Out: ExchangeData> {
+    /// The receivers from each of the previous blocks.
+    receivers: Vec<SimpleStartReceiver<Out>>,
+    /// Whether all the senders of the corresponding receiver have been dropped, so it should not
+    /// be selected anymore.
+    disconnected: Vec<bool>,
+}
+
+impl<Out: ExchangeData> UnionStartReceiver<Out> {
+    pub(super) fn new(previous_block_ids: Vec<BlockId>) -> Self {
+        assert!(
+            !previous_block_ids.is_empty(),
+            "A union needs at least one previous block"
+        );
+        Self {
+            disconnected: vec![false; previous_block_ids.len()],
+            receivers: previous_block_ids
+                .into_iter()
+                .map(SimpleStartReceiver::new)
+                .collect(),
+        }
+    }
+
+    /// Receive the next batch from any of the previous blocks, or fail with a timeout if provided.
+    fn select(
+        &mut self,
+        timeout: Option<Duration>,
+    ) -> Result<NetworkMessage<Out>, RecvTimeoutError> {
+        loop {
+            let (indices, receivers): (Vec<usize>, Vec<&NetworkReceiver<Out>>) = self
+                .receivers
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| !self.disconnected[i])
+                .map(|(i, r)| (i, r.receiver.as_ref().unwrap()))
+                .unzip();
+            if receivers.is_empty() {
+                return Err(RecvTimeoutError::Disconnected);
+            }
+
+            let (i, message) = match timeout {
+                Some(timeout) => NetworkReceiver::select_any_timeout(&receivers, timeout)?,
+                None => NetworkReceiver::select_any(&receivers),
+            };
+            match message {
+                Ok(message) => return Ok(message),
+                // a previous block has ended and its messages have all been received
+                Err(_) => self.disconnected[indices[i]] = true,
+            }
+        }
+    }
+}
+
+impl<Out: ExchangeData> StartReceiver for UnionStartReceiver<Out> {
+    type Out = Out;
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        for receiver in self.receivers.iter_mut() {
+            receiver.setup(metadata);
+        }
+    }
+
+    fn prev_replicas(&self) -> Vec<Coord> {
+        self.receivers
+            .iter()
+            .flat_map(|r| r.prev_replicas())
+            .collect()
+    }
+
+    fn recv_timeout(&mut self, timeout: Duration) -> Result<NetworkMessage<Out>, RecvTimeoutError> {
+        self.select(Some(timeout))
+    }
+
+    fn recv(&mut self) -> NetworkMessage<Out> {
+        self.select(None).expect("Network receiver failed")
+    }
+
+    fn structure(&self) -> BlockStructure {
+        let mut operator = OperatorStructure::new::<Out, _>("Start");
+        for receiver in &self.receivers {
+            operator
+                .receivers
+                .push(OperatorReceiver::new::<Out>(receiver.previous_block_id));
+        }
+        BlockStructure::default().add_operator(operator)
+    }
+}
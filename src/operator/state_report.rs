@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use serde::Serialize;
+
+use crate::network::Coord;
+use crate::operator::{DataKey, Operator};
+use crate::KeyedStream;
+
+/// The size of the state held by an operator for a key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyState<K> {
+    /// The key.
+    pub key: K,
+    /// The size of the state of the key, serialized with `bincode`.
+    pub bytes: usize,
+    /// The number of entries held for the key, e.g. the open sessions of
+    /// [`KeyedStream::sessionize`], or 1 for an accumulator.
+    pub items: usize,
+}
+
+/// Summary of the keyed state of an operator, for diagnosing skewed keys and leaks.
+///
+/// The report is built from a [`StateReportHandle`], obtained with [`KeyedStream::state_report`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StateReport<K> {
+    /// The state of each key, from the largest to the smallest.
+    keys: Vec<KeyState<K>>,
+}
+
+impl<K> StateReport<K> {
+    /// The number of keys with a state.
+    pub fn num_keys(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// The total size of the state of all the keys.
+    pub fn total_bytes(&self) -> usize {
+        self.keys.iter().map(|k| k.bytes).sum()
+    }
+
+    /// The state of the `n` keys with the largest state, from the largest.
+    pub fn largest(&self, n: usize) -> &[KeyState<K>] {
+        &self.keys[..n.min(self.keys.len())]
+    }
+
+    /// The state of all the keys, from the largest to the smallest.
+    pub fn keys(&self) -> &[KeyState<K>] {
+        &self.keys
+    }
+}
+
+impl<K> FromIterator<KeyState<K>> for StateReport<K> {
+    fn from_iter<T: IntoIterator<Item = KeyState<K>>>(iter: T) -> Self {
+        let mut keys: Vec<_> = iter.into_iter().collect();
+        keys.sort_by_key(|k| std::cmp::Reverse(k.bytes));
+        Self { keys }
+    }
+}
+
+/// The state published by each replica of an operator.
+type PublishedState<K> = Arc<Mutex<HashMap<Coord, Vec<KeyState<K>>>>>;
+
+fn serialized_size<V: Serialize>(value: &V) -> usize {
+    bincode::serialized_size(value).expect("cannot compute the size of the state") as usize
+}
+
+/// A stateful keyed operator that can publish the size of the state it holds for each key.
+///
+/// See [`KeyedStream::state_report`].
+pub trait ReportState: Operator {
+    /// The key of the state.
+    type Key: DataKey;
+    /// The state held for each key.
+    type State;
+
+    /// Publish the state of this operator to `probe`, from now on.
+    fn report_state(&mut self, probe: StateProbe<Self::Key, Self::State>);
+}
+
+/// Where each replica of a stateful operator publishes the size of its state.
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""), Debug)]
+pub struct StateProbe<K, S> {
+    coord: Option<Coord>,
+    #[derivative(Debug = "ignore")]
+    published: PublishedState<K>,
+    #[derivative(Debug = "ignore")]
+    size: fn(&S) -> usize,
+}
+
+impl<K: Clone, S> StateProbe<K, S> {
+    pub(crate) fn setup(&mut self, coord: Coord) {
+        self.coord = Some(coord);
+    }
+
+    /// Replace the state published by this replica with the entries held for each key.
+    pub(crate) fn publish<'a>(&self, state: impl IntoIterator<Item = (&'a K, &'a S, usize)>)
+    where
+        K: 'a,
+        S: 'a,
+    {
+        let keys = state
+            .into_iter()
+            .map(|(key, state, items)| KeyState {
+                key: key.clone(),
+                bytes: (self.size)(state),
+                items,
+            })
+            .collect();
+        let coord = self.coord.expect("StateProbe: setup was not called");
+        self.published.lock().insert(coord, keys);
+    }
+}
+
+/// Handle for reading the size of the state of an operator, obtained from
+/// [`KeyedStream::state_report`].
+///
+/// The handle can be moved to other threads and queried while the computation is running. Each
+/// replica of the operator publishes its state when it flushes its output and at the end of the
+/// stream, before emitting the results: after the computation has ended the handle reports the
+/// state held by the operator when the stream ended.
+///
+/// Only the replicas running in this process are reported.
+pub struct StateReportHandle<K> {
+    published: PublishedState<K>,
+}
+
+impl<K> Clone for StateReportHandle<K> {
+    fn clone(&self) -> Self {
+        Self {
+            published: self.published.clone(),
+        }
+    }
+}
+
+impl<K: Clone> StateReportHandle<K> {
+    /// The last state published by the replicas of the operator.
+    pub fn report(&self) -> StateReport<K> {
+        self.published.lock().values().flatten().cloned().collect()
+    }
+}
+
+impl<K, Op> KeyedStream<Op>
+where
+    K: DataKey,
+    Op: ReportState<Key = K> + 'static,
+    Op::Out: crate::stream::KeyedItem,
+    Op::State: Serialize,
+{
+    /// Track the size of the state that the last stateful operator of the stream (e.g.
+    /// [`KeyedStream::fold`], [`KeyedStream::reduce`] or [`KeyedStream::sessionize`]) holds for
+    /// each key, to find the keys whose state is much larger than the others.
+    ///
+    /// The stream is returned unchanged, together with a [`StateReportHandle`] for building a
+    /// [`StateReport`] on demand, while the job runs or after it has ended.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # let mut env = StreamContext::new_local();
+    /// let s = env.stream_iter(0..100u32).group_by(|&n| n % 10 == 0);
+    /// let (s, state) = s.fold(Vec::new(), |acc, n| acc.push(n)).state_report();
+    /// s.for_each(std::mem::drop);
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let report = state.report();
+    /// assert_eq!(report.num_keys(), 2);
+    /// // the state of the key `false` holds 90 numbers
+    /// assert_eq!(report.largest(1)[0].key, false);
+    /// ```
+    pub fn state_report(mut self) -> (KeyedStream<Op>, StateReportHandle<K>) {
+        let published = PublishedState::default();
+        let probe = StateProbe {
+            coord: None,
+            published: published.clone(),
+            size: serialized_size::<Op::State>,
+        };
+        self.0.block.operators.report_state(probe);
+        (self, StateReportHandle { published })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::RuntimeConfig;
+    use crate::environment::StreamContext;
+    use crate::operator::source::IteratorSource;
+
+    #[test]
+    fn state_report_skew() {
+        let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        let source = IteratorSource::new(0..100u32);
+        let (s, state) = env
+            .stream(source)
+            .group_by(|n| (n % 10).min(2))
+            .fold(Vec::new(), |acc, n| acc.push(n))
+            .state_report();
+        let res = s.collect_vec();
+        env.execute_blocking();
+
+        let report = state.report();
+        assert_eq!(report.num_keys(), 3);
+        let largest = report.largest(5);
+        assert_eq!(largest.len(), 3);
+        // 80 numbers have key 2, 10 have keys 0 and 1
+        assert_eq!(largest[0].key, 2);
+        assert_eq!(largest[0].items, 1);
+        assert_eq!(largest[0].bytes, 8 + 80 * 4);
+        assert_eq!(largest[1].bytes, 8 + 10 * 4);
+
+        // the accumulators held at the end are the results
+        let res = res.get().unwrap();
+        let bytes = res.iter().map(|(_, v)| 8 + v.len() * 4).sum::<usize>();
+        assert_eq!(report.total_bytes(), bytes);
+    }
+
+    #[test]
+    fn state_report_group_by_reduce() {
+        let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        let source = IteratorSource::new(0..100u32);
+        let (s, state) = env
+            .stream(source)
+            .group_by_reduce(|n| n % 7, |a, b| *a += b)
+            .state_report();
+        s.for_each(std::mem::drop);
+        env.execute_blocking();
+
+        // the global reduction holds an `Option<u32>` per key
+        let report = state.report();
+        assert_eq!(report.num_keys(), 7);
+        assert!(report
+            .keys()
+            .iter()
+            .all(|k| k.bytes == 1 + 4 && k.items == 1));
+    }
+}
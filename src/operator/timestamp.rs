@@ -0,0 +1,151 @@
+//! Helpers for converting between [`Timestamp`]s and the time types of `std`.
+//!
+//! A [`Timestamp`] is a signed number of ticks since the Unix epoch, so that instants before 1970
+//! can be represented as well. The duration of a tick is not fixed: it's up to the user to choose
+//! the [`TimeUnit`] that better fits the data, and to use it consistently for the timestamps, the
+//! watermarks and the parameters of the operators (e.g. the size of an
+//! [`EventTimeWindow`](crate::operator::window::EventTimeWindow)).
+//!
+//! ## Example
+//!
+//! ```
+//! # use std::time::{Duration, UNIX_EPOCH};
+//! # use renoir::operator::timestamp::{self, TimeUnit};
+//! let before_epoch = UNIX_EPOCH - Duration::from_secs(60);
+//! let ts = timestamp::from_system_time(before_epoch, TimeUnit::Millis);
+//! assert_eq!(ts, -60_000);
+//! assert_eq!(timestamp::to_system_time(ts, TimeUnit::Millis), Some(before_epoch));
+//! assert_eq!(TimeUnit::Millis.convert(ts, TimeUnit::Seconds), -60);
+//! ```
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::operator::Timestamp;
+
+/// The duration of a tick of a [`Timestamp`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TimeUnit {
+    Seconds,
+    Millis,
+    Micros,
+    Nanos,
+}
+
+impl TimeUnit {
+    /// The number of nanoseconds in a tick.
+    fn nanos(self) -> i128 {
+        match self {
+            TimeUnit::Seconds => 1_000_000_000,
+            TimeUnit::Millis => 1_000_000,
+            TimeUnit::Micros => 1_000,
+            TimeUnit::Nanos => 1,
+        }
+    }
+
+    /// Convert a timestamp expressed in this unit to the unit `to`.
+    ///
+    /// When converting to a coarser unit the result is rounded towards negative infinity, so that
+    /// the order of the timestamps is preserved. The result saturates if it doesn't fit in a
+    /// [`Timestamp`].
+    pub fn convert(self, ts: Timestamp, to: TimeUnit) -> Timestamp {
+        saturate((ts as i128 * self.nanos()).div_euclid(to.nanos()))
+    }
+}
+
+/// Convert a duration since the Unix epoch to a timestamp in the given unit.
+///
+/// The result saturates if it doesn't fit in a [`Timestamp`].
+pub fn from_duration(duration: Duration, unit: TimeUnit) -> Timestamp {
+    saturate(duration.as_nanos() as i128 / unit.nanos())
+}
+
+/// Convert a timestamp in the given unit to the duration since the Unix epoch.
+///
+/// Returns `None` if the timestamp is before the epoch, since a [`Duration`] cannot be negative.
+pub fn to_duration(ts: Timestamp, unit: TimeUnit) -> Option<Duration> {
+    let nanos = ts as i128 * unit.nanos();
+    (nanos >= 0).then(|| duration_from_nanos(nanos))
+}
+
+/// Convert an instant to a timestamp in the given unit, the instant may also be before the Unix
+/// epoch.
+///
+/// The result saturates if it doesn't fit in a [`Timestamp`].
+pub fn from_system_time(time: SystemTime, unit: TimeUnit) -> Timestamp {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(after) => from_duration(after, unit),
+        Err(e) => saturate((-(e.duration().as_nanos() as i128)).div_euclid(unit.nanos())),
+    }
+}
+
+/// Convert a timestamp in the given unit to the corresponding instant.
+///
+/// Returns `None` if the instant cannot be represented by a [`SystemTime`] on this platform.
+pub fn to_system_time(ts: Timestamp, unit: TimeUnit) -> Option<SystemTime> {
+    let nanos = ts as i128 * unit.nanos();
+    if nanos >= 0 {
+        UNIX_EPOCH.checked_add(duration_from_nanos(nanos))
+    } else {
+        UNIX_EPOCH.checked_sub(duration_from_nanos(-nanos))
+    }
+}
+
+/// The time elapsed since the Unix epoch, as a timestamp in the given unit.
+pub fn now(unit: TimeUnit) -> Timestamp {
+    from_system_time(SystemTime::now(), unit)
+}
+
+fn saturate(value: i128) -> Timestamp {
+    value.clamp(Timestamp::MIN as i128, Timestamp::MAX as i128) as Timestamp
+}
+
+fn duration_from_nanos(nanos: i128) -> Duration {
+    let secs = nanos / 1_000_000_000;
+    let subsec = nanos % 1_000_000_000;
+    Duration::new(secs as u64, subsec as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::*;
+
+    #[test]
+    fn convert_units() {
+        assert_eq!(TimeUnit::Seconds.convert(3, TimeUnit::Millis), 3000);
+        assert_eq!(TimeUnit::Millis.convert(1999, TimeUnit::Seconds), 1);
+        assert_eq!(TimeUnit::Millis.convert(-1, TimeUnit::Seconds), -1);
+        assert_eq!(TimeUnit::Nanos.convert(42, TimeUnit::Nanos), 42);
+        assert_eq!(
+            TimeUnit::Seconds.convert(Timestamp::MAX, TimeUnit::Nanos),
+            Timestamp::MAX
+        );
+    }
+
+    #[test]
+    fn system_time_round_trip() {
+        for ts in [-1_000_000_123, -1, 0, 1, 1_700_000_000_456] {
+            let time = to_system_time(ts, TimeUnit::Micros).unwrap();
+            assert_eq!(from_system_time(time, TimeUnit::Micros), ts);
+        }
+        // the range of SystemTime depends on the platform, the extremes must not panic
+        for ts in [Timestamp::MIN, Timestamp::MAX] {
+            if let Some(time) = to_system_time(ts, TimeUnit::Seconds) {
+                assert_eq!(from_system_time(time, TimeUnit::Seconds), ts);
+            }
+        }
+        let before = UNIX_EPOCH - Duration::from_millis(1500);
+        assert_eq!(from_system_time(before, TimeUnit::Seconds), -2);
+    }
+
+    #[test]
+    fn duration_conversion() {
+        let duration = Duration::from_millis(12_345);
+        assert_eq!(from_duration(duration, TimeUnit::Millis), 12_345);
+        assert_eq!(to_duration(12_345, TimeUnit::Millis), Some(duration));
+        assert_eq!(to_duration(-1, TimeUnit::Nanos), None);
+    }
+}
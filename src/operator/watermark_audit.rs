@@ -0,0 +1,294 @@
+//! Debugging of the propagation of the watermarks.
+//!
+//! When a window never fires, the cause is usually an operator that holds back the watermarks:
+//! its elements move forward in event time but the watermark behind them does not. A
+//! [`WatermarkAudit`] records, at the points of the job marked with
+//! [`Stream::audit_watermarks`], the last watermark that passed and the largest timestamp of the
+//! elements, and flags the points where the two are too far apart.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::block::{BlockStructure, OperatorStructure};
+use crate::network::Coord;
+use crate::operator::{Operator, StreamElement, Timestamp};
+use crate::scheduler::ExecutionMetadata;
+use crate::Stream;
+
+/// What has been observed by a replica at an audited point of the job.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WatermarkProbe {
+    /// The name of the audited point.
+    pub stage: String,
+    /// The replica that observed the stream.
+    pub coord: Coord,
+    /// The last watermark emitted by the previous operator, if any.
+    pub last_watermark: Option<Timestamp>,
+    /// The largest timestamp of the elements, if any.
+    pub max_timestamp: Option<Timestamp>,
+    /// The number of watermarks observed.
+    pub watermarks: usize,
+    /// The number of timestamped elements observed.
+    pub items: usize,
+}
+
+impl WatermarkProbe {
+    fn new(stage: String, coord: Coord) -> Self {
+        Self {
+            stage,
+            coord,
+            last_watermark: None,
+            max_timestamp: None,
+            watermarks: 0,
+            items: 0,
+        }
+    }
+
+    /// How far the watermark is behind the elements, `None` if no element has been observed.
+    ///
+    /// If elements have been observed but no watermark, the lag is `Timestamp::MAX`.
+    pub fn lag(&self) -> Option<Timestamp> {
+        let max = self.max_timestamp?;
+        Some(match self.last_watermark {
+            Some(watermark) => max.saturating_sub(watermark).max(0),
+            None => Timestamp::MAX,
+        })
+    }
+
+    /// Whether the watermark is behind the elements by more than `threshold`.
+    pub fn is_held_back(&self, threshold: Timestamp) -> bool {
+        self.lag().is_some_and(|lag| lag > threshold)
+    }
+}
+
+/// Collector of the watermarks observed at the audited points of a job.
+///
+/// The points are marked with [`Stream::audit_watermarks`]. A replica whose watermark lags behind
+/// its elements by more than the threshold when the stream ends is reported with a warning in the
+/// log, and can be inspected after the execution with [`WatermarkAudit::held_back`].
+///
+/// **Note**: only the replicas running in the current process are collected.
+#[derive(Clone, Debug)]
+pub struct WatermarkAudit {
+    threshold: Timestamp,
+    probes: Arc<Mutex<HashMap<(String, Coord), WatermarkProbe>>>,
+}
+
+impl WatermarkAudit {
+    /// Create an audit that flags the watermarks lagging behind the elements by more than
+    /// `threshold`.
+    pub fn new(threshold: Timestamp) -> Self {
+        Self {
+            threshold,
+            probes: Default::default(),
+        }
+    }
+
+    fn record(&self, probe: &WatermarkProbe) {
+        self.probes
+            .lock()
+            .insert((probe.stage.clone(), probe.coord), probe.clone());
+    }
+
+    /// All the probes, sorted by stage and replica.
+    pub fn probes(&self) -> Vec<WatermarkProbe> {
+        let mut probes: Vec<_> = self.probes.lock().values().cloned().collect();
+        probes.sort_by(|a, b| (&a.stage, a.coord).cmp(&(&b.stage, b.coord)));
+        probes
+    }
+
+    /// The probes whose watermark lags behind the elements by more than the threshold.
+    pub fn held_back(&self) -> Vec<WatermarkProbe> {
+        let mut probes = self.probes();
+        probes.retain(|p| p.is_held_back(self.threshold));
+        probes
+    }
+}
+
+#[derive(Clone, Derivative)]
+#[derivative(Debug)]
+pub struct AuditWatermarks<Op: Operator> {
+    prev: Op,
+    #[derivative(Debug = "ignore")]
+    audit: WatermarkAudit,
+    stage: String,
+    probe: Option<WatermarkProbe>,
+}
+
+impl<Op: Operator> Display for AuditWatermarks<Op> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} -> AuditWatermarks({})", self.prev, self.stage)
+    }
+}
+
+impl<Op: Operator> AuditWatermarks<Op> {
+    /// Publish the probe at the end of the stream, warning if the watermark is held back.
+    fn finish(&mut self) {
+        let probe = self.probe.as_mut().expect("setup was not called");
+        self.audit.record(probe);
+        if probe.is_held_back(self.audit.threshold) {
+            log::warn!(
+                "{} watermark held back at {}: last watermark {:?}, max timestamp {:?}",
+                probe.coord,
+                probe.stage,
+                probe.last_watermark,
+                probe.max_timestamp,
+            );
+        }
+        *probe = WatermarkProbe::new(probe.stage.clone(), probe.coord);
+    }
+}
+
+impl<Op: Operator> Operator for AuditWatermarks<Op> {
+    type Out = Op::Out;
+
+    fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+        self.prev.setup(metadata);
+        let probe = WatermarkProbe::new(self.stage.clone(), metadata.coord);
+        self.audit.record(&probe);
+        self.probe = Some(probe);
+    }
+
+    #[inline]
+    fn next(&mut self) -> StreamElement<Self::Out> {
+        let el = self.prev.next();
+        let probe = self.probe.as_mut().expect("setup was not called");
+        match &el {
+            StreamElement::Timestamped(_, ts) => {
+                probe.items += 1;
+                probe.max_timestamp = probe.max_timestamp.max(Some(*ts));
+            }
+            StreamElement::Watermark(ts) => {
+                probe.watermarks += 1;
+                probe.last_watermark = Some(*ts);
+                self.audit.record(probe);
+            }
+            StreamElement::FlushBatch => self.audit.record(probe),
+            StreamElement::FlushAndRestart => self.finish(),
+            StreamElement::Item(_) | StreamElement::Terminate => {}
+        }
+        el
+    }
+
+    fn structure(&self) -> BlockStructure {
+        self.prev
+            .structure()
+            .add_operator(OperatorStructure::new::<Self::Out, _>("AuditWatermarks"))
+    }
+}
+
+impl<Op: Operator + 'static> Stream<Op> {
+    /// Record in `audit` the watermarks and the timestamps of the elements at this point of the
+    /// stream, naming it `stage`.
+    ///
+    /// The stream is not modified. Placing an audited point after each operator shows which one
+    /// holds back the watermarks, see [`WatermarkAudit`].
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::watermark_audit::WatermarkAudit;
+    /// # let mut env = StreamContext::new_local();
+    /// let audit = WatermarkAudit::new(10);
+    /// env.stream_iter(0..100i64)
+    ///     // the watermarks are generated every 50 elements
+    ///     .add_timestamps(|&n| n, |&n, &ts| (n % 50 == 0).then_some(ts))
+    ///     .audit_watermarks(&audit, "source")
+    ///     .for_each(|_| {});
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let held_back = audit.held_back();
+    /// assert_eq!(held_back.len(), 1);
+    /// assert_eq!(held_back[0].last_watermark, Some(50));
+    /// assert_eq!(held_back[0].max_timestamp, Some(99));
+    /// ```
+    pub fn audit_watermarks(
+        self,
+        audit: &WatermarkAudit,
+        stage: impl Into<String>,
+    ) -> Stream<AuditWatermarks<Op>> {
+        let audit = audit.clone();
+        self.add_operator(|prev| AuditWatermarks {
+            prev,
+            audit,
+            stage: stage.into(),
+            probe: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Display;
+
+    use crate::block::BlockStructure;
+    use crate::config::RuntimeConfig;
+    use crate::environment::StreamContext;
+    use crate::operator::source::IteratorSource;
+    use crate::operator::watermark_audit::WatermarkAudit;
+    use crate::operator::{Operator, StreamElement};
+    use crate::scheduler::ExecutionMetadata;
+
+    /// An operator that holds back all the watermarks.
+    #[derive(Clone, Debug)]
+    struct DropWatermarks<Op>(Op);
+
+    impl<Op: Operator> Display for DropWatermarks<Op> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{} -> DropWatermarks", self.0)
+        }
+    }
+
+    impl<Op: Operator> Operator for DropWatermarks<Op> {
+        type Out = Op::Out;
+
+        fn setup(&mut self, metadata: &mut ExecutionMetadata) {
+            self.0.setup(metadata);
+        }
+
+        fn next(&mut self) -> StreamElement<Op::Out> {
+            loop {
+                match self.0.next() {
+                    StreamElement::Watermark(_) => continue,
+                    el => return el,
+                }
+            }
+        }
+
+        fn structure(&self) -> BlockStructure {
+            self.0.structure()
+        }
+    }
+
+    #[test]
+    fn audit_finds_held_back_operator() {
+        let env = StreamContext::new(RuntimeConfig::local(2).unwrap());
+        let audit = WatermarkAudit::new(5);
+        env.stream(IteratorSource::new(0..100i64))
+            .add_timestamps(|&n| n, |_, &ts| Some(ts))
+            .audit_watermarks(&audit, "source")
+            .add_operator(DropWatermarks)
+            .audit_watermarks(&audit, "dropped")
+            .for_each(|_| {});
+        env.execute_blocking();
+
+        let probes = audit.probes();
+        assert_eq!(probes.len(), 2);
+        assert_eq!(probes[1].stage, "source");
+        assert_eq!(probes[1].watermarks, 100);
+        assert_eq!(probes[1].last_watermark, Some(99));
+        assert_eq!(probes[1].lag(), Some(0));
+
+        let held_back = audit.held_back();
+        assert_eq!(held_back.len(), 1);
+        assert_eq!(held_back[0].stage, "dropped");
+        assert_eq!(held_back[0].items, 100);
+        assert_eq!(held_back[0].last_watermark, None);
+        assert_eq!(held_back[0].lag(), Some(i64::MAX));
+    }
+}
@@ -0,0 +1,101 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use super::{super::*, Fold};
+use crate::operator::{Data, DataKey, Operator};
+use crate::stream::{KeyedStream, WindowedStream};
+
+/// Number of previous windows of each key kept as the baseline of
+/// [`WindowedStream::threshold_alert`].
+const BASELINE_WINDOWS: usize = 16;
+
+/// A window whose aggregate exceeded the threshold computed from the previous windows, emitted by
+/// [`WindowedStream::threshold_alert`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ThresholdAlert {
+    /// The sum of the values of the window.
+    pub value: f64,
+    /// The percentile of the sums of the previous windows.
+    pub baseline: f64,
+    /// The threshold exceeded by `value`, the baseline multiplied by the factor.
+    pub threshold: f64,
+}
+
+/// The nearest-rank `percentile` of the values, which must not be empty.
+fn percentile_of(values: &VecDeque<f64>, percentile: f64) -> f64 {
+    let mut sorted: Vec<f64> = values.iter().copied().collect();
+    sorted.sort_unstable_by(f64::total_cmp);
+    let rank = (percentile / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+impl<Key, Out, WindowDescr, OperatorChain> WindowedStream<OperatorChain, Out, WindowDescr>
+where
+    WindowDescr: WindowDescription<Out> + 'static,
+    OperatorChain: Operator<Out = (Key, Out)> + 'static,
+    Key: DataKey,
+    Out: Data + Into<f64>,
+{
+    /// Emit an alert for each window whose sum is greater than `factor` times the `percentile`
+    /// (between 0 and 100) of the sums of the previous windows of the same key.
+    ///
+    /// The baseline is made of the last 16 windows of each key, and no alert is emitted until a
+    /// key has 16 previous windows. All the windows are part of the baseline, including the ones
+    /// that raised an alert.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # use renoir::operator::window::CountWindow;
+    /// # let mut env = StreamContext::new_local();
+    /// // a spike in the 20th window
+    /// let s = env.stream_iter((0..150u32).map(|i| if i / 5 == 20 { 10.0 } else { 1.0 }));
+    /// let res = s
+    ///     .group_by(|_| ())
+    ///     .window(CountWindow::tumbling(5))
+    ///     .threshold_alert(95.0, 2.0)
+    ///     .drop_key()
+    ///     .collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let res = res.get().unwrap();
+    /// assert_eq!(res.len(), 1);
+    /// assert_eq!(res[0].value, 50.0);
+    /// assert_eq!(res[0].baseline, 5.0);
+    /// ```
+    pub fn threshold_alert(
+        self,
+        percentile: f64,
+        factor: f64,
+    ) -> KeyedStream<impl Operator<Out = (Key, ThresholdAlert)>> {
+        assert!(
+            (0.0..=100.0).contains(&percentile),
+            "the percentile of threshold_alert must be between 0 and 100"
+        );
+        let acc = Fold::new(0.0, |sum: &mut f64, x: Out| *sum += x.into());
+        let mut history = VecDeque::with_capacity(BASELINE_WINDOWS + 1);
+        self.add_window_operator("WindowThresholdAlert", acc)
+            .rich_filter_map(move |(_, value)| {
+                let alert = if history.len() == BASELINE_WINDOWS {
+                    let baseline = percentile_of(&history, percentile);
+                    let threshold = baseline * factor;
+                    (value > threshold).then_some(ThresholdAlert {
+                        value,
+                        baseline,
+                        threshold,
+                    })
+                } else {
+                    None
+                };
+                history.push_back(value);
+                if history.len() > BASELINE_WINDOWS {
+                    history.pop_front();
+                }
+                alert
+            })
+    }
+}
@@ -0,0 +1,92 @@
+use super::super::*;
+use crate::operator::{Data, DataKey, Operator};
+use crate::stream::{KeyedStream, WindowedStream};
+
+#[derive(Clone)]
+struct JoinBack<I, S, F>
+where
+    F: FnMut(&mut S, &I),
+{
+    elements: Vec<I>,
+    state: S,
+    f: F,
+}
+
+impl<I, S, F> WindowAccumulator for JoinBack<I, S, F>
+where
+    I: Clone + Send + 'static,
+    S: Clone + Send + 'static,
+    F: FnMut(&mut S, &I) + Clone + Send + 'static,
+{
+    type In = I;
+
+    type Out = Vec<(I, S)>;
+
+    #[inline]
+    fn process(&mut self, el: Self::In) {
+        (self.f)(&mut self.state, &el);
+        self.elements.push(el);
+    }
+
+    #[inline]
+    fn output(self) -> Self::Out {
+        let state = self.state;
+        self.elements
+            .into_iter()
+            .map(|el| (el, state.clone()))
+            .collect()
+    }
+}
+
+impl<Key, Out, WindowDescr, OperatorChain> WindowedStream<OperatorChain, Out, WindowDescr>
+where
+    WindowDescr: WindowDescription<Out> + 'static,
+    OperatorChain: Operator<Out = (Key, Out)> + 'static,
+    Key: DataKey,
+    Out: Data,
+{
+    /// Fold the elements of each window into an accumulator value, like [`WindowedStream::fold`],
+    /// and emit each element of the window paired with the final value of the accumulator.
+    ///
+    /// This attaches an aggregate of the window to its elements (e.g. each event with the total of
+    /// its session) without joining the stream with the aggregated one. The elements are emitted
+    /// when the window closes, with the timestamp of the window. An element belonging to more than
+    /// one window is emitted once for each of them.
+    ///
+    /// **Note**: all the elements of a window are kept until the window closes.
+    ///
+    /// ## Example
+    /// ```
+    /// # use renoir::{StreamContext, RuntimeConfig};
+    /// # use renoir::operator::source::IteratorSource;
+    /// # use renoir::operator::window::CountWindow;
+    /// # let mut env = StreamContext::new_local();
+    /// let s = env.stream_iter(0..5);
+    /// let res = s
+    ///     .group_by(|&n| n % 2)
+    ///     .window(CountWindow::tumbling(2))
+    ///     .aggregate_and_join_back(0, |acc, &n| *acc += n)
+    ///     .collect_vec();
+    ///
+    /// env.execute_blocking();
+    ///
+    /// let mut res = res.get().unwrap();
+    /// res.sort_unstable();
+    /// assert_eq!(res, vec![(0, (0, 2)), (0, (2, 2)), (1, (1, 4)), (1, (3, 4))]);
+    /// ```
+    pub fn aggregate_and_join_back<NewOut: Data, F>(
+        self,
+        init: NewOut,
+        fold: F,
+    ) -> KeyedStream<impl Operator<Out = (Key, (Out, NewOut))>>
+    where
+        F: FnMut(&mut NewOut, &Out) + Clone + Send + 'static,
+    {
+        let acc = JoinBack {
+            elements: Vec::new(),
+            state: init,
+            f: fold,
+        };
+        self.add_window_operator("WindowJoinBack", acc).flatten()
+    }
+}
@@ -2,9 +2,13 @@ mod fold;
 // mod columnar;
 pub(super) use fold::{Fold, FoldFirst};
 
+mod alert;
+pub use alert::ThresholdAlert;
+
 mod collect_vec;
 mod count;
 mod join;
+mod join_back;
 mod max;
 mod min;
 mod nth;
@@ -142,6 +142,10 @@ impl<T: Data> WindowDescription<T> for EventTimeWindow {
             ws: Default::default(),
         }
     }
+
+    fn time_domain(&self) -> TimeDomain {
+        TimeDomain::EventTime
+    }
 }
 
 #[cfg(test)]
@@ -206,4 +210,15 @@ mod tests {
         let expected: Vec<Vec<_>> = vec![vec![1], vec![15, 16], vec![30, 31]];
         assert_eq!(received, expected)
     }
+
+    #[test]
+    #[should_panic(expected = "Event time windows need a timestamped stream")]
+    fn event_time_window_without_timestamps() {
+        let env = crate::StreamContext::new_local();
+        env.stream_iter(0..10i64)
+            .group_by(|x| x % 2)
+            .window(EventTimeWindow::tumbling(3))
+            .count()
+            .collect_vec();
+    }
 }
@@ -2,16 +2,18 @@ use std::collections::VecDeque;
 use std::time::{Duration, Instant};
 
 use super::super::*;
+use crate::operator::clock::{Clock, SystemClock};
 use crate::operator::{Data, StreamElement};
 
 #[derive(Clone)]
-pub struct ProcessingTimeWindowManager<A>
+pub struct ProcessingTimeWindowManager<A, C>
 where
     A: WindowAccumulator,
 {
     init: A,
     size: Duration,
     slide: Duration,
+    clock: C,
     ws: VecDeque<Slot<A>>,
 }
 
@@ -35,7 +37,7 @@ impl<A> Slot<A> {
     }
 }
 
-impl<A: WindowAccumulator> WindowManager for ProcessingTimeWindowManager<A>
+impl<A: WindowAccumulator, C: Clock> WindowManager for ProcessingTimeWindowManager<A, C>
 where
     A::In: Data,
     A::Out: Data,
@@ -46,7 +48,7 @@ where
 
     #[inline]
     fn process(&mut self, el: StreamElement<A::In>) -> Self::Output {
-        let now = Instant::now();
+        let now = self.clock.now();
         match el {
             StreamElement::Item(item) | StreamElement::Timestamped(item, _) => {
                 // TODO: Windows are not aligned if there are periods without windows, evaluate if it needs to be changed
@@ -88,10 +90,14 @@ where
 }
 
 /// Window based on wall clock at time of processing
+///
+/// The time is read from the [`SystemClock`], use [`ProcessingTimeWindow::with_clock`] for
+/// changing the source of the time.
 #[derive(Clone)]
-pub struct ProcessingTimeWindow {
+pub struct ProcessingTimeWindow<C = SystemClock> {
     size: Duration,
     slide: Duration,
+    clock: C,
 }
 
 impl ProcessingTimeWindow {
@@ -99,18 +105,38 @@ impl ProcessingTimeWindow {
     pub fn sliding(size: Duration, slide: Duration) -> Self {
         assert!(!size.is_zero(), "window size must be > 0");
         assert!(!slide.is_zero(), "window slide must be > 0");
-        Self { size, slide }
+        Self {
+            size,
+            slide,
+            clock: SystemClock,
+        }
     }
 
     #[inline]
     pub fn tumbling(size: Duration) -> Self {
         assert!(!size.is_zero(), "window size must be > 0");
-        Self { size, slide: size }
+        Self {
+            size,
+            slide: size,
+            clock: SystemClock,
+        }
+    }
+}
+
+impl<C: Clock> ProcessingTimeWindow<C> {
+    /// Read the processing time from the given clock.
+    #[inline]
+    pub fn with_clock<C2: Clock>(self, clock: C2) -> ProcessingTimeWindow<C2> {
+        ProcessingTimeWindow {
+            size: self.size,
+            slide: self.slide,
+            clock,
+        }
     }
 }
 
-impl<T: Data> WindowDescription<T> for ProcessingTimeWindow {
-    type Manager<A: WindowAccumulator<In = T>> = ProcessingTimeWindowManager<A>;
+impl<T: Data, C: Clock> WindowDescription<T> for ProcessingTimeWindow<C> {
+    type Manager<A: WindowAccumulator<In = T>> = ProcessingTimeWindowManager<A, C>;
 
     #[inline]
     fn build<A: WindowAccumulator<In = T>>(&self, accumulator: A) -> Self::Manager<A> {
@@ -118,14 +144,20 @@ impl<T: Data> WindowDescription<T> for ProcessingTimeWindow {
             init: accumulator,
             size: self.size,
             slide: self.slide,
+            clock: self.clock.clone(),
             ws: Default::default(),
         }
     }
+
+    fn time_domain(&self) -> TimeDomain {
+        TimeDomain::ProcessingTime
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::operator::clock::ManualClock;
     use crate::operator::window::aggr::Fold;
 
     macro_rules! save_result {
@@ -172,4 +204,32 @@ mod tests {
         assert_eq!(n_windows, expected_n);
         assert_eq!(received, (1..100).collect::<Vec<_>>())
     }
+
+    #[test]
+    fn processing_time_window_manual_clock() {
+        let clock = ManualClock::new();
+        let window = ProcessingTimeWindow::sliding(Duration::from_secs(4), Duration::from_secs(2))
+            .with_clock(clock.clone());
+
+        let fold: Fold<isize, Vec<isize>, _> = Fold::new(Vec::new(), |v, el| v.push(el));
+        let mut manager = window.build(fold);
+
+        let mut received = Vec::new();
+        for i in 0..10 {
+            let ret = manager.process(StreamElement::Item(i));
+            received.extend(ret.into_iter().map(|r| r.unwrap_item()));
+            clock.advance(Duration::from_secs(1));
+        }
+        let ret = manager.process(StreamElement::FlushAndRestart);
+        received.extend(ret.into_iter().map(|r| r.unwrap_item()));
+
+        let expected: Vec<Vec<_>> = vec![
+            vec![0, 1, 2, 3],
+            vec![2, 3, 4, 5],
+            vec![4, 5, 6, 7],
+            vec![6, 7, 8, 9],
+            vec![8, 9],
+        ];
+        assert_eq!(received, expected);
+    }
 }
@@ -1,15 +1,17 @@
 use std::time::{Duration, Instant};
 
 use super::super::*;
+use crate::operator::clock::{Clock, SystemClock};
 use crate::operator::{Data, StreamElement};
 
 #[derive(Clone)]
-pub struct SessionWindowManager<A>
+pub struct SessionWindowManager<A, C>
 where
     A: WindowAccumulator,
 {
     init: A,
     gap: Duration,
+    clock: C,
     w: Option<Slot<A>>,
 }
 
@@ -26,7 +28,7 @@ impl<A> Slot<A> {
     }
 }
 
-impl<A: WindowAccumulator> WindowManager for SessionWindowManager<A>
+impl<A: WindowAccumulator, C: Clock> WindowManager for SessionWindowManager<A, C>
 where
     A::In: Data,
     A::Out: Data,
@@ -37,7 +39,7 @@ where
 
     #[inline]
     fn process(&mut self, el: StreamElement<A::In>) -> Self::Output {
-        let ts = Instant::now();
+        let ts = self.clock.now();
 
         let ret = match &self.w {
             Some(slot) if ts - slot.last > self.gap => {
@@ -65,30 +67,53 @@ where
 }
 
 /// Window that splits after if no element is received for a fixed wall clock duration
+///
+/// The time is read from the [`SystemClock`], use [`SessionWindow::with_clock`] for changing the
+/// source of the time.
 #[derive(Clone)]
-pub struct SessionWindow {
+pub struct SessionWindow<C = SystemClock> {
     gap: Duration,
+    clock: C,
 }
 
 impl SessionWindow {
     #[inline]
     pub fn new(gap_millis: Duration) -> Self {
         assert!(!gap_millis.is_zero(), "window size must be > 0");
-        Self { gap: gap_millis }
+        Self {
+            gap: gap_millis,
+            clock: SystemClock,
+        }
+    }
+}
+
+impl<C: Clock> SessionWindow<C> {
+    /// Read the processing time from the given clock.
+    #[inline]
+    pub fn with_clock<C2: Clock>(self, clock: C2) -> SessionWindow<C2> {
+        SessionWindow {
+            gap: self.gap,
+            clock,
+        }
     }
 }
 
-impl<T: Data> WindowDescription<T> for SessionWindow {
-    type Manager<A: WindowAccumulator<In = T>> = SessionWindowManager<A>;
+impl<T: Data, C: Clock> WindowDescription<T> for SessionWindow<C> {
+    type Manager<A: WindowAccumulator<In = T>> = SessionWindowManager<A, C>;
 
     #[inline]
     fn build<A: WindowAccumulator<In = T>>(&self, accumulator: A) -> Self::Manager<A> {
         SessionWindowManager {
             init: accumulator,
             gap: self.gap,
+            clock: self.clock.clone(),
             w: Default::default(),
         }
     }
+
+    fn time_domain(&self) -> TimeDomain {
+        TimeDomain::ProcessingTime
+    }
 }
 
 #[cfg(test)]
@@ -96,6 +121,7 @@ mod tests {
     use std::time::Duration;
 
     use super::*;
+    use crate::operator::clock::ManualClock;
     use crate::operator::window::aggr::Fold;
 
     macro_rules! save_result {
@@ -130,4 +156,26 @@ mod tests {
             vec![(0..33).collect(), (33..80).collect(), (80..100).collect()];
         assert_eq!(received, expected)
     }
+
+    #[test]
+    fn session_window_manual_clock() {
+        let clock = ManualClock::new();
+        let window = SessionWindow::new(Duration::from_secs(10)).with_clock(clock.clone());
+
+        let fold = Fold::new(Vec::new(), |v, el| v.push(el));
+        let mut manager = window.build(fold);
+
+        let mut received = Vec::new();
+        for i in 0..100i64 {
+            if i == 33 || i == 80 {
+                clock.advance(Duration::from_secs(11));
+            }
+            save_result!(manager.process(StreamElement::Item(i)), received);
+        }
+        save_result!(manager.process(StreamElement::FlushAndRestart), received);
+
+        let expected: Vec<Vec<_>> =
+            vec![(0..33).collect(), (33..80).collect(), (80..100).collect()];
+        assert_eq!(received, expected)
+    }
 }
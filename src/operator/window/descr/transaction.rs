@@ -137,6 +137,10 @@ impl<T: Data, F: Fn(&T) -> TransactionOp + Data> WindowDescription<T> for Transa
             w: None,
         }
     }
+
+    fn time_domain(&self) -> TimeDomain {
+        TimeDomain::EventTime
+    }
 }
 
 // #[cfg(test)]
@@ -4,11 +4,14 @@ use std::collections::{HashMap, VecDeque};
 use std::fmt::Display;
 use std::marker::PhantomData;
 
+pub use aggr::ThresholdAlert;
 pub use descr::*;
 // pub use aggregator::*;
 // pub use description::*;
 
-use crate::block::{GroupHasherBuilder, OperatorStructure, Replication};
+use crate::block::{
+    GroupHasherBuilder, OperatorStructure, Replication, StateEntries, TimestampUsage,
+};
 use crate::operator::{Data, DataKey, ExchangeData, Operator, StreamElement, Timestamp};
 use crate::stream::{KeyedStream, Stream, WindowedStream};
 
@@ -26,6 +29,26 @@ pub trait WindowDescription<T> {
     /// Build a window manager that dispatches elements of each window to a clone of the
     /// accumulator passed as parameter
     fn build<A: WindowAccumulator<In = T>>(&self, accumulator: A) -> Self::Manager<A>;
+
+    /// The notion of time the windows are defined on.
+    ///
+    /// Windows in [`TimeDomain::EventTime`] can only be applied to streams whose elements are
+    /// timestamped. The default is [`TimeDomain::Untimed`], which is never rejected by the job
+    /// graph validation.
+    fn time_domain(&self) -> TimeDomain {
+        TimeDomain::Untimed
+    }
+}
+
+/// The notion of time a window is defined on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeDomain {
+    /// The windows are defined on the timestamps of the elements and closed by the watermarks.
+    EventTime,
+    /// The windows are defined on the wall clock of the replica processing the elements.
+    ProcessingTime,
+    /// The windows do not depend on time, for example they are defined on the number of elements.
+    Untimed,
 }
 
 /// Trait for operations that can be performed on windows. Operations must be incremental
@@ -133,6 +156,8 @@ where
     ///
     /// It is used only for tracing purposes.
     name: String,
+    /// Whether the windows need timestamped elements.
+    timestamps: TimestampUsage,
     /// The manager that will build the windows.
     manager: KeyedWindowManager<Key, In, Out, W>,
     /// A buffer for storing ready items.
@@ -221,9 +246,10 @@ where
     }
 
     fn structure(&self) -> crate::block::BlockStructure {
-        self.prev
-            .structure()
-            .add_operator(OperatorStructure::new::<(Key, Out), _>(&self.name))
+        let mut operator = OperatorStructure::new::<(Key, Out), _>(&self.name)
+            .with_state::<(Key, W)>(StateEntries::PerKey);
+        operator.timestamps = self.timestamps;
+        self.prev.structure().add_operator(operator)
     }
 }
 
@@ -234,11 +260,13 @@ where
     pub(crate) fn new(
         prev: Prev,
         name: String,
+        timestamps: TimestampUsage,
         manager: KeyedWindowManager<Key, In, Out, W>,
     ) -> Self {
         Self {
             prev,
             name,
+            timestamps,
             manager,
             output_buffer: Default::default(),
         }
@@ -265,6 +293,10 @@ where
         A: WindowAccumulator<In = Out, Out = NewOut>,
     {
         let stream = self.inner;
+        let timestamps = match self.descr.time_domain() {
+            TimeDomain::EventTime => TimestampUsage::Require,
+            TimeDomain::ProcessingTime | TimeDomain::Untimed => TimestampUsage::Forward,
+        };
         let init = self.descr.build::<A>(accumulator);
 
         let manager: KeyedWindowManager<Key, Out, NewOut, WindowDescr::Manager<A>> =
@@ -276,7 +308,7 @@ where
             };
 
         stream // .add_operator(Reorder::new)
-            .add_operator(|prev| WindowOperator::new(prev, name.into(), manager))
+            .add_operator(|prev| WindowOperator::new(prev, name.into(), timestamps, manager))
     }
 }
 
@@ -308,10 +340,20 @@ where
     /// res.sort_unstable();
     /// assert_eq!(res, vec![(0, 0 + 2 + 4), (0, 4 + 6 + 8), (1, 1 + 3 + 5)]);
     /// ```
+    ///
+    /// **Note**: windows in [`TimeDomain::EventTime`] need the elements to be timestamped (e.g.
+    /// with [`KeyedStream::add_timestamps`]), this function panics if the stream is known to have
+    /// no timestamps.
     pub fn window<WinOut: Data, WinDescr: WindowDescription<Out>>(
         self,
         descr: WinDescr,
     ) -> WindowedStream<impl Operator<Out = (Key, Out)>, WinOut, WinDescr> {
+        if descr.time_domain() == TimeDomain::EventTime && self.0.is_timestamped() == Some(false) {
+            panic!(
+                "Event time windows need a timestamped stream, use `add_timestamps` before \
+                applying the window"
+            );
+        }
         WindowedStream {
             inner: self,
             descr,
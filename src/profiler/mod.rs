@@ -30,8 +30,10 @@ pub trait Profiler {
 
 /// Tracing information of the current execution.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
-pub(crate) struct TracingData {
+pub struct TracingData {
+    /// The structure of each replica of the blocks.
     pub structures: Vec<(Coord, BlockStructure)>,
+    /// The results of the profilers, empty if the `profiler` feature is not enabled.
     pub profilers: Vec<ProfilerResult>,
 }
 
@@ -51,22 +53,17 @@ pub(crate) struct TracingData {
 //     }
 // }
 
-pub fn log_trace(structures: Vec<(Coord, BlockStructure)>, profilers: Vec<ProfilerResult>) {
+pub fn log_trace(data: &TracingData) {
     if !cfg!(feature = "profiler") {
         return;
     }
 
     use std::io::Write as _;
-    let data = TracingData {
-        structures,
-        profilers,
-    };
-
     let mut stderr = std::io::stderr().lock();
     writeln!(
         stderr,
         "__renoir_TRACING_DATA__{}",
-        serde_json::to_string(&data).unwrap()
+        serde_json::to_string(data).unwrap()
     )
     .unwrap();
 }
@@ -94,7 +91,7 @@ mod without_profiler {
     use crate::network::Coord;
     use crate::profiler::*;
 
-    /// The fake profiler for when the `profiler` feature is disabled.
+    // The fake profiler for when the `profiler` feature is disabled.
     // static PROFILER: UnsafeCell<NoOpProfiler> = UnsafeCell::new(NoOpProfiler);
 
     /// Fake profiler. This is used when the `profiler` feature is not enabled.
@@ -3,16 +3,18 @@ use std::collections::HashMap;
 use std::fmt::Write as FmtWrite;
 use std::fs::File;
 use std::io::prelude::*;
-use std::io::BufReader;
+use std::io::ErrorKind;
 use std::net::TcpStream;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD as B64, Engine};
 
+use parking_lot::Mutex;
 use sha2::Digest;
 #[cfg(feature = "ssh")]
-use ssh2::Session;
+use ssh2::{Channel, Session};
 
 use crate::config::CONFIG_ENV_VAR;
 use crate::config::HOST_ID_ENV_VAR;
@@ -23,6 +25,8 @@ use crate::scheduler::HostId;
 
 /// Size of the buffer usedahash to send the executable file via SCP.
 pub(crate) const SCP_BUFFER_SIZE: usize = 512 * 1024;
+/// How often the output of the remote processes is checked when there is nothing to read.
+const OUTPUT_POLL_INTERVAL: Duration = Duration::from_millis(10);
 
 /// Execution results returned by a remote worker.
 struct HostExecutionResult {
@@ -70,6 +74,11 @@ pub(crate) fn spawn_remote_workers(config: RemoteConfig) {
 
     let start = Instant::now();
     let exe_hash = executable_hash();
+    let log_file = config.log_file.as_ref().map(|path| {
+        let file = File::create(path)
+            .unwrap_or_else(|e| panic!("Cannot create the log file {}: {e:?}", path.display()));
+        Arc::new(Mutex::new(file))
+    });
     let mut join_handles = Vec::new();
     let mut host_dup: HashMap<String, usize> = HashMap::new(); // Used to detect deployments with replicated host
     for (host_id, host) in config.hosts.iter().enumerate() {
@@ -82,9 +91,14 @@ pub(crate) fn spawn_remote_workers(config: RemoteConfig) {
 
         let config = config.clone();
         let host = host.clone();
+        let output = HostOutput {
+            host_id: host_id as _,
+            log_file: log_file.clone(),
+            tracing: None,
+        };
         let join_handle = std::thread::Builder::new()
             .name(format!("remote-{host_id:02}",))
-            .spawn(move || remote_worker(host_id as _, host, config, exe_uid))
+            .spawn(move || remote_worker(host_id as _, host, config, exe_uid, output))
             .unwrap();
         join_handles.push(join_handle);
     }
@@ -107,7 +121,10 @@ pub(crate) fn spawn_remote_workers(config: RemoteConfig) {
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap();
-        let file_name = format!("renoir-trace-{}.json", now.as_secs());
+        let file_name = match &config.job {
+            Some(job) => format!("renoir-trace-{}-{}.json", job.id, now.as_secs()),
+            None => format!("renoir-trace-{}.json", now.as_secs()),
+        };
         let target = path.join(file_name);
         let mut target = std::fs::File::create(target).expect("Cannot create tracing json file");
         serde_json::to_writer(&mut target, &tracing_data)
@@ -135,7 +152,7 @@ fn is_spawned_process() -> bool {
 /// - Send the local executable using SCP
 /// - Make it executable using `chmod`
 /// - Spawn the worker setting the correct environment variables
-/// - Redirect the remote stderr to the local one
+/// - Redirect the remote stdout and stderr to the local ones, see `HostOutput`
 /// - Remove the remote file on exit
 ///
 /// This function is allowed to block (i.e. not be asynchronous) since it will be run inside a
@@ -145,6 +162,7 @@ fn remote_worker(
     mut host: HostConfig,
     config: RemoteConfig,
     executable_uid: String,
+    mut output: HostOutput,
 ) -> HostExecutionResult {
     if host.ssh.username.is_none() {
         host.ssh.username = Some(whoami::username());
@@ -201,7 +219,7 @@ fn remote_worker(
     log::debug!("executable located at {}", current_exe.display());
 
     // generate a temporary file on remote host
-    let remote_path = Path::new("/tmp/renoir/").join(format!(
+    let remote_path = config.remote_dir().join(format!(
         "{}-{}",
         current_exe.file_name().unwrap().to_string_lossy(),
         executable_uid
@@ -229,23 +247,7 @@ fn remote_worker(
     let mut channel = session.channel_session().unwrap();
     channel.exec(&command).unwrap();
 
-    let stderr_reader = BufReader::new(channel.stderr());
-    let stdout_reader = BufReader::new(&mut channel);
-
-    let mut tracing_data = None;
-
-    for line in stdout_reader.lines().map_while(Result::ok) {
-        println!("{host_id}|{line}");
-    }
-
-    // copy to stderr the output of the remote process
-    for line in stderr_reader.lines().map_while(Result::ok) {
-        if let Some(trace) = try_parse_trace(&line) {
-            tracing_data = Some(trace);
-        } else {
-            eprintln!("{host_id}|{line}");
-        }
-    }
+    forward_output(&session, &mut channel, &mut output);
 
     channel.wait_close().unwrap();
     let exit_code = channel.exit_status().unwrap();
@@ -276,13 +278,84 @@ fn remote_worker(
     }
 
     HostExecutionResult {
-        tracing: tracing_data,
+        tracing: output.tracing,
         execution_time,
         sync_time,
         exit_code,
     }
 }
 
+/// Destination of the output of a remote host.
+///
+/// Each line is forwarded to the local stdout or stderr prefixed by the id of the host, and is
+/// also appended to the combined log file, if any. The tracing data sent by the host is kept
+/// instead of being forwarded.
+struct HostOutput {
+    host_id: HostId,
+    log_file: Option<Arc<Mutex<File>>>,
+    tracing: Option<TracingData>,
+}
+
+impl HostOutput {
+    fn line(&mut self, line: &str, stderr: bool) {
+        if stderr {
+            if let Some(trace) = try_parse_trace(line) {
+                self.tracing = Some(trace);
+                return;
+            }
+            eprintln!("{}|{line}", self.host_id);
+        } else {
+            println!("{}|{line}", self.host_id);
+        }
+        if let Some(file) = &self.log_file {
+            writeln!(file.lock(), "{}|{line}", self.host_id)
+                .expect("Failed to write to the log file");
+        }
+    }
+}
+
+/// Forward the stdout and the stderr of the remote process as they are produced, until both of
+/// them are closed.
+fn forward_output(session: &Session, channel: &mut Channel, output: &mut HostOutput) {
+    // the two streams are polled, so that a stream is not blocked until the other is closed
+    session.set_blocking(false);
+    let mut streams = [
+        (channel.stream(0), false, Vec::new(), true),
+        (channel.stderr(), true, Vec::new(), true),
+    ];
+    let mut buf = [0u8; 8 * 1024];
+    while streams.iter().any(|(_, _, _, open)| *open) {
+        let mut received = false;
+        for (stream, stderr, pending, open) in streams.iter_mut().filter(|s| s.3) {
+            match stream.read(&mut buf) {
+                Ok(0) => {
+                    *open = false;
+                    if !pending.is_empty() {
+                        output.line(&String::from_utf8_lossy(pending), *stderr);
+                    }
+                }
+                Ok(n) => {
+                    received = true;
+                    pending.extend_from_slice(&buf[..n]);
+                    while let Some(end) = pending.iter().position(|&b| b == b'\n') {
+                        let line: Vec<u8> = pending.drain(..=end).collect();
+                        output.line(&String::from_utf8_lossy(&line[..end]), *stderr);
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(e) => {
+                    warn!("{}|failed to read the output: {e:?}", output.host_id);
+                    *open = false;
+                }
+            }
+        }
+        if !received {
+            std::thread::sleep(OUTPUT_POLL_INTERVAL);
+        }
+    }
+    session.set_blocking(true);
+}
+
 /// Execute a command remotely and return the standard output and the exit code.
 fn run_remote_command(session: &mut Session, command: &str) -> (String, i32) {
     log::debug!("remote command: {}", command);
@@ -322,9 +395,13 @@ fn send_executable(
         return;
     }
 
-    let (msg, result) = run_remote_command(session, "mkdir -p /tmp/renoir");
+    let remote_dir = remote_path.parent().unwrap().to_str().unwrap();
+    let (msg, result) = run_remote_command(
+        session,
+        &format!("mkdir -p {}", shell_escape::escape(remote_dir.into())),
+    );
     if result != 0 {
-        warn!("failed to create /tmp/renoir directory [{result}]: {msg}");
+        warn!("failed to create {remote_dir} directory [{result}]: {msg}");
     }
 
     let mut local_file = File::open(local_path).unwrap();
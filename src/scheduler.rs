@@ -1,14 +1,20 @@
 use std::any::TypeId;
 use std::collections::HashMap;
 use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::thread::JoinHandle;
 
-use crate::block::{BatchMode, Block, BlockStructure, JobGraphGenerator, Replication};
-use crate::config::{LocalConfig, RemoteConfig, RuntimeConfig};
+use crate::block::{
+    BatchMode, Block, BlockPlan, BlockStructure, ConnectionPlan, ExecutionPlan, JobGraphGenerator,
+    JobGraphValidator, Priority, Replication, TimestampUsage,
+};
+use crate::config::{LocalConfig, QuotaConfig, RemoteConfig, RuntimeConfig};
+use crate::listener::ExecutionListener;
 use crate::network::{Coord, NetworkTopology};
 use crate::operator::Operator;
-use crate::profiler::{log_trace, wait_profiler};
-use crate::worker::spawn_worker;
+use crate::profiler::{log_trace, wait_profiler, TracingData};
+use crate::worker::{setup_worker, SpawnWorkerFn};
 use crate::CoordUInt;
 
 /// Identifier of a block in the job graph.
@@ -19,7 +25,7 @@ pub type HostId = CoordUInt;
 pub type ReplicaId = CoordUInt;
 
 type BlockInitFn =
-    Box<dyn FnOnce(&mut ExecutionMetadata) -> (JoinHandle<()>, BlockStructure) + Send>;
+    Box<dyn FnOnce(&mut ExecutionMetadata) -> (BlockStructure, SpawnWorkerFn) + Send>;
 
 /// Metadata used to initialize a block at the start of an execution
 #[derive(Debug)]
@@ -36,6 +42,84 @@ pub struct ExecutionMetadata<'a> {
     pub(crate) network: &'a mut NetworkTopology,
     /// The batching mode to use inside this block.
     pub batch_mode: BatchMode,
+    /// The priority of the streams coming from the previous blocks.
+    pub(crate) prev_priorities: Vec<(BlockId, Priority)>,
+    /// The resources used by the job on this host, shared by all the blocks.
+    pub(crate) quota: Arc<QuotaUsage>,
+}
+
+impl ExecutionMetadata<'_> {
+    /// The priority of the stream coming from the previous block `block_id`.
+    pub(crate) fn prev_priority(&self, block_id: BlockId) -> Priority {
+        self.prev_priorities
+            .iter()
+            .find(|(b, _)| *b == block_id)
+            .map(|(_, priority)| *priority)
+            .unwrap_or_default()
+    }
+}
+
+/// The resources used by the job on this host, checked against the limits of its [`QuotaConfig`].
+#[derive(Debug, Default)]
+pub(crate) struct QuotaUsage {
+    limits: QuotaConfig,
+    output_rows: AtomicU64,
+    spill_bytes: AtomicU64,
+    /// The number of replicas of stateful operators on this host, sharing the state budget.
+    stateful_operators: AtomicUsize,
+}
+
+impl QuotaUsage {
+    pub(crate) fn new(limits: QuotaConfig) -> Self {
+        Self {
+            limits,
+            ..Default::default()
+        }
+    }
+
+    /// Count the items that reached a sink, failing the job if they exceed the quota.
+    pub(crate) fn add_output_rows(&self, rows: u64) {
+        Self::add(
+            &self.output_rows,
+            rows,
+            self.limits.max_output_rows,
+            "output rows",
+        );
+    }
+
+    /// Count the bytes written to disk, failing the job if they exceed the quota.
+    pub(crate) fn add_spill_bytes(&self, bytes: u64) {
+        Self::add(
+            &self.spill_bytes,
+            bytes,
+            self.limits.max_spill_bytes,
+            "bytes spilled to disk",
+        );
+    }
+
+    /// Set the number of replicas of stateful operators on this host, among which the state budget
+    /// is split.
+    pub(crate) fn set_stateful_operators(&self, operators: usize) {
+        self.stateful_operators.store(operators, Ordering::Relaxed);
+    }
+
+    /// The memory in bytes that the state of a replica of a stateful operator can use, if the job
+    /// has a state budget.
+    pub(crate) fn state_budget(&self) -> Option<usize> {
+        let limit = self.limits.max_state_bytes?;
+        let operators = self.stateful_operators.load(Ordering::Relaxed).max(1);
+        Some((limit / operators as u64) as usize)
+    }
+
+    fn add(counter: &AtomicU64, amount: u64, limit: Option<u64>, what: &str) {
+        let Some(limit) = limit else {
+            return;
+        };
+        let total = counter.fetch_add(amount, Ordering::Relaxed) + amount;
+        if total > limit {
+            panic!("Job quota exceeded: {total} {what}, but the limit is {limit}");
+        }
+    }
 }
 
 /// Information about a block in the job graph.
@@ -43,6 +127,8 @@ pub struct ExecutionMetadata<'a> {
 struct SchedulerBlockInfo {
     /// String representation of the block.
     repr: String,
+    /// The structure of the block, before its operators are set up.
+    structure: BlockStructure,
     /// All the replicas, grouped by host.
     replicas: HashMap<HostId, Vec<Coord>, crate::block::CoordHasherBuilder>,
     /// All the global ids, grouped by coordinate.
@@ -51,6 +137,8 @@ struct SchedulerBlockInfo {
     batch_mode: BatchMode,
     /// Whether this block has `NextStrategy::OnlyOne`.
     is_only_one_strategy: bool,
+    /// The priority of the stream produced by this block.
+    priority: Priority,
 }
 
 /// The `Scheduler` is the entity that keeps track of all the blocks of the job graph and when the
@@ -68,6 +156,12 @@ pub(crate) struct Scheduler {
     block_init: Vec<(Coord, BlockInitFn)>,
     /// The network topology that keeps track of all the connections inside the execution graph.
     network: NetworkTopology,
+    /// Whether the elements produced by each block are timestamped, if it can be known.
+    timestamped: HashMap<BlockId, Option<bool>, crate::block::CoordHasherBuilder>,
+    /// The resources used by the job on this host.
+    quota: Arc<QuotaUsage>,
+    /// The listeners notified of the events of the execution.
+    listeners: Vec<Arc<dyn ExecutionListener>>,
 }
 
 impl Scheduler {
@@ -77,6 +171,9 @@ impl Scheduler {
             prev_blocks: Default::default(),
             block_info: Default::default(),
             block_init: Default::default(),
+            timestamped: Default::default(),
+            quota: Arc::new(QuotaUsage::new(config.quota())),
+            listeners: Default::default(),
             network: NetworkTopology::new(config.clone()),
             config,
         }
@@ -94,6 +191,8 @@ impl Scheduler {
     {
         let block_id = block.id;
         let info = self.block_info(&block);
+        let timestamped = self.is_timestamped(&info.structure);
+        self.timestamped.insert(block_id, timestamped);
         debug!(
             "schedule block (b{:02}): {}",
             block_id,
@@ -122,11 +221,28 @@ impl Scheduler {
             // spawn the actual worker
             self.block_init.push((
                 coord,
-                Box::new(move |metadata| spawn_worker(block, metadata)),
+                Box::new(move |metadata| setup_worker(block, metadata)),
             ));
         }
     }
 
+    /// Register a listener that is notified of the events of the execution.
+    pub(crate) fn add_listener(&mut self, listener: Arc<dyn ExecutionListener>) {
+        self.listeners.push(listener);
+    }
+
+    /// Notify the listeners that the job has completed and log its tracing data.
+    fn complete(&self, structures: Vec<(Coord, BlockStructure)>) {
+        let data = TracingData {
+            structures,
+            profilers: wait_profiler(),
+        };
+        for listener in &self.listeners {
+            listener.on_job_completed(&data);
+        }
+        log_trace(&data);
+    }
+
     /// Connect a pair of blocks inside the job graph.
     pub(crate) fn connect_blocks(&mut self, from: BlockId, to: BlockId, typ: TypeId) {
         debug!("connect block {} -> {}", from, to);
@@ -147,39 +263,102 @@ impl Scheduler {
         self.prev_blocks.entry(to).or_default().push((from, typ));
     }
 
-    fn build_all(&mut self) -> (Vec<JoinHandle<()>>, Vec<(Coord, BlockStructure)>) {
+    fn build_all(&mut self) -> (Vec<SpawnWorkerFn>, Vec<(Coord, BlockStructure)>) {
+        if !self.listeners.is_empty() {
+            let plan = self.plan();
+            for listener in &self.listeners {
+                listener.on_job_start(&plan);
+            }
+        }
         self.build_execution_graph();
         self.network.build();
         self.network.log();
 
-        let mut join = vec![];
+        let stateful_operators = self
+            .block_init
+            .iter()
+            .map(|(coord, _)| {
+                let structure = &self.block_info[&coord.block_id].structure;
+                structure
+                    .operators
+                    .iter()
+                    .filter(|op| op.state.is_some())
+                    .count()
+            })
+            .sum();
+        self.quota.set_stateful_operators(stateful_operators);
+
+        let mut spawn = vec![];
         let mut block_structures = vec![];
         let mut job_graph_generator = JobGraphGenerator::new();
+        let mut validator = JobGraphValidator::new();
 
         for (coord, init_fn) in self.block_init.drain(..) {
             let block_info = &self.block_info[&coord.block_id];
             let replicas = block_info.replicas.values().flatten().cloned().collect();
             let global_id = block_info.global_ids[&coord];
+            let prev = self.network.prev(coord);
+            let mut prev_priorities: Vec<_> = prev
+                .iter()
+                .filter_map(|(c, _)| Some((c.block_id, self.block_info.get(&c.block_id)?.priority)))
+                .collect();
+            prev_priorities.dedup();
             let mut metadata = ExecutionMetadata {
                 coord,
                 replicas,
                 global_id,
-                prev: self.network.prev(coord),
+                prev,
                 network: &mut self.network,
                 batch_mode: block_info.batch_mode,
+                prev_priorities,
+                quota: self.quota.clone(),
             };
-            let (handle, structure) = init_fn(&mut metadata);
-            join.push(handle);
+            let (structure, spawn_fn) = init_fn(&mut metadata);
+            for listener in &self.listeners {
+                listener.on_block_scheduled(coord.block_id, global_id, &structure);
+            }
+            spawn.push(spawn_fn);
             block_structures.push((coord, structure.clone()));
-            job_graph_generator.add_block(coord.block_id, structure);
+            job_graph_generator.add_block(coord.block_id, structure.clone());
+            validator.add_block(coord.block_id, structure);
         }
 
         let job_graph = job_graph_generator.finalize();
         log::debug!("job graph:\n{}", job_graph);
+        check_unique_uids(&block_structures);
+
+        for (&from, next) in &self.next_blocks {
+            for &(to, _, _) in next {
+                validator.add_connection(from, to);
+            }
+        }
+        self.check_replication(&mut validator);
+        let issues = validator.finalize();
+        if !issues.is_empty() {
+            panic!(
+                "The job graph is not valid, {} problems found:\n{}",
+                issues.len(),
+                issues
+                    .iter()
+                    .map(|issue| format!("- {issue}"))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            );
+        }
+
+        (spawn, block_structures)
+    }
+
+    /// Spawn the workers of the blocks built by `build_all`.
+    ///
+    /// The sources must start only once all the hosts are ready to exchange data, so this has to
+    /// be called after the wait returned by `NetworkTopology::startup_wait`.
+    fn spawn_all(&mut self, spawn: Vec<SpawnWorkerFn>) -> Vec<JoinHandle<()>> {
+        let join = spawn.into_iter().map(|spawn_fn| spawn_fn()).collect();
 
         self.network.finalize();
 
-        (join, block_structures)
+        join
     }
 
     #[cfg(feature = "tokio")]
@@ -196,7 +375,13 @@ impl Scheduler {
             self.block_info.len(),
         );
 
-        let (join, block_structures) = self.build_all();
+        let (spawn, block_structures) = self.build_all();
+        // the demultiplexers are tasks of this runtime: wait for them outside of it
+        let wait = self.network.startup_wait();
+        tokio::task::spawn_blocking(move || wait.wait())
+            .await
+            .expect("The network failed to start");
+        let join = self.spawn_all(spawn);
 
         let (_, join_result) = tokio::join!(
             self.network.stop_and_wait(),
@@ -209,7 +394,7 @@ impl Scheduler {
 
         join_result.expect("Could not join worker threads");
 
-        log_trace(block_structures, wait_profiler());
+        self.complete(block_structures);
     }
 
     /// Start the computation returning the list of handles used to join the workers.
@@ -236,7 +421,12 @@ impl Scheduler {
                 .build()
                 .unwrap()
                 .block_on(async move {
-                    let (join, block_structures) = self.build_all();
+                    let (spawn, block_structures) = self.build_all();
+                    let wait = self.network.startup_wait();
+                    tokio::task::spawn_blocking(move || wait.wait())
+                        .await
+                        .expect("The network failed to start");
+                    let join = self.spawn_all(spawn);
 
                     let (_, join_result) = tokio::join!(
                         self.network.stop_and_wait(),
@@ -247,20 +437,91 @@ impl Scheduler {
                         })
                     );
                     join_result.expect("Could not join worker threads");
-                    log_trace(block_structures, wait_profiler());
+                    self.complete(block_structures);
                 });
         }
         #[cfg(not(feature = "tokio"))]
         {
-            let (join, block_structures) = self.build_all();
+            let (spawn, block_structures) = self.build_all();
+            self.network.startup_wait().wait();
+            let join = self.spawn_all(spawn);
 
             for handle in join {
                 handle.join().unwrap();
             }
 
             self.network.stop_and_wait();
-            let profiler_results = wait_profiler();
-            log_trace(block_structures, profiler_results);
+            self.complete(block_structures);
+        }
+    }
+
+    /// Look for the blocks that send their elements to the replica with the same id, but whose
+    /// replicas do not all have a counterpart in the next block: the elements of those replicas
+    /// would have nowhere to go.
+    fn check_replication(&self, validator: &mut JobGraphValidator) {
+        for (from_block_id, next) in self.next_blocks.iter() {
+            let from = &self.block_info[from_block_id];
+            for &(to_block_id, _, fragile) in next.iter() {
+                if !from.is_only_one_strategy && !fragile {
+                    continue;
+                }
+                let to: Vec<_> = self.block_info[&to_block_id]
+                    .replicas
+                    .values()
+                    .flatten()
+                    .collect();
+                if to.len() == 1 {
+                    continue;
+                }
+                let unconnected = from
+                    .replicas
+                    .values()
+                    .flatten()
+                    .filter(|f| {
+                        !to.iter()
+                            .any(|t| t.host_id == f.host_id && t.replica_id == f.replica_id)
+                    })
+                    .count();
+                if unconnected > 0 {
+                    validator.add_issue(format!(
+                        "block {from_block_id} ({}) has {unconnected} replicas without a \
+                        corresponding replica in block {to_block_id} ({}), change the \
+                        replication of one of the two blocks",
+                        from.repr, self.block_info[&to_block_id].repr,
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Whether the elements produced by a chain of operators are timestamped.
+    ///
+    /// If no operator of the chain assigns or drops the timestamps, the blocks the chain receives
+    /// from are checked. Returns `None` if it cannot be known, for example because one of the
+    /// previous blocks has not been scheduled yet.
+    pub(crate) fn is_timestamped(&self, structure: &BlockStructure) -> Option<bool> {
+        let mut prev = vec![];
+        for operator in structure.operators.iter().rev() {
+            match operator.timestamps {
+                TimestampUsage::Assign => return Some(true),
+                TimestampUsage::Drop => return Some(false),
+                TimestampUsage::Forward | TimestampUsage::Require => {}
+            }
+            prev.extend(operator.receivers.iter().map(|r| r.previous_block_id));
+        }
+        if prev.is_empty() {
+            return None;
+        }
+        let prev: Vec<_> = prev
+            .into_iter()
+            .map(|block_id| self.timestamped.get(&block_id).copied().flatten())
+            .collect();
+        if prev.contains(&Some(false)) {
+            Some(false)
+        } else if prev.iter().all(|t| *t == Some(true)) {
+            Some(true)
+        } else {
+            None
         }
     }
 
@@ -272,6 +533,14 @@ impl Scheduler {
     /// Build the execution graph for the network topology, multiplying each block of the job graph
     /// into all its replicas.
     fn build_execution_graph(&mut self) {
+        for (from, to, typ, fragile) in self.execution_edges() {
+            self.network.connect(from, to, typ, fragile);
+        }
+    }
+
+    /// The channels of the execution graph, between the replicas of the connected blocks.
+    fn execution_edges(&self) -> Vec<(Coord, Coord, TypeId, bool)> {
+        let mut edges = vec![];
         for (from_block_id, next) in self.next_blocks.iter() {
             let from = &self.block_info[from_block_id];
             for &(to_block_id, typ, fragile) in next.iter() {
@@ -286,15 +555,61 @@ impl Scheduler {
                                 || (to_coord.host_id == from_coord.host_id
                                     && to_coord.replica_id == from_coord.replica_id)
                             {
-                                self.network.connect(from_coord, *to_coord, typ, fragile);
+                                edges.push((from_coord, *to_coord, typ, fragile));
                             }
                         } else {
-                            self.network.connect(from_coord, *to_coord, typ, fragile);
+                            edges.push((from_coord, *to_coord, typ, fragile));
                         }
                     }
                 }
             }
         }
+        edges
+    }
+
+    /// Describe how the job would be executed, without setting up its blocks.
+    pub(crate) fn plan(&self) -> ExecutionPlan {
+        let num_hosts = match &self.config {
+            RuntimeConfig::Local(_) => 1,
+            RuntimeConfig::Remote(remote) => remote.hosts.len(),
+        };
+        let mut blocks: Vec<_> = self
+            .block_info
+            .iter()
+            .map(|(&block_id, info)| BlockPlan {
+                block_id,
+                repr: info.repr.clone(),
+                structure: info.structure.clone(),
+                replicas: (0..num_hosts as HostId)
+                    .map(|host_id| info.replicas(host_id).len() as CoordUInt)
+                    .collect(),
+            })
+            .collect();
+        blocks.sort_by_key(|b| b.block_id);
+
+        let mut connections: HashMap<(BlockId, BlockId), ConnectionPlan> = HashMap::new();
+        for (from, to, _, _) in self.execution_edges() {
+            let conn = connections
+                .entry((from.block_id, to.block_id))
+                .or_insert_with(|| ConnectionPlan {
+                    from: from.block_id,
+                    to: to.block_id,
+                    local_channels: 0,
+                    remote_channels: 0,
+                });
+            if from.host_id == to.host_id {
+                conn.local_channels += 1;
+            } else {
+                conn.remote_channels += 1;
+            }
+        }
+        let mut connections: Vec<_> = connections.into_values().collect();
+        connections.sort_by_key(|c| (c.from, c.to));
+
+        ExecutionPlan {
+            blocks,
+            connections,
+        }
     }
 
     fn log_topology(&self) {
@@ -318,10 +633,12 @@ impl Scheduler {
     where
         OperatorChain: Operator,
     {
-        match &self.config {
+        let mut info = match &self.config {
             RuntimeConfig::Local(local) => self.local_block_info(block, local),
             RuntimeConfig::Remote(remote) => self.remote_block_info(block, remote),
-        }
+        };
+        info.structure = block.operators.structure();
+        info
     }
 
     /// Extract the `SchedulerBlockInfo` of a block that runs only locally.
@@ -352,10 +669,12 @@ impl Scheduler {
         let global_ids = (0..instances).map(|r| (Coord::new(block.id, host_id, r), r));
         SchedulerBlockInfo {
             repr: block.to_string(),
+            structure: Default::default(),
             replicas: vec![(host_id, replicas.collect())].into_iter().collect(),
             global_ids: global_ids.into_iter().collect(),
             batch_mode: block.batch_mode,
             is_only_one_strategy: block.is_only_one_strategy,
+            priority: block.priority,
         }
     }
 
@@ -422,10 +741,12 @@ impl Scheduler {
 
         SchedulerBlockInfo {
             repr: block.to_string(),
+            structure: Default::default(),
             replicas,
             global_ids,
             batch_mode: block.batch_mode,
             is_only_one_strategy: block.is_only_one_strategy,
+            priority: block.priority,
         }
     }
 }
@@ -437,6 +758,31 @@ impl SchedulerBlockInfo {
     }
 }
 
+/// Warn if the same uid has been given to operators of different blocks: the uids are meant to
+/// identify the operators across different versions of the job, so they should be unique.
+fn check_unique_uids(block_structures: &[(Coord, BlockStructure)]) {
+    let mut uids: HashMap<&str, (BlockId, usize)> = HashMap::new();
+    for (coord, structure) in block_structures {
+        for (index, operator) in structure.operators.iter().enumerate() {
+            let Some(uid) = operator.uid.as_deref() else {
+                continue;
+            };
+            let position = (coord.block_id, index);
+            match uids.get(uid) {
+                Some(&other) if other != position => {
+                    warn!(
+                        "operator uid {uid:?} is used by block {} and block {}",
+                        other.0, position.0
+                    );
+                }
+                _ => {
+                    uids.insert(uid, position);
+                }
+            }
+        }
+    }
+}
+
 #[cfg(not(feature = "tokio"))]
 #[cfg(test)]
 mod tests {
@@ -461,4 +807,63 @@ mod tests {
         let _stream = env.stream(source).shuffle();
         env.execute_blocking();
     }
+
+    #[test]
+    #[cfg(feature = "timestamp")]
+    #[should_panic(expected = "needs timestamped elements")]
+    fn test_scheduler_panic_on_interval_join_without_timestamps() {
+        let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        let left = env.stream(IteratorSource::new(0..10i64));
+        let right = env
+            .stream(IteratorSource::new(0..10i64))
+            .add_timestamps(|&x| x, |_, _| None);
+        left.interval_join(right, 0, 1).collect_vec();
+        env.execute_blocking();
+    }
+
+    #[test]
+    #[should_panic(expected = "never terminates")]
+    fn test_scheduler_panic_on_unbounded_collect() {
+        let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+        let source = IteratorSource::new(0..);
+        env.stream(source).shuffle().map(|x| x + 1).collect_vec();
+        env.execute_blocking();
+    }
+
+    #[test]
+    fn test_scheduler_plan_remote() {
+        let hosts = (0..2)
+            .map(|i| crate::config::HostConfig {
+                address: "127.0.0.1".into(),
+                base_port: 9500 + i * 100,
+                num_cores: 3,
+                ssh: Default::default(),
+                perf_path: None,
+            })
+            .collect::<Vec<_>>();
+        let config = crate::config::ConfigBuilder::new_remote()
+            .add_hosts(&hosts)
+            .host_id(0)
+            .build()
+            .unwrap();
+        let env = StreamContext::new(config);
+        env.stream(IteratorSource::new(0..100u64))
+            .group_by(|n| n % 10)
+            .fold(0, |acc, n| *acc += n)
+            .collect_vec();
+
+        let plan = env.plan();
+        assert_eq!(plan.blocks.len(), 3);
+        // the source has a single replica, the fold one per core
+        assert_eq!(plan.blocks[0].replicas, vec![1, 0]);
+        assert_eq!(plan.blocks[1].replicas, vec![3, 3]);
+        assert_eq!(plan.blocks[2].replicas, vec![1, 0]);
+        assert_eq!(plan.connections.len(), 2);
+        assert_eq!(plan.connections[0].local_channels, 3);
+        assert_eq!(plan.connections[0].remote_channels, 3);
+        assert_eq!(plan.connections[1].local_channels, 3);
+        assert_eq!(plan.connections[1].remote_channels, 3);
+        assert_eq!(plan.blocks[1].state_size(10, 0), 160);
+        assert_eq!(plan.blocks[0].state_size(10, 0), 0);
+    }
 }
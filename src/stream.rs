@@ -125,6 +125,12 @@ where
         Stream::new(self.ctx, self.block.add_operator(get_operator))
     }
 
+    /// Whether the elements of the stream are timestamped, `None` if it cannot be known.
+    pub(crate) fn is_timestamped(&self) -> Option<bool> {
+        let structure = self.block.operators.structure();
+        self.ctx.lock().scheduler_mut().is_timestamped(&structure)
+    }
+
     /// Add a new block to the stream, closing and registering the previous one. The new block is
     /// connected to the previous one.
     ///
@@ -268,6 +274,66 @@ where
         Stream::new(ctx, new_block)
     }
 
+    /// Similar to `.binary_connection`, but with any number of incoming blocks of the same type.
+    ///
+    /// All the incoming blocks are closed and a single new block is created, whose `Start`
+    /// receives from all of them without wrapping the elements.
+    ///
+    /// This won't add any network shuffle, hence the next strategy will be `OnlyOne`. For this
+    /// reason all the input streams must have the same parallelism and must be inside the same
+    /// iteration, otherwise this function panics.
+    pub(crate) fn union_connection(self, others: Vec<Self>) -> Stream<impl Operator<Out = Op::Out>>
+    where
+        Op: 'static,
+        Op::Out: ExchangeData,
+    {
+        let Stream { block, ctx } = self;
+
+        let batch_mode = block.batch_mode;
+        let scheduling = block.scheduling.clone();
+        let iteration_ctx = block.iteration_ctx.clone();
+        let blocks: Vec<_> = std::iter::once(block)
+            .chain(others.into_iter().map(|s| s.block))
+            .collect();
+        for b in &blocks[1..] {
+            if b.scheduling.replication != scheduling.replication {
+                panic!(
+                    "The parallelism of the blocks coming inside a union must be equal. \
+                    {} is {:?}, {} is {:?}",
+                    blocks[0], scheduling.replication, b, b.scheduling.replication
+                );
+            }
+            if b.iteration_ctx() != blocks[0].iteration_ctx() {
+                panic!("The blocks coming inside a union must be inside the same iteration");
+            }
+        }
+
+        // close previous blocks
+        let mut env_lock = ctx.lock();
+        let ids: Vec<_> = blocks
+            .into_iter()
+            .map(|b| {
+                let mut b =
+                    b.add_operator(|prev| End::new(prev, NextStrategy::only_one(), batch_mode));
+                b.is_only_one_strategy = true;
+                env_lock.close_block(b)
+            })
+            .collect();
+
+        let source = Start::union(ids.clone(), iteration_ctx.last().cloned());
+        let mut new_block = env_lock.new_block(source, batch_mode, iteration_ctx);
+        for &id in &ids {
+            env_lock.connect_blocks::<Op::Out>(id, new_block.id);
+        }
+
+        drop(env_lock);
+
+        // make sure the new block has the same parallelism of the previous ones
+        new_block.scheduling = scheduling;
+
+        Stream::new(ctx, new_block)
+    }
+
     /// Clone the given block, taking care of connecting the new block to the same previous blocks
     /// of the original one.
     pub(crate) fn clone(&mut self) -> Self {
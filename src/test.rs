@@ -115,6 +115,8 @@ impl<T: ExchangeData> FakeNetworkTopology<T> {
             prev: self.prev.clone(),
             network: &mut self.topology,
             batch_mode: BatchMode::adaptive(100, Duration::from_millis(100)),
+            prev_priorities: Default::default(),
+            quota: Default::default(),
         }
     }
 
@@ -0,0 +1,277 @@
+//! Utilities for writing end-to-end tests of pipelines against golden files.
+//!
+//! A test reads its input from a fixture with [`load_fixture`], runs the pipeline in a local
+//! multi-replica environment with [`run_local`], and compares the output with a golden file using
+//! [`assert_golden`] or [`assert_golden_by_key`].
+//!
+//! Fixtures and golden files are in the JSON Lines format: one JSON value per line. Since the order
+//! of the output of a parallel pipeline is not deterministic, the output is compared ignoring the
+//! order of the items. When the output does not match, the assertion panics with the items that
+//! are missing from the output and the ones that are not expected.
+//!
+//! Setting the environment variable [`UPDATE_GOLDEN_ENV_VAR`] writes the output to the golden
+//! files instead of comparing it, for creating them or accepting a change of the output.
+//!
+//! This module is available only with the `testing` feature, usually enabled just for the tests
+//! of the crate using renoir:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! renoir = { version = "*", features = ["testing"] }
+//! ```
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # use renoir::testing::{assert_golden_by_key, load_fixture, run_local};
+//! let words: Vec<String> = load_fixture("tests/fixtures/words.jsonl");
+//! let counts = run_local(words, 4, |s| {
+//!     s.group_by_count(|w| w.clone()).collect_vec()
+//! });
+//! assert_golden_by_key("tests/golden/word_count.jsonl", &counts, |(word, _)| word.clone());
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::operator::sink::StreamOutput;
+use crate::operator::source::IteratorSource;
+use crate::{CoordUInt, RuntimeConfig, Stream, StreamContext};
+
+/// Environment variable that, when set, makes the assertions write the golden files instead of
+/// checking them.
+pub const UPDATE_GOLDEN_ENV_VAR: &str = "RENOIR_UPDATE_GOLDEN";
+
+/// Maximum number of different items shown when the output does not match.
+const MAX_SHOWN_ITEMS: usize = 20;
+
+/// The stream of the items of the input passed to [`run_local`].
+pub type FixtureStream<T> = Stream<IteratorSource<std::vec::IntoIter<T>>>;
+
+/// Read the items of a fixture in the JSON Lines format, skipping the empty lines.
+pub fn load_fixture<T: DeserializeOwned>(path: impl AsRef<Path>) -> Vec<T> {
+    let path = path.as_ref();
+    read_lines(path)
+        .unwrap_or_else(|err| panic!("cannot read fixture {path:?}: {err:?}"))
+        .into_iter()
+        .enumerate()
+        .map(|(i, line)| {
+            serde_json::from_str(&line).unwrap_or_else(|err| {
+                panic!("invalid item at line {} of fixture {path:?}: {err}", i + 1)
+            })
+        })
+        .collect()
+}
+
+/// Run a pipeline on the items of `input` in a local environment with `parallelism` replicas,
+/// returning its output.
+///
+/// `pipeline` builds the job from the stream of the input, and returns the output of the job,
+/// e.g. with [`Stream::collect_vec`].
+pub fn run_local<In, Out, F>(input: Vec<In>, parallelism: CoordUInt, pipeline: F) -> Vec<Out>
+where
+    In: Send + 'static,
+    F: FnOnce(FixtureStream<In>) -> StreamOutput<Vec<Out>>,
+{
+    let config = RuntimeConfig::local(parallelism).expect("invalid parallelism");
+    let env = StreamContext::new(config);
+    let output = pipeline(env.stream_iter(input.into_iter()));
+    env.execute_blocking();
+    output
+        .get()
+        .expect("the pipeline did not produce its output")
+}
+
+/// Check that `actual` contains the same items of the golden file at `path`, in any order.
+///
+/// The items are compared by their JSON serialization.
+pub fn assert_golden<T: Serialize>(path: impl AsRef<Path>, actual: &[T]) {
+    let path = path.as_ref();
+    let actual: Vec<_> = actual.iter().map(to_line).collect();
+    compare_golden(path, actual, |_| Some(String::new()));
+}
+
+/// Check that `actual` contains the same items of the golden file at `path`, in any order, showing
+/// the differences grouped by the key returned by `keyer`.
+///
+/// The items are compared by their JSON serialization.
+pub fn assert_golden_by_key<T, K, F>(path: impl AsRef<Path>, actual: &[T], keyer: F)
+where
+    T: Serialize + DeserializeOwned,
+    K: Serialize,
+    F: Fn(&T) -> K,
+{
+    let path = path.as_ref();
+    let mut keys = BTreeMap::new();
+    let actual = actual
+        .iter()
+        .map(|item| {
+            let line = to_line(item);
+            keys.insert(line.clone(), to_line(&keyer(item)));
+            line
+        })
+        .collect();
+    compare_golden(path, actual, |line| {
+        if let Some(key) = keys.get(line) {
+            return Some(key.clone());
+        }
+        let item: T = serde_json::from_str(line).ok()?;
+        Some(to_line(&keyer(&item)))
+    });
+}
+
+/// Compare the serialized items with the golden file, or update it.
+fn compare_golden(path: &Path, mut actual: Vec<String>, key: impl Fn(&str) -> Option<String>) {
+    actual.sort_unstable();
+    if std::env::var_os(UPDATE_GOLDEN_ENV_VAR).is_some() {
+        write_golden(path, &actual);
+        return;
+    }
+
+    let golden: Vec<serde_json::Value> = load_fixture(path);
+    let mut golden: Vec<_> = golden.iter().map(to_line).collect();
+    golden.sort_unstable();
+    if golden == actual {
+        return;
+    }
+
+    let diff = diff_lines(&golden, &actual, |line| key(line).unwrap_or_default());
+    panic!(
+        "the output does not match the golden file {path:?} (set {UPDATE_GOLDEN_ENV_VAR} to \
+         update it):\n{diff}"
+    );
+}
+
+/// Describe the items missing from `actual` and the unexpected ones, grouped by `key`.
+fn diff_lines(golden: &[String], actual: &[String], key: impl Fn(&str) -> String) -> String {
+    let mut groups: BTreeMap<String, (Vec<&str>, Vec<&str>)> = BTreeMap::new();
+    let (mut i, mut j) = (0, 0);
+    while i < golden.len() || j < actual.len() {
+        // both are sorted: walk them together like in a merge
+        match (golden.get(i), actual.get(j)) {
+            (Some(g), Some(a)) if g == a => {
+                i += 1;
+                j += 1;
+            }
+            (Some(g), Some(a)) if g > a => {
+                groups.entry(key(a)).or_default().1.push(a);
+                j += 1;
+            }
+            (None, Some(a)) => {
+                groups.entry(key(a)).or_default().1.push(a);
+                j += 1;
+            }
+            (Some(g), _) => {
+                groups.entry(key(g)).or_default().0.push(g);
+                i += 1;
+            }
+            (None, None) => unreachable!(),
+        }
+    }
+
+    let missing: usize = groups.values().map(|(m, _)| m.len()).sum();
+    let unexpected: usize = groups.values().map(|(_, u)| u.len()).sum();
+    let mut diff = format!("{missing} missing items (-), {unexpected} unexpected items (+)\n");
+    let mut shown = 0;
+    for (key, (missing, unexpected)) in &groups {
+        if shown >= MAX_SHOWN_ITEMS {
+            let _ = writeln!(diff, "...");
+            break;
+        }
+        let indent = if key.is_empty() {
+            ""
+        } else {
+            let _ = writeln!(diff, "key {key}:");
+            "  "
+        };
+        let lines = missing
+            .iter()
+            .map(|l| ('-', l))
+            .chain(unexpected.iter().map(|l| ('+', l)));
+        for (sign, line) in lines.take(MAX_SHOWN_ITEMS - shown) {
+            let _ = writeln!(diff, "{indent}{sign} {line}");
+            shown += 1;
+        }
+    }
+    diff
+}
+
+/// Serialize an item to JSON, with the fields of the objects sorted like in a parsed line.
+fn to_line<T: Serialize>(item: &T) -> String {
+    serde_json::to_value(item)
+        .expect("cannot serialize the item to JSON")
+        .to_string()
+}
+
+fn read_lines(path: &Path) -> std::io::Result<Vec<String>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut lines = vec![];
+    for line in reader.lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            lines.push(line);
+        }
+    }
+    Ok(lines)
+}
+
+fn write_golden(path: &Path, lines: &[String]) {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .unwrap_or_else(|err| panic!("cannot create directory {dir:?}: {err:?}"));
+    }
+    let mut content = lines.join("\n");
+    content.push('\n');
+    std::fs::write(path, content)
+        .unwrap_or_else(|err| panic!("cannot write golden file {path:?}: {err:?}"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{assert_golden, assert_golden_by_key, diff_lines, load_fixture, run_local};
+
+    #[test]
+    fn golden_word_count() {
+        let dir = tempfile::tempdir().unwrap();
+        let fixture = dir.path().join("words.jsonl");
+        std::fs::write(&fixture, "\"a\"\n\"b\"\n\n\"a\"\n").unwrap();
+        let golden = dir.path().join("count.jsonl");
+        std::fs::write(&golden, "[\"b\",1]\n[\"a\",2]\n").unwrap();
+
+        let words: Vec<String> = load_fixture(&fixture);
+        let counts = run_local(words, 4, |s| s.group_by_count(|w| w.clone()).collect_vec());
+        assert_golden(&golden, &counts);
+        assert_golden_by_key(&golden, &counts, |(word, _)| word.clone());
+    }
+
+    #[test]
+    #[should_panic(expected = "1 missing items (-), 1 unexpected items (+)")]
+    fn golden_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let golden = dir.path().join("count.jsonl");
+        std::fs::write(&golden, "[\"b\",1]\n[\"a\",2]\n").unwrap();
+        assert_golden_by_key(&golden, &[("a".to_string(), 3u64), ("b".into(), 1)], |p| {
+            p.0.clone()
+        });
+    }
+
+    #[test]
+    fn golden_diff_by_key() {
+        let golden = ["[\"a\",1]", "[\"b\",1]", "[\"c\",1]"].map(String::from);
+        let actual = ["[\"a\",1]", "[\"b\",2]", "[\"d\",1]"].map(String::from);
+        let diff = diff_lines(&golden, &actual, |line| line[1..4].to_string());
+        assert_eq!(
+            diff,
+            "2 missing items (-), 2 unexpected items (+)\n\
+             key \"b\":\n  - [\"b\",1]\n  + [\"b\",2]\n\
+             key \"c\":\n  - [\"c\",1]\n\
+             key \"d\":\n  + [\"d\",1]\n"
+        );
+    }
+}
@@ -54,10 +54,15 @@ impl<F: FnOnce()> Drop for CatchPanic<F> {
     }
 }
 
-pub(crate) fn spawn_worker<OperatorChain>(
+/// Function that starts the thread of a worker whose block has already been set up.
+pub(crate) type SpawnWorkerFn = Box<dyn FnOnce() -> JoinHandle<()> + Send>;
+
+/// Setup the operators of the block, returning its structure and the function for starting the
+/// worker thread.
+pub(crate) fn setup_worker<OperatorChain>(
     mut block: Block<OperatorChain>,
     metadata: &mut ExecutionMetadata,
-) -> (JoinHandle<()>, BlockStructure)
+) -> (BlockStructure, SpawnWorkerFn)
 where
     OperatorChain: Operator + 'static,
     OperatorChain::Out: Send,
@@ -69,16 +74,18 @@ where
     block.operators.setup(metadata);
     let structure = block.operators.structure();
 
-    let join_handle = std::thread::Builder::new()
-        .name(format!("block-{}", block.id))
-        .spawn(move || {
-            // remember in the thread-local the coordinate of this block
-            COORD.with(|x| *x.borrow_mut() = Some(coord));
-            do_work(block, coord)
-        })
-        .unwrap();
+    let spawn = move || {
+        std::thread::Builder::new()
+            .name(format!("block-{}.{}", block.id, coord.replica_id))
+            .spawn(move || {
+                // remember in the thread-local the coordinate of this block
+                COORD.with(|x| *x.borrow_mut() = Some(coord));
+                do_work(block, coord)
+            })
+            .unwrap()
+    };
 
-    (join_handle, structure)
+    (structure, Box::new(spawn))
 }
 
 fn do_work<Op: Operator>(mut block: Block<Op>, coord: Coord) {
@@ -0,0 +1,40 @@
+use renoir::operator::source::IteratorSource;
+use utils::TestHelper;
+
+mod utils;
+
+#[test]
+fn asof_join_keyed_stream() {
+    TestHelper::local_remote_env(|env| {
+        // the right side has an element every 3 time units, the left side every time unit
+        let source = IteratorSource::new(0..30i64);
+        let source2 = IteratorSource::new((0..30i64).filter(|x| x % 3 == 0));
+
+        let right = env
+            .stream(source2)
+            .add_timestamps(|&x| x, |&x, &ts| if x % 2 == 0 { Some(ts) } else { None })
+            .group_by(|x| x % 2);
+        let res = env
+            .stream(source)
+            .add_timestamps(|&x| x, |&x, &ts| if x % 5 == 0 { Some(ts) } else { None })
+            .group_by(|x| x % 2)
+            .asof_join(right, 4)
+            .collect_vec();
+
+        env.execute_blocking();
+
+        if let Some(mut res) = res.get() {
+            let mut expected = Vec::new();
+            for l in 0..30i64 {
+                let r = (0..=l)
+                    .rev()
+                    .find(|r| r % 3 == 0 && r % 2 == l % 2)
+                    .filter(|r| l - r <= 4);
+                expected.push((l % 2, (l, r)));
+            }
+            expected.sort_unstable();
+            res.sort_unstable();
+            assert_eq!(res, expected);
+        }
+    });
+}
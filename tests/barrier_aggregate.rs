@@ -0,0 +1,69 @@
+use itertools::Itertools;
+
+use renoir::operator::source::IteratorSource;
+use utils::TestHelper;
+
+mod utils;
+
+#[test]
+fn barrier_aggregate_shuffle() {
+    TestHelper::local_remote_env(|env| {
+        let source = IteratorSource::new(0..100u64);
+        let res = env
+            .stream(source)
+            .shuffle()
+            .barrier_aggregate(0, |count, _| *count += 1, |count, c| *count += c)
+            .collect_vec();
+        env.execute_blocking();
+        if let Some(res) = res.get() {
+            let res = res.into_iter().sorted().collect_vec();
+            assert_eq!(res, (0..100).map(|n| (n, 100)).collect_vec());
+        }
+    });
+}
+
+#[test]
+fn barrier_aggregate_empty_replicas() {
+    TestHelper::local_remote_env(|env| {
+        // all the elements end up in a single replica, the others have nothing to contribute
+        let source = IteratorSource::new(0..10u64);
+        let res = env
+            .stream(source)
+            .group_by(|_| 0)
+            .drop_key()
+            .barrier_aggregate(0, |sum, n| *sum += n, |sum, s| *sum += s)
+            .collect_vec();
+        env.execute_blocking();
+        if let Some(res) = res.get() {
+            let res = res.into_iter().sorted().collect_vec();
+            assert_eq!(res, (0..10).map(|n| (n, 45)).collect_vec());
+        }
+    });
+}
+
+#[test]
+fn barrier_aggregate_iteration() {
+    TestHelper::local_remote_env(|env| {
+        let source = IteratorSource::new(0..10u64);
+        // every iteration the elements are replaced with the maximum of the previous one
+        let (state, res) = env.stream(source).shuffle().iterate(
+            3,
+            0u64,
+            |s, _| {
+                s.barrier_aggregate(0, |max, &n| *max = n.max(*max), |max, m| *max = m.max(*max))
+                    .map(|(n, max)| n + max)
+            },
+            |_: &mut (), _| {},
+            |_, _| {},
+            |_| true,
+        );
+        state.for_each(std::mem::drop);
+        let res = res.collect_vec();
+        env.execute_blocking();
+        if let Some(res) = res.get() {
+            let res = res.into_iter().sorted().collect_vec();
+            // 9, 18 + 9, 36 + 18 + 9
+            assert_eq!(res, (0..10).map(|n| n + 63).collect_vec());
+        }
+    });
+}
@@ -0,0 +1,62 @@
+use itertools::Itertools;
+use renoir::operator::source::IteratorSource;
+use utils::TestHelper;
+
+mod utils;
+
+#[test]
+fn cross_broadcast_other() {
+    TestHelper::local_remote_env(|env| {
+        let big = env.stream(IteratorSource::new(0..100u32)).shuffle();
+        let small = env.stream(IteratorSource::new(0..3u32));
+        let res = big.cross(small).collect_vec();
+        env.execute_blocking();
+
+        if let Some(res) = res.get() {
+            let res = res.into_iter().sorted().collect_vec();
+            let expected = (0..100).cartesian_product(0..3).collect_vec();
+            assert_eq!(res, expected);
+        }
+    });
+}
+
+#[test]
+fn cross_broadcast_self() {
+    TestHelper::local_remote_env(|env| {
+        let small = env.stream(IteratorSource::new(0..3u32));
+        let big = env.stream(IteratorSource::new(0..100u32)).shuffle();
+        let res = small
+            .cross_filter(big, |a, b| (a + b) % 2 == 0)
+            .collect_vec();
+        env.execute_blocking();
+
+        if let Some(res) = res.get() {
+            let res = res.into_iter().sorted().collect_vec();
+            let expected = (0..3)
+                .cartesian_product(0..100)
+                .filter(|(a, b)| (a + b) % 2 == 0)
+                .collect_vec();
+            assert_eq!(res, expected);
+        }
+    });
+}
+
+#[test]
+fn cross_map_combiner() {
+    TestHelper::local_remote_env(|env| {
+        let big = env.stream(IteratorSource::new(0..100u32)).shuffle();
+        let small = env.stream(IteratorSource::new(0..5u32));
+        let res = big.cross_map(small, |a, b| a * 10 + b).collect_vec();
+        env.execute_blocking();
+
+        if let Some(res) = res.get() {
+            let res = res.into_iter().sorted().collect_vec();
+            let expected = (0..100)
+                .cartesian_product(0..5)
+                .map(|(a, b)| a * 10 + b)
+                .sorted()
+                .collect_vec();
+            assert_eq!(res, expected);
+        }
+    });
+}
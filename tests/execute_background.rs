@@ -0,0 +1,56 @@
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake};
+use std::thread::{self, Thread};
+
+use renoir::operator::source::IteratorSource;
+use renoir::{RuntimeConfig, StreamContext};
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Minimal executor driving a future on the current thread.
+fn block_on<F: Future>(fut: F) -> F::Output {
+    let mut fut = pin!(fut);
+    let waker = Arc::new(ThreadWaker(thread::current())).into();
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match fut.as_mut().poll(&mut cx) {
+            Poll::Ready(res) => return res,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+#[test]
+fn execute_background_await() {
+    let env = StreamContext::new(RuntimeConfig::local(4).unwrap());
+    let res = env
+        .stream(IteratorSource::new(0..1000u64))
+        .shuffle()
+        .map(|n| n * 2)
+        .collect_count();
+
+    let handle = env.execute_background();
+    block_on(handle);
+
+    assert_eq!(res.get(), Some(1000));
+}
+
+#[test]
+#[should_panic]
+fn execute_background_panic() {
+    let env = StreamContext::new(RuntimeConfig::local(1).unwrap());
+    env.stream(IteratorSource::new(0..10u64))
+        .map(|n| if n == 5 { panic!("boom") } else { n })
+        .for_each(drop);
+
+    let handle = env.execute_background();
+    block_on(handle);
+}
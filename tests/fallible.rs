@@ -0,0 +1,42 @@
+use renoir::operator::source::IteratorSource;
+use utils::TestHelper;
+
+mod utils;
+
+#[test]
+fn flatten_option_stream() {
+    TestHelper::local_remote_env(|env| {
+        let source = IteratorSource::new(0..10u8);
+        let res = env
+            .stream(source)
+            .map(|x| if x % 2 == 1 { Some(x * 2 + 1) } else { None })
+            .flatten_option()
+            .collect_vec();
+        env.execute_blocking();
+        if let Some(res) = res.get() {
+            assert_eq!(res, &[3, 7, 11, 15, 19]);
+        }
+    });
+}
+
+#[test]
+fn ok_or_log_stream() {
+    TestHelper::local_remote_env(|env| {
+        let source = IteratorSource::new(0..10u8);
+        let res = env
+            .stream(source)
+            .map(|x| {
+                if x < 5 {
+                    Ok(x)
+                } else {
+                    Err(format!("{x} is too big"))
+                }
+            })
+            .ok_or_log()
+            .collect_vec();
+        env.execute_blocking();
+        if let Some(res) = res.get() {
+            assert_eq!(res, &[0, 1, 2, 3, 4]);
+        }
+    });
+}
@@ -0,0 +1,40 @@
+use renoir::operator::source::IteratorSource;
+use utils::TestHelper;
+
+mod utils;
+
+#[test]
+fn filter_in_set() {
+    TestHelper::local_remote_env(|env| {
+        let source = IteratorSource::new(0..1000u64);
+        let keys = IteratorSource::new((0..100u64).map(|x| x * 7));
+        let res = env
+            .stream(source)
+            .shuffle()
+            .filter_in_set(env.stream(keys).shuffle(), |x| *x, 100, 1e-6)
+            .collect_vec();
+        env.execute_blocking();
+
+        if let Some(mut res) = res.get() {
+            res.sort_unstable();
+            assert_eq!(res, (0..100).map(|x| x * 7).collect::<Vec<_>>());
+        }
+    });
+}
+
+#[test]
+fn filter_in_set_no_keys() {
+    TestHelper::local_remote_env(|env| {
+        let source = IteratorSource::new(0..100u64);
+        let keys = IteratorSource::new(std::iter::empty::<u64>());
+        let res = env
+            .stream(source)
+            .filter_in_set(env.stream(keys), |x| *x, 10, 0.01)
+            .collect_vec();
+        env.execute_blocking();
+
+        if let Some(res) = res.get() {
+            assert!(res.is_empty());
+        }
+    });
+}
@@ -0,0 +1,73 @@
+use renoir::operator::graph::Graph;
+use utils::TestHelper;
+
+mod utils;
+
+/// Two components: a path `0 - 1 - ... - 9` listed backwards and a triangle `20 - 21 - 22` with a
+/// tail `22 - 23`.
+fn edges() -> Vec<(u64, u64)> {
+    let mut edges: Vec<_> = (1..10).rev().map(|x| (x, x - 1)).collect();
+    edges.extend([(20, 21), (21, 22), (22, 20), (22, 23)]);
+    edges
+}
+
+#[test]
+fn graph_connected_components() {
+    TestHelper::local_remote_env(|env| {
+        let edges = env.stream_iter(edges().into_iter()).shuffle();
+        let res = Graph::from_edges(edges)
+            .connected_components()
+            .collect_vec();
+        env.execute_blocking();
+
+        if let Some(mut res) = res.get() {
+            res.sort_unstable();
+            let mut expected: Vec<_> = (0..10).map(|x| (x, 0)).collect();
+            expected.extend((20..24).map(|x| (x, 20)));
+            assert_eq!(res, expected);
+        }
+    });
+}
+
+#[test]
+fn graph_page_rank() {
+    TestHelper::local_remote_env(|env| {
+        // a star pointing to 0, and 0 pointing back to all the others
+        let edges: Vec<(u64, u64)> = (1..5).flat_map(|x| [(x, 0), (0, x)]).collect();
+        let edges = env.stream_iter(edges.into_iter()).shuffle();
+        let res = Graph::from_edges(edges).page_rank(60).collect_vec();
+        env.execute_blocking();
+
+        if let Some(mut res) = res.get() {
+            res.sort_unstable_by_key(|(x, _)| *x);
+            assert_eq!(res.len(), 5);
+            let total: f64 = res.iter().map(|(_, r)| r).sum();
+            assert!((total - 5.0).abs() < 1e-6);
+            // the fixed point: r0 = 0.15 + 0.85 * 4 * r, r = 0.15 + 0.85 * r0 / 4
+            let r = 0.15 * (1.0 + 0.85 / 4.0) / (1.0 - 0.85 * 0.85);
+            let r0 = 0.15 + 0.85 * 4.0 * r;
+            assert!((res[0].1 - r0).abs() < 1e-3);
+            for (_, rank) in &res[1..] {
+                assert!((rank - r).abs() < 1e-3);
+            }
+        }
+    });
+}
+
+#[test]
+fn graph_triangle_count() {
+    TestHelper::local_remote_env(|env| {
+        let mut edges = edges();
+        // a complete graph on 4 vertices has 4 triangles
+        edges.extend([(30, 31), (30, 32), (30, 33), (31, 32), (31, 33), (32, 33)]);
+        // repeated edges and self loops are ignored
+        edges.extend([(21, 20), (5, 5)]);
+        let edges = env.stream_iter(edges.into_iter()).shuffle();
+        let res = Graph::from_edges(edges).triangle_count().collect_vec();
+        env.execute_blocking();
+
+        if let Some(res) = res.get() {
+            assert_eq!(res, vec![5]);
+        }
+    });
+}
@@ -24,3 +24,28 @@ fn group_by_stream() {
         }
     });
 }
+
+#[test]
+fn group_by_re_key() {
+    TestHelper::local_remote_env(|env| {
+        let source = IteratorSource::new(0..100u8);
+        let res = env
+            .stream(source)
+            .group_by(|&n| n % 10)
+            .re_key(|&k, &n| (k % 2, n / 50))
+            .fold(0u32, |acc, n| *acc += n as u32)
+            .collect_vec();
+        env.execute_blocking();
+        if let Some(res) = res.get() {
+            let res = res.into_iter().sorted().collect_vec();
+            let expected = (0..100u8)
+                .map(|n| ((n % 2, n / 50), n as u32))
+                .into_group_map()
+                .into_iter()
+                .map(|(k, v)| (k, v.into_iter().sum::<u32>()))
+                .sorted()
+                .collect_vec();
+            assert_eq!(res, expected);
+        }
+    });
+}
@@ -236,6 +236,53 @@ fn join_hash_sort_merge_outer2() {
     });
 }
 
+#[test]
+fn keyed_join() {
+    TestHelper::local_remote_env(|env| {
+        let s1 = env.stream_iter(0..100u32).group_by(|n| n % 7);
+        let s2 = env.stream_iter(0..10u32).group_by(|n| n % 5);
+        let res = s1.join(s2).unkey().collect_vec();
+        env.execute_blocking();
+
+        if let Some(mut res) = res.get() {
+            let mut expected = vec![];
+            for a in 0..100 {
+                for b in 0..10 {
+                    if a % 7 == b % 5 {
+                        expected.push((a % 7, (a, b)));
+                    }
+                }
+            }
+            res.sort_unstable();
+            expected.sort_unstable();
+            assert_eq!(res, expected);
+        }
+    });
+}
+
+#[test]
+fn keyed_join_outer() {
+    TestHelper::local_remote_env(|env| {
+        let s1 = env.stream_iter(0..20u32).group_by(|n| n % 7);
+        let s2 = env.stream_iter(0..10u32).group_by(|n| n % 10);
+        let res = s1.join_outer(s2).unkey().collect_vec();
+        env.execute_blocking();
+
+        if let Some(mut res) = res.get() {
+            let mut expected = vec![];
+            for a in 0..20 {
+                expected.push((a % 7, (Some(a), Some(a % 7))));
+            }
+            for b in 7..10 {
+                expected.push((b, (None, Some(b))));
+            }
+            res.sort_unstable();
+            expected.sort_unstable();
+            assert_eq!(res, expected);
+        }
+    });
+}
+
 #[test]
 fn self_join() {
     TestHelper::local_remote_env(|env| {
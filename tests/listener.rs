@@ -0,0 +1,60 @@
+use std::sync::{Arc, Mutex};
+
+use renoir::operator::source::IteratorSource;
+use renoir::structure::{BlockStructure, ExecutionPlan};
+use renoir::{ExecutionListener, TracingData};
+use utils::TestHelper;
+
+mod utils;
+
+#[derive(Default)]
+struct Events {
+    started: usize,
+    blocks: usize,
+    scheduled: usize,
+    completed: Vec<usize>,
+}
+
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Events>>);
+
+impl ExecutionListener for Recorder {
+    fn on_job_start(&self, plan: &ExecutionPlan) {
+        let mut events = self.0.lock().unwrap();
+        events.started += 1;
+        events.blocks = plan.blocks.len();
+    }
+
+    fn on_block_scheduled(&self, _block_id: u64, _global_id: u64, _structure: &BlockStructure) {
+        self.0.lock().unwrap().scheduled += 1;
+    }
+
+    fn on_job_completed(&self, tracing: &TracingData) {
+        let mut events = self.0.lock().unwrap();
+        events.completed.push(tracing.structures.len());
+    }
+}
+
+#[test]
+fn execution_listener() {
+    TestHelper::local_remote_env(|env| {
+        let recorder = Recorder::default();
+        env.add_listener(recorder.clone());
+        let source = IteratorSource::new(0..10u8);
+        let res = env
+            .stream(source)
+            .group_by_sum(|x| x % 2, |x| x as u64)
+            .collect_vec();
+        env.execute_blocking();
+        if let Some(mut res) = res.get() {
+            res.sort_unstable();
+            assert_eq!(res, &[(0, 20), (1, 25)]);
+        }
+
+        let events = recorder.0.lock().unwrap();
+        assert_eq!(events.started, 1);
+        assert_eq!(events.blocks, 3);
+        assert!(events.scheduled > 0);
+        assert_eq!(events.completed, vec![events.scheduled]);
+    });
+}
@@ -1,10 +1,9 @@
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use itertools::Itertools;
 
 use renoir::operator::source::IteratorSource;
-use renoir::Replication;
+use renoir::{BatchMode, Replication};
 use utils::{TestHelper, WatermarkChecker};
 
 mod utils;
@@ -89,8 +88,10 @@ fn merge_with_timestamps() {
         let source1 = IteratorSource::new(0..10u64);
         let source2 = IteratorSource::new(100..110u64);
 
+        // every message is sent alone, so no watermark is coalesced
         let stream1 = env
             .stream(source1)
+            .batch_mode(BatchMode::fixed(1))
             .add_timestamps(
                 |&x| x as i64,
                 |&x, &ts| if x % 2 == 1 { Some(ts) } else { None },
@@ -98,24 +99,111 @@ fn merge_with_timestamps() {
             .shuffle();
         let stream2 = env
             .stream(source2)
+            .batch_mode(BatchMode::fixed(1))
             .add_timestamps(
                 |&x| x as i64 % 10,
                 |&x, &ts| if x % 2 == 1 { Some(ts) } else { None },
             )
             .shuffle();
 
-        let num_watermarks = Arc::new(AtomicUsize::new(0));
+        let watermarks = Arc::new(Mutex::new(Vec::new()));
         let stream = stream1
             .merge(stream2)
             .shuffle()
             .replication(Replication::One)
-            .add_operator(|prev| WatermarkChecker::new(prev, num_watermarks.clone()));
+            .add_operator(|prev| WatermarkChecker::new(prev, watermarks.clone()));
         let res = stream.collect_vec();
 
         env.execute_blocking();
         if let Some(res) = res.get() {
             assert_eq!(res.len(), 20);
-            assert_eq!(num_watermarks.load(Ordering::Acquire), 5);
+            assert_eq!(*watermarks.lock().unwrap(), vec![1, 3, 5, 7, 9]);
+        }
+    });
+}
+
+#[test]
+fn merge_coalesces_watermarks() {
+    TestHelper::local_remote_env(|env| {
+        let streams = [0..10u64, 100..110u64].map(|range| {
+            // each source sends all its messages in a single batch, where the consecutive
+            // watermarks left by the filter are coalesced into the last one
+            env.stream(IteratorSource::new(range))
+                .batch_mode(BatchMode::fixed(1024))
+                .add_timestamps(|&x| x as i64 % 100, |_, &ts| Some(ts))
+                .filter(|x| x % 5 == 0)
+        });
+        let [stream1, stream2] = streams;
+
+        let watermarks = Arc::new(Mutex::new(Vec::new()));
+        let stream = stream1
+            .merge(stream2)
+            .replication(Replication::One)
+            .add_operator(|prev| WatermarkChecker::new(prev, watermarks.clone()));
+        let res = stream.collect_vec();
+
+        env.execute_blocking();
+        if let Some(res) = res.get() {
+            assert_eq!(res.len(), 4);
+            // without coalescing the frontier would advance through all the 10 timestamps
+            assert_eq!(*watermarks.lock().unwrap(), vec![4, 9]);
+        }
+    });
+}
+
+#[test]
+fn union_streams() {
+    TestHelper::local_remote_env(|env| {
+        let streams = (0..5u16)
+            .map(|i| {
+                // the last stream is empty
+                let end = if i == 4 { 4000 } else { (i + 1) * 1000 };
+                env.stream(IteratorSource::new(i * 1000..end))
+            })
+            .collect_vec();
+        let mut streams = streams.into_iter();
+        let first = streams.next().unwrap();
+
+        let res = first.union(streams.collect()).collect_vec();
+        env.execute_blocking();
+        if let Some(res) = res.get() {
+            let res_sorted = res.into_iter().sorted().collect_vec();
+            let expected = (0..4000u16).collect_vec();
+            assert_eq!(res_sorted, expected);
+        }
+    });
+}
+
+#[test]
+fn union_with_timestamps() {
+    TestHelper::local_remote_env(|env| {
+        let streams = (0..3u64)
+            .map(|i| {
+                // every message is sent alone, so no watermark is coalesced
+                env.stream(IteratorSource::new(i * 100..i * 100 + 10))
+                    .batch_mode(BatchMode::fixed(1))
+                    .add_timestamps(
+                        |&x| x as i64 % 100,
+                        |&x, &ts| if x % 2 == 1 { Some(ts) } else { None },
+                    )
+                    .shuffle()
+            })
+            .collect_vec();
+        let mut streams = streams.into_iter();
+        let first = streams.next().unwrap();
+
+        let watermarks = Arc::new(Mutex::new(Vec::new()));
+        let stream = first
+            .union(streams.collect())
+            .shuffle()
+            .replication(Replication::One)
+            .add_operator(|prev| WatermarkChecker::new(prev, watermarks.clone()));
+        let res = stream.collect_vec();
+
+        env.execute_blocking();
+        if let Some(res) = res.get() {
+            assert_eq!(res.len(), 30);
+            assert_eq!(*watermarks.lock().unwrap(), vec![1, 3, 5, 7, 9]);
         }
     });
 }
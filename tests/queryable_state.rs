@@ -0,0 +1,42 @@
+use std::sync::{Arc, Barrier, Mutex};
+
+use renoir::operator::source::IteratorSource;
+use renoir::operator::QueryableStateClient;
+use utils::TestHelper;
+
+mod utils;
+
+#[test]
+fn queryable_state_across_hosts() {
+    let num_hosts = 4;
+    let barrier = Arc::new(Barrier::new(num_hosts));
+    let addresses = Arc::new(Mutex::new(Vec::new()));
+    TestHelper::remote_env(
+        Arc::new(move |env| {
+            let source = IteratorSource::new(0..100u32);
+            let (s, state) = env
+                .stream(source)
+                .group_by(|n| n % 10)
+                .reduce(|a, b| *a += b)
+                .queryable_state_endpoint("127.0.0.1:0");
+            s.for_each(std::mem::drop);
+            addresses.lock().unwrap().push(state.local_addr().unwrap());
+            env.execute_blocking();
+
+            // every host queries the state of all the hosts, keeping its own endpoint open until
+            // all of them are done
+            barrier.wait();
+            let addresses = addresses.lock().unwrap().clone();
+            let mut client = QueryableStateClient::connect(addresses).unwrap();
+            for k in 0..10 {
+                let expected = (0..100).filter(|n| n % 10 == k).sum::<u32>();
+                assert_eq!(client.get(&k).unwrap(), Some(expected));
+            }
+            assert_eq!(client.get(&10).unwrap(), None);
+            barrier.wait();
+            drop(state);
+        }),
+        num_hosts as _,
+        2,
+    );
+}
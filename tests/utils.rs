@@ -3,9 +3,9 @@
 use std::fmt::Display;
 use std::marker::PhantomData;
 use std::str::FromStr;
-use std::sync::atomic::{AtomicU16, AtomicUsize, Ordering};
+use std::sync::atomic::AtomicU16;
 use std::sync::mpsc::RecvTimeoutError;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use itertools::{process_results, Itertools};
@@ -29,7 +29,7 @@ static TEST_INDEX: AtomicU16 = AtomicU16::new(0);
 /// A fake operator that makes sure the watermarks are consistent with the data.
 ///
 /// This will check that no data has a timestamp lower or equal than a previous watermark. This also
-/// keeps track of the number of watermarks received.
+/// records the watermarks received, in order.
 #[derive(Clone)]
 pub struct WatermarkChecker<Out: Data, PreviousOperator>
 where
@@ -37,7 +37,7 @@ where
 {
     last_watermark: Option<Timestamp>,
     prev: PreviousOperator,
-    received_watermarks: Arc<AtomicUsize>,
+    received_watermarks: Arc<Mutex<Vec<Timestamp>>>,
     _out: PhantomData<Out>,
 }
 
@@ -51,7 +51,7 @@ where
 }
 
 impl<Out: Data, PreviousOperator: Operator<Out = Out>> WatermarkChecker<Out, PreviousOperator> {
-    pub fn new(prev: PreviousOperator, received_watermarks: Arc<AtomicUsize>) -> Self {
+    pub fn new(prev: PreviousOperator, received_watermarks: Arc<Mutex<Vec<Timestamp>>>) -> Self {
         Self {
             last_watermark: None,
             prev,
@@ -83,7 +83,7 @@ impl<Out: Data, PreviousOperator: Operator<Out = Out>> Operator
                     assert!(ts > w);
                 }
                 self.last_watermark = Some(*ts);
-                self.received_watermarks.fetch_add(1, Ordering::Release);
+                self.received_watermarks.lock().unwrap().push(*ts);
             }
             _ => {}
         }
@@ -152,7 +152,7 @@ impl TestHelper {
         let mut hosts = vec![];
         for host_id in 0..num_hosts {
             let test_id: u16 = thread_rng().gen(); //TEST_INDEX.fetch_add(1, Ordering::SeqCst) + 1;
-           
+
             let high_part = (test_id & 0xff00) >> 8;
             let low_part = test_id & 0xff;
             let address = format!("127.{high_part}.{low_part}.{host_id}");
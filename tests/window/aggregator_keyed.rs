@@ -189,3 +189,63 @@ fn test_map_window_keyed() {
         }
     });
 }
+
+#[test]
+fn test_aggregate_and_join_back_window_keyed() {
+    TestHelper::local_remote_env(|env| {
+        let source = IteratorSource::new(0..10u8);
+        let res = env
+            .stream(source)
+            .group_by(|x| x % 2)
+            .window(CountWindow::tumbling(3))
+            .aggregate_and_join_back(0u8, |acc, &x| *acc = (*acc).max(x))
+            .collect_vec();
+        env.execute_blocking();
+        if let Some(mut res) = res.get() {
+            res.sort_unstable();
+            assert_eq!(
+                res,
+                vec![
+                    (0, (0, 4)), // [0, 2, 4]
+                    (0, (2, 4)),
+                    (0, (4, 4)),
+                    // [6, 8] is not complete
+                    (1, (1, 5)), // [1, 3, 5]
+                    (1, (3, 5)),
+                    (1, (5, 5)),
+                    // [7, 9] is not complete
+                ]
+            );
+        }
+    });
+}
+
+#[test]
+fn test_threshold_alert_window_keyed() {
+    TestHelper::local_remote_env(|env| {
+        // key 0 has a spike in its 20th and 25th windows, key 1 is steady
+        let source = IteratorSource::new(0..400u32);
+        let res = env
+            .stream(source)
+            .group_by(|x| x % 2)
+            .map(|(k, x)| {
+                let window = x / 2 / 4;
+                if *k == 0 && (window == 20 || window == 25) {
+                    10u32
+                } else {
+                    1
+                }
+            })
+            .window(CountWindow::tumbling(4))
+            .threshold_alert(50.0, 3.0)
+            .collect_vec();
+        env.execute_blocking();
+        if let Some(res) = res.get() {
+            let res = res
+                .into_iter()
+                .map(|(k, alert)| (k, alert.value, alert.baseline, alert.threshold))
+                .collect_vec();
+            assert_eq!(res, vec![(0, 40.0, 4.0, 12.0), (0, 40.0, 4.0, 12.0)]);
+        }
+    });
+}